@@ -0,0 +1,184 @@
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use futures_util::StreamExt;
+use hyper::{Request, StatusCode};
+use tokio::runtime::Runtime;
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{DavFileSystem, OpenOptions},
+    localfs::LocalFs,
+    memfs::MemFs,
+    memls::MemLs,
+    DavHandler,
+};
+use webdav_ss::aggregate::AggregateBuilder;
+
+const MB: usize = 1024 * 1024;
+
+fn handler(fs: Box<dyn DavFileSystem>) -> DavHandler {
+    DavHandler::builder()
+        .filesystem(fs)
+        .locksystem(MemLs::new())
+        .hide_symlinks(false)
+        .build_handler()
+}
+
+fn aggregate(fs: Box<dyn DavFileSystem>) -> Box<dyn DavFileSystem> {
+    AggregateBuilder::new()
+        .add_route(("/m", fs))
+        .build()
+        .unwrap()
+}
+
+async fn send(dav: &DavHandler, req: Request<hyper::Body>) -> (StatusCode, usize) {
+    let resp = dav.handle(req).await;
+    let status = resp.status();
+    let mut body = resp.into_body();
+    let mut len = 0;
+    while let Some(chunk) = body.next().await {
+        len += chunk.unwrap().len();
+    }
+    (status, len)
+}
+
+async fn put(fs: &dyn DavFileSystem, path: &str, data: Bytes) {
+    let opts = OpenOptions {
+        write: true,
+        create: true,
+        ..Default::default()
+    };
+    let mut f = fs.open(&DavPath::new(path).unwrap(), opts).await.unwrap();
+    f.write_bytes(data).await.unwrap();
+    f.flush().await.unwrap();
+}
+
+fn propfind(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("propfind");
+    group.sample_size(20);
+    for &files in &[100usize, 1000, 5000] {
+        let fs = aggregate(MemFs::new());
+        rt.block_on(async {
+            fs.create_dir(&DavPath::new("/m/dir/").unwrap())
+                .await
+                .unwrap();
+            for i in 0..files {
+                put(
+                    fs.as_ref(),
+                    &format!("/m/dir/file{}.txt", i),
+                    Bytes::from_static(b"x"),
+                )
+                .await;
+            }
+        });
+        let dav = &handler(fs);
+        group.throughput(Throughput::Elements(files as u64));
+        group.bench_with_input(BenchmarkId::new("depth1", files), &files, |b, _| {
+            b.to_async(&rt).iter(|| async move {
+                let req = Request::builder()
+                    .method("PROPFIND")
+                    .uri("/m/dir/")
+                    .header("Depth", "1")
+                    .body(hyper::Body::empty())
+                    .unwrap();
+                let (status, _) = send(dav, req).await;
+                assert_eq!(status, StatusCode::MULTI_STATUS);
+            })
+        });
+    }
+    group.finish();
+}
+
+fn transfer(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let dir = std::env::temp_dir().join(format!("webdav_ss_bench_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let backends: Vec<(&str, Box<dyn DavFileSystem>)> = vec![
+        ("mem", MemFs::new()),
+        ("fs", LocalFs::new(&dir, false, false, false)),
+    ];
+    let data = &Bytes::from(vec![7u8; 16 * MB]);
+
+    let mut group = c.benchmark_group("transfer");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    for (name, fs) in backends {
+        let dav = &handler(aggregate(fs));
+        group.bench_function(BenchmarkId::new("put", name), |b| {
+            b.to_async(&rt).iter(|| async move {
+                let req = Request::builder()
+                    .method("PUT")
+                    .uri("/m/big.bin")
+                    .body(hyper::Body::from(data.clone()))
+                    .unwrap();
+                let (status, _) = send(dav, req).await;
+                assert!(status.is_success());
+            })
+        });
+        group.bench_function(BenchmarkId::new("get", name), |b| {
+            b.to_async(&rt).iter(|| async move {
+                let req = Request::builder()
+                    .method("GET")
+                    .uri("/m/big.bin")
+                    .body(hyper::Body::empty())
+                    .unwrap();
+                let (status, len) = send(dav, req).await;
+                assert_eq!(status, StatusCode::OK);
+                assert_eq!(len, data.len());
+            })
+        });
+    }
+    group.finish();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Creates `width` dirs with `width` files each on every level down to `depth`.
+async fn tree(fs: &dyn DavFileSystem, path: String, depth: usize, width: usize) {
+    fs.create_dir(&DavPath::new(&path).unwrap()).await.unwrap();
+    for i in 0..width {
+        put(fs, &format!("{}f{}", path, i), Bytes::from_static(b"data")).await;
+    }
+    if depth > 0 {
+        for i in 0..width {
+            let sub = format!("{}d{}/", path, i);
+            Box::pin(tree(fs, sub, depth - 1, width)).await;
+        }
+    }
+}
+
+fn rename(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("rename");
+    group.sample_size(20);
+    for &depth in &[2usize, 4] {
+        let fs = aggregate(MemFs::new());
+        rt.block_on(tree(fs.as_ref(), "/m/a/".into(), depth, 3));
+        let dav = &handler(fs);
+        let mut flip = false;
+        group.bench_with_input(BenchmarkId::new("move_tree", depth), &depth, |b, _| {
+            b.to_async(&rt).iter(|| {
+                // moves the tree back and forth so every iteration has a source
+                flip = !flip;
+                let (from, to) = if flip {
+                    ("/m/a/", "/m/b/")
+                } else {
+                    ("/m/b/", "/m/a/")
+                };
+                async move {
+                    let req = Request::builder()
+                        .method("MOVE")
+                        .uri(from)
+                        .header("Destination", to)
+                        .body(hyper::Body::empty())
+                        .unwrap();
+                    let (status, _) = send(dav, req).await;
+                    assert!(status.is_success());
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, propfind, transfer, rename);
+criterion_main!(benches);
@@ -0,0 +1,156 @@
+//! Same scenarios as `dav` benchmark against s3 backend on minio started in docker.
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use futures_util::StreamExt;
+use hyper::{Request, StatusCode};
+use testcontainers::{
+    clients::Cli,
+    images::generic::{GenericImage, Stream, WaitFor},
+    Docker, Image, RunArgs,
+};
+use tokio::runtime::Runtime;
+use webdav_handler::{fs::DavFileSystem, memls::MemLs, DavHandler};
+use webdav_ss::{
+    aggregate::AggregateBuilder,
+    backend::{content_type::ContentTypes, s3_backend::S3Backend},
+    configuration::{Filesystem, S3Authentication},
+};
+
+const MB: usize = 1024 * 1024;
+
+async fn s3() -> Box<dyn DavFileSystem> {
+    let config = Filesystem::S3 {
+        region: "us-east-1".into(),
+        bucket: "bench".into(),
+        url: "http://localhost:9000".into(),
+        path_style: true,
+        ensure_bucket: true,
+        auth: S3Authentication::Values {
+            access_key_value: "minioadmin".into(),
+            secret_key_value: "minioadmin".into(),
+        },
+        sidecar_props: false,
+        case_insensitive: false,
+        negative_cache_ttl: 2000,
+        spool_threshold: 64 * 1024 * 1024,
+        discover_region: false,
+        redirect_downloads: None,
+        redirect_expiry: 300,
+        download_range: 8 * 1024 * 1024,
+        download_parallelism: 4,
+        listing_cache: 0,
+        deep_listing: false,
+        pricing: None,
+    };
+    let fs = S3Backend::new(config, ContentTypes::default())
+        .await
+        .unwrap();
+    AggregateBuilder::new()
+        .add_route(("/m", fs))
+        .build()
+        .unwrap()
+}
+
+fn request(method: &str, uri: &str) -> hyper::http::request::Builder {
+    Request::builder().method(method).uri(uri)
+}
+
+async fn send(
+    dav: &DavHandler,
+    req: hyper::http::request::Builder,
+    body: hyper::Body,
+) -> (StatusCode, usize) {
+    let resp = dav.handle(req.body(body).unwrap()).await;
+    let status = resp.status();
+    let mut body = resp.into_body();
+    let mut len = 0;
+    while let Some(chunk) = body.next().await {
+        len += chunk.unwrap().len();
+    }
+    (status, len)
+}
+
+fn minio(c: &mut Criterion) {
+    let docker = Cli::default();
+    let image = GenericImage::new("minio/minio")
+        .with_wait_for(WaitFor::LogMessage {
+            message: "Detected default credentials".into(),
+            stream: Stream::StdOut,
+        })
+        .with_args(vec!["server".into(), "/data".into()]);
+    let container = docker.run_with_args(image, RunArgs::default().with_mapped_port((9000, 9000)));
+
+    let rt = Runtime::new().unwrap();
+    let dav = &DavHandler::builder()
+        .filesystem(rt.block_on(s3()))
+        .locksystem(MemLs::new())
+        .build_handler();
+    let data = &Bytes::from(vec![7u8; 16 * MB]);
+
+    let mut group = c.benchmark_group("s3");
+    group.sample_size(10);
+
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("put", |b| {
+        b.to_async(&rt).iter(|| async move {
+            let (status, _) = send(dav, request("PUT", "/m/big.bin"), data.clone().into()).await;
+            assert!(status.is_success());
+        })
+    });
+    group.bench_function("get", |b| {
+        b.to_async(&rt).iter(|| async move {
+            let (status, len) = send(dav, request("GET", "/m/big.bin"), hyper::Body::empty()).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(len, data.len());
+        })
+    });
+
+    let files: u64 = 500;
+    rt.block_on(async {
+        send(dav, request("MKCOL", "/m/dir/"), hyper::Body::empty()).await;
+        for i in 0..files {
+            let uri = format!("/m/dir/file{}.txt", i);
+            send(dav, request("PUT", &uri), hyper::Body::from("x")).await;
+        }
+    });
+    group.throughput(Throughput::Elements(files));
+    group.bench_with_input(BenchmarkId::new("propfind", files), &files, |b, _| {
+        b.to_async(&rt).iter(|| async move {
+            let (status, _) = send(
+                dav,
+                request("PROPFIND", "/m/dir/").header("Depth", "1"),
+                hyper::Body::empty(),
+            )
+            .await;
+            assert_eq!(status, StatusCode::MULTI_STATUS);
+        })
+    });
+
+    let mut flip = false;
+    group.throughput(Throughput::Elements(files));
+    group.bench_with_input(BenchmarkId::new("move_tree", files), &files, |b, _| {
+        b.to_async(&rt).iter(|| {
+            flip = !flip;
+            let (from, to) = if flip {
+                ("/m/dir/", "/m/moved/")
+            } else {
+                ("/m/moved/", "/m/dir/")
+            };
+            async move {
+                let (status, _) = send(
+                    dav,
+                    request("MOVE", from).header("Destination", to),
+                    hyper::Body::empty(),
+                )
+                .await;
+                assert!(status.is_success());
+            }
+        })
+    });
+    group.finish();
+    container.stop();
+    container.rm();
+}
+
+criterion_group!(benches, minio);
+criterion_main!(benches);
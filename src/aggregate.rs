@@ -1,30 +1,127 @@
-use crate::backend::prop_storages::{mem::Memory, PropStorage};
+use crate::backend::{
+    checksum::{checksum_prop, ChecksumFile, ChecksumTarget, DedupeFile},
+    content_type::ContentTypes,
+    journal::{Journal, JournaledFile, Op, Pending},
+    live_props::{
+        live_props, merge_props, mount_prop, reported_by_handler, tree_prop, TREE_PROPS,
+        WEBDAV_SS_NAMESPACE,
+    },
+    prop_storages::{mem::Memory, PropStorage},
+    tree_stats::TreeStats,
+    wrappers::breaker::Breaker,
+};
 
 use super::backend::normalized_path::NormalizedPath;
 use anyhow::{anyhow, Result};
-use futures_util::FutureExt;
+use futures_util::{FutureExt, Stream, StreamExt};
 use percent_encoding::{percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use std::{
     collections::{HashMap, HashSet},
+    future::Future,
     path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
 };
-use tracing::{debug, instrument, span, Instrument, Level};
+use tracing::{debug, error, instrument, span, warn, Instrument, Level};
 use webdav_handler::{
     davpath::DavPath,
     fs::{
-        DavDirEntry, DavFile, DavFileSystem, DavMetaData, FsError, FsFuture, FsResult, FsStream,
-        OpenOptions, ReadDirMeta,
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, DavProp, FsError, FsFuture, FsResult,
+        FsStream, OpenOptions, ReadDirMeta,
     },
 };
 
 type Routes = HashMap<String, Box<dyn DavFileSystem>>;
 
+/// Journal entries after which props are flushed to drop done ones.
+const COMPACT_AFTER: usize = 1000;
+/// Least time between compactions, entries of long running operations may
+/// keep journal over `COMPACT_AFTER` and props should not be flushed on
+/// every operation then.
+const COMPACT_INTERVAL: Duration = Duration::from_secs(10);
+
 const ENC: &AsciiSet = &NON_ALPHANUMERIC.remove(b'.').remove(b'/').remove(b'"');
 
+tokio::task_local! {
+    // get_quota has no path argument, so mount is found by request path
+    static REQUEST: RequestScope;
+}
+
+/// Request handled by current task.
+#[derive(Clone)]
+struct RequestScope {
+    path: String,
+    /// metadata got last with its path; dav handler asks for props of path
+    /// right after it got metadata of it, so get_props does not ask again
+    meta: Arc<Mutex<Option<(Vec<u8>, Box<dyn DavMetaData>)>>>,
+}
+
+impl RequestScope {
+    fn new(path: String) -> Self {
+        RequestScope {
+            path,
+            meta: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+fn meta_key(path: &[u8]) -> &[u8] {
+    path.strip_suffix(b"/").unwrap_or(path)
+}
+
+fn remember_meta(key: Vec<u8>, meta: &dyn DavMetaData) {
+    let _ = REQUEST.try_with(|r| *r.meta.lock().unwrap() = Some((key, meta.box_clone())));
+}
+
+fn remembered_meta(path: &DavPath) -> Option<Box<dyn DavMetaData>> {
+    let key = meta_key(path.as_bytes());
+    REQUEST
+        .try_with(|r| match &*r.meta.lock().unwrap() {
+            Some((k, meta)) if k == key => Some(meta.clone()),
+            _ => None,
+        })
+        .ok()
+        .flatten()
+}
+
+/// Runs `f` knowing path of request it handles, needed for quota of mounts.
+pub async fn with_request_path<F: Future>(path: String, f: F) -> F::Output {
+    REQUEST.scope(RequestScope::new(path), f).await
+}
+
+/// Same as `with_request_path` for response body which is generated lazily,
+/// as PROPFIND one is.
+pub fn stream_with_request_path<S: Stream + Unpin>(
+    path: String,
+    mut body: S,
+) -> impl Stream<Item = S::Item> {
+    let scope = RequestScope::new(path);
+    async_stream::stream! {
+        while let Some(item) = REQUEST.scope(scope.clone(), body.next()).await {
+            yield item;
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Aggregate {
     filesystems: Routes,
     props: Box<dyn PropStorage>,
+    // routes which store dead properties by themselves
+    backend_props: HashSet<String>,
+    // timestamp reported for virtual directories
+    created: SystemTime,
+    content_types: ContentTypes,
+    // routes behind circuit breaker, degraded ones are marked in listings
+    breakers: HashMap<String, Arc<Breaker>>,
+    // backend type of routes reported on their roots
+    backend_types: HashMap<String, String>,
+    // routes skipping unchanged uploads up to size
+    dedupe: HashMap<String, usize>,
+    // operations whose props may be stale after crash
+    journal: Option<Arc<Journal>>,
+    // totals of collections reported in tree props
+    tree_stats: Option<Arc<TreeStats>>,
 }
 
 impl Aggregate {
@@ -32,12 +129,253 @@ impl Aggregate {
         Aggregate {
             filesystems: Routes::new(),
             props,
+            backend_props: HashSet::new(),
+            created: SystemTime::now(),
+            content_types: ContentTypes::default(),
+            breakers: HashMap::new(),
+            backend_types: HashMap::new(),
+            dedupe: HashMap::new(),
+            journal: None,
+            tree_stats: None,
+        }
+    }
+
+    /// Routes are stored without trailing slash, root route is "/".
+    fn route_name(route: &str) -> String {
+        let r = route.trim_end_matches('/');
+        if r.is_empty() {
+            "/".into()
+        } else {
+            r.into()
+        }
+    }
+
+    pub fn set_content_types(&mut self, types: ContentTypes) {
+        self.content_types = types;
+    }
+
+    pub fn set_backend_props(&mut self, route: &str) {
+        self.backend_props.insert(Aggregate::route_name(route));
+    }
+
+    pub fn set_breaker(&mut self, route: &str, breaker: Arc<Breaker>) {
+        self.breakers.insert(Aggregate::route_name(route), breaker);
+    }
+
+    pub fn set_backend_type(&mut self, route: &str, backend: &str) {
+        self.backend_types
+            .insert(Aggregate::route_name(route), backend.to_string());
+    }
+
+    pub fn set_dedupe(&mut self, route: &str, max: usize) {
+        self.dedupe.insert(Aggregate::route_name(route), max);
+    }
+
+    pub fn set_journal(&mut self, journal: Arc<Journal>) {
+        self.journal = Some(journal);
+    }
+
+    pub fn set_tree_stats(&mut self, stats: Arc<TreeStats>) {
+        self.tree_stats = Some(stats);
+    }
+
+    /// Drops cached totals of collections containing `path`.
+    fn tree_changed(&self, path: &DavPath) {
+        if let Some(ref t) = self.tree_stats {
+            t.changed(path);
+        }
+    }
+
+    fn is_tree_prop(&self, prop: &DavProp) -> bool {
+        self.tree_stats.is_some()
+            && prop.namespace.as_deref() == Some(WEBDAV_SS_NAMESPACE)
+            && TREE_PROPS.contains(&prop.name.as_str())
+    }
+
+    /// Value of tree prop `name` of collection.
+    async fn tree_prop(&self, path: &DavPath, name: &str) -> FsResult<Vec<u8>> {
+        let stats = self.tree_stats.as_ref().ok_or(FsError::NotFound)?;
+        if !self.metadata(path).await?.is_dir() {
+            return Err(FsError::NotFound);
+        }
+        let tree = stats.get(self, path).await?;
+        let value = if name == "tree-bytes" {
+            tree.bytes
+        } else {
+            tree.items
+        };
+        Ok(tree_prop(name, value, true).xml.unwrap_or_default())
+    }
+
+    /// Journals `op` before it is started, entry is done when returned value
+    /// is dropped after props are changed.
+    async fn record(&self, op: Op) -> FsResult<Option<Pending>> {
+        let journal = match self.journal {
+            Some(ref j) => j,
+            None => return Ok(None),
+        };
+        if journal.len() >= COMPACT_AFTER {
+            self.compact(journal).await;
+        }
+        journal.record(op).await.map(Some).map_err(|e| {
+            error!("can't write journal: {}", e);
+            FsError::GeneralFailure
+        })
+    }
+
+    /// Flushes props so entries of done operations can be dropped.
+    async fn compact(&self, journal: &Arc<Journal>) {
+        let checkpoint = match journal.start_compaction(COMPACT_INTERVAL) {
+            Some(c) => c,
+            None => return,
+        };
+        match self.props.flush().await {
+            Ok(()) => {
+                if let Err(e) = journal.clear_until(checkpoint).await {
+                    error!("can't compact journal: {}", e);
+                }
+            }
+            Err(e) => error!("can't flush props for journal compaction: {:?}", e),
+        }
+        journal.end_compaction();
+    }
+
+    /// Reconciles props with data for operations journaled by previous run,
+    /// they may have stopped between the two. Returns count of operations.
+    pub async fn recover(&self) -> Result<usize> {
+        let journal = match self.journal {
+            Some(ref j) => j,
+            None => return Ok(0),
+        };
+        let ops = journal.pending()?;
+        for op in &ops {
+            if let Err(e) = self.replay(op).await {
+                warn!("can't replay journal entry {:?}: {:?}", op, e);
+            }
+        }
+        self.props
+            .flush()
+            .await
+            .map_err(|e| anyhow!("can't flush props: {:?}", e))?;
+        journal.clear_until(journal.checkpoint()).await?;
+        Ok(ops.len())
+    }
+
+    async fn replay(&self, op: &Op) -> FsResult<()> {
+        let path = |p: &str| DavPath::new(p).map_err(|_| FsError::GeneralFailure);
+        let exists = |p: &str| {
+            let p = path(p);
+            async move {
+                match p {
+                    Ok(p) => self.metadata(&p).await.is_ok(),
+                    Err(_) => false,
+                }
+            }
+        };
+        match op {
+            // checksum may be of previous content, unknown one is safer
+            Op::Write { path: p } => {
+                self.patch_props(&path(p)?, vec![(false, checksum_prop(None))])
+                    .await?;
+            }
+            Op::Remove { path: p } => {
+                if !exists(p).await {
+                    let p: NormalizedPath = path(p)?.into();
+                    self.props.remove_file(&p).await?;
+                    self.props.remove_dir(&p).await?;
+                }
+            }
+            Op::Rename { from, to } => {
+                if !exists(from).await && exists(to).await {
+                    let from: NormalizedPath = path(from)?.into();
+                    self.props.rename(&from, &path(to)?.into()).await?;
+                }
+            }
+            Op::Copy { from, to } => {
+                let to_path: NormalizedPath = path(to)?.into();
+                if exists(to).await && !self.props.have_props(&to_path).await {
+                    self.props.copy(&path(from)?.into(), &to_path).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// True if path belongs to route and the route reports it missing.
+    /// Degraded routes report nothing, failover may lack what primary has.
+    pub async fn is_removed(&self, path: &DavPath) -> bool {
+        match self.find_named_route(path) {
+            Ok((name, _, _)) if !self.breakers.get(&name).map_or(false, |b| b.is_degraded()) => {
+                matches!(self.metadata(path).await, Err(FsError::NotFound))
+            }
+            _ => false,
+        }
+    }
+
+    /// Drops props of paths removed from backends without going through
+    /// aggregate, e.g. objects deleted in bucket by another tool. Returns
+    /// count of paths.
+    pub async fn drop_stale_props(&self) -> Result<usize> {
+        let all = self
+            .props
+            .get_all()
+            .await
+            .map_err(|e| anyhow!("can't list props: {:?}", e))?;
+        let mut dropped = 0;
+        for (p, _) in all {
+            let url = format!(
+                "/{}",
+                percent_encode(p.trim_start_matches('/').as_bytes(), ENC)
+            );
+            let path = match DavPath::new(&url) {
+                Ok(path) => path,
+                Err(_) => continue,
+            };
+            if !self.is_removed(&path).await {
+                continue;
+            }
+            debug!(msg = "dropping props of removed path", path = %p);
+            self.props
+                .remove_file(&p)
+                .await
+                .map_err(|e| anyhow!("can't remove props of {}: {:?}", p, e))?;
+            dropped += 1;
+        }
+        if dropped > 0 {
+            self.props
+                .flush()
+                .await
+                .map_err(|e| anyhow!("can't flush props: {:?}", e))?;
         }
+        Ok(dropped)
+    }
+
+    /// Properties of route if path is its root, so listing of directory with
+    /// mount points shows which storage each of them is.
+    async fn mount_props(&self, path: &DavPath, do_content: bool) -> Vec<DavProp> {
+        let (name, route) = match self.find_named_route(path) {
+            Ok((name, route, p)) if p.as_url_string() == "/" => (name, route),
+            _ => return vec![],
+        };
+        let mut r = vec![];
+        if let Some(backend) = self.backend_types.get(&name) {
+            r.push(mount_prop("backend", backend, do_content));
+        }
+        if let Ok((used, quota)) = route.get_quota().await {
+            r.push(mount_prop("used-bytes", &used.to_string(), do_content));
+            if let Some(q) = quota {
+                r.push(mount_prop("quota-bytes", &q.to_string(), do_content));
+            }
+        }
+        if self.breakers.get(&name).map_or(false, |b| b.is_degraded()) {
+            r.push(mount_prop("degraded", "true", do_content));
+        }
+        r
     }
 
     #[instrument(level = "debug", err, skip(self, fs))]
     pub fn add_route(&mut self, (route, fs): (&str, Box<dyn DavFileSystem>)) -> Result<()> {
-        let route = route.into();
+        let route = Aggregate::route_name(route);
         if self.filesystems.contains_key(&route) {
             return Err(anyhow!("aggregate already contains this route: {}", route,));
         }
@@ -46,8 +384,15 @@ impl Aggregate {
         Ok(())
     }
 
-    #[instrument(level = "debug", err, skip(self))]
     fn find_route(&self, route: &DavPath) -> FsResult<(Box<dyn DavFileSystem>, DavPath)> {
+        self.find_named_route(route).map(|(_, fs, path)| (fs, path))
+    }
+
+    #[instrument(level = "debug", err, skip(self))]
+    fn find_named_route(
+        &self,
+        route: &DavPath,
+    ) -> FsResult<(String, Box<dyn DavFileSystem>, DavPath)> {
         let col = route.is_collection();
         let pb = route.as_pathbuf();
         for p in pb.ancestors() {
@@ -66,6 +411,7 @@ impl Aggregate {
                 }
                 debug!(route = %p, path = %path);
                 return Ok((
+                    p.clone(),
                     self.filesystems.get(&p).unwrap().clone(),
                     DavPath::new(&path).unwrap(),
                 ));
@@ -74,6 +420,30 @@ impl Aggregate {
         Err(FsError::NotFound)
     }
 
+    /// Returns route and its path if route keeps dead properties by itself.
+    fn find_props_route(&self, path: &DavPath) -> Option<(Box<dyn DavFileSystem>, DavPath)> {
+        match self.find_named_route(path) {
+            Ok((name, route, path)) if self.backend_props.contains(&name) => Some((route, path)),
+            _ => None,
+        }
+    }
+
+    /// Directory which does not exist in any filesystem but contains mount points.
+    fn is_virtual_dir(&self, path: &DavPath) -> bool {
+        let p: NormalizedPath = path.into();
+        if p.is_root() {
+            return true;
+        }
+        let p = format!("/{}", p.as_dir().as_str());
+        self.filesystems.keys().any(|k| k.starts_with(&p))
+    }
+
+    fn virtual_metadata(&self) -> Box<dyn DavMetaData> {
+        Box::new(AggregateMetaData {
+            created: self.created,
+        }) as Box<dyn DavMetaData>
+    }
+
     // TODO: rewrite method with better code.
     #[instrument(level = "debug", skip(self))]
     fn find_routes_at_level(&self, level: &DavPath) -> FsResult<Vec<String>> {
@@ -112,22 +482,24 @@ impl Aggregate {
     }
 }
 
+/// Metadata of virtual directories. Timestamps should not change between
+/// requests or clients will think directory was modified.
 #[derive(Debug, Clone)]
 struct AggregateMetaData {
-    path: NormalizedPath,
+    created: SystemTime,
 }
 
 impl DavMetaData for AggregateMetaData {
     fn len(&self) -> u64 {
-        4 * 1024
+        0
     }
 
-    fn created(&self) -> FsResult<std::time::SystemTime> {
-        Ok(std::time::SystemTime::now())
+    fn created(&self) -> FsResult<SystemTime> {
+        Ok(self.created)
     }
 
-    fn modified(&self) -> FsResult<std::time::SystemTime> {
-        Ok(std::time::SystemTime::now())
+    fn modified(&self) -> FsResult<SystemTime> {
+        Ok(self.created)
     }
 
     fn is_dir(&self) -> bool {
@@ -136,31 +508,89 @@ impl DavMetaData for AggregateMetaData {
 }
 
 struct AggregateDirEntry {
-    path: NormalizedPath,
+    name: Vec<u8>,
+    meta: Box<dyn DavMetaData>,
 }
 
 impl DavDirEntry for AggregateDirEntry {
     fn name(&self) -> Vec<u8> {
-        self.path.clone().into()
+        self.name.clone()
+    }
+
+    fn metadata<'a>(&'a self) -> FsFuture<Box<dyn DavMetaData>> {
+        async move { Ok(self.meta.clone()) }.boxed()
+    }
+}
+
+/// Listed entry remembering metadata it is asked for, see `RequestScope`.
+struct RememberingDirEntry {
+    inner: Box<dyn DavDirEntry>,
+    dir: Vec<u8>,
+}
+
+impl DavDirEntry for RememberingDirEntry {
+    fn name(&self) -> Vec<u8> {
+        self.inner.name()
     }
 
     fn metadata<'a>(&'a self) -> FsFuture<Box<dyn DavMetaData>> {
         async move {
-            Ok(Box::new(AggregateMetaData {
-                path: self.path.clone(),
-            }) as Box<dyn DavMetaData>)
+            let meta = self.inner.metadata().await?;
+            let mut key = meta_key(&self.dir).to_vec();
+            key.push(b'/');
+            key.extend_from_slice(meta_key(&self.inner.name()));
+            remember_meta(key, meta.as_ref());
+            Ok(meta)
         }
         .boxed()
     }
+
+    fn is_dir<'a>(&'a self) -> FsFuture<bool> {
+        self.inner.is_dir()
+    }
+
+    fn is_file<'a>(&'a self) -> FsFuture<bool> {
+        self.inner.is_file()
+    }
+
+    fn is_symlink<'a>(&'a self) -> FsFuture<bool> {
+        self.inner.is_symlink()
+    }
 }
 
 impl DavFileSystem for Aggregate {
     fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
         let span = span!(Level::INFO, "Aggregate::open");
         async move {
-            let (route, path) = self.find_route(&path)?;
-            let result = route.open(&path, options).await;
-            Ok(result?)
+            let (name, route, route_path) = self.find_named_route(&path)?;
+            if !options.write {
+                return route.open(&route_path, options).await;
+            }
+            self.tree_changed(path);
+            let truncated = options.truncate || options.create_new;
+            let target = if self.backend_props.contains(&name) {
+                ChecksumTarget::Filesystem(route.clone(), route_path.clone())
+            } else {
+                ChecksumTarget::Storage(self.props.clone(), path.into())
+            };
+            let pending = self
+                .record(Op::Write {
+                    path: path.as_url_string(),
+                })
+                .await?;
+            let file = match self.dedupe.get(&name) {
+                Some(max) if options.truncate && !options.create_new => {
+                    DedupeFile::open(route, route_path, options, target, *max).await?
+                }
+                _ => {
+                    let file = route.open(&route_path, options).await?;
+                    Box::new(ChecksumFile::new(file, target, truncated)) as Box<dyn DavFile>
+                }
+            };
+            Ok(match pending {
+                Some(p) => JournaledFile::new(file, p),
+                None => file,
+            })
         }
         .instrument(span)
         .boxed()
@@ -189,32 +619,54 @@ impl DavFileSystem for Aggregate {
         //      /mem
         async move {
             let dirs = self.find_routes_at_level(path)?;
+            // mount points hide entries with the same name of filesystem they mounted into
+            let mounted: HashSet<Vec<u8>> = dirs
+                .iter()
+                .filter_map(|d| d.rsplit('/').next())
+                .map(|d| d.as_bytes().to_vec())
+                .collect();
 
-            let mut agg_dirs = vec![];
-            match self.find_route(&path) {
-                Ok((route, path)) => {
-                    match route.read_dir(&path, meta).await {
-                        Ok(mut result) => {
-                            while let Some(i) = result.next().await {
-                                agg_dirs.push(i);
-                            }
-                        },
-                        _ => {},
-                    }
-                },
-                _ => {}
+            // entries of routed filesystem are passed through as they arrive
+            let routed = match self.find_route(&path) {
+                Ok((route, path)) => route.read_dir(&path, meta).await.ok(),
+                _ => None,
             };
 
             debug!(msg = "generated dirs to output", dirs = ?dirs);
+            let dir = path.as_bytes().to_vec();
+            let mut mount_dirs = vec![];
+            for d in dirs {
+                let name = d.rsplit('/').next().unwrap_or("").as_bytes().to_vec();
+                let dir_path = format!("{}/", percent_encode(d.as_bytes(), ENC));
+                let meta = match DavPath::new(&dir_path) {
+                    Ok(p) => self.metadata(&p).await,
+                    Err(_) => Err(FsError::NotFound),
+                };
+                let meta = meta.unwrap_or_else(|_| self.virtual_metadata());
+                mount_dirs.push(AggregateDirEntry { name, meta });
+            }
             let ss = stream! {
-                for d in agg_dirs {
-                    debug!(msg = "yield from route");
-                    yield d;
+                if let Some(mut routed) = routed {
+                    while let Some(i) = routed.next().await {
+                        let name = i.name();
+                        let name = match name.strip_suffix(b"/") {
+                            Some(n) => n,
+                            None => &name,
+                        };
+                        if mounted.contains(name) {
+                            continue;
+                        }
+                        debug!(msg = "yield from route");
+                        let entry = RememberingDirEntry { inner: i, dir: dir.clone() };
+                        yield Box::new(entry) as Box<dyn DavDirEntry>;
+                    }
                 }
 
-                for d in dirs {
-                    debug!(msg = "yield aggregate dirs", name = %d);
-                    yield Box::new(AggregateDirEntry{ path: format!("{}/", d).into() }) as Box<dyn DavDirEntry>;
+                for d in mount_dirs {
+                    debug!(msg = "yield aggregate dirs", name = %String::from_utf8_lossy(&d.name));
+                    let inner = Box::new(d) as Box<dyn DavDirEntry>;
+                    let entry = RememberingDirEntry { inner, dir: dir.clone() };
+                    yield Box::new(entry) as Box<dyn DavDirEntry>;
                 }
             };
             Ok(Box::pin(ss) as FsStream<Box<dyn DavDirEntry>>)
@@ -226,13 +678,18 @@ impl DavFileSystem for Aggregate {
     fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
         let span = span!(Level::INFO, "Aggregate::metadata");
         async move {
-            let p: NormalizedPath = path.clone().into();
-            if p.is_root() {
-                return Ok(Box::new(AggregateMetaData { path: p }) as Box<dyn DavMetaData>);
+            let result = match self.find_route(&path) {
+                Ok((route, path)) => route.metadata(&path).await,
+                Err(e) => Err(e),
+            };
+            let result = match result {
+                Err(FsError::NotFound) if self.is_virtual_dir(path) => Ok(self.virtual_metadata()),
+                r => r,
+            };
+            if let Ok(ref meta) = result {
+                remember_meta(meta_key(path.as_bytes()).to_vec(), meta.as_ref());
             }
-            let (route, path) = self.find_route(&path)?;
-            let result = route.metadata(&path).await;
-            Ok(result?)
+            result
         }
         .instrument(span)
         .boxed()
@@ -241,6 +698,7 @@ impl DavFileSystem for Aggregate {
     fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
         let span = span!(Level::INFO, "Aggregate::create_dir");
         async move {
+            self.tree_changed(path);
             let (route, path) = self.find_route(&path)?;
             let result = route.create_dir(&path).await;
             Ok(result?)
@@ -252,12 +710,19 @@ impl DavFileSystem for Aggregate {
     fn remove_file<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
         let span = span!(Level::INFO, "Aggregate::remove_file");
         async move {
+            self.tree_changed(path);
             let orig_path = path.clone();
-            let (route, path) = self.find_route(&path)?;
-            Ok(route
-                .remove_file(&path)
-                .await
-                .and(self.props.remove_file(&orig_path.into()).await)?)
+            let (name, route, path) = self.find_named_route(&path)?;
+            if self.backend_props.contains(&name) {
+                return route.remove_file(&path).await;
+            }
+            let _pending = self
+                .record(Op::Remove {
+                    path: orig_path.as_url_string(),
+                })
+                .await?;
+            let result = route.remove_file(&path).await;
+            Ok(result.and(self.props.remove_file(&orig_path.into()).await)?)
         }
         .instrument(span)
         .boxed()
@@ -266,12 +731,19 @@ impl DavFileSystem for Aggregate {
     fn remove_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
         let span = span!(Level::INFO, "Aggregate::remove_dir");
         async move {
+            self.tree_changed(path);
             let orig_path = path.clone();
-            let (route, path) = self.find_route(&path)?;
-            Ok(route
-                .remove_dir(&path)
-                .await
-                .and(self.props.remove_dir(&orig_path.into()).await)?)
+            let (name, route, path) = self.find_named_route(&path)?;
+            if self.backend_props.contains(&name) {
+                return route.remove_dir(&path).await;
+            }
+            let _pending = self
+                .record(Op::Remove {
+                    path: orig_path.as_url_string(),
+                })
+                .await?;
+            let result = route.remove_dir(&path).await;
+            Ok(result.and(self.props.remove_dir(&orig_path.into()).await)?)
         }
         .instrument(span)
         .boxed()
@@ -280,14 +752,27 @@ impl DavFileSystem for Aggregate {
     fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
         let span = span!(Level::INFO, "Aggregate::rename");
         async move {
+            self.tree_changed(from);
+            self.tree_changed(to);
             let orig_from = from.clone();
             let orig_to = to.clone();
-            let (route, from) = self.find_route(&from)?;
-            let (_, to) = self.find_route(&to)?;
-            Ok(route
-                .rename(&from, &to)
-                .await
-                .and(self.props.rename(&orig_from.into(), &orig_to.into()).await)?)
+            let (name, route, from) = self.find_named_route(&from)?;
+            let (to_name, _, to) = self.find_named_route(&to)?;
+            if name != to_name {
+                // moving between filesystems is not supported
+                return Err(FsError::NotImplemented);
+            }
+            if self.backend_props.contains(&name) {
+                return route.rename(&from, &to).await;
+            }
+            let _pending = self
+                .record(Op::Rename {
+                    from: orig_from.as_url_string(),
+                    to: orig_to.as_url_string(),
+                })
+                .await?;
+            let result = route.rename(&from, &to).await;
+            Ok(result.and(self.props.rename(&orig_from.into(), &orig_to.into()).await)?)
         }
         .instrument(span)
         .boxed()
@@ -296,14 +781,27 @@ impl DavFileSystem for Aggregate {
     fn copy<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
         let span = span!(Level::INFO, "Aggregate::copy");
         async move {
+            self.tree_changed(from);
+            self.tree_changed(to);
             let orig_from = from.clone();
             let orig_to = to.clone();
-            let (route, from) = self.find_route(&from)?;
-            let (_, to) = self.find_route(&to)?;
-            Ok(route
-                .copy(&from, &to)
-                .await
-                .and(self.props.copy(&orig_from.into(), &orig_to.into()).await)?)
+            let (name, route, from) = self.find_named_route(&from)?;
+            let (to_name, _, to) = self.find_named_route(&to)?;
+            if name != to_name {
+                // moving between filesystems is not supported
+                return Err(FsError::NotImplemented);
+            }
+            if self.backend_props.contains(&name) {
+                return route.copy(&from, &to).await;
+            }
+            let _pending = self
+                .record(Op::Copy {
+                    from: orig_from.as_url_string(),
+                    to: orig_to.as_url_string(),
+                })
+                .await?;
+            let result = route.copy(&from, &to).await;
+            Ok(result.and(self.props.copy(&orig_from.into(), &orig_to.into()).await)?)
         }
         .instrument(span)
         .boxed()
@@ -311,9 +809,11 @@ impl DavFileSystem for Aggregate {
 
     fn have_props<'a>(
         &'a self,
-        path: &'a DavPath,
+        _path: &'a DavPath,
     ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
-        Box::pin(async move { self.props.have_props(&path.into()).await })
+        // live props are derived from metadata so every existing path has
+        // them, and dav handler asks only about paths it got metadata of
+        Box::pin(async move { true })
     }
 
     fn patch_props<'a>(
@@ -323,6 +823,9 @@ impl DavFileSystem for Aggregate {
     ) -> FsFuture<Vec<(hyper::StatusCode, webdav_handler::fs::DavProp)>> {
         let span = span!(Level::INFO, "Aggregate::patch_props");
         async move {
+            if let Some((route, path)) = self.find_props_route(path) {
+                return route.patch_props(&path, patch).await;
+            }
             let mut r = vec![];
             let path: NormalizedPath = path.into();
             for (set, prop) in patch {
@@ -345,9 +848,17 @@ impl DavFileSystem for Aggregate {
         prop: webdav_handler::fs::DavProp,
     ) -> FsFuture<Vec<u8>> {
         let span = span!(Level::INFO, "Aggregate::get_prop");
-        async move { self.props.get_prop(&path.into(), prop).await }
-            .instrument(span)
-            .boxed()
+        async move {
+            if self.is_tree_prop(&prop) {
+                return self.tree_prop(path, &prop.name).await;
+            }
+            match self.find_props_route(path) {
+                Some((route, path)) => route.get_prop(&path, prop).await,
+                None => self.props.get_prop(&path.into(), prop).await,
+            }
+        }
+        .instrument(span)
+        .boxed()
     }
 
     #[instrument(level = "debug", skip(self))]
@@ -357,15 +868,59 @@ impl DavFileSystem for Aggregate {
         do_content: bool,
     ) -> FsFuture<Vec<webdav_handler::fs::DavProp>> {
         let span = span!(Level::INFO, "Aggregate::get_props");
-        async move { self.props.get_props(&path.into(), do_content).await }
-            .instrument(span)
-            .boxed()
+        async move {
+            let dead = match self.find_props_route(path) {
+                Some((route, path)) => route.get_props(&path, do_content).await?,
+                None => self.props.get_props(&path.into(), do_content).await?,
+            };
+            let meta = match remembered_meta(path) {
+                Some(meta) => Ok(meta),
+                None => self.metadata(path).await,
+            };
+            let mut live = match meta {
+                Ok(meta) => {
+                    let mut r = live_props(path, meta.as_ref(), &self.content_types, do_content);
+                    // computed only when asked for by name, allprop would walk every
+                    // listed collection
+                    if meta.is_dir() && !do_content && self.tree_stats.is_some() {
+                        r.extend(TREE_PROPS.iter().map(|n| tree_prop(n, 0, false)));
+                    }
+                    r
+                }
+                Err(_) => vec![],
+            };
+            live.extend(self.mount_props(path, do_content).await);
+            let mut props = merge_props(live, dead);
+            props.retain(|p| !reported_by_handler(p, do_content));
+            Ok(props)
+        }
+        .instrument(span)
+        .boxed()
+    }
+
+    fn get_quota<'a>(&'a self) -> FsFuture<(u64, Option<u64>)> {
+        async move {
+            let path = REQUEST
+                .try_with(|r| DavPath::new(r.path.as_str()))
+                .map_err(|_| FsError::NotImplemented)?
+                .map_err(|_| FsError::NotFound)?;
+            let (route, _) = self.find_route(&path)?;
+            route.get_quota().await
+        }
+        .boxed()
     }
 }
 
 pub struct AggregateBuilder {
     routes: Vec<(String, Box<dyn DavFileSystem>)>,
     props: Box<dyn PropStorage>,
+    backend_props: Vec<String>,
+    content_types: ContentTypes,
+    breakers: Vec<(String, Arc<Breaker>)>,
+    backend_types: Vec<(String, String)>,
+    dedupe: Vec<(String, usize)>,
+    journal: Option<Arc<Journal>>,
+    tree_stats: Option<Arc<TreeStats>>,
 }
 
 impl AggregateBuilder {
@@ -373,6 +928,13 @@ impl AggregateBuilder {
         AggregateBuilder {
             routes: vec![],
             props: Memory::new(),
+            backend_props: vec![],
+            content_types: ContentTypes::default(),
+            breakers: vec![],
+            backend_types: vec![],
+            dedupe: vec![],
+            journal: None,
+            tree_stats: None,
         }
     }
 
@@ -386,11 +948,75 @@ impl AggregateBuilder {
         self
     }
 
+    /// Route will keep dead properties by itself instead of common props storage.
+    pub fn set_backend_props(mut self, route: &str) -> Self {
+        self.backend_props.push(route.to_string());
+        self
+    }
+
+    /// Content types reported in getcontenttype property.
+    pub fn set_content_types(mut self, types: ContentTypes) -> Self {
+        self.content_types = types;
+        self
+    }
+
+    /// Route root will report `degraded` property while breaker is open.
+    pub fn set_breaker(mut self, route: &str, breaker: Arc<Breaker>) -> Self {
+        self.breakers.push((route.to_string(), breaker));
+        self
+    }
+
+    /// Backend type reported in `backend` property of route root.
+    pub fn set_backend_type(mut self, route: &str, backend: &str) -> Self {
+        self.backend_types
+            .push((route.to_string(), backend.to_string()));
+        self
+    }
+
+    /// Uploads to route up to `max` bytes are not written if content equals
+    /// stored one.
+    pub fn set_dedupe(mut self, route: &str, max: usize) -> Self {
+        self.dedupe.push((route.to_string(), max));
+        self
+    }
+
+    /// Operations changing data and props of routes without backend props
+    /// are journaled, see `Aggregate::recover`.
+    pub fn set_journal(mut self, journal: Arc<Journal>) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Collections will report `tree-bytes` and `tree-items` props.
+    pub fn set_tree_stats(mut self, stats: Arc<TreeStats>) -> Self {
+        self.tree_stats = Some(stats);
+        self
+    }
+
     pub fn build(self) -> Result<Box<Aggregate>> {
         let mut agg = Aggregate::new(self.props);
+        agg.set_content_types(self.content_types);
         for (route, fs) in self.routes {
             agg.add_route((&route, fs))?;
         }
+        for route in self.backend_props {
+            agg.set_backend_props(&route);
+        }
+        for (route, breaker) in self.breakers {
+            agg.set_breaker(&route, breaker);
+        }
+        for (route, backend) in self.backend_types {
+            agg.set_backend_type(&route, &backend);
+        }
+        for (route, max) in self.dedupe {
+            agg.set_dedupe(&route, max);
+        }
+        if let Some(journal) = self.journal {
+            agg.set_journal(journal);
+        }
+        if let Some(stats) = self.tree_stats {
+            agg.set_tree_stats(stats);
+        }
         Ok(Box::new(agg))
     }
 }
@@ -400,6 +1026,7 @@ mod tests {
     use webdav_handler::{davpath::DavPath, memfs::MemFs};
 
     use super::*;
+    use crate::test_utils::put;
 
     fn helper_path(s: &'static str) -> DavPath {
         DavPath::new(&s).unwrap()
@@ -437,6 +1064,309 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn check_root_route() -> Result<()> {
+        let mut fs = AggregateBuilder::new().build()?;
+        fs.add_route(("/", MemFs::new()))?;
+        fs.add_route(("/minio/", MemFs::new()))?;
+
+        let (name, _, f) = fs.find_named_route(&helper_path("/minio/file"))?;
+        assert_eq!(name, "/minio");
+        assert_eq!(f, helper_path("/file"));
+
+        let (name, _, f) = fs.find_named_route(&helper_path("/other/file"))?;
+        assert_eq!(name, "/");
+        assert_eq!(f, helper_path("/other/file"));
+
+        let (name, _, f) = fs.find_named_route(&helper_path("/"))?;
+        assert_eq!(name, "/");
+        assert_eq!(f, helper_path("/"));
+
+        assert_eq!(
+            fs.find_routes_at_level(&helper_path("/"))?,
+            vec!["/minio".to_string()]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_dir_with_root_route() -> Result<()> {
+        use futures_util::StreamExt;
+
+        let root = MemFs::new();
+        // hidden by mount point
+        root.create_dir(&helper_path("/minio/")).await?;
+
+        let mut fs = AggregateBuilder::new().build()?;
+        fs.add_route(("/", root.clone()))?;
+        fs.add_route(("/minio", MemFs::new()))?;
+        fs.create_dir(&helper_path("/docs/")).await?;
+
+        let entries = fs
+            .read_dir(&helper_path("/"), ReadDirMeta::None)
+            .await?
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(entries.len(), 2);
+        assert!(fs.metadata(&helper_path("/docs/")).await?.is_dir());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn virtual_dirs() -> Result<()> {
+        use futures_util::StreamExt;
+
+        let mut fs = AggregateBuilder::new().build()?;
+        fs.add_route(("/tmp/fs1", MemFs::new()))?;
+        fs.add_route(("/tmp/fs2", MemFs::new()))?;
+
+        let m1 = fs.metadata(&helper_path("/tmp/")).await?;
+        let m2 = fs.metadata(&helper_path("/tmp")).await?;
+        assert!(m1.is_dir());
+        assert_eq!(m1.modified()?, m2.modified()?);
+        assert_eq!(m1.etag(), m2.etag());
+        assert!(fs.metadata(&helper_path("/tm/")).await.is_err());
+
+        let mut names = vec![];
+        let mut entries = fs
+            .read_dir(&helper_path("/tmp/"), ReadDirMeta::None)
+            .await?;
+        while let Some(e) = entries.next().await {
+            names.push(String::from_utf8(e.name())?);
+        }
+        names.sort();
+        assert_eq!(names, vec!["fs1", "fs2"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn quota_of_request_mount() -> Result<()> {
+        use crate::backend::wrappers::usage::{Usage, UsageFs};
+        use std::sync::Arc;
+
+        let mut fs = AggregateBuilder::new().build()?;
+        fs.add_route((
+            "/a",
+            UsageFs::new(MemFs::new(), Arc::new(Usage::default()), Some(1)),
+        ))?;
+        fs.add_route((
+            "/b",
+            UsageFs::new(MemFs::new(), Arc::new(Usage::default()), Some(2)),
+        ))?;
+
+        let quota = with_request_path("/b/file".into(), fs.get_quota()).await?;
+        assert_eq!(quota, (0, Some(2)));
+        assert!(fs.get_quota().await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reuses_listed_metadata() -> Result<()> {
+        let fs = AggregateBuilder::new()
+            .add_route(("/data", MemFs::new()))
+            .build()?;
+        crate::test_utils::put(fs.as_ref(), "/data/a.txt", b"a").await?;
+        let a = helper_path("/data/a.txt");
+        assert!(remembered_meta(&a).is_none());
+
+        with_request_path("/data/".into(), async {
+            let mut entries = fs
+                .read_dir(&helper_path("/data/"), ReadDirMeta::Data)
+                .await?;
+            let entry = entries.next().await.expect("listed file");
+            assert_eq!(entry.metadata().await?.len(), 1);
+            assert_eq!(remembered_meta(&a).map(|m| m.len()), Some(1));
+            assert!(remembered_meta(&helper_path("/data/")).is_none());
+
+            fs.metadata(&helper_path("/data/")).await?;
+            assert!(remembered_meta(&helper_path("/data")).is_some());
+            assert!(remembered_meta(&a).is_none());
+            Ok::<_, FsError>(())
+        })
+        .await?;
+
+        let props = fs.get_props(&a, true).await?;
+        assert!(!props.iter().any(|p| p.name == "getcontenttype"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn mount_root_props() -> Result<()> {
+        use crate::backend::wrappers::usage::{Usage, UsageFs};
+        use std::sync::Arc;
+
+        let fs = AggregateBuilder::new()
+            .add_route((
+                "/data",
+                UsageFs::new(MemFs::new(), Arc::new(Usage::default()), Some(10)),
+            ))
+            .set_backend_type("/data", "mem")
+            .build()?;
+        fs.create_dir(&helper_path("/data/dir/")).await?;
+
+        let props = fs.get_props(&helper_path("/data/"), true).await?;
+        let value = |name: &str| {
+            props
+                .iter()
+                .find(|p| p.name == name)
+                .and_then(|p| p.xml.clone())
+                .map(String::from_utf8)
+        };
+        assert_eq!(
+            value("backend").transpose()?,
+            Some("<backend xmlns=\"urn:webdav_ss:\">mem</backend>".into())
+        );
+        assert!(value("quota-bytes").is_some());
+        assert!(value("degraded").is_none());
+
+        let props = fs.get_props(&helper_path("/data/dir/"), true).await?;
+        assert!(!props.iter().any(|p| p.name == "backend"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn replays_journal() -> Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("webdav_ss_agg_journal_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let props = Memory::new();
+        let data = MemFs::new();
+        let build = || -> Result<Box<Aggregate>> {
+            AggregateBuilder::new()
+                .add_route(("/fs", data.clone()))
+                .set_props_storage(props.clone())
+                .set_journal(Journal::open(&path)?)
+                .build()
+        };
+
+        let fs = build()?;
+        put(fs.as_ref(), "/fs/a.txt", b"data").await?;
+        let color = DavProp {
+            name: "color".into(),
+            namespace: Some("urn:test".into()),
+            prefix: None,
+            xml: Some(b"<color xmlns=\"urn:test\">red</color>".to_vec()),
+        };
+        fs.patch_props(&helper_path("/fs/a.txt"), vec![(true, color.clone())])
+            .await?;
+
+        // stopped after data was moved, before props were
+        fs.journal
+            .as_ref()
+            .unwrap()
+            .record(Op::Rename {
+                from: "/fs/a.txt".into(),
+                to: "/fs/b.txt".into(),
+            })
+            .await?;
+        data.rename(&helper_path("/a.txt"), &helper_path("/b.txt"))
+            .await?;
+
+        let fs = build()?;
+        assert_eq!(fs.recover().await?, 2);
+        let b = helper_path("/fs/b.txt");
+        assert!(fs.get_prop(&b, color).await.is_ok());
+        assert!(fs.get_prop(&b, checksum_prop(None)).await.is_err());
+        assert!(fs.journal.as_ref().unwrap().pending()?.is_empty());
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn drops_stale_props() -> Result<()> {
+        use crate::backend::wrappers::{
+            breaker::{Breaker, BreakerFs},
+            chaos::{Chaos, ChaosFs, Faults},
+        };
+
+        let props = Memory::new();
+        let data = MemFs::new();
+        let faults = Faults {
+            error_rate: 1.0,
+            ..Faults::default()
+        };
+        let breaker = Arc::new(Breaker::new(1, std::time::Duration::from_secs(3600)));
+        let down = BreakerFs::new(
+            ChaosFs::new(MemFs::new(), Chaos::new(faults, 1)),
+            None,
+            breaker.clone(),
+        );
+        let fs = AggregateBuilder::new()
+            .add_route(("/fs", data.clone()))
+            .add_route(("/down", down))
+            .set_breaker("/down", breaker)
+            .set_props_storage(props.clone())
+            .build()?;
+        put(data.as_ref(), "/kept.txt", b"").await?;
+        let color = DavProp {
+            name: "color".into(),
+            namespace: Some("urn:test".into()),
+            prefix: None,
+            xml: Some(b"<color xmlns=\"urn:test\">red</color>".to_vec()),
+        };
+        for p in ["fs/kept.txt", "fs/a b.txt", "down/x.txt", "unmounted/x.txt"].iter() {
+            props
+                .patch_prop(&(*p).into(), (true, color.clone()))
+                .await?;
+        }
+        assert!(fs.metadata(&helper_path("/down/x.txt")).await.is_err());
+
+        assert_eq!(fs.drop_stale_props().await?, 1);
+        assert!(!props.have_props(&"fs/a b.txt".into()).await);
+        for p in ["fs/kept.txt", "down/x.txt", "unmounted/x.txt"].iter() {
+            assert!(props.have_props(&(*p).into()).await, "{}", p);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn tree_props() -> Result<()> {
+        let fs = AggregateBuilder::new()
+            .add_route(("/data", MemFs::new()))
+            .set_tree_stats(TreeStats::new(std::time::Duration::from_secs(60)))
+            .build()?;
+        fs.create_dir(&helper_path("/data/dir/")).await?;
+        put(fs.as_ref(), "/data/dir/a.txt", b"data").await?;
+
+        let prop = |name: &str| DavProp {
+            name: name.into(),
+            namespace: Some(WEBDAV_SS_NAMESPACE.into()),
+            prefix: None,
+            xml: None,
+        };
+        let value = fs.get_prop(&helper_path("/"), prop("tree-bytes")).await?;
+        assert_eq!(
+            value,
+            b"<tree-bytes xmlns=\"urn:webdav_ss:\">4</tree-bytes>".to_vec()
+        );
+        let value = fs
+            .get_prop(&helper_path("/data/"), prop("tree-items"))
+            .await?;
+        assert_eq!(
+            value,
+            b"<tree-items xmlns=\"urn:webdav_ss:\">2</tree-items>".to_vec()
+        );
+        assert!(fs
+            .get_prop(&helper_path("/data/dir/a.txt"), prop("tree-bytes"))
+            .await
+            .is_err());
+
+        fs.remove_file(&helper_path("/data/dir/a.txt")).await?;
+        let value = fs
+            .get_prop(&helper_path("/data/"), prop("tree-items"))
+            .await?;
+        assert_eq!(
+            value,
+            b"<tree-items xmlns=\"urn:webdav_ss:\">1</tree-items>".to_vec()
+        );
+
+        let names = fs.get_props(&helper_path("/data/dir/"), false).await?;
+        assert!(names
+            .iter()
+            .any(|p| p.name == "tree-bytes" && p.xml.is_none()));
+        Ok(())
+    }
+
     fn add_route(fs: &mut Box<Aggregate>, route: &str) {
         let _ = fs.add_route((route, MemFs::new()));
     }
@@ -0,0 +1,129 @@
+use hyper::{
+    header::{HeaderValue, ALLOW, CONTENT_LENGTH},
+    Method, Response, StatusCode,
+};
+use tracing::warn;
+
+/// Files Finder looks up on every mounted volume and folder to find out how
+/// to treat them, backends are not asked about them.
+const FINDER_PROBES: &[&str] = &[
+    ".hidden",
+    ".localized",
+    ".ql_disablethumbnails",
+    ".ql_disablecache",
+    ".metadata_never_index",
+    ".metadata_never_index_unless_rootfs",
+    ".metadata_direct_scope_only",
+    ".Spotlight-V100",
+    ".Trashes",
+    ".TemporaryItems",
+    ".fseventsd",
+    ".VolumeIcon.icns",
+    "Backups.backupdb",
+    "mach_kernel",
+];
+
+/// AppleDouble files and Finder view settings, writes of them succeed
+/// without storing anything.
+pub(crate) fn finder_ignored() -> Vec<String> {
+    vec!["._*".into(), ".DS_Store".into()]
+}
+
+/// 404 for Finder probes, which would otherwise reach backends like S3 for
+/// each folder opened and stall Finder while they are answered.
+pub(crate) fn finder_probe(method: &Method, path: &str) -> Option<Response<hyper::Body>> {
+    if !matches!(method.as_str(), "GET" | "HEAD" | "PROPFIND") {
+        return None;
+    }
+    let name = path.trim_end_matches('/').rsplit('/').next()?;
+    if !FINDER_PROBES.contains(&name) {
+        return None;
+    }
+    Some(
+        Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .header(CONTENT_LENGTH, 0)
+            .body(hyper::Body::empty())
+            .unwrap(),
+    )
+}
+
+/// Windows WebClient checks server with OPTIONS on root before mapping drive
+/// and only accepts servers declaring themselves as DAV authoring ones.
+/// Root is not a mount usually, so it is answered here when handler fails.
+pub(crate) fn windows_options(path: &str, resp: &mut Response<hyper::Body>) {
+    if path == "/" && !resp.status().is_success() {
+        *resp = Response::builder()
+            .status(StatusCode::OK)
+            .header("DAV", "1, 2")
+            .header(ALLOW, "OPTIONS,PROPFIND")
+            .header(CONTENT_LENGTH, 0)
+            .body(hyper::Body::empty())
+            .unwrap();
+    }
+    resp.headers_mut()
+        .insert("MS-Author-Via", HeaderValue::from_static("DAV"));
+}
+
+/// WebClient misreads chunked multistatus and error responses, so every
+/// response except file downloads is sent with Content-Length.
+pub(crate) async fn with_length(
+    method: &Method,
+    resp: Response<hyper::Body>,
+) -> Response<hyper::Body> {
+    if *method == Method::GET || resp.headers().contains_key(CONTENT_LENGTH) {
+        return resp;
+    }
+    let (mut parts, body) = resp.into_parts();
+    match hyper::body::to_bytes(body).await {
+        Ok(bytes) => {
+            parts.headers.insert(CONTENT_LENGTH, bytes.len().into());
+            Response::from_parts(parts, hyper::Body::from(bytes))
+        }
+        Err(e) => {
+            warn!(msg = "can't buffer response", err = %e);
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .header(CONTENT_LENGTH, 0)
+                .body(hyper::Body::empty())
+                .unwrap()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn windows_responses() -> anyhow::Result<()> {
+        let mut resp = Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(hyper::Body::empty())?;
+        windows_options("/", &mut resp);
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers()["MS-Author-Via"], "DAV");
+        assert_eq!(resp.headers()["DAV"], "1, 2");
+
+        let chunks: Vec<Result<_, std::io::Error>> =
+            vec![Ok("<multistatus>"), Ok("</multistatus>")];
+        let resp = Response::new(hyper::Body::wrap_stream(futures_util::stream::iter(chunks)));
+        let method = Method::from_bytes(b"PROPFIND")?;
+        let resp = with_length(&method, resp).await;
+        assert_eq!(resp.headers()[CONTENT_LENGTH], "27");
+        assert_eq!(
+            hyper::body::to_bytes(resp.into_body()).await?,
+            "<multistatus></multistatus>"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn finder_probes() {
+        let propfind = Method::from_bytes(b"PROPFIND").unwrap();
+        assert!(finder_probe(&propfind, "/files/.Trashes/").is_some());
+        assert!(finder_probe(&Method::GET, "/files/dir/.hidden").is_some());
+        assert!(finder_probe(&Method::PUT, "/files/dir/.hidden").is_none());
+        assert!(finder_probe(&Method::GET, "/files/dir/report.txt").is_none());
+    }
+}
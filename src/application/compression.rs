@@ -0,0 +1,303 @@
+use crate::{backend::mounts::innermost_mount, configuration::Compression};
+use bytes::Bytes;
+use flate2::write::GzEncoder;
+use futures_util::StreamExt;
+use hyper::{
+    header::{
+        HeaderValue, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, ETAG, RANGE,
+        VARY,
+    },
+    Method, Request, Response, StatusCode,
+};
+use std::io::Write;
+use tracing::warn;
+
+/// Types which are compressed already, compressing them again only costs
+/// cpu.
+const COMPRESSED: &[&str] = &[
+    "image/",
+    "video/",
+    "audio/",
+    "application/zip",
+    "application/gzip",
+    "application/x-gzip",
+    "application/zstd",
+    "application/x-7z-compressed",
+    "application/x-rar-compressed",
+    "application/x-bzip2",
+    "application/x-xz",
+    "application/pdf",
+    "font/woff",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Coding {
+    Zstd,
+    Gzip,
+}
+
+impl Coding {
+    fn parse(name: &str) -> Option<Coding> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "zstd" => Some(Coding::Zstd),
+            "gzip" => Some(Coding::Gzip),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Coding::Zstd => "zstd",
+            Coding::Gzip => "gzip",
+        }
+    }
+}
+
+/// Compression settings of mount.
+#[derive(Clone)]
+pub(crate) struct Rules {
+    mount: String,
+    /// in order of preference
+    codings: Vec<Coding>,
+    types: Vec<String>,
+    min_size: u64,
+    level: i32,
+}
+
+impl Rules {
+    pub fn new(mount: &str, conf: &Compression) -> anyhow::Result<Rules> {
+        let codings = conf
+            .encodings
+            .iter()
+            .map(|e| Coding::parse(e).ok_or_else(|| anyhow::anyhow!("unknown encoding {}", e)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Rules {
+            mount: mount.to_string(),
+            codings,
+            types: conf.types.clone(),
+            min_size: conf.min_size,
+            level: conf.level,
+        })
+    }
+
+    fn compresses(&self, content_type: &str) -> bool {
+        let t = content_type.to_ascii_lowercase();
+        if COMPRESSED.iter().any(|c| t.starts_with(c)) {
+            return false;
+        }
+        self.types.is_empty() || self.types.iter().any(|p| t.starts_with(p.as_str()))
+    }
+
+    /// Preferred coding of mount accepted by client, q=0 rejects coding.
+    fn choose(&self, accept: &str) -> Option<Coding> {
+        let accepted = accept
+            .split(',')
+            .filter_map(|item| {
+                let mut parts = item.split(';');
+                let coding = Coding::parse(parts.next()?)?;
+                let rejected = parts.any(|p| {
+                    let q = p
+                        .trim()
+                        .strip_prefix("q=")
+                        .and_then(|q| q.parse::<f32>().ok());
+                    q == Some(0.0)
+                });
+                Some(coding).filter(|_| !rejected)
+            })
+            .collect::<Vec<_>>();
+        self.codings.iter().copied().find(|c| accepted.contains(c))
+    }
+}
+
+/// Rules of the innermost mount with compression decoded request path
+/// belongs to.
+pub(crate) fn rules_for<'a>(path: &str, rules: &'a [Rules]) -> Option<&'a Rules> {
+    innermost_mount(path, rules, |r| r.mount.as_str())
+}
+
+/// Coding GET response to request will be compressed with, None for range
+/// requests and clients not accepting mount codings.
+pub(crate) fn negotiate<B>(req: &Request<B>, rules: &Rules) -> Option<Coding> {
+    if req.method() != Method::GET || req.headers().contains_key(RANGE) {
+        return None;
+    }
+    let accept = req.headers().get(ACCEPT_ENCODING)?.to_str().ok()?;
+    rules.choose(accept)
+}
+
+enum Encoder {
+    Zstd(zstd::stream::write::Encoder<'static, Vec<u8>>),
+    Gzip(GzEncoder<Vec<u8>>),
+}
+
+impl Encoder {
+    fn new(coding: Coding, level: i32) -> std::io::Result<Encoder> {
+        Ok(match coding {
+            Coding::Zstd => Encoder::Zstd(zstd::stream::write::Encoder::new(vec![], level)?),
+            Coding::Gzip => {
+                let level = flate2::Compression::new(level.clamp(0, 9) as u32);
+                Encoder::Gzip(GzEncoder::new(vec![], level))
+            }
+        })
+    }
+
+    /// Compresses `data`, returns output produced so far.
+    fn write(&mut self, data: &[u8]) -> std::io::Result<Bytes> {
+        let out = match self {
+            Encoder::Zstd(e) => {
+                e.write_all(data)?;
+                e.flush()?;
+                e.get_mut()
+            }
+            Encoder::Gzip(e) => {
+                e.write_all(data)?;
+                e.flush()?;
+                e.get_mut()
+            }
+        };
+        Ok(Bytes::from(std::mem::take(out)))
+    }
+
+    fn finish(self) -> std::io::Result<Bytes> {
+        Ok(Bytes::from(match self {
+            Encoder::Zstd(e) => e.finish()?,
+            Encoder::Gzip(e) => e.finish()?,
+        }))
+    }
+}
+
+/// Compresses successful response body if its type and size are worth it.
+/// Content-Length is dropped and strong ETag is weakened, as content
+/// differs from stored one.
+pub(crate) fn apply(
+    resp: Response<hyper::Body>,
+    coding: Coding,
+    rules: &Rules,
+) -> Response<hyper::Body> {
+    let headers = resp.headers();
+    if resp.status() != StatusCode::OK || headers.contains_key(CONTENT_ENCODING) {
+        return resp;
+    }
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    if !rules.compresses(content_type) {
+        return resp;
+    }
+    let len = headers
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if len.map_or(false, |l| l < rules.min_size) {
+        return resp;
+    }
+    let mut encoder = match Encoder::new(coding, rules.level) {
+        Ok(e) => e,
+        Err(e) => {
+            warn!("can't create {} encoder: {}", coding.name(), e);
+            return resp;
+        }
+    };
+    let (mut parts, body) = resp.into_parts();
+    parts.headers.remove(CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(CONTENT_ENCODING, HeaderValue::from_static(coding.name()));
+    parts
+        .headers
+        .append(VARY, HeaderValue::from_static("Accept-Encoding"));
+    let weak = parts
+        .headers
+        .get(ETAG)
+        .and_then(|v| v.to_str().ok())
+        .filter(|e| !e.starts_with("W/"))
+        .and_then(|e| HeaderValue::from_str(&format!("W/{}", e)).ok());
+    if let Some(v) = weak {
+        parts.headers.insert(ETAG, v);
+    }
+    let body = async_stream::stream! {
+        let mut body = body;
+        while let Some(chunk) = body.next().await {
+            let chunk = match chunk {
+                Ok(c) => c,
+                Err(e) => {
+                    yield Err(std::io::Error::new(std::io::ErrorKind::Other, e));
+                    return;
+                }
+            };
+            match encoder.write(&chunk) {
+                Ok(out) if out.is_empty() => {}
+                r => yield r,
+            }
+        }
+        yield encoder.finish();
+    };
+    Response::from_parts(parts, hyper::Body::wrap_stream(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(types: &[&str]) -> Rules {
+        let conf = Compression {
+            encodings: vec!["zstd".into(), "gzip".into()],
+            types: types.iter().map(|t| t.to_string()).collect(),
+            min_size: 4,
+            level: 3,
+        };
+        Rules::new("/docs", &conf).unwrap()
+    }
+
+    #[test]
+    fn chooses_accepted_coding() {
+        let r = rules(&[]);
+        assert_eq!(r.choose("gzip, deflate, br"), Some(Coding::Gzip));
+        assert_eq!(r.choose("gzip;q=0.5, zstd"), Some(Coding::Zstd));
+        assert_eq!(r.choose("zstd;q=0, gzip"), Some(Coding::Gzip));
+        assert_eq!(r.choose("identity"), None);
+        assert!(r.compresses("text/plain; charset=utf-8"));
+        assert!(!r.compresses("image/png"));
+        assert!(!rules(&["text/"]).compresses("application/json"));
+    }
+
+    #[tokio::test]
+    async fn compresses_body() -> anyhow::Result<()> {
+        let r = rules(&["text/"]);
+        let req = |range: bool| {
+            let mut b = Request::get("/docs/a.txt").header(ACCEPT_ENCODING, "gzip");
+            if range {
+                b = b.header(RANGE, "bytes=0-1");
+            }
+            b.body(()).unwrap()
+        };
+        assert!(negotiate(&req(true), &r).is_none());
+        let coding = negotiate(&req(false), &r).unwrap();
+
+        let text = "text ".repeat(100);
+        let resp = Response::builder()
+            .header(CONTENT_TYPE, "text/plain")
+            .header(CONTENT_LENGTH, text.len())
+            .header(ETAG, "\"abc\"")
+            .body(hyper::Body::from(text.clone()))?;
+        let resp = apply(resp, coding, &r);
+        assert_eq!(resp.headers()[CONTENT_ENCODING], "gzip");
+        assert_eq!(resp.headers()[ETAG], "W/\"abc\"");
+        assert!(!resp.headers().contains_key(CONTENT_LENGTH));
+        let body = hyper::body::to_bytes(resp.into_body()).await?;
+        assert!(body.len() < text.len());
+        let mut decoded = String::new();
+        std::io::Read::read_to_string(&mut flate2::read::GzDecoder::new(&body[..]), &mut decoded)?;
+        assert_eq!(decoded, text);
+
+        let small = Response::builder()
+            .header(CONTENT_TYPE, "text/plain")
+            .header(CONTENT_LENGTH, 2)
+            .body(hyper::Body::from("ab"))?;
+        assert!(!apply(small, coding, &r)
+            .headers()
+            .contains_key(CONTENT_ENCODING));
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use hyper::{header::CONTENT_TYPE, Response};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    net::{IpAddr, SocketAddr},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+
+struct Conn {
+    peer: SocketAddr,
+    opened: Instant,
+    requests: AtomicU64,
+    active: AtomicUsize,
+    /// end of last request, or opening while there was none
+    idle_since: Mutex<Instant>,
+}
+
+/// Open client connections, for finding clients holding many of them.
+pub(crate) struct Connections {
+    /// connections one address may keep open, unlimited if not set
+    per_ip: Option<usize>,
+    next: AtomicU64,
+    open: Mutex<BTreeMap<u64, Arc<Conn>>>,
+}
+
+/// Connection listed while it is kept, requests on it are reported through it.
+pub(crate) struct Tracked {
+    connections: Arc<Connections>,
+    id: u64,
+    conn: Arc<Conn>,
+}
+
+impl Tracked {
+    pub fn request_started(&self) {
+        self.conn.requests.fetch_add(1, Ordering::Relaxed);
+        self.conn.active.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn request_finished(&self) {
+        *self.conn.idle_since.lock().unwrap() = Instant::now();
+        self.conn.active.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.connections.open.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl Connections {
+    pub fn new(per_ip: Option<usize>) -> Arc<Connections> {
+        Arc::new(Connections {
+            per_ip,
+            next: AtomicU64::new(0),
+            open: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    /// None if address of `peer` has as many connections as allowed.
+    pub fn open(self: &Arc<Self>, peer: SocketAddr) -> Option<Tracked> {
+        let mut open = self.open.lock().unwrap();
+        if let Some(max) = self.per_ip {
+            let same = open.values().filter(|c| c.peer.ip() == peer.ip()).count();
+            if same >= max {
+                return None;
+            }
+        }
+        let id = self.next.fetch_add(1, Ordering::Relaxed);
+        let now = Instant::now();
+        let conn = Arc::new(Conn {
+            peer,
+            opened: now,
+            requests: AtomicU64::new(0),
+            active: AtomicUsize::new(0),
+            idle_since: Mutex::new(now),
+        });
+        open.insert(id, conn.clone());
+        Some(Tracked {
+            connections: self.clone(),
+            id,
+            conn,
+        })
+    }
+
+    /// Counts per address, most connections first, then one line per
+    /// connection, oldest first. Idle is 0 while a request is handled.
+    pub fn render(&self) -> String {
+        let open = self.open.lock().unwrap();
+        let mut per_ip: HashMap<IpAddr, usize> = HashMap::new();
+        for c in open.values() {
+            *per_ip.entry(c.peer.ip()).or_default() += 1;
+        }
+        let mut per_ip = per_ip.into_iter().collect::<Vec<_>>();
+        per_ip.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let mut out = String::new();
+        let _ = writeln!(out, "open={}", open.len());
+        for (ip, n) in per_ip {
+            let _ = writeln!(out, "ip={} connections={}", ip, n);
+        }
+        for c in open.values() {
+            let active = c.active.load(Ordering::Relaxed);
+            let idle = if active > 0 {
+                0
+            } else {
+                c.idle_since.lock().unwrap().elapsed().as_secs()
+            };
+            let _ = writeln!(
+                out,
+                "connection peer={} age={}s requests={} active={} idle={}s",
+                c.peer,
+                c.opened.elapsed().as_secs(),
+                c.requests.load(Ordering::Relaxed),
+                active,
+                idle
+            );
+        }
+        out
+    }
+
+    pub fn response(&self) -> Response<hyper::Body> {
+        Response::builder()
+            .header(CONTENT_TYPE, "text/plain")
+            .body(self.render().into())
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_connections_per_ip() -> anyhow::Result<()> {
+        let connections = Connections::new(Some(2));
+        let a = connections.open("10.0.0.1:1000".parse()?).unwrap();
+        let _b = connections.open("10.0.0.1:1001".parse()?).unwrap();
+        let _c = connections.open("10.0.0.2:1000".parse()?).unwrap();
+        assert!(connections.open("10.0.0.1:1002".parse()?).is_none());
+
+        a.request_started();
+        let out = connections.render();
+        assert!(out.starts_with("open=3\nip=10.0.0.1 connections=2\nip=10.0.0.2 connections=1\n"));
+        assert!(out.contains("connection peer=10.0.0.1:1000 age=0s requests=1 active=1 idle=0s\n"));
+        a.request_finished();
+
+        drop(a);
+        assert!(connections.open("10.0.0.1:1002".parse()?).is_some());
+        Ok(())
+    }
+}
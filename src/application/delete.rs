@@ -0,0 +1,191 @@
+use crate::backend::{walk::walk, xml::escape};
+use hyper::{Request, Response, StatusCode};
+use tracing::{error, info};
+use webdav_handler::{
+    body::Body,
+    davpath::DavPath,
+    fs::{DavFileSystem, FsError},
+    ls::DavLockSystem,
+};
+
+/// Files removed at once.
+const PARALLEL: usize = 16;
+/// Removed files between progress messages.
+const PROGRESS_EVERY: usize = 1000;
+
+fn status(e: FsError) -> StatusCode {
+    match e {
+        FsError::Forbidden => StatusCode::FORBIDDEN,
+        FsError::NotImplemented => StatusCode::NOT_IMPLEMENTED,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Multistatus listing paths which were not removed.
+fn render(failed: &[(DavPath, StatusCode)]) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<D:multistatus xmlns:D=\"DAV:\">",
+    );
+    for (path, status) in failed {
+        xml.push_str(&format!(
+            "<D:response><D:href>{}</D:href><D:status>HTTP/1.1 {}</D:status></D:response>",
+            escape(&path.as_url_string()),
+            status
+        ));
+    }
+    xml.push_str("</D:multistatus>\n");
+    xml
+}
+
+fn is_within(path: &DavPath, dir: &DavPath) -> bool {
+    path.as_url_string().starts_with(&dir.as_url_string())
+}
+
+/// Collection whose content this handles, None for requests left to dav
+/// handler: files, conditional requests and trees with locks, which it
+/// checks tokens of.
+async fn target<B>(
+    fs: &dyn DavFileSystem,
+    ls: Option<&dyn DavLockSystem>,
+    req: &Request<B>,
+) -> Option<DavPath> {
+    let conditional = ["if", "if-match", "if-none-match", "if-unmodified-since"];
+    if conditional.iter().any(|h| req.headers().contains_key(*h)) {
+        return None;
+    }
+    let depth = req.headers().get("Depth").and_then(|v| v.to_str().ok());
+    if !matches!(depth, None | Some("infinity")) {
+        return None;
+    }
+    let path = DavPath::new(req.uri().path()).ok()?;
+    if path.as_url_string() == "/" || !fs.metadata(&path).await.ok()?.is_dir() {
+        return None;
+    }
+    if let Some(ls) = ls {
+        ls.check(&path, None, true, true, vec![]).ok()?;
+    }
+    let url = path.as_url_string();
+    if url.ends_with('/') {
+        return Some(path);
+    }
+    DavPath::new(&format!("{}/", url)).ok()
+}
+
+/// Removes collection with everything in it. Tree is listed first, then
+/// files are removed `PARALLEL` at a time and directories deepest first.
+/// Paths which fail are reported in multistatus, directories containing
+/// them are kept. None if request is left to dav handler.
+pub(crate) async fn handle<B>(
+    fs: Box<dyn DavFileSystem>,
+    ls: Option<&dyn DavLockSystem>,
+    req: &Request<B>,
+) -> Option<Response<Body>> {
+    let root = target(fs.as_ref(), ls, req).await?;
+    let entries = match walk(fs.as_ref(), &root).await {
+        Ok(e) => e,
+        Err(e) => return Some(empty(status(e))),
+    };
+    let (dirs, files): (Vec<_>, Vec<_>) = entries.into_iter().partition(|(_, m)| m.is_dir());
+    let total = files.len();
+    info!(msg = "deleting tree", path = %root, files = total, dirs = dirs.len());
+
+    let mut failed = vec![];
+    let mut removed = 0;
+    let mut report = PROGRESS_EVERY;
+    for chunk in files.chunks(PARALLEL) {
+        let tasks = chunk
+            .iter()
+            .map(|(path, _)| {
+                let (fs, path) = (fs.clone(), path.clone());
+                tokio::spawn(async move { fs.remove_file(&path).await })
+            })
+            .collect::<Vec<_>>();
+        for ((path, _), task) in chunk.iter().zip(tasks) {
+            match task.await {
+                // removed by someone else meanwhile
+                Ok(Ok(())) | Ok(Err(FsError::NotFound)) => removed += 1,
+                Ok(Err(e)) => failed.push((path.clone(), status(e))),
+                Err(e) => {
+                    error!(msg = "removing file panicked", path = %path, err = %e);
+                    failed.push((path.clone(), StatusCode::INTERNAL_SERVER_ERROR));
+                }
+            }
+        }
+        if removed >= report {
+            info!(msg = "deleting tree", path = %root, removed, total);
+            report += PROGRESS_EVERY;
+        }
+    }
+
+    let mut dirs = dirs.into_iter().map(|(p, _)| p).collect::<Vec<_>>();
+    dirs.sort_by_key(|p| std::cmp::Reverse(p.as_url_string().matches('/').count()));
+    for dir in dirs {
+        if failed.iter().any(|(p, _)| is_within(p, &dir)) {
+            continue;
+        }
+        match fs.remove_dir(&dir).await {
+            Ok(()) | Err(FsError::NotFound) => {}
+            Err(e) => failed.push((dir, status(e))),
+        }
+    }
+    info!(msg = "deleted tree", path = %root, removed, failed = failed.len());
+
+    if !failed.is_empty() {
+        return Some(
+            Response::builder()
+                .status(StatusCode::MULTI_STATUS)
+                .header("Content-Type", "application/xml; charset=utf-8")
+                .body(Body::from(render(&failed)))
+                .unwrap(),
+        );
+    }
+    Some(match fs.remove_dir(&root).await {
+        Ok(()) => empty(StatusCode::NO_CONTENT),
+        Err(e) => empty(status(e)),
+    })
+}
+
+fn empty(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::put;
+    use webdav_handler::{memfs::MemFs, memls::MemLs};
+
+    fn path(p: &str) -> DavPath {
+        DavPath::new(p).unwrap()
+    }
+
+    #[tokio::test]
+    async fn deletes_tree() -> anyhow::Result<()> {
+        let fs = MemFs::new();
+        fs.create_dir(&path("/dir/")).await?;
+        fs.create_dir(&path("/dir/sub/")).await?;
+        for f in ["/dir/a.txt", "/dir/sub/b.txt", "/file.txt"].iter() {
+            put(fs.as_ref(), f, b"").await?;
+        }
+        let delete = |uri: &str| Request::delete(uri).body(()).unwrap();
+
+        assert!(handle(fs.clone(), None, &delete("/file.txt"))
+            .await
+            .is_none());
+        let ls = MemLs::new();
+        let resp = handle(fs.clone(), Some(ls.as_ref()), &delete("/dir/"))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert!(fs.metadata(&path("/dir/")).await.is_err());
+        assert!(fs.metadata(&path("/file.txt")).await.is_ok());
+
+        let failed = vec![(path("/dir/a.txt"), StatusCode::FORBIDDEN)];
+        assert!(render(&failed)
+            .contains("<D:href>/dir/a.txt</D:href><D:status>HTTP/1.1 403 Forbidden</D:status>"));
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+use crate::configuration;
+use bytes::Bytes;
+use chrono::Utc;
+use hyper::{header::CONTENT_TYPE, http::HeaderMap, Request, Response};
+use std::{
+    fmt::Write,
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use tokio::{fs::OpenOptions, io::AsyncWriteExt, sync::Mutex};
+use tracing::warn;
+
+/// Headers with credentials, their values are not written.
+const REDACTED: &[&str] = &[
+    "authorization",
+    "cookie",
+    "proxy-authorization",
+    "set-cookie",
+];
+
+/// Methods which bodies are XML even if client doesn't say so.
+const XML_METHODS: &[&str] = &["PROPFIND", "PROPPATCH", "LOCK", "REPORT", "SEARCH"];
+
+/// Writes headers and XML bodies of sampled requests and their responses to
+/// file, in the format of `curl -v`. File is reopened for every exchange so
+/// it can be rotated externally.
+pub(crate) struct Dump {
+    path: PathBuf,
+    every: u64,
+    bodies: bool,
+    max_body: usize,
+    count: AtomicU64,
+    // keeps exchanges from interleaving
+    write: Mutex<()>,
+}
+
+fn is_xml(headers: &HeaderMap, method: Option<&str>) -> bool {
+    match headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok()) {
+        Some(ct) => ct.contains("xml"),
+        None => method.map_or(false, |m| XML_METHODS.contains(&m)),
+    }
+}
+
+impl Dump {
+    pub fn new(conf: &configuration::Dump) -> Self {
+        Dump {
+            path: PathBuf::from(&conf.path),
+            every: conf.every.max(1),
+            bodies: conf.bodies,
+            max_body: conf.max_body,
+            count: AtomicU64::new(0),
+            write: Mutex::new(()),
+        }
+    }
+
+    /// Number of request if it is dumped.
+    pub fn sample(&self) -> Option<u64> {
+        let n = self.count.fetch_add(1, Ordering::Relaxed);
+        if n % self.every == 0 {
+            Some(n + 1)
+        } else {
+            None
+        }
+    }
+
+    fn headers(&self, out: &mut String, prefix: char, headers: &HeaderMap) {
+        for (k, v) in headers {
+            let v = if REDACTED.contains(&k.as_str()) {
+                "<redacted>"
+            } else {
+                v.to_str().unwrap_or("<binary>")
+            };
+            let _ = writeln!(out, "{} {}: {}", prefix, k, v);
+        }
+        let _ = writeln!(out, "{}", prefix);
+    }
+
+    fn body(&self, out: &mut String, prefix: char, body: &Bytes) {
+        let shown = &body[..body.len().min(self.max_body)];
+        for line in String::from_utf8_lossy(shown).lines() {
+            let _ = writeln!(out, "{} {}", prefix, line);
+        }
+        if body.len() > shown.len() {
+            let _ = writeln!(
+                out,
+                "{} ... {} bytes more",
+                prefix,
+                body.len() - shown.len()
+            );
+        }
+    }
+
+    /// Renders request, XML body is read to be written and put back.
+    pub async fn request(
+        &self,
+        n: u64,
+        req: Request<hyper::Body>,
+    ) -> (Request<hyper::Body>, String) {
+        let mut out = format!("=== #{} {}\n", n, Utc::now().to_rfc3339());
+        let _ = writeln!(out, "> {} {} {:?}", req.method(), req.uri(), req.version());
+        self.headers(&mut out, '>', req.headers());
+        if !self.bodies || !is_xml(req.headers(), Some(req.method().as_str())) {
+            return (req, out);
+        }
+        let (parts, body) = req.into_parts();
+        let body = match hyper::body::to_bytes(body).await {
+            Ok(b) => b,
+            Err(e) => {
+                // handler gets the same error reading empty body
+                let _ = writeln!(out, "> <can't read body: {}>", e);
+                Bytes::new()
+            }
+        };
+        self.body(&mut out, '>', &body);
+        (Request::from_parts(parts, hyper::Body::from(body)), out)
+    }
+
+    /// Renders response after request rendered by `request` and writes
+    /// both, XML body is read to be written and put back.
+    pub async fn response(
+        &self,
+        mut out: String,
+        resp: Response<hyper::Body>,
+    ) -> Response<hyper::Body> {
+        let _ = writeln!(out, "< {:?} {}", resp.version(), resp.status());
+        self.headers(&mut out, '<', resp.headers());
+        let resp = if self.bodies && is_xml(resp.headers(), None) {
+            let (parts, body) = resp.into_parts();
+            match hyper::body::to_bytes(body).await {
+                Ok(b) => {
+                    self.body(&mut out, '<', &b);
+                    Response::from_parts(parts, hyper::Body::from(b))
+                }
+                Err(e) => {
+                    let _ = writeln!(out, "< <can't read body: {}>", e);
+                    Response::from_parts(parts, hyper::Body::empty())
+                }
+            }
+        } else {
+            resp
+        };
+        out.push('\n');
+        if let Err(e) = self.write(out.as_bytes()).await {
+            warn!(msg = "can't write dump", path = %self.path.display(), err = %e);
+        }
+        resp
+    }
+
+    async fn write(&self, data: &[u8]) -> std::io::Result<()> {
+        let _guard = self.write.lock().await;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(data).await?;
+        file.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::AUTHORIZATION;
+
+    #[tokio::test]
+    async fn dumps_sampled_exchanges() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("webdav_ss_dump_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let dump = Dump::new(&configuration::Dump {
+            path: path.to_string_lossy().into_owned(),
+            every: 2,
+            bodies: true,
+            max_body: 10,
+        });
+        assert_eq!(dump.sample(), Some(1));
+        assert_eq!(dump.sample(), None);
+        assert_eq!(dump.sample(), Some(3));
+
+        let req = Request::builder()
+            .method("PROPFIND")
+            .uri("/files/")
+            .header(AUTHORIZATION, "Basic c2VjcmV0")
+            .body(hyper::Body::from("<propfind/>"))?;
+        let (req, out) = dump.request(1, req).await;
+        assert_eq!(hyper::body::to_bytes(req.into_body()).await?, "<propfind/>");
+        let resp = Response::builder()
+            .status(207)
+            .header(CONTENT_TYPE, "application/xml")
+            .body(hyper::Body::from("<multistatus></multistatus>"))?;
+        let resp = dump.response(out, resp).await;
+        assert_eq!(
+            hyper::body::to_bytes(resp.into_body()).await?,
+            "<multistatus></multistatus>"
+        );
+
+        let text = std::fs::read_to_string(&path)?;
+        assert!(text.contains("> PROPFIND /files/ HTTP/1.1"));
+        assert!(text.contains("> authorization: <redacted>"));
+        assert!(text.contains("> <propfind/"));
+        assert!(text.contains("< <multistat\n< ... 17 bytes more"));
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use hyper::{
+    header::{HeaderValue, CONTENT_LENGTH, CONTENT_TYPE},
+    Response, StatusCode,
+};
+use tracing::debug;
+use webdav_handler::body::Body;
+
+/// Namespace of conditions without DAV counterpart.
+const NAMESPACE: &str = "urn:webdav_ss:";
+
+/// Error body with precondition or postcondition code, RFC 4918 section 16.
+fn condition_body(status: StatusCode) -> Option<String> {
+    let condition = match status {
+        StatusCode::INSUFFICIENT_STORAGE => "<D:quota-not-exceeded/>".to_string(),
+        StatusCode::LOCKED => "<D:no-conflicting-lock/>".to_string(),
+        StatusCode::BAD_GATEWAY => format!("<S:backend-unavailable xmlns:S=\"{}\"/>", NAMESPACE),
+        _ => return None,
+    };
+    Some(format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<D:error xmlns:D=\"DAV:\">{}</D:error>\n",
+        condition
+    ))
+}
+
+/// Error with plain text reason, for requests served without dav handler.
+pub(crate) fn error(status: StatusCode, reason: &str) -> Response<Body> {
+    debug!(msg = "request failed", status = %status, reason = reason);
+    Response::builder()
+        .status(status)
+        .body(Body::from(reason.to_string()))
+        .unwrap()
+}
+
+/// Backend failures surface from handler as 500, on mounts backed by remote
+/// storage they mean storage can't be reached and are reported as 502.
+/// With `xml` error responses without body of their own get one naming
+/// failed condition.
+pub(crate) fn map<B: From<String>>(mut resp: Response<B>, remote: bool, xml: bool) -> Response<B> {
+    if remote && resp.status() == StatusCode::INTERNAL_SERVER_ERROR {
+        *resp.status_mut() = StatusCode::BAD_GATEWAY;
+    }
+    // handler sets content type when it sends body
+    if !xml || resp.headers().contains_key(CONTENT_TYPE) {
+        return resp;
+    }
+    let body = match condition_body(resp.status()) {
+        Some(b) => b,
+        None => return resp,
+    };
+    let (mut parts, _) = resp.into_parts();
+    parts.headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("application/xml; charset=utf-8"),
+    );
+    parts.headers.insert(CONTENT_LENGTH, body.len().into());
+    Response::from_parts(parts, B::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(s: StatusCode) -> Response<hyper::Body> {
+        Response::builder()
+            .status(s)
+            .body(hyper::Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn maps_failures() -> anyhow::Result<()> {
+        let resp = map(status(StatusCode::INTERNAL_SERVER_ERROR), false, true);
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let resp = map(status(StatusCode::INTERNAL_SERVER_ERROR), true, false);
+        assert_eq!(resp.status(), StatusCode::BAD_GATEWAY);
+        assert!(!resp.headers().contains_key(CONTENT_TYPE));
+
+        let resp = map(status(StatusCode::INTERNAL_SERVER_ERROR), true, true);
+        let body = hyper::body::to_bytes(resp.into_body()).await?;
+        assert!(std::str::from_utf8(&body)?.contains("<S:backend-unavailable"));
+        let resp = map(status(StatusCode::INSUFFICIENT_STORAGE), false, true);
+        let body = hyper::body::to_bytes(resp.into_body()).await?;
+        assert!(std::str::from_utf8(&body)?.contains("<D:quota-not-exceeded/>"));
+
+        let mut resp = status(StatusCode::LOCKED);
+        resp.headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/xml"));
+        let resp = map(resp, false, true);
+        assert!(hyper::body::to_bytes(resp.into_body()).await?.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,270 @@
+use crate::backend::{mounts, wrappers::path_map::ENC};
+use futures_util::{future::BoxFuture, FutureExt};
+use hyper::{Request, Response, StatusCode, Uri};
+use percent_encoding::percent_encode;
+use serde::{Deserialize, Serialize};
+use std::{process::Stdio, sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines},
+    process::{Child, ChildStdin, ChildStdout, Command},
+    sync::Mutex,
+};
+use tracing::{debug, error};
+
+/// Request as seen by filter. Paths are percent-decoded, so a rule for
+/// `/secret` also covers `/%73ecret`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FilterRequest {
+    pub method: String,
+    pub path: String,
+    /// path of Destination header of MOVE and COPY
+    pub destination: Option<String>,
+    pub user: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum Decision {
+    Allow,
+    Deny {
+        #[serde(default = "default_deny_status")]
+        status: u16,
+    },
+    /// serve request as if it was made to another decoded path
+    Rewrite {
+        path: String,
+    },
+}
+
+fn default_deny_status() -> u16 {
+    403
+}
+
+/// Site policy checked before request is handled.
+pub trait RequestFilter: Send + Sync {
+    fn check<'a>(&'a self, req: &'a FilterRequest) -> BoxFuture<'a, Decision>;
+}
+
+/// Applies decision of `filter` to request, Err is response for denied one.
+pub(crate) async fn apply<B>(
+    filter: &Arc<dyn RequestFilter>,
+    mut req: Request<B>,
+    user: Option<String>,
+) -> Result<Request<B>, Response<hyper::Body>> {
+    let destination = req
+        .headers()
+        .get("Destination")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<Uri>().ok())
+        .map(|u| mounts::decode(u.path()).into_owned());
+    let check = FilterRequest {
+        method: req.method().to_string(),
+        path: mounts::decode(req.uri().path()).into_owned(),
+        destination,
+        user,
+    };
+    match filter.check(&check).await {
+        Decision::Allow => Ok(req),
+        Decision::Deny { status } => {
+            debug!(msg = "request denied by filter", path = %check.path, status = status);
+            let status = StatusCode::from_u16(status).unwrap_or(StatusCode::FORBIDDEN);
+            Err(Response::builder()
+                .status(status)
+                .body(hyper::Body::empty())
+                .unwrap())
+        }
+        Decision::Rewrite { path } => {
+            let path = percent_encode(path.as_bytes(), ENC).to_string();
+            let uri = match req.uri().query() {
+                Some(q) => format!("{}?{}", path, q),
+                None => path,
+            };
+            match uri.parse::<Uri>() {
+                Ok(uri) => {
+                    debug!(msg = "request rewritten by filter", from = %check.path, to = %uri);
+                    *req.uri_mut() = uri;
+                    Ok(req)
+                }
+                Err(_) => {
+                    error!(msg = "filter returned invalid path", path = %uri);
+                    Err(Response::builder()
+                        .status(StatusCode::INTERNAL_SERVER_ERROR)
+                        .body(hyper::Body::empty())
+                        .unwrap())
+                }
+            }
+        }
+    }
+}
+
+struct Process {
+    // killed when dropped
+    _child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+}
+
+/// Filter implemented by external program. It is started once and gets one
+/// JSON request per line on stdin, answering each with a line of JSON
+/// decision, e.g. `{"action": "deny", "status": 404}`. Program is restarted
+/// if it exits or answers garbage, requests are denied meanwhile.
+pub struct ScriptFilter {
+    command: Vec<String>,
+    timeout: Duration,
+    process: Mutex<Option<Process>>,
+}
+
+impl ScriptFilter {
+    pub fn new(command: Vec<String>, timeout: Duration) -> anyhow::Result<Self> {
+        if command.is_empty() {
+            return Err(anyhow::anyhow!("filter command is empty"));
+        }
+        Ok(ScriptFilter {
+            command,
+            timeout,
+            process: Mutex::new(None),
+        })
+    }
+
+    fn spawn(&self) -> anyhow::Result<Process> {
+        let mut child = Command::new(&self.command[0])
+            .args(&self.command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap()).lines();
+        Ok(Process {
+            _child: child,
+            stdin,
+            stdout,
+        })
+    }
+
+    async fn ask(&self, req: &FilterRequest) -> anyhow::Result<Decision> {
+        let mut process = self.process.lock().await;
+        if process.is_none() {
+            *process = Some(self.spawn()?);
+        }
+        let p = process.as_mut().unwrap();
+        let mut line = serde_json::to_string(req)?;
+        line.push('\n');
+        let answer = tokio::time::timeout(self.timeout, async {
+            p.stdin.write_all(line.as_bytes()).await?;
+            p.stdin.flush().await?;
+            p.stdout.next_line().await
+        })
+        .await;
+        let decision = match answer {
+            Ok(Ok(Some(l))) => serde_json::from_str(&l).map_err(anyhow::Error::from),
+            Ok(Ok(None)) => Err(anyhow::anyhow!("filter exited")),
+            Ok(Err(e)) => Err(e.into()),
+            Err(_) => Err(anyhow::anyhow!("filter timed out")),
+        };
+        if decision.is_err() {
+            // answers of restarted program match requests again
+            *process = None;
+        }
+        decision
+    }
+}
+
+impl RequestFilter for ScriptFilter {
+    fn check<'a>(&'a self, req: &'a FilterRequest) -> BoxFuture<'a, Decision> {
+        async move {
+            self.ask(req).await.unwrap_or_else(|e| {
+                error!(msg = "request filter failed", err = %e);
+                Decision::Deny { status: 503 }
+            })
+        }
+        .boxed()
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn request(path: &str) -> FilterRequest {
+        FilterRequest {
+            method: "GET".into(),
+            path: path.into(),
+            destination: None,
+            user: None,
+        }
+    }
+
+    struct DenySecret;
+
+    impl RequestFilter for DenySecret {
+        fn check<'a>(&'a self, req: &'a FilterRequest) -> BoxFuture<'a, Decision> {
+            let mut paths = std::iter::once(&req.path).chain(req.destination.iter());
+            let decision = if paths.any(|p| p.starts_with("/secret")) {
+                Decision::Deny { status: 403 }
+            } else if req.path == "/old file" {
+                Decision::Rewrite {
+                    path: "/new file".into(),
+                }
+            } else {
+                Decision::Allow
+            };
+            futures_util::future::ready(decision).boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn applies_to_decoded_paths() {
+        let f: Arc<dyn RequestFilter> = Arc::new(DenySecret);
+        let req = |method, uri, dest: Option<&str>| {
+            let mut b = Request::builder().method(method).uri(uri);
+            if let Some(d) = dest {
+                b = b.header("Destination", d);
+            }
+            b.body(()).unwrap()
+        };
+
+        let denied = apply(&f, req("GET", "/%73ecret/a.txt", None), None).await;
+        assert_eq!(denied.unwrap_err().status(), StatusCode::FORBIDDEN);
+        let moved = req("MOVE", "/a.txt", Some("http://localhost/%73ecret/a.txt"));
+        assert!(apply(&f, moved, None).await.is_err());
+        let moved = req("MOVE", "/a.txt", Some("http://localhost/b.txt"));
+        assert!(apply(&f, moved, None).await.is_ok());
+        let rewritten = apply(&f, req("GET", "/old%20file?x=1", None), None).await;
+        assert_eq!(rewritten.unwrap().uri(), "/new%20file?x=1");
+    }
+
+    #[tokio::test]
+    async fn script_decisions() -> anyhow::Result<()> {
+        let script = r#"while read l; do
+            case "$l" in
+                *secret*) echo '{"action": "deny", "status": 404}' ;;
+                *old*) echo '{"action": "rewrite", "path": "/new"}' ;;
+                *crash*) exit 1 ;;
+                *) echo '{"action": "allow"}' ;;
+            esac
+        done"#;
+        let f = ScriptFilter::new(
+            vec!["sh".into(), "-c".into(), script.into()],
+            Duration::from_secs(5),
+        )?;
+        assert_eq!(f.check(&request("/a.txt")).await, Decision::Allow);
+        assert_eq!(
+            f.check(&request("/secret.txt")).await,
+            Decision::Deny { status: 404 }
+        );
+        assert_eq!(
+            f.check(&request("/old")).await,
+            Decision::Rewrite {
+                path: "/new".into()
+            }
+        );
+        assert_eq!(
+            f.check(&request("/crash")).await,
+            Decision::Deny { status: 503 }
+        );
+        // restarted after exit
+        assert_eq!(f.check(&request("/a.txt")).await, Decision::Allow);
+        Ok(())
+    }
+}
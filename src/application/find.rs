@@ -0,0 +1,79 @@
+use crate::backend::wrappers::index::Index;
+use hyper::{header::CONTENT_TYPE, Method, Request, Response, StatusCode};
+use std::sync::Arc;
+use tracing::debug;
+
+/// Search endpoint over index of all mounts: `GET <endpoint>?q=words` with
+/// optional `scope` (href prefix) and `limit`, answered with JSON list of
+/// matching entries.
+pub(crate) struct Find {
+    endpoint: String,
+    index: Arc<Index>,
+    max_results: usize,
+}
+
+/// Value of query parameter, `+` and percent-encoding are decoded if `decode`.
+pub(super) fn param(query: &str, name: &str, decode: bool) -> Option<String> {
+    query.split('&').find_map(|kv| {
+        let mut it = kv.splitn(2, '=');
+        if it.next()? != name {
+            return None;
+        }
+        let v = it.next().unwrap_or("");
+        if !decode {
+            return Some(v.to_string());
+        }
+        let v = v.replace('+', " ");
+        Some(
+            percent_encoding::percent_decode_str(&v)
+                .decode_utf8_lossy()
+                .into_owned(),
+        )
+    })
+}
+
+impl Find {
+    pub fn new(endpoint: &str, index: Arc<Index>, max_results: usize) -> Self {
+        Find {
+            endpoint: endpoint.to_string(),
+            index,
+            max_results,
+        }
+    }
+
+    pub fn handles<B>(&self, req: &Request<B>) -> bool {
+        req.method() == Method::GET && req.uri().path() == self.endpoint
+    }
+
+    pub fn handle<B>(&self, req: &Request<B>) -> Response<hyper::Body> {
+        let query = req.uri().query().unwrap_or("");
+        let words = param(query, "q", true).unwrap_or_default();
+        // hrefs in index are percent-encoded as well
+        let scope = param(query, "scope", false).unwrap_or_else(|| "/".into());
+        let limit = param(query, "limit", false)
+            .and_then(|l| l.parse().ok())
+            .unwrap_or(self.max_results)
+            .min(self.max_results);
+        let hits = self.index.search(&words, &scope, limit);
+        debug!(msg = "search", query = %words, scope = %scope, found = hits.len());
+        Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, "application/json")
+            .body(hyper::Body::from(serde_json::to_vec(&hits).unwrap()))
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params() {
+        let q = "q=tax+report%21&scope=/a%20b/&limit=5";
+        assert_eq!(param(q, "q", true).as_deref(), Some("tax report!"));
+        assert_eq!(param(q, "scope", false).as_deref(), Some("/a%20b/"));
+        assert_eq!(param(q, "limit", false).as_deref(), Some("5"));
+        assert_eq!(param(q, "other", true), None);
+    }
+}
@@ -0,0 +1,320 @@
+use super::errors::error;
+use crate::backend::open_opts;
+use crate::backend::{
+    mounts::{self, in_mount},
+    walk::child_path,
+    xml::escape,
+};
+use futures_util::{Future, StreamExt};
+use hyper::{
+    header::{HeaderValue, CONTENT_TYPE},
+    Request, Response, StatusCode,
+};
+use webdav_handler::{
+    body::Body,
+    davpath::DavPath,
+    fs::{DavFileSystem, ReadDirMeta},
+};
+use xmltree::Element;
+
+const CALDAV_NAMESPACE: &str = "urn:ietf:params:xml:ns:caldav";
+const CARDDAV_NAMESPACE: &str = "urn:ietf:params:xml:ns:carddav";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Calendar,
+    Addressbook,
+}
+
+impl Kind {
+    fn extension(&self) -> &'static str {
+        match self {
+            Kind::Calendar => ".ics",
+            Kind::Addressbook => ".vcf",
+        }
+    }
+
+    fn data_element(&self) -> &'static str {
+        match self {
+            Kind::Calendar => "C:calendar-data",
+            Kind::Addressbook => "C:address-data",
+        }
+    }
+
+    fn namespace(&self) -> &'static str {
+        match self {
+            Kind::Calendar => CALDAV_NAMESPACE,
+            Kind::Addressbook => CARDDAV_NAMESPACE,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Report {
+    /// explicit list of resources
+    Multiget(Kind, Vec<String>),
+    /// every resource of the collection, filters are not evaluated
+    Query(Kind),
+}
+
+fn parse_report(body: &[u8]) -> Result<Report, String> {
+    let root = Element::parse(body).map_err(|e| e.to_string())?;
+    let kind = match root.name.as_str() {
+        "calendar-multiget" | "calendar-query" => Kind::Calendar,
+        "addressbook-multiget" | "addressbook-query" => Kind::Addressbook,
+        n => return Err(format!("unsupported report: {}", n)),
+    };
+    if root.name.ends_with("-query") {
+        return Ok(Report::Query(kind));
+    }
+    let hrefs = root
+        .children
+        .iter()
+        .filter_map(|n| n.as_element())
+        .filter(|e| e.name == "href")
+        .filter_map(|e| e.get_text().map(|t| t.trim().to_string()))
+        .collect();
+    Ok(Report::Multiget(kind, hrefs))
+}
+
+/// Adds calendar-access and addressbook classes to DAV header of OPTIONS.
+pub(crate) fn advertise<B>(resp: &mut Response<B>) {
+    let v = match resp.headers().get("DAV").and_then(|v| v.to_str().ok()) {
+        Some(v) => format!("{}, calendar-access, addressbook", v),
+        None => "1, calendar-access, addressbook".to_string(),
+    };
+    if let Ok(v) = HeaderValue::from_str(&v) {
+        resp.headers_mut().insert("DAV", v);
+    }
+}
+
+/// MKCALENDAR creates plain collection, body with initial properties is dropped.
+pub(crate) fn rewrite_mkcalendar(req: &mut Request<hyper::Body>) {
+    if req.method().as_str() == "MKCALENDAR" {
+        *req.method_mut() = hyper::Method::from_bytes(b"MKCOL").unwrap();
+        *req.body_mut() = hyper::Body::empty();
+        req.headers_mut().remove(CONTENT_TYPE);
+    }
+}
+
+async fn read_all(fs: &dyn DavFileSystem, path: &DavPath) -> Option<(Vec<u8>, Option<String>)> {
+    let mut f = fs.open(path, open_opts::read()).await.ok()?;
+    let meta = f.metadata().await.ok()?;
+    let mut data = Vec::with_capacity(meta.len() as usize);
+    while (data.len() as u64) < meta.len() {
+        let chunk = f.read_bytes(meta.len() as usize - data.len()).await.ok()?;
+        if chunk.is_empty() {
+            break;
+        }
+        data.extend_from_slice(&chunk);
+    }
+    Some((data, meta.etag()))
+}
+
+fn not_found(href: &str) -> String {
+    format!(
+        "<D:response><D:href>{}</D:href><D:status>HTTP/1.1 404 Not Found</D:status></D:response>",
+        escape(href)
+    )
+}
+
+async fn resource_xml(fs: &dyn DavFileSystem, path: &DavPath, kind: Kind) -> String {
+    let url = path.as_url_string();
+    let href = escape(&url);
+    match read_all(fs, path).await {
+        Some((data, etag)) => {
+            let etag = etag
+                .map(|e| format!("<D:getetag>\"{}\"</D:getetag>", escape(&e)))
+                .unwrap_or_default();
+            format!(
+                "<D:response><D:href>{href}</D:href><D:propstat><D:prop>{etag}\
+                 <{el}>{data}</{el}></D:prop><D:status>HTTP/1.1 200 OK</D:status>\
+                 </D:propstat></D:response>",
+                href = href,
+                etag = etag,
+                el = kind.data_element(),
+                data = escape(&String::from_utf8_lossy(&data)),
+            )
+        }
+        None => not_found(&url),
+    }
+}
+
+async fn collection_members(fs: &dyn DavFileSystem, dir: &DavPath, kind: Kind) -> Vec<DavPath> {
+    let mut r = vec![];
+    let mut entries = match fs.read_dir(dir, ReadDirMeta::None).await {
+        Ok(e) => e,
+        Err(_) => return r,
+    };
+    while let Some(e) = entries.next().await {
+        let name = e.name();
+        if !String::from_utf8_lossy(&name).ends_with(kind.extension()) {
+            continue;
+        }
+        if let Ok(p) = child_path(dir, &name, false) {
+            r.push(p);
+        }
+    }
+    r
+}
+
+/// Answers calendar/addressbook multiget and query REPORTs with stored
+/// resources. Multiget hrefs are served only inside `mount` and if
+/// `readable` permits reading them, others are answered with 404 as are
+/// absolute urls.
+pub(crate) async fn handle_report<F, Fut>(
+    fs: &dyn DavFileSystem,
+    req: Request<hyper::Body>,
+    mount: &str,
+    readable: F,
+) -> Response<Body>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = bool>,
+{
+    let dir = match DavPath::new(req.uri().path()) {
+        Ok(p) => p,
+        Err(_) => return error(StatusCode::BAD_REQUEST, "invalid path"),
+    };
+    let body = match hyper::body::to_bytes(req.into_body()).await {
+        Ok(b) => b,
+        Err(_) => return error(StatusCode::BAD_REQUEST, "can't read body"),
+    };
+    let report = match parse_report(&body) {
+        Ok(r) => r,
+        Err(e) => return error(StatusCode::FORBIDDEN, &e),
+    };
+    let kind = match report {
+        Report::Multiget(kind, _) | Report::Query(kind) => kind,
+    };
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+         <D:multistatus xmlns:D=\"DAV:\" xmlns:C=\"{}\">",
+        kind.namespace()
+    );
+    match report {
+        Report::Multiget(_, hrefs) => {
+            for h in hrefs {
+                let path = DavPath::new(&h)
+                    .ok()
+                    .filter(|_| in_mount(&mounts::decode(&h), mount));
+                let entry = match path {
+                    Some(p) if readable(h.clone()).await => resource_xml(fs, &p, kind).await,
+                    _ => not_found(&h),
+                };
+                xml.push_str(&entry);
+            }
+        }
+        Report::Query(_) => {
+            for p in collection_members(fs, &dir, kind).await {
+                xml.push_str(&resource_xml(fs, &p, kind).await);
+            }
+        }
+    }
+    xml.push_str("</D:multistatus>\n");
+
+    Response::builder()
+        .status(StatusCode::MULTI_STATUS)
+        .header(CONTENT_TYPE, "application/xml; charset=utf-8")
+        .body(Body::from(xml))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::put;
+    use webdav_handler::memfs::MemFs;
+
+    #[test]
+    fn reports() {
+        let multiget = r#"<C:calendar-multiget xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+            <D:prop><D:getetag/><C:calendar-data/></D:prop>
+            <D:href>/cal/home/a.ics</D:href>
+            <D:href>/cal/home/b.ics</D:href>
+        </C:calendar-multiget>"#;
+        assert_eq!(
+            parse_report(multiget.as_bytes()).unwrap(),
+            Report::Multiget(
+                Kind::Calendar,
+                vec!["/cal/home/a.ics".into(), "/cal/home/b.ics".into()]
+            )
+        );
+        let query = r#"<C:addressbook-query xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:carddav">
+            <D:prop><D:getetag/></D:prop>
+        </C:addressbook-query>"#;
+        assert_eq!(
+            parse_report(query.as_bytes()).unwrap(),
+            Report::Query(Kind::Addressbook)
+        );
+        assert!(parse_report(b"<D:sync-collection xmlns:D=\"DAV:\"/>").is_err());
+    }
+
+    #[tokio::test]
+    async fn query() -> anyhow::Result<()> {
+        let fs = MemFs::new();
+        fs.create_dir(&DavPath::new("/home/")?).await?;
+        let ics = b"BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n";
+        put(fs.as_ref(), "/home/a.ics", ics).await?;
+        put(fs.as_ref(), "/home/notes.txt", b"").await?;
+
+        let req = Request::builder()
+            .method("REPORT")
+            .uri("/home/")
+            .body(hyper::Body::from(
+                r#"<C:calendar-query xmlns:C="urn:ietf:params:xml:ns:caldav"/>"#,
+            ))?;
+        let resp = handle_report(fs.as_ref(), req, "/", |_| async { true }).await;
+        assert_eq!(resp.status(), StatusCode::MULTI_STATUS);
+        let body = resp
+            .into_body()
+            .map(|c| c.unwrap())
+            .collect::<Vec<_>>()
+            .await
+            .concat();
+        let body = String::from_utf8(body)?;
+        assert!(body.contains("<D:href>/home/a.ics</D:href>"));
+        assert!(body.contains("<C:calendar-data>BEGIN:VCALENDAR"));
+        assert!(!body.contains("notes.txt"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn multiget_scope() -> anyhow::Result<()> {
+        let fs = MemFs::new();
+        for p in ["/cal/a.ics", "/cal/b.ics", "/other/c.ics"].iter() {
+            let dir = DavPath::new(&p[..p.rfind('/').unwrap() + 1])?;
+            let _ = fs.create_dir(&dir).await;
+            put(fs.as_ref(), p, b"BEGIN:VCALENDAR\r\n").await?;
+        }
+        let req = Request::builder()
+            .method("REPORT")
+            .uri("/cal/")
+            .body(hyper::Body::from(
+                r#"<C:calendar-multiget xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+                <D:href>/cal/a.ics</D:href>
+                <D:href>/cal/b.ics</D:href>
+                <D:href>/other/c.ics</D:href>
+                <D:href>http://localhost/cal/a.ics</D:href>
+                </C:calendar-multiget>"#,
+            ))?;
+        let readable = |h: String| async move { h != "/cal/b.ics" };
+        let resp = handle_report(fs.as_ref(), req, "/cal", readable).await;
+        let body = resp
+            .into_body()
+            .map(|c| c.unwrap())
+            .collect::<Vec<_>>()
+            .await
+            .concat();
+        let body = String::from_utf8(body)?;
+        assert_eq!(body.matches("<C:calendar-data>").count(), 1);
+        for href in ["/cal/b.ics", "/other/c.ics", "http://localhost/cal/a.ics"].iter() {
+            let missing = format!(
+                "<D:href>{}</D:href><D:status>HTTP/1.1 404 Not Found</D:status>",
+                href
+            );
+            assert!(body.contains(&missing), "{}", href);
+        }
+        Ok(())
+    }
+}
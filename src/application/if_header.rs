@@ -0,0 +1,106 @@
+use hyper::{Request, Response, StatusCode};
+use webdav_handler::{body::Body, davpath::DavPath, ls::DavLockSystem};
+
+/// State tokens of If header, RFC 4918 section 10.4. Resource tags and
+/// tokens under `Not` are skipped, etags are left to handler.
+pub(crate) fn submitted_tokens<B>(req: &Request<B>) -> Vec<String> {
+    let header = match req.headers().get("If").and_then(|v| v.to_str().ok()) {
+        Some(h) => h,
+        None => return vec![],
+    };
+    let mut tokens = vec![];
+    let (mut in_list, mut negated) = (false, false);
+    let mut rest = header;
+    while let Some(c) = rest.chars().next() {
+        match c {
+            '(' => in_list = true,
+            ')' => {
+                in_list = false;
+                negated = false;
+            }
+            '<' => {
+                let end = match rest.find('>') {
+                    Some(e) => e,
+                    None => break,
+                };
+                if in_list && !negated {
+                    tokens.push(rest[1..end].to_string());
+                }
+                negated = false;
+                rest = &rest[end + 1..];
+                continue;
+            }
+            '[' => {
+                // etags may contain anything but the closing bracket
+                let end = match rest.find(']') {
+                    Some(e) => e,
+                    None => break,
+                };
+                negated = false;
+                rest = &rest[end + 1..];
+                continue;
+            }
+            _ if rest.len() >= 3 && rest[..3].eq_ignore_ascii_case("not") => {
+                negated = true;
+                rest = &rest[3..];
+                continue;
+            }
+            _ => {}
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    tokens
+}
+
+/// 423 if request path is locked and none of the submitted tokens is of the
+/// lock, for methods changing content which dav handler does not serve.
+pub(crate) fn check_locks<B>(ls: &dyn DavLockSystem, req: &Request<B>) -> Option<Response<Body>> {
+    let path = DavPath::new(req.uri().path()).ok()?;
+    let tokens = submitted_tokens(req);
+    let tokens = tokens.iter().map(|t| t.as_str()).collect();
+    ls.check(&path, None, true, false, tokens).err()?;
+    Some(
+        Response::builder()
+            .status(StatusCode::LOCKED)
+            .body(Body::empty())
+            .unwrap(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use webdav_handler::memls::MemLs;
+
+    fn request(cond: Option<&str>) -> Request<()> {
+        let mut b = Request::patch("/fs/a%20b.txt");
+        if let Some(c) = cond {
+            b = b.header("If", c);
+        }
+        b.body(()).unwrap()
+    }
+
+    #[test]
+    fn parses_tokens() {
+        let r = request(Some(
+            "<http://host/fs/a%20b.txt> (<urn:uuid:1> [\"e<t>ag\"]) \
+             (Not <urn:uuid:2> <urn:uuid:3>) ([W/\"x\"])",
+        ));
+        assert_eq!(submitted_tokens(&r), vec!["urn:uuid:1", "urn:uuid:3"]);
+        assert!(submitted_tokens(&request(None)).is_empty());
+    }
+
+    #[test]
+    fn checks_locks() {
+        let ls = MemLs::new();
+        let path = DavPath::new("/fs/").unwrap();
+        let l = ls.lock(&path, None, None, None, false, true).unwrap();
+
+        let resp = check_locks(ls.as_ref(), &request(None)).unwrap();
+        assert_eq!(resp.status(), StatusCode::LOCKED);
+        let cond = format!("(<{}>)", l.token);
+        assert!(check_locks(ls.as_ref(), &request(Some(&cond))).is_none());
+        let cond = format!("(Not <{}>)", l.token);
+        assert!(check_locks(ls.as_ref(), &request(Some(&cond))).is_some());
+    }
+}
@@ -0,0 +1,196 @@
+use crate::backend::{walk::child_path, xml::escape};
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use hyper::{
+    header::{ACCEPT, CONTENT_TYPE},
+    Method, Request, Response, StatusCode,
+};
+use serde::Serialize;
+use webdav_handler::{
+    body::Body,
+    davpath::DavPath,
+    fs::{DavFileSystem, FsError, ReadDirMeta},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Format {
+    Html,
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+struct Item {
+    name: String,
+    href: String,
+    is_dir: bool,
+    size: Option<u64>,
+    modified: Option<String>,
+}
+
+/// Listing requested by GET, JSON with `?format=json` or HTML for browsers.
+/// Whether path is a collection is checked later.
+pub(crate) fn format<B>(req: &Request<B>) -> Option<Format> {
+    if req.method() != Method::GET {
+        return None;
+    }
+    let json = req
+        .uri()
+        .query()
+        .map_or(false, |q| q.split('&').any(|p| p == "format=json"));
+    if json {
+        return Some(Format::Json);
+    }
+    let html = req
+        .headers()
+        .get(ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .map_or(false, |v| v.contains("text/html"));
+    if html {
+        Some(Format::Html)
+    } else {
+        None
+    }
+}
+
+fn html(path: &str, items: &[Item]) -> String {
+    let title = escape(&percent_encoding::percent_decode_str(path).decode_utf8_lossy());
+    let mut page = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Index of {0}</title></head>\
+         <body><h1>Index of {0}</h1><table>\
+         <tr><th>Name</th><th>Size</th><th>Modified</th></tr>",
+        title
+    );
+    if path != "/" {
+        page.push_str("<tr><td><a href=\"../\">../</a></td><td></td><td></td></tr>");
+    }
+    for i in items {
+        let name = if i.is_dir {
+            format!("{}/", i.name)
+        } else {
+            i.name.clone()
+        };
+        page.push_str(&format!(
+            "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td></tr>",
+            escape(&i.href),
+            escape(&name),
+            i.size.map(|s| s.to_string()).unwrap_or_default(),
+            i.modified.as_deref().unwrap_or("")
+        ));
+    }
+    page.push_str("</table></body></html>\n");
+    page
+}
+
+/// Listing of collection at `path`, None if it is not a collection.
+pub(crate) async fn handle(
+    fs: &dyn DavFileSystem,
+    path: &str,
+    format: Format,
+) -> Option<Response<Body>> {
+    let dir = DavPath::new(path).ok()?;
+    if !fs.metadata(&dir).await.ok()?.is_dir() {
+        return None;
+    }
+    let mut entries = match fs.read_dir(&dir, ReadDirMeta::Data).await {
+        Ok(e) => e,
+        Err(e) => {
+            let status = match e {
+                FsError::Forbidden => StatusCode::FORBIDDEN,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            return Some(
+                Response::builder()
+                    .status(status)
+                    .body(Body::empty())
+                    .unwrap(),
+            );
+        }
+    };
+    let mut items = vec![];
+    while let Some(e) = entries.next().await {
+        let meta = match e.metadata().await {
+            Ok(m) => m,
+            Err(_) => continue,
+        };
+        let href = match child_path(&dir, &e.name(), meta.is_dir()) {
+            Ok(p) => p.as_url_string(),
+            Err(_) => continue,
+        };
+        let name = String::from_utf8_lossy(&e.name())
+            .trim_end_matches('/')
+            .to_string();
+        items.push(Item {
+            name,
+            href,
+            is_dir: meta.is_dir(),
+            size: if meta.is_dir() {
+                None
+            } else {
+                Some(meta.len())
+            },
+            modified: meta
+                .modified()
+                .ok()
+                .map(|t| DateTime::<Utc>::from(t).to_rfc3339()),
+        });
+    }
+    // directories first, then by name
+    items.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+
+    let (content_type, body) = match format {
+        Format::Json => ("application/json", serde_json::to_vec(&items).unwrap()),
+        Format::Html => (
+            "text/html; charset=utf-8",
+            html(&dir.as_url_string(), &items).into_bytes(),
+        ),
+    };
+    Some(
+        Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, content_type)
+            .body(Body::from(Bytes::from(body)))
+            .unwrap(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::put;
+    use webdav_handler::memfs::MemFs;
+
+    #[tokio::test]
+    async fn lists_collection() -> anyhow::Result<()> {
+        let fs = MemFs::new();
+        fs.create_dir(&DavPath::new("/docs/")?).await?;
+        fs.create_dir(&DavPath::new("/docs/sub/")?).await?;
+        put(fs.as_ref(), "/docs/a%20b.txt", b"data").await?;
+
+        let req = Request::builder()
+            .uri("/docs/")
+            .header(ACCEPT, "text/html,application/xhtml+xml")
+            .body(())?;
+        assert_eq!(format(&req), Some(Format::Html));
+        let req = Request::builder().uri("/docs/?format=json").body(())?;
+        assert_eq!(format(&req), Some(Format::Json));
+        assert_eq!(format(&Request::builder().uri("/docs/").body(())?), None);
+
+        let resp = handle(fs.as_ref(), "/docs/", Format::Json).await.unwrap();
+        let body = resp
+            .into_body()
+            .map(|c| c.unwrap())
+            .collect::<Vec<_>>()
+            .await
+            .concat();
+        let items: serde_json::Value = serde_json::from_slice(&body)?;
+        assert_eq!(items[0]["href"], "/docs/sub/");
+        assert_eq!(items[1]["href"], "/docs/a%20b.txt");
+        assert_eq!(items[1]["size"], 4);
+
+        assert!(handle(fs.as_ref(), "/docs/a%20b.txt", Format::Html)
+            .await
+            .is_none());
+        Ok(())
+    }
+}
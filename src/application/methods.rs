@@ -0,0 +1,215 @@
+use crate::backend::mounts::innermost_mount;
+use crate::configuration::Advertise;
+use anyhow::Context;
+use hyper::{
+    header::{HeaderValue, ALLOW},
+    Method, Response, StatusCode,
+};
+use webdav_handler::body::Body;
+
+/// Methods server answers, reported in Allow of rejected requests.
+const KNOWN: &[&str] = &[
+    "OPTIONS",
+    "GET",
+    "HEAD",
+    "PUT",
+    "PATCH",
+    "DELETE",
+    "PROPFIND",
+    "PROPPATCH",
+    "MKCOL",
+    "COPY",
+    "MOVE",
+    "LOCK",
+    "UNLOCK",
+    "SEARCH",
+    "REPORT",
+];
+
+/// Methods permitted on mount by its allow and deny lists.
+#[derive(Clone)]
+pub(crate) struct MethodRules {
+    mount: String,
+    allow: Option<Vec<String>>,
+    deny: Vec<String>,
+}
+
+fn upper(methods: Vec<String>) -> Vec<String> {
+    methods
+        .into_iter()
+        .map(|m| m.to_ascii_uppercase())
+        .collect()
+}
+
+impl MethodRules {
+    pub fn new(mount: &str, allow: Option<Vec<String>>, deny: Vec<String>) -> Self {
+        MethodRules {
+            mount: mount.to_string(),
+            allow: allow.map(upper),
+            deny: upper(deny),
+        }
+    }
+
+    fn permits(&self, method: &str) -> bool {
+        let allowed = self
+            .allow
+            .as_ref()
+            .map_or(true, |a| a.iter().any(|m| m == method));
+        allowed && !self.deny.iter().any(|m| m == method)
+    }
+
+    fn allow_header(&self) -> HeaderValue {
+        let methods = KNOWN
+            .iter()
+            .filter(|m| self.permits(m))
+            .copied()
+            .collect::<Vec<_>>();
+        HeaderValue::from_str(&methods.join(",")).unwrap()
+    }
+
+    /// 405 response if method is not permitted.
+    pub fn check(&self, method: &Method) -> Option<Response<hyper::Body>> {
+        if self.permits(method.as_str()) {
+            return None;
+        }
+        Some(
+            Response::builder()
+                .status(StatusCode::METHOD_NOT_ALLOWED)
+                .header(ALLOW, self.allow_header())
+                .body(hyper::Body::empty())
+                .unwrap(),
+        )
+    }
+
+    /// Removes methods which are not permitted from Allow of OPTIONS response.
+    pub fn restrict_allow(&self, resp: &mut Response<Body>) {
+        let headers = resp.headers_mut();
+        let v = match headers.get(ALLOW).and_then(|v| v.to_str().ok()) {
+            Some(v) => v
+                .split(',')
+                .map(|t| t.trim())
+                .filter(|t| self.permits(&t.to_ascii_uppercase()))
+                .collect::<Vec<_>>()
+                .join(","),
+            None => return,
+        };
+        if let Ok(v) = HeaderValue::from_str(&v) {
+            headers.insert(ALLOW, v);
+        }
+    }
+}
+
+/// Configured DAV and Allow values of mount OPTIONS responses.
+#[derive(Clone)]
+pub(crate) struct Advertised {
+    mount: String,
+    dav: Option<HeaderValue>,
+    allow: Option<HeaderValue>,
+}
+
+impl Advertised {
+    pub fn new(mount: &str, conf: &Advertise) -> anyhow::Result<Self> {
+        let value = |v: &Option<String>| {
+            v.as_deref()
+                .map(HeaderValue::from_str)
+                .transpose()
+                .context("invalid advertised header value")
+        };
+        Ok(Advertised {
+            mount: mount.to_string(),
+            dav: value(&conf.dav)?,
+            allow: value(&conf.allow)?,
+        })
+    }
+
+    /// Replaces headers of OPTIONS response, it is done after all other
+    /// changes so configured values are sent as is.
+    pub fn apply(&self, resp: &mut Response<Body>) {
+        let headers = resp.headers_mut();
+        if let Some(ref v) = self.dav {
+            headers.insert("DAV", v.clone());
+        }
+        if let Some(ref v) = self.allow {
+            headers.insert(ALLOW, v.clone());
+        }
+    }
+}
+
+/// Advertised values of the innermost mount with them decoded path belongs
+/// to.
+pub(crate) fn advertised_for<'a>(path: &str, mounts: &'a [Advertised]) -> Option<&'a Advertised> {
+    innermost_mount(path, mounts, |a| a.mount.as_str())
+}
+
+/// Rules of the innermost mount with rules decoded path belongs to.
+pub(crate) fn rules_for<'a>(path: &str, rules: &'a [MethodRules]) -> Option<&'a MethodRules> {
+    innermost_mount(path, rules, |r| r.mount.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_denied_methods() {
+        let rules = vec![
+            MethodRules::new("/drive", None, vec!["proppatch".into(), "LOCK".into()]),
+            MethodRules::new(
+                "/drive/public",
+                Some(vec!["GET".into(), "HEAD".into(), "OPTIONS".into()]),
+                vec![],
+            ),
+        ];
+        let check = |path, method: &str| {
+            rules_for(path, &rules)
+                .unwrap()
+                .check(&Method::from_bytes(method.as_bytes()).unwrap())
+        };
+        assert!(check("/drive/a.txt", "PUT").is_none());
+        let resp = check("/drive/a.txt", "PROPPATCH").unwrap();
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        let allow: Vec<_> = resp.headers()[ALLOW].to_str().unwrap().split(',').collect();
+        assert!(allow.contains(&"PUT") && !allow.contains(&"LOCK"));
+
+        let resp = check("/drive/public/a.txt", "PUT").unwrap();
+        assert_eq!(resp.headers()[ALLOW], "OPTIONS,GET,HEAD");
+        assert!(rules_for("/other/a.txt", &rules).is_none());
+
+        let mut resp = Response::new(Body::empty());
+        resp.headers_mut().insert(
+            ALLOW,
+            HeaderValue::from_static("GET,PUT,PROPPATCH,LOCK,UNLOCK"),
+        );
+        rules[0].restrict_allow(&mut resp);
+        assert_eq!(resp.headers()[ALLOW], "GET,PUT,UNLOCK");
+    }
+
+    #[test]
+    fn replaces_advertised_headers() -> anyhow::Result<()> {
+        let conf = Advertise {
+            dav: Some("1, 3".into()),
+            allow: None,
+        };
+        let mounts = vec![Advertised::new("/drive", &conf)?];
+        let mut resp = Response::new(Body::empty());
+        resp.headers_mut().insert(
+            "DAV",
+            HeaderValue::from_static("1, 2, sabredav-partialupdate"),
+        );
+        resp.headers_mut()
+            .insert(ALLOW, HeaderValue::from_static("GET,PUT"));
+        advertised_for("/drive/dir/", &mounts)
+            .unwrap()
+            .apply(&mut resp);
+        assert_eq!(resp.headers()["DAV"], "1, 3");
+        assert_eq!(resp.headers()[ALLOW], "GET,PUT");
+        assert!(advertised_for("/other/", &mounts).is_none());
+
+        let conf = Advertise {
+            dav: Some("1\n".into()),
+            allow: None,
+        };
+        assert!(Advertised::new("/drive", &conf).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,273 @@
+use super::{find::param, panics};
+use crate::{
+    backend::{
+        live_props::{TREE_PROPS, WEBDAV_SS_NAMESPACE},
+        s3_backend::{ApiCalls, Call},
+        wrappers::{access::Access, breaker::Breaker, usage::Usage},
+    },
+    configuration::Pricing,
+    events,
+};
+use hyper::{header::CONTENT_TYPE, Response, StatusCode};
+use serde_json::json;
+use std::{fmt::Write, sync::Arc};
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{DavFileSystem, DavProp, FsError},
+};
+
+fn label(v: &str) -> String {
+    v.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Usage and health of mounts in prometheus text format.
+pub(crate) fn render(
+    usage: &[(String, Arc<Usage>)],
+    breakers: &[(String, Arc<Breaker>)],
+    access: &[(String, Arc<Access>)],
+    calls: &[(String, Arc<ApiCalls>, Option<Pricing>)],
+) -> String {
+    let mut out = String::new();
+    let gauges: [(&str, &str, fn(&Usage) -> u64); 2] = [
+        (
+            "webdav_ss_mount_bytes",
+            "Bytes stored on mount.",
+            Usage::bytes,
+        ),
+        (
+            "webdav_ss_mount_objects",
+            "Files stored on mount.",
+            Usage::objects,
+        ),
+    ];
+    for (name, help, value) in gauges.iter() {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} gauge", name);
+        for (mount, u) in usage {
+            let _ = writeln!(out, "{}{{mount=\"{}\"}} {}", name, label(mount), value(u));
+        }
+    }
+    if !breakers.is_empty() {
+        let name = "webdav_ss_mount_degraded";
+        let _ = writeln!(out, "# HELP {} Mount backend is failing.", name);
+        let _ = writeln!(out, "# TYPE {} gauge", name);
+        for (mount, b) in breakers {
+            let v = b.is_degraded() as u8;
+            let _ = writeln!(out, "{}{{mount=\"{}\"}} {}", name, label(mount), v);
+        }
+    }
+    if !access.is_empty() {
+        let name = "webdav_ss_mount_read_only";
+        let _ = writeln!(out, "# HELP {} Mount storage denies writes.", name);
+        let _ = writeln!(out, "# TYPE {} gauge", name);
+        for (mount, a) in access {
+            let v = a.is_read_only() as u8;
+            let _ = writeln!(out, "{}{{mount=\"{}\"}} {}", name, label(mount), v);
+        }
+    }
+    if !calls.is_empty() {
+        let name = "webdav_ss_s3_requests_total";
+        let _ = writeln!(out, "# HELP {} Requests sent to bucket of mount.", name);
+        let _ = writeln!(out, "# TYPE {} counter", name);
+        for (mount, c, _) in calls {
+            for call in Call::ALL.iter() {
+                let _ = writeln!(
+                    out,
+                    "{}{{mount=\"{}\",call=\"{}\"}} {}",
+                    name,
+                    label(mount),
+                    call.name(),
+                    c.count(*call)
+                );
+            }
+        }
+        let name = "webdav_ss_s3_egress_bytes_total";
+        let _ = writeln!(
+            out,
+            "# HELP {} Bytes downloaded from bucket of mount.",
+            name
+        );
+        let _ = writeln!(out, "# TYPE {} counter", name);
+        for (mount, c, _) in calls {
+            let _ = writeln!(out, "{}{{mount=\"{}\"}} {}", name, label(mount), c.egress());
+        }
+    }
+    if calls.iter().any(|(_, _, p)| p.is_some()) {
+        let name = "webdav_ss_s3_estimated_monthly_cost";
+        let _ = writeln!(
+            out,
+            "# HELP {} Cost of requests and egress of a month at rate seen since start.",
+            name
+        );
+        let _ = writeln!(out, "# TYPE {} gauge", name);
+        for (mount, c, pricing) in calls {
+            if let Some(p) = pricing {
+                let v = c.monthly_cost(p);
+                let _ = writeln!(out, "{}{{mount=\"{}\"}} {:.4}", name, label(mount), v);
+            }
+        }
+    }
+    let name = "webdav_ss_panics_total";
+    let _ = writeln!(out, "# HELP {} Requests which handling panicked.", name);
+    let _ = writeln!(out, "# TYPE {} counter", name);
+    let _ = writeln!(out, "{} {}", name, panics::count());
+    let name = "webdav_ss_events_dropped_total";
+    let _ = writeln!(out, "# HELP {} Events dropped on full sink queue.", name);
+    let _ = writeln!(out, "# TYPE {} counter", name);
+    let _ = writeln!(out, "{} {}", name, events::dropped());
+    out
+}
+
+pub(crate) fn response(
+    usage: &[(String, Arc<Usage>)],
+    breakers: &[(String, Arc<Breaker>)],
+    access: &[(String, Arc<Access>)],
+    calls: &[(String, Arc<ApiCalls>, Option<Pricing>)],
+) -> Response<hyper::Body> {
+    Response::builder()
+        .header(CONTENT_TYPE, "text/plain; version=0.0.4")
+        .body(render(usage, breakers, access, calls).into())
+        .unwrap()
+}
+
+/// Readiness probe, fails while any mount is degraded. Read-only mounts are
+/// listed but still serve reads.
+pub(crate) fn ready(
+    breakers: &[(String, Arc<Breaker>)],
+    access: &[(String, Arc<Access>)],
+) -> Response<hyper::Body> {
+    let degraded = breakers
+        .iter()
+        .filter(|(_, b)| b.is_degraded())
+        .map(|(m, _)| format!("degraded: {}\n", m))
+        .collect::<String>();
+    let read_only = access
+        .iter()
+        .filter(|(_, a)| a.is_read_only())
+        .map(|(m, _)| format!("read-only: {}\n", m))
+        .collect::<String>();
+    let (status, body) = if degraded.is_empty() {
+        (StatusCode::OK, format!("ok\n{}", read_only))
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, degraded + &read_only)
+    };
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "text/plain")
+        .body(body.into())
+        .unwrap()
+}
+
+/// Text content of prop element.
+fn prop_value(xml: &[u8]) -> u64 {
+    let xml = String::from_utf8_lossy(xml);
+    xml.split(&['<', '>'][..])
+        .nth(2)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Totals of collection in `path` query parameter. They are read from tree
+/// props, so cache is shared with PROPFIND.
+pub(crate) async fn tree(fs: &dyn DavFileSystem, query: Option<&str>) -> Response<hyper::Body> {
+    let path = param(query.unwrap_or(""), "path", false).and_then(|p| DavPath::new(&p).ok());
+    let path = match path {
+        Some(p) => p,
+        None => return status(StatusCode::BAD_REQUEST),
+    };
+    let mut values = vec![];
+    for name in TREE_PROPS {
+        let prop = DavProp {
+            name: name.to_string(),
+            namespace: Some(WEBDAV_SS_NAMESPACE.into()),
+            prefix: None,
+            xml: None,
+        };
+        match fs.get_prop(&path, prop).await {
+            Ok(xml) => values.push(prop_value(&xml)),
+            Err(FsError::NotFound) => return status(StatusCode::NOT_FOUND),
+            Err(_) => return status(StatusCode::INTERNAL_SERVER_ERROR),
+        }
+    }
+    let body = json!({
+        "path": path.as_url_string(),
+        "bytes": values[0],
+        "items": values[1],
+    });
+    Response::builder()
+        .header(CONTENT_TYPE, "application/json")
+        .body(body.to_string().into())
+        .unwrap()
+}
+
+fn status(status: StatusCode) -> Response<hyper::Body> {
+    Response::builder()
+        .status(status)
+        .body(hyper::Body::empty())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_gauges() {
+        let usage = vec![("/fs".to_string(), Arc::new(Usage::default()))];
+        let out = render(&usage, &[], &[], &[]);
+        assert!(out.contains("# TYPE webdav_ss_mount_bytes gauge\n"));
+        assert!(out.contains("webdav_ss_mount_bytes{mount=\"/fs\"} 0\n"));
+        assert!(out.contains("webdav_ss_mount_objects{mount=\"/fs\"} 0\n"));
+        assert_eq!(label("a\"b\\"), "a\\\"b\\\\");
+        assert!(!out.contains("webdav_ss_mount_degraded"));
+        assert!(out.contains("# TYPE webdav_ss_panics_total counter\n"));
+    }
+
+    #[test]
+    fn tree_values() {
+        assert_eq!(
+            prop_value(b"<tree-bytes xmlns=\"urn:webdav_ss:\">42</tree-bytes>"),
+            42
+        );
+        assert_eq!(prop_value(b""), 0);
+    }
+
+    #[test]
+    fn readiness() {
+        let breaker = Arc::new(Breaker::new(1, std::time::Duration::from_secs(60)));
+        let breakers = vec![("/s3".to_string(), breaker)];
+        assert_eq!(ready(&breakers, &[]).status(), StatusCode::OK);
+        assert!(render(&[], &breakers, &[], &[])
+            .contains("webdav_ss_mount_degraded{mount=\"/s3\"} 0\n"));
+
+        let access = vec![("/fs".to_string(), Arc::new(Access::default()))];
+        assert!(
+            render(&[], &[], &access, &[]).contains("webdav_ss_mount_read_only{mount=\"/fs\"} 0\n")
+        );
+        assert_eq!(ready(&[], &access).status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn renders_request_counts() {
+        let pricing = Pricing {
+            per_1000_writes: 5.0,
+            per_1000_reads: 0.4,
+            per_gb_egress: 0.09,
+        };
+        let calls = vec![
+            (
+                "/s3".to_string(),
+                Arc::new(ApiCalls::default()),
+                Some(pricing),
+            ),
+            ("/other".to_string(), Arc::new(ApiCalls::default()), None),
+        ];
+        let out = render(&[], &[], &[], &calls);
+        assert!(out.contains("webdav_ss_s3_requests_total{mount=\"/s3\",call=\"put\"} 0\n"));
+        assert!(out.contains("webdav_ss_s3_egress_bytes_total{mount=\"/other\"} 0\n"));
+        assert!(out.contains("webdav_ss_s3_estimated_monthly_cost{mount=\"/s3\"} 0.0000\n"));
+        assert!(!out.contains("webdav_ss_s3_estimated_monthly_cost{mount=\"/other\"}"));
+    }
+}
@@ -1,88 +1,2027 @@
+mod compat;
+mod compression;
+mod connections;
+mod delete;
+mod dump;
+mod errors;
+pub mod filter;
+mod find;
+mod groupware;
+mod if_header;
+mod listing;
+mod methods;
+mod metrics;
+mod panics;
+mod patch;
+mod path_locks;
+mod propfind;
+mod reconcile;
+mod redirect;
+mod reports;
+mod search;
+mod timeouts;
+mod transfers;
+mod tus;
+mod ui;
+
 use crate::{
-    backend::prop_storages::{mem::Memory, yaml::Yaml, PropStorage},
-    configuration::PropsStorage,
+    backend::{
+        content_type::ContentTypes,
+        journal::Journal,
+        lock_systems::{aggregate::AggregateLs, policy::PolicyLs, redis::RedisLs},
+        mounts::{self, in_mount, innermost_mount},
+        prop_storages::{mem::Memory, sqlite::Sqlite, yaml::Yaml, PropStorage},
+        registry::{Factory, Registry},
+        snapshot::{self, Schedule, Snapshots},
+        tree_stats::TreeStats,
+        watcher,
+        wrappers::{
+            access::{Access, AccessFs},
+            antivirus::{AntivirusFs, Clamd},
+            breaker::{Breaker, BreakerFs},
+            case::CaseInsensitiveFs,
+            disk_space::{self, DiskSpaceFs},
+            drop_box::DropBoxFs,
+            hardlinks::BreakLinksFs,
+            ignore::{IgnoreAction, IgnoreFs},
+            index::{self, Index, IndexFs},
+            path_map::{ChainMapper, MountMapper, PathMapFs, PathMapper},
+            read_only::ReadOnlyFs,
+            rewrite::Rewriter,
+            staging::StagingFs,
+            symlinks::{ListSymlinksFs, NoSymlinksFs},
+            throttle::ThrottleFs,
+            unicode::UnicodeNormalizer,
+            usage::{self, Usage, UsageFs},
+            versions::VersionsFs,
+        },
+    },
+    configuration::{Events, LockStorage, Pricing, Propfind, PropsStorage},
+    events::{
+        audit::AuditLog, mqtt::MqttSink, nats::NatsSink, webhook::WebhookSink, Event, EventSink,
+        Notifier,
+    },
 };
 
 use super::{
-    aggregate::AggregateBuilder,
-    backend::s3_backend::S3Backend,
-    configuration::{Configuration, Filesystem},
+    aggregate::{self, AggregateBuilder},
+    backend::s3_backend::{ApiCalls, S3Backend},
+    configuration::{Configuration, Filesystem, FilesystemType, Middleware, VirtualHost},
 };
+use anyhow::Context;
+use connections::Connections;
+use dump::Dump;
+use filter::{RequestFilter, ScriptFilter};
+use find::Find;
 use hyper::{
+    header::{
+        HeaderMap, HeaderName, HeaderValue, ALLOW, CACHE_CONTROL, CONTENT_LENGTH, CONTENT_TYPE,
+        HOST,
+    },
+    server::accept,
     service::{make_service_fn, service_fn},
-    Server,
+    Method, Request, Response, Server, StatusCode,
+};
+use methods::{Advertised, MethodRules};
+use notify::RecommendedWatcher;
+use path_locks::PathLocks;
+use redirect::Redirect;
+use reports::{Report, Reporter};
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+use timeouts::TimeoutIo;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    time::Instant,
 };
-use std::{convert::Infallible, net::SocketAddr, path::PathBuf, str::FromStr};
-use tracing::{error, instrument};
-use webdav_handler::memls::MemLs;
+use tracing::{error, info, instrument, warn};
+use transfers::{Direction, Peer, Transfers};
+use tus::Tus;
+use ui::Ui;
 use webdav_handler::DavHandler;
+use webdav_handler::{body::Body, ls::DavLockSystem};
+use webdav_handler::{fakels::FakeLs, memls::MemLs};
 use webdav_handler::{fs::DavFileSystem, localfs::LocalFs, memfs::MemFs};
 
-async fn get_backend_by_type(fs: Filesystem) -> Box<dyn DavFileSystem> {
+/// Methods answered by read-only mirror.
+const MIRROR_METHODS: &[&str] = &["OPTIONS", "GET", "HEAD", "PROPFIND", "SEARCH", "REPORT"];
+
+/// Requests of S3 backend are counted into `calls` if given.
+pub(crate) async fn get_backend_by_type(
+    fs: Filesystem,
+    content_types: &ContentTypes,
+    registry: &Registry,
+    calls: Option<Arc<ApiCalls>>,
+) -> anyhow::Result<Box<dyn DavFileSystem>> {
     match fs {
-        Filesystem::FS { path } => {
+        Filesystem::FS {
+            path,
+            public,
+            macos,
+            follow_symlinks,
+            case_insensitive,
+            staging,
+            staging_dir,
+            ..
+        } => {
+            // case insensitive lookup may resolve to other name than staged one
+            if staging && case_insensitive {
+                return Err(anyhow::anyhow!(
+                    "mount {} can't be case insensitive with staging enabled, set staging: false",
+                    path
+                ));
+            }
             // TODO: move dir check
             if let Err(_) = std::fs::metadata(&path) {
-                std::fs::create_dir_all(&path).unwrap();
+                std::fs::create_dir_all(&path)
+                    .with_context(|| format!("can't create mount directory {}", path))?;
+            }
+            let mut fs: Box<dyn DavFileSystem> =
+                LocalFs::new(&path, public, case_insensitive, macos);
+            if staging {
+                let dir = match staging_dir {
+                    Some(d) => PathBuf::from(d),
+                    None => PathBuf::from(format!("{}.staging", path.trim_end_matches('/'))),
+                };
+                fs = StagingFs::new(fs, PathBuf::from(&path), dir, public)
+                    .context("can't set up staging directory")?;
+            }
+            let fs = DiskSpaceFs::new(fs, &path);
+            match follow_symlinks {
+                None => Ok(fs),
+                Some(true) => Ok(ListSymlinksFs::new(fs)),
+                Some(false) => Ok(NoSymlinksFs::new(fs)),
+            }
+        }
+        Filesystem::Mem => Ok(MemFs::new()),
+        a @ Filesystem::S3 { .. } => {
+            let case_insensitive = matches!(
+                a,
+                Filesystem::S3 {
+                    case_insensitive: true,
+                    ..
+                }
+            );
+            let mut fs = S3Backend::new_unboxed(a, content_types.clone()).await?;
+            if let Some(calls) = calls {
+                fs = fs.with_calls(calls);
+            }
+            let fs = Box::new(fs) as Box<dyn DavFileSystem>;
+            if case_insensitive {
+                Ok(CaseInsensitiveFs::new(fs))
+            } else {
+                Ok(fs)
+            }
+        }
+        Filesystem::Plugin { backend, options } => registry
+            .create(&backend, &options)
+            .await
+            .with_context(|| format!("can't create {} backend", backend)),
+    }
+}
+
+/// Builds aggregate with a single mount from configuration, used by
+/// maintenance commands working on one mount.
+pub(crate) async fn build_mount(
+    config: Configuration,
+    mount: &str,
+) -> anyhow::Result<(Box<dyn DavFileSystem>, Box<dyn PropStorage>)> {
+    let fs = config
+        .filesystems
+        .into_iter()
+        .find(|f| f.mount_path == mount)
+        .ok_or_else(|| anyhow::anyhow!("mount not found: {}", mount))?;
+    let props = get_props_storage_by_conf(config.prop_storage.unwrap_or(PropsStorage::Mem));
+    let mut builder = AggregateBuilder::new().set_props_storage(props.clone());
+    if uses_backend_props(&fs.fs) {
+        builder = builder.set_backend_props(&fs.mount_path);
+    }
+    let types = ContentTypes::new(config.content_types);
+    let agg = builder
+        .add_route((
+            &fs.mount_path,
+            get_backend_by_type(fs.fs, &types, &Registry::default(), None).await?,
+        ))
+        .set_content_types(types)
+        .build()?;
+    Ok((agg as Box<dyn DavFileSystem>, props))
+}
+
+/// Wraps mount backend into filters enabled in mount configuration.
+fn wrap_backend(
+    mut backend: Box<dyn DavFileSystem>,
+    fs: &FilesystemType,
+) -> anyhow::Result<Box<dyn DavFileSystem>> {
+    if let (
+        Filesystem::FS {
+            ref path,
+            case_insensitive,
+            ..
+        },
+        Some(_),
+    ) = (&fs.fs, &fs.snapshots)
+    {
+        backend = BreakLinksFs::new(backend, PathBuf::from(path), *case_insensitive);
+    }
+    // older per-mount options come first, then explicit chain
+    let legacy = fs
+        .versions
+        .clone()
+        .map(Middleware::Versions)
+        .into_iter()
+        .chain(fs.ignore.clone().map(Middleware::Ignore));
+    for m in legacy.chain(fs.middleware.iter().cloned()) {
+        backend = get_middleware_by_conf(&m, &fs.fs, backend)?;
+    }
+    Ok(backend)
+}
+
+/// Wraps mount backend with one middleware of its chain.
+fn get_middleware_by_conf(
+    m: &Middleware,
+    fs: &Filesystem,
+    backend: Box<dyn DavFileSystem>,
+) -> anyhow::Result<Box<dyn DavFileSystem>> {
+    match m {
+        Middleware::ReadOnly => Ok(ReadOnlyFs::new(backend)),
+        Middleware::DropBox => Ok(DropBoxFs::new(backend)),
+        Middleware::Ignore(ref ignore) => IgnoreFs::new(backend, &ignore.patterns, ignore.action)
+            .context("invalid ignore pattern"),
+        Middleware::Versions(ref versions) => {
+            if matches!(fs, Filesystem::S3 { .. }) {
+                anyhow::bail!("s3 mounts don't support versions, use bucket versioning");
             }
-            LocalFs::new(&path, false, false, false)
+            Ok(VersionsFs::new(backend, versions.keep))
         }
-        Filesystem::Mem => MemFs::new(),
-        a @ Filesystem::S3 { .. } => S3Backend::new(a).await.unwrap(),
+        Middleware::Throttle { read, write } => Ok(ThrottleFs::new(backend, *read, *write)),
+        Middleware::Antivirus {
+            ref address,
+            ref quarantine,
+        } => Ok(AntivirusFs::new(
+            backend,
+            Clamd::new(address),
+            quarantine.clone(),
+        )),
+    }
+}
+
+/// Adds path mappings enabled in mount configuration.
+fn add_mount_mappers(mapper: &mut MountMapper, fs: &FilesystemType) {
+    let mut chain: Vec<Arc<dyn PathMapper>> = vec![];
+    if let Some(form) = fs.unicode_normalization {
+        chain.push(Arc::new(UnicodeNormalizer::new(form)));
+    }
+    if !fs.rewrite.is_empty() {
+        chain.push(Arc::new(Rewriter::new(fs.rewrite.clone())));
+    }
+    match chain.len() {
+        0 => {}
+        1 => mapper.add(&fs.mount_path, chain.pop().unwrap()),
+        _ => mapper.add(&fs.mount_path, Arc::new(ChainMapper(chain))),
     }
 }
 
-fn get_props_storage_by_conf(p: PropsStorage) -> Box<dyn PropStorage> {
+/// Snapshots of mount, stored where its policy says or in default place.
+pub(crate) async fn get_snapshots_by_conf(
+    fs: &FilesystemType,
+    content_types: &ContentTypes,
+) -> anyhow::Result<Snapshots> {
+    let dir = fs.snapshots.as_ref().and_then(|p| p.path.as_deref());
+    match fs.fs {
+        Filesystem::FS { ref path, .. } => Ok(Snapshots::fs(path, dir)),
+        Filesystem::S3 { .. } => Ok(Snapshots::S3(
+            S3Backend::new_unboxed(fs.fs.clone(), content_types.clone()).await?,
+        )),
+        Filesystem::Mem | Filesystem::Plugin { .. } => Err(anyhow::anyhow!(
+            "{} mounts don't support snapshots",
+            fs.fs.type_name()
+        )),
+    }
+}
+
+/// Filesystems which keep dead properties by themselves instead of common storage.
+pub(crate) fn uses_backend_props(fs: &Filesystem) -> bool {
+    matches!(
+        fs,
+        Filesystem::S3 {
+            sidecar_props: true,
+            ..
+        }
+    )
+}
+
+pub(crate) fn get_props_storage_by_conf(p: PropsStorage) -> Box<dyn PropStorage> {
     match p {
-        PropsStorage::Yaml { path } => Yaml::new(PathBuf::from_str(&path).unwrap()),
+        PropsStorage::Yaml {
+            path,
+            dump_interval,
+        } => Yaml::new(
+            PathBuf::from_str(&path).unwrap(),
+            Duration::from_secs(dump_interval),
+        ),
+        PropsStorage::Sqlite { path } => {
+            Sqlite::new(&path).expect("can't open sqlite prop storage")
+        }
         PropsStorage::Mem => Memory::new(),
     }
 }
 
+pub(crate) async fn get_lock_system_by_conf(
+    l: LockStorage,
+) -> anyhow::Result<Box<dyn DavLockSystem>> {
+    match l {
+        LockStorage::Mem => Ok(MemLs::new()),
+        // disabled mounts never reach lock system, see handle_without_locks
+        LockStorage::Fake | LockStorage::Disabled => Ok(FakeLs::new()),
+        LockStorage::Redis {
+            url,
+            prefix,
+            max_timeout,
+        } => {
+            let ls = RedisLs::new(&url, &prefix, Duration::from_secs(max_timeout))
+                .await
+                .context("can't connect to redis lock storage")?;
+            Ok(ls)
+        }
+    }
+}
+
+/// Request path and Destination decode to UTF-8.
+fn utf8_paths<B>(req: &Request<B>) -> bool {
+    let dest = req
+        .headers()
+        .get("Destination")
+        .and_then(|d| d.to_str().ok())
+        .and_then(|d| d.parse::<hyper::Uri>().ok());
+    mounts::is_utf8(req.uri().path()) && dest.map_or(true, |d| mounts::is_utf8(d.path()))
+}
+
+fn remove_tokens(value: &HeaderValue, remove: &[&str]) -> Option<HeaderValue> {
+    let v = value
+        .to_str()
+        .ok()?
+        .split(',')
+        .map(|t| t.trim())
+        .filter(|t| !remove.contains(t))
+        .collect::<Vec<_>>()
+        .join(",");
+    HeaderValue::from_str(&v).ok()
+}
+
+/// Hides locking from mounts which have it disabled: LOCK/UNLOCK are rejected
+/// and OPTIONS does not advertise class 2.
+async fn handle_without_locks(dav_server: DavHandler, req: Request<hyper::Body>) -> Response<Body> {
+    let method = req.method().clone();
+    if method.as_str() == "LOCK" || method.as_str() == "UNLOCK" {
+        return Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .body(Body::empty())
+            .unwrap();
+    }
+    let mut resp = dav_server.handle(req).await;
+    if method == Method::OPTIONS {
+        let headers = resp.headers_mut();
+        if let Some(v) = headers.get("DAV").and_then(|v| remove_tokens(v, &["2"])) {
+            headers.insert("DAV", v);
+        }
+        if let Some(v) = headers
+            .get(ALLOW)
+            .and_then(|v| remove_tokens(v, &["LOCK", "UNLOCK"]))
+        {
+            headers.insert(ALLOW, v);
+        }
+    }
+    resp
+}
+
+/// Content type for GET/HEAD responses of files, handler guesses it without
+/// configured overrides.
+fn file_content_type<B>(req: &Request<B>, types: &ContentTypes) -> Option<String> {
+    let m = req.method();
+    let path = req.uri().path();
+    if (m != Method::GET && m != Method::HEAD) || path.ends_with('/') {
+        return None;
+    }
+    let path = percent_encoding::percent_decode_str(path).decode_utf8_lossy();
+    Some(types.guess(&*path))
+}
+
+fn set_content_type<B>(resp: &mut Response<B>, ct: &str) {
+    // only file responses carry content type, errors and redirects are left alone
+    if !resp.status().is_success() || !resp.headers().contains_key(CONTENT_TYPE) {
+        return;
+    }
+    if let Ok(v) = HeaderValue::from_str(ct) {
+        resp.headers_mut().insert(CONTENT_TYPE, v);
+    }
+}
+
+fn get_notifier_by_conf(conf: Events) -> anyhow::Result<Notifier> {
+    let mut sinks: Vec<Arc<dyn EventSink>> = vec![];
+    for url in conf.webhooks {
+        let sink = WebhookSink::new(&url, Duration::from_secs(conf.webhook_timeout))
+            .context("invalid webhook url")?;
+        sinks.push(Arc::new(sink));
+    }
+    for m in conf.mqtt {
+        let sink = MqttSink::new(&m.url, &m.topic).context("invalid mqtt url")?;
+        sinks.push(Arc::new(sink));
+    }
+    for n in conf.nats {
+        let sink = NatsSink::new(&n.url, &n.subject).context("invalid nats url")?;
+        sinks.push(Arc::new(sink));
+    }
+    if let Some(ref path) = conf.audit {
+        sinks.push(Arc::new(AuditLog::new(path)));
+    }
+    Ok(Notifier::new(
+        sinks,
+        conf.retries,
+        Duration::from_secs(conf.retry_delay),
+        conf.queue_size,
+    ))
+}
+
+/// Innermost mount decoded path belongs to.
+fn mount_of(path: &str, mounts: &[String]) -> Option<String> {
+    innermost_mount(path, mounts, |m| m.as_str()).cloned()
+}
+
+fn request_user<B>(req: &Request<B>, user_header: Option<&str>) -> Option<String> {
+    user_header
+        .and_then(|h| req.headers().get(h))
+        .and_then(|v| v.to_str().ok())
+        .map(String::from)
+}
+
+/// Event for requests changing content, sent if request succeeds.
+fn change_event<B>(
+    req: &Request<B>,
+    mounts: &[String],
+    user_header: Option<&str>,
+) -> Option<Event> {
+    let method = req.method().as_str();
+    if !matches!(
+        method,
+        "PUT" | "PATCH" | "DELETE" | "MOVE" | "COPY" | "MKCOL"
+    ) {
+        return None;
+    }
+    let path = req.uri().path();
+    let mut event = Event::new(method, path);
+    event.mount = mount_of(&mounts::decode(path), mounts);
+    event.destination = req
+        .headers()
+        .get("Destination")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<hyper::Uri>().ok())
+        .map(|u| u.path().to_string());
+    event.user = request_user(req, user_header);
+    if method == "PUT" || method == "PATCH" {
+        event.size = req
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok());
+    }
+    Some(event)
+}
+
+/// True if change request succeeded as a whole. Multistatus of DELETE, MOVE
+/// or COPY lists paths which failed, so the change is partial.
+fn completed(status: StatusCode) -> bool {
+    status.is_success() && status != StatusCode::MULTI_STATUS
+}
+
+/// Host name of request without port, from absolute uri or Host header.
+fn request_host<B>(req: &Request<B>) -> Option<&str> {
+    if let Some(h) = req.uri().host() {
+        return Some(h);
+    }
+    let host = req.headers().get(HOST)?.to_str().ok()?;
+    if host.starts_with('[') {
+        // ipv6 literal
+        return host.split(']').next().map(|h| &h[1..]);
+    }
+    host.split(':').next()
+}
+
+/// Rejects PUT to fs mount before body is read when its length exceeds free
+/// space of mount filesystem.
+fn insufficient_space<B>(
+    req: &Request<B>,
+    path: &str,
+    disks: &[(String, PathBuf)],
+) -> Option<Response<hyper::Body>> {
+    if req.method() != Method::PUT {
+        return None;
+    }
+    let len = req
+        .headers()
+        .get(CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .parse::<u64>()
+        .ok()?;
+    let (_, root) = innermost_mount(path, disks, |(m, _)| m.as_str())?;
+    if disk_space::fits(root, len) {
+        return None;
+    }
+    Some(
+        Response::builder()
+            .status(StatusCode::INSUFFICIENT_STORAGE)
+            .body(hyper::Body::empty())
+            .unwrap(),
+    )
+}
+
+/// Extra headers of the innermost mount with them decoded request path
+/// belongs to.
+fn mount_headers<'a>(path: &str, mounts: &'a [(String, HeaderMap)]) -> Option<&'a HeaderMap> {
+    innermost_mount(path, mounts, |(m, _)| m.as_str()).map(|(_, h)| h)
+}
+
+/// Cache-Control of the innermost mount decoded request path belongs to.
+fn cache_control<'a>(path: &str, mounts: &'a [(String, HeaderValue)]) -> Option<&'a HeaderValue> {
+    innermost_mount(path, mounts, |(m, _)| m.as_str()).map(|(_, v)| v)
+}
+
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut term = signal(SignalKind::terminate()).expect("can't install signal handler");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {},
+            _ = term.recv() => {},
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
 pub struct Application {
     addr: String,
-    dav_server: DavHandler,
+    /// address and handler of read-only mirror
+    mirror: Option<(String, Arc<Handler>)>,
+    header_timeout: Duration,
+    connections: Arc<Connections>,
+    handler: Arc<Handler>,
+    props: Vec<Box<dyn PropStorage>>,
+    // kept alive while application runs
+    _watchers: Vec<RecommendedWatcher>,
 }
 
-impl Application {
-    pub async fn build(config: Configuration) -> Application {
+/// Application for programs embedding the server: filesystems and prop
+/// storage constructed by them are used along with configuration.
+pub struct ApplicationBuilder {
+    config: Configuration,
+    filesystems: Vec<(String, Box<dyn DavFileSystem>)>,
+    props: Option<Box<dyn PropStorage>>,
+    registry: Registry,
+    filter: Option<Arc<dyn RequestFilter>>,
+}
+
+impl ApplicationBuilder {
+    pub fn new(config: Configuration) -> Self {
+        ApplicationBuilder {
+            config,
+            filesystems: vec![],
+            props: None,
+            registry: Registry::default(),
+            filter: None,
+        }
+    }
+
+    /// Replaces request filter from configuration.
+    pub fn set_request_filter(mut self, filter: Arc<dyn RequestFilter>) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Makes backend available to mounts configured with `type: plugin`
+    /// and `backend: <name>`.
+    pub fn register_backend(mut self, name: &str, factory: Factory) -> Self {
+        self.registry.register(name, factory);
+        self
+    }
+
+    /// Mounts `fs` at `mount`, it is served as is without wrappers of configured mounts.
+    pub fn add_filesystem(mut self, mount: &str, fs: Box<dyn DavFileSystem>) -> Self {
+        self.filesystems.push((mount.to_string(), fs));
+        self
+    }
+
+    /// Replaces prop storage from configuration.
+    pub fn set_props_storage(mut self, props: Box<dyn PropStorage>) -> Self {
+        self.props = Some(props);
+        self
+    }
+
+    pub async fn build(self) -> anyhow::Result<Application> {
+        let mut config = self.config;
         let addr = format!("{}:{}", config.app.host, config.app.port);
-        let mut fs = AggregateBuilder::new();
+        let header_timeout = Duration::from_secs(config.timeouts.header);
+        let connections = Connections::new(config.connections.per_ip);
+        // connections are shared by hosts, listed by top level only
+        let connections_path = config.metrics.connections_path.take();
+        // one filter process serves all hosts
+        let filter = match (self.filter, config.request_filter.take()) {
+            (Some(f), _) => Some(f),
+            (None, Some(conf)) => {
+                let f = ScriptFilter::new(conf.command, Duration::from_millis(conf.timeout))?;
+                Some(Arc::new(f) as Arc<dyn RequestFilter>)
+            }
+            (None, None) => None,
+        };
+        let error_reports = config.error_reports.take();
+        let mirror_addr = config.mirror.take().map(|m| {
+            let host = m.host.unwrap_or_else(|| config.app.host.clone());
+            format!("{}:{}", host, m.port)
+        });
+        let mut host_configs = vec![];
+        for h in std::mem::take(&mut config.hosts) {
+            if h.names.is_empty() {
+                anyhow::bail!("virtual host without names");
+            }
+            host_configs.push(host_config(&config, h));
+        }
 
-        for fss in config.filesystems {
-            fs = fs.add_route((&fss.mount_path, get_backend_by_type(fss.fs).await));
+        let (mut handler, props, mut watchers) = build_handler(
+            config,
+            self.filesystems,
+            self.props,
+            &self.registry,
+            filter.clone(),
+        )
+        .await?;
+        if let Some(ref conf) = error_reports {
+            let reporter = Reporter::new(conf).context("can't set up error reports")?;
+            reporter.install_panic_hook();
+            handler.reporter = Some(reporter);
         }
+        handler.connections = connections_path.map(|p| (p, connections.clone()));
+        let mut props = vec![props];
+        for (names, config) in host_configs {
+            let (h, p, w) = build_handler(config, vec![], None, &self.registry, filter.clone())
+                .await
+                .with_context(|| format!("can't build virtual host {}", names.join(", ")))?;
+            handler.hosts.push((names, Arc::new(h)));
+            props.push(p);
+            watchers.extend(w);
+        }
+        let mirror = mirror_addr.map(|a| (a, Arc::new(handler.mirror())));
+        Ok(Application {
+            addr,
+            mirror,
+            header_timeout,
+            connections,
+            handler: Arc::new(handler),
+            props,
+            _watchers: watchers,
+        })
+    }
+}
 
-        fs = fs.set_props_storage(get_props_storage_by_conf(
-            config.prop_storage.unwrap_or(PropsStorage::Mem),
-        ));
+/// Configuration of virtual host, it shares everything except mounts and
+/// their storages with the top level.
+fn host_config(config: &Configuration, host: VirtualHost) -> (Vec<String>, Configuration) {
+    let uploads = config.uploads.clone().map(|mut u| {
+        // uploads of hosts are kept apart, their ids are valid only at their host
+        let dir = tus::uploads_dir(&u).join(&host.names[0]);
+        u.dir = Some(dir.to_string_lossy().into_owned());
+        u
+    });
+    // props of hosts are apart, so are their operations
+    let journal = config
+        .journal
+        .as_ref()
+        .map(|j| format!("{}.{}", j, host.names[0]));
+    let config = Configuration {
+        filesystems: host.filesystems,
+        prop_storage: host.prop_storage,
+        lock_storage: host.lock_storage,
+        journal,
+        uploads,
+        hosts: vec![],
+        // exchanges are dumped before routing to hosts
+        dump: None,
+        ..config.clone()
+    };
+    (host.names, config)
+}
 
-        let dav_server = DavHandler::builder()
-            .filesystem(fs.build().expect("cant build aggregate"))
-            .locksystem(MemLs::new())
-            .build_handler();
+/// Builds handler serving mounts of `config` and `filesystems` added by
+/// embedding program.
+async fn build_handler(
+    config: Configuration,
+    filesystems: Vec<(String, Box<dyn DavFileSystem>)>,
+    props: Option<Box<dyn PropStorage>>,
+    registry: &Registry,
+    filter: Option<Arc<dyn RequestFilter>>,
+) -> anyhow::Result<(Handler, Box<dyn PropStorage>, Vec<RecommendedWatcher>)> {
+    let mut fs = AggregateBuilder::new();
+    let mut no_lock_mounts = vec![];
+    let mut cache_control = vec![];
+    let mut headers = vec![];
+    let mut remote_mounts = vec![];
+    let mut backend_types = vec![];
+    let mut groupware_mounts = vec![];
+    let mut mounts = vec![];
+    let mut watched = vec![];
+    let mut usage = vec![];
+    let mut breakers = vec![];
+    let mut access = vec![];
+    let mut disks = vec![];
+    let mut api_calls = vec![];
+    let mut redirects = vec![];
+    let mut methods = vec![];
+    let mut advertised = vec![];
+    let mut compression = vec![];
+    let mut mapper = MountMapper::default();
+    let content_types = ContentTypes::new(config.content_types);
+    let global_disabled = matches!(config.lock_storage, Some(LockStorage::Disabled));
+    let mut ls = AggregateLs::new(
+        get_lock_system_by_conf(config.lock_storage.unwrap_or(LockStorage::Mem)).await?,
+    );
 
-        Application { addr, dav_server }
+    for fss in config.filesystems {
+        let no_locks = match &fss.lock_storage {
+            Some(LockStorage::Disabled) => true,
+            _ if !fss.locking => true,
+            Some(l) => {
+                ls.add_route(&fss.mount_path, get_lock_system_by_conf(l.clone()).await?);
+                false
+            }
+            None => global_disabled,
+        };
+        if no_locks && config.compat.macos {
+            ls.add_route(&fss.mount_path, FakeLs::new());
+        } else if no_locks {
+            no_lock_mounts.push(fss.mount_path.clone());
+        }
+        if uses_backend_props(&fss.fs) {
+            fs = fs.set_backend_props(&fss.mount_path);
+        }
+        if let Some(ref v) = fss.cache_control {
+            let v = HeaderValue::from_str(v).context("invalid cache_control value")?;
+            cache_control.push((fss.mount_path.clone(), v));
+        }
+        if !fss.headers.is_empty() {
+            let mut map = HeaderMap::new();
+            for (k, v) in &fss.headers {
+                let name = HeaderName::from_bytes(k.as_bytes())
+                    .with_context(|| format!("invalid header name {}", k))?;
+                let value = HeaderValue::from_str(v)
+                    .with_context(|| format!("invalid value of header {}", k))?;
+                map.insert(name, value);
+            }
+            headers.push((fss.mount_path.clone(), map));
+        }
+        mounts.push(fss.mount_path.clone());
+        if matches!(fss.fs, Filesystem::S3 { .. } | Filesystem::Plugin { .. })
+            || fss.breaker.is_some()
+        {
+            remote_mounts.push(fss.mount_path.clone());
+        }
+        fs = fs.set_backend_type(&fss.mount_path, fss.fs.type_name());
+        if let Some(max) = fss.dedupe {
+            fs = fs.set_dedupe(&fss.mount_path, max);
+        }
+        if let Some(ref c) = fss.compression {
+            let rules = compression::Rules::new(&fss.mount_path, c)
+                .with_context(|| format!("invalid compression of {}", fss.mount_path))?;
+            compression.push(rules);
+        }
+        backend_types.push((fss.mount_path.clone(), fss.fs.type_name().to_string()));
+        if let Filesystem::FS {
+            ref path,
+            watch: true,
+            ..
+        } = fss.fs
+        {
+            watched.push((path.clone(), fss.mount_path.clone()));
+        }
+        if let Filesystem::FS { ref path, .. } = fss.fs {
+            disks.push((fss.mount_path.clone(), PathBuf::from(path)));
+        }
+        if fss.groupware {
+            groupware_mounts.push(fss.mount_path.clone());
+        }
+        if fss.allow_methods.is_some() || !fss.deny_methods.is_empty() {
+            methods.push(MethodRules::new(
+                &fss.mount_path,
+                fss.allow_methods.clone(),
+                fss.deny_methods.clone(),
+            ));
+        }
+        if let Some(ref a) = fss.advertise {
+            advertised.push(Advertised::new(&fss.mount_path, a)?);
+        }
+        if let Some(ref policy) = fss.snapshots {
+            let schedule = Schedule::parse(&policy.schedule)
+                .map_err(|e| anyhow::anyhow!("invalid snapshot schedule: {}", e))?;
+            let snapshots = get_snapshots_by_conf(&fss, &content_types)
+                .await
+                .context("can't set up snapshots")?;
+            snapshot::spawn(fss.mount_path.clone(), snapshots, schedule, policy.keep);
+        }
+        if let Filesystem::S3 {
+            redirect_downloads: Some(min_size),
+            redirect_expiry,
+            case_insensitive,
+            ..
+        } = fss.fs
+        {
+            // presigned urls name objects by their key, which mapped paths don't match
+            let mapped = fss.unicode_normalization.is_some() || !fss.rewrite.is_empty();
+            if case_insensitive || mapped {
+                warn!(mount = %fss.mount_path, "redirects don't work with path mapping");
+            } else {
+                let backend = S3Backend::new_unboxed(fss.fs.clone(), content_types.clone())
+                    .await
+                    .context("can't set up download redirects")?;
+                redirects.push(Redirect::new(
+                    &fss.mount_path,
+                    backend,
+                    min_size,
+                    Duration::from_secs(redirect_expiry),
+                ));
+            }
+        }
+        add_mount_mappers(&mut mapper, &fss);
+        let calls = match fss.fs {
+            Filesystem::S3 { pricing, .. } => {
+                let c = Arc::new(ApiCalls::default());
+                api_calls.push((fss.mount_path.clone(), c.clone(), pricing));
+                Some(c)
+            }
+            _ => None,
+        };
+        let mut backend =
+            get_backend_by_type(fss.fs.clone(), &content_types, registry, calls).await?;
+        if let Some(ref policy) = fss.breaker {
+            let failover = match policy.failover {
+                Some(ref f) => {
+                    Some(get_backend_by_type(f.clone(), &content_types, registry, None).await?)
+                }
+                None => None,
+            };
+            let b = Arc::new(Breaker::new(
+                policy.failures,
+                Duration::from_secs(policy.cooldown),
+            ));
+            backend = BreakerFs::new(backend, failover, b.clone());
+            fs = fs.set_breaker(&fss.mount_path, b.clone());
+            breakers.push((fss.mount_path.clone(), b));
+        }
+        // before middleware so writes denied by it don't count
+        let a = Arc::new(Access::default());
+        backend = AccessFs::new(backend, a.clone());
+        access.push((fss.mount_path.clone(), a));
+        let mut backend = wrap_backend(backend, &fss)?;
+        if config.metrics.path.is_some() || fss.quota.is_some() {
+            let u = Arc::new(Usage::default());
+            let refresh = Duration::from_secs(config.metrics.usage_refresh);
+            usage::spawn_refresh(backend.clone(), u.clone(), refresh);
+            backend = UsageFs::new(backend, u.clone(), fss.quota);
+            usage.push((fss.mount_path.clone(), u));
+        }
+        fs = fs.add_route((&fss.mount_path, backend));
+    }
+    for (mount, backend) in filesystems {
+        mounts.push(mount.clone());
+        fs = fs.add_route((&mount, backend));
+    }
+
+    let tree_ttl = config.propfind.tree_stats_ttl.is_some();
+    let props = match props {
+        Some(p) => p,
+        None => get_props_storage_by_conf(config.prop_storage.unwrap_or(PropsStorage::Mem)),
+    };
+    let watchers = watched
+        .into_iter()
+        .map(|(path, mount)| watcher::watch(PathBuf::from(path), mount, props.clone()))
+        .collect::<notify::Result<Vec<_>>>()
+        .context("can't watch filesystem")?;
+    fs = fs
+        .set_props_storage(props.clone())
+        .set_content_types(content_types.clone());
+
+    let policy = config.lock_policy;
+    let aggregate_ls = ls.clone();
+    let ls: Box<dyn DavLockSystem> = PolicyLs::new(
+        ls,
+        policy.default_timeout.map(Duration::from_secs),
+        policy.max_timeout.map(Duration::from_secs),
+        policy.allow_infinite,
+    );
+
+    // mappings are applied before routing so props are stored under mapped paths too
+    let journal = match config.journal {
+        Some(ref j) => Some(Journal::open(Path::new(j)).context("can't open journal")?),
+        None => None,
+    };
+    if let Some(ref j) = journal {
+        fs = fs.set_journal(j.clone());
+    }
+    if let Some(ttl) = config.propfind.tree_stats_ttl {
+        fs = fs.set_tree_stats(TreeStats::new(Duration::from_secs(ttl)));
+    }
+    let fs = fs.build().context("can't build aggregate")?;
+    let replayed = fs.recover().await.context("can't replay journal")?;
+    if replayed > 0 {
+        info!("fixed props after {} journaled operations", replayed);
+    }
+    if let Some(secs) = config.reconcile_interval {
+        reconcile::spawn(fs.clone(), aggregate_ls, Duration::from_secs(secs));
+    }
+    let mut fs: Box<dyn DavFileSystem> = fs;
+    if !mapper.is_empty() {
+        fs = PathMapFs::new(fs, Arc::new(mapper));
+    }
+    if config.compat.macos {
+        fs = IgnoreFs::new(fs, &compat::finder_ignored(), IgnoreAction::Swallow)
+            .context("invalid ignore pattern")?;
+    }
+    // indexed by request paths, changes of all handlers go through it
+    let find = config.search_index.as_ref().map(|c| {
+        let i = Arc::new(Index::new(content_types.clone(), c.content_limit));
+        fs = IndexFs::new(fs.clone(), i.clone());
+        index::spawn_build(fs.clone(), i.clone());
+        Find::new(&c.path, i, c.max_results)
+    });
+
+    let tus = match config.uploads {
+        Some(ref u) => {
+            let tus = Tus::new(u).context("can't create uploads directory")?;
+            tus::spawn_cleanup(
+                tus.dir().to_path_buf(),
+                Duration::from_secs(u.expire * 3600),
+            );
+            Some(tus)
+        }
+        None => None,
+    };
+
+    let tree_path = config.metrics.tree_path.clone().filter(|_| tree_ttl);
+
+    // symlinks are hidden from listings unless mount lists them
+    let dav_server = DavHandler::builder()
+        .filesystem(fs.clone())
+        .locksystem(ls.clone())
+        .build_handler();
+
+    let handler = Handler {
+        dav_server,
+        fs,
+        ls,
+        no_lock_mounts,
+        propfind: config.propfind,
+        content_types,
+        cache_control,
+        headers,
+        remote_mounts,
+        backend_types,
+        reporter: None,
+        connections: None,
+        xml_errors: config.errors.xml_bodies,
+        groupware_mounts,
+        mounts,
+        user_header: config.events.user_header.clone(),
+        notifier: get_notifier_by_conf(config.events)?,
+        body_timeout: Duration::from_secs(config.timeouts.body),
+        request_timeout: config.timeouts.request.map(Duration::from_secs),
+        metrics_path: config.metrics.path,
+        usage,
+        ready_path: config.metrics.ready_path,
+        tree_path,
+        transfers: config
+            .metrics
+            .transfers_path
+            .map(|p| (p, Arc::new(Transfers::default()))),
+        breakers,
+        access,
+        disks,
+        api_calls,
+        redirects,
+        methods,
+        advertised,
+        compression,
+        windows: config.compat.windows,
+        macos: config.compat.macos,
+        dump: config.dump.as_ref().map(Dump::new),
+        path_locks: PathLocks::default(),
+        tus,
+        filter,
+        find,
+        ui: config.ui.map(|u| Ui::new(&u.path)),
+        journal,
+        read_only: None,
+        hosts: vec![],
+    };
+    Ok((handler, props, watchers))
+}
+
+impl Application {
+    pub async fn build(config: Configuration) -> anyhow::Result<Application> {
+        ApplicationBuilder::new(config).build().await
+    }
+
+    /// Handler of requests, for serving application without `run`.
+    pub fn handler(&self) -> Arc<Handler> {
+        self.handler.clone()
+    }
+
+    /// Writes changed properties, to be called when application stops
+    /// serving requests.
+    pub async fn flush(&self) -> anyhow::Result<()> {
+        let journals = self.handler.journals();
+        let checkpoints = journals.iter().map(|j| j.checkpoint()).collect::<Vec<_>>();
+        for p in &self.props {
+            p.flush()
+                .await
+                .map_err(|e| anyhow::anyhow!("can't flush props storage: {:?}", e))?;
+        }
+        // props of operations done before flush are written
+        for (j, c) in journals.iter().zip(checkpoints) {
+            j.clear_until(c).await.context("can't clear journal")?;
+        }
+        Ok(())
     }
 
     #[instrument(skip(self))]
     pub async fn run(self) {
-        let dav_server = self.dav_server;
-
-        let make_svc = make_service_fn(move |_conn| {
-            let dav_server = dav_server.clone();
-            async move {
-                let func = move |req| {
-                    let dav_server = dav_server.clone();
-                    async move { Ok::<_, Infallible>(dav_server.handle(req).await) }
+        let main = serve(
+            &self.addr,
+            self.handler.clone(),
+            self.header_timeout,
+            self.connections.clone(),
+        );
+        match self.mirror {
+            Some((ref addr, ref handler)) => {
+                info!("serving read-only mirror on {}", addr);
+                let mirror = serve(
+                    addr,
+                    handler.clone(),
+                    self.header_timeout,
+                    self.connections.clone(),
+                );
+                tokio::join!(main, mirror);
+            }
+            None => main.await,
+        }
+        if let Err(e) = self.flush().await {
+            error!("{}", e);
+        }
+    }
+}
+
+/// Accepts connections on `addr` until shutdown signal.
+async fn serve(
+    addr: &str,
+    handler: Arc<Handler>,
+    header_timeout: Duration,
+    connections: Arc<Connections>,
+) {
+    let make_svc = make_service_fn(move |conn: &TimeoutIo<TcpStream>| {
+        let conn_deadline = conn.deadline();
+        let peer = conn.get_ref().peer_addr().ok().map(Peer);
+        let handler = handler.clone();
+        async move {
+            let func = move |mut req: Request<hyper::Body>| {
+                if let Some(p) = peer {
+                    req.extensions_mut().insert(p);
+                }
+                let handler = handler.clone();
+                let request_deadline = handler.deadline();
+                let guard = conn_deadline.request_started(request_deadline);
+                async move {
+                    let resp = handler.respond(req, request_deadline).await;
+                    Ok::<_, Infallible>(
+                        resp.map(|b| timeouts::response_body(b, Some(guard), request_deadline)),
+                    )
+                }
+            };
+            Ok::<_, Infallible>(service_fn(func))
+        }
+    });
+    let addr = SocketAddr::from_str(addr).expect("can't parse host and port");
+    let listener = TcpListener::bind(&addr).await.expect("can't bind address");
+    let incoming = timeouts::incoming(listener, header_timeout, connections);
+    let srv = Server::builder(accept::from_stream(incoming))
+        .serve(make_svc)
+        .with_graceful_shutdown(shutdown_signal());
+    if let Err(e) = srv.await {
+        error!("error running server: {}", e);
+    }
+}
+
+/// Serves requests of application. Programs accepting connections by
+/// themselves call `handle` from their hyper service.
+pub struct Handler {
+    dav_server: DavHandler,
+    fs: Box<dyn DavFileSystem>,
+    /// same as of dav handler, checked before deleting trees
+    ls: Box<dyn DavLockSystem>,
+    no_lock_mounts: Vec<String>,
+    propfind: Propfind,
+    content_types: ContentTypes,
+    cache_control: Vec<(String, HeaderValue)>,
+    headers: Vec<(String, HeaderMap)>,
+    /// mounts which backend failures are reported as 502
+    remote_mounts: Vec<String>,
+    backend_types: Vec<(String, String)>,
+    /// set on top-level handler only, failures of hosts are reported by it
+    reporter: Option<Arc<Reporter>>,
+    /// path listing connections and connections accepted by application,
+    /// set on top-level handler only
+    connections: Option<(String, Arc<Connections>)>,
+    xml_errors: bool,
+    groupware_mounts: Vec<String>,
+    mounts: Vec<String>,
+    notifier: Notifier,
+    user_header: Option<String>,
+    body_timeout: Duration,
+    request_timeout: Option<Duration>,
+    metrics_path: Option<String>,
+    usage: Vec<(String, Arc<Usage>)>,
+    ready_path: Option<String>,
+    /// path answering totals of collections
+    tree_path: Option<String>,
+    /// path of transfers listing and bodies being transferred
+    transfers: Option<(String, Arc<Transfers>)>,
+    breakers: Vec<(String, Arc<Breaker>)>,
+    /// mounts whose storage may refuse writes
+    access: Vec<(String, Arc<Access>)>,
+    /// local directories of fs mounts, checked for free space
+    disks: Vec<(String, PathBuf)>,
+    /// requests sent by S3 mounts and prices to estimate their cost from
+    api_calls: Vec<(String, Arc<ApiCalls>, Option<Pricing>)>,
+    redirects: Vec<Redirect>,
+    methods: Vec<MethodRules>,
+    advertised: Vec<Advertised>,
+    /// mounts compressing GET responses
+    compression: Vec<compression::Rules>,
+    windows: bool,
+    macos: bool,
+    dump: Option<Dump>,
+    path_locks: PathLocks,
+    tus: Option<Tus>,
+    filter: Option<Arc<dyn RequestFilter>>,
+    find: Option<Find>,
+    ui: Option<Ui>,
+    journal: Option<Arc<Journal>>,
+    /// set on mirror handlers, permits only reading methods
+    read_only: Option<MethodRules>,
+    /// handlers of virtual hosts by their names
+    hosts: Vec<(Vec<String>, Arc<Handler>)>,
+}
+
+impl Handler {
+    /// Handler serving the same mounts and hosts read-only, through the same
+    /// request filter. Uploads, dumps, journals and endpoints other than
+    /// listings are left to the main one.
+    fn mirror(&self) -> Handler {
+        let fs = ReadOnlyFs::new(self.fs.clone());
+        let dav_server = DavHandler::builder()
+            .filesystem(fs.clone())
+            .locksystem(self.ls.clone())
+            .build_handler();
+        let read_only = MIRROR_METHODS.iter().map(|m| m.to_string()).collect();
+        Handler {
+            dav_server,
+            fs,
+            ls: self.ls.clone(),
+            no_lock_mounts: self.no_lock_mounts.clone(),
+            propfind: self.propfind.clone(),
+            content_types: self.content_types.clone(),
+            cache_control: self.cache_control.clone(),
+            headers: self.headers.clone(),
+            remote_mounts: self.remote_mounts.clone(),
+            backend_types: self.backend_types.clone(),
+            reporter: self.reporter.clone(),
+            connections: None,
+            xml_errors: self.xml_errors,
+            groupware_mounts: self.groupware_mounts.clone(),
+            mounts: self.mounts.clone(),
+            notifier: self.notifier.clone(),
+            user_header: None,
+            body_timeout: self.body_timeout,
+            request_timeout: self.request_timeout,
+            metrics_path: None,
+            usage: vec![],
+            ready_path: None,
+            tree_path: None,
+            transfers: None,
+            breakers: vec![],
+            access: vec![],
+            disks: vec![],
+            api_calls: vec![],
+            redirects: self.redirects.clone(),
+            methods: self.methods.clone(),
+            advertised: self.advertised.clone(),
+            compression: self.compression.clone(),
+            windows: self.windows,
+            macos: self.macos,
+            dump: None,
+            path_locks: self.path_locks.clone(),
+            tus: None,
+            filter: self.filter.clone(),
+            find: None,
+            ui: None,
+            journal: None,
+            read_only: Some(MethodRules::new("/", Some(read_only), vec![])),
+            hosts: self
+                .hosts
+                .iter()
+                .map(|(names, h)| (names.clone(), Arc::new(h.mirror())))
+                .collect(),
+        }
+    }
+
+    /// Journals of this handler and its hosts.
+    fn journals(&self) -> Vec<Arc<Journal>> {
+        let hosts = self.hosts.iter().flat_map(|(_, h)| h.journals());
+        self.journal.iter().cloned().chain(hosts).collect()
+    }
+
+    /// Handles request within configured request timeout, response body is
+    /// cut when it expires.
+    pub async fn handle(&self, req: Request<hyper::Body>) -> Response<hyper::Body> {
+        let deadline = self.deadline();
+        self.respond(req, deadline)
+            .await
+            .map(|b| timeouts::response_body(b, None, deadline))
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.request_timeout.map(|t| Instant::now() + t)
+    }
+
+    async fn respond(
+        &self,
+        req: Request<hyper::Body>,
+        deadline: Option<Instant>,
+    ) -> Response<hyper::Body> {
+        let sampled = self.dump.as_ref().and_then(|d| d.sample().map(|n| (d, n)));
+        let (req, dumped) = match sampled {
+            Some((d, n)) => {
+                let (req, out) = d.request(n, req).await;
+                (req, Some((d, out)))
+            }
+            None => (req, None),
+        };
+        if !utf8_paths(&req) {
+            return Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(hyper::Body::from("path is not valid UTF-8"))
+                .unwrap();
+        }
+        if let Some((ref p, ref c)) = self.connections {
+            if req.method() == Method::GET && p == req.uri().path() {
+                return match self.filtered(req).await {
+                    Ok(_) => c.response(),
+                    Err(resp) => resp,
                 };
-                Ok::<_, Infallible>(service_fn(func))
             }
-        });
-        let addr = SocketAddr::from_str(&self.addr).expect("can't parse host and port");
-        let srv = Server::bind(&addr).serve(make_svc);
-        if let Err(e) = srv.await {
-            error!("error running server: {}", e);
         }
+        let handler = self.route(&req);
+        let decoded = mounts::decode(req.uri().path()).into_owned();
+        let headers = mount_headers(&decoded, &handler.headers);
+        let remote = handler.remote_mounts.iter().any(|m| in_mount(&decoded, m));
+        let context = self
+            .reporter
+            .as_ref()
+            .map(|_| (req.method().to_string(), req.uri().path().to_string()));
+        let dispatched = panics::isolate(handler.dispatch(req));
+        let resp = match deadline {
+            Some(d) => tokio::time::timeout_at(d, dispatched)
+                .await
+                .unwrap_or_else(|_| timeouts::deadline_exceeded()),
+            None => dispatched.await,
+        };
+        let resp = resp.map(panics::body);
+        let mut resp = errors::map(resp, remote, handler.xml_errors);
+        if let (Some(r), Some((method, path))) = (self.reporter.as_ref(), context) {
+            let status = resp.status();
+            if status == StatusCode::INTERNAL_SERVER_ERROR || status == StatusCode::BAD_GATEWAY {
+                r.report(handler.failure_report(method, path, status));
+            }
+        }
+        if let Some(h) = headers {
+            for (k, v) in h {
+                resp.headers_mut().insert(k, v.clone());
+            }
+        }
+        match dumped {
+            Some((d, out)) => d.response(out, resp).await,
+            None => resp,
+        }
+    }
+
+    fn failure_report(&self, method: String, path: String, status: StatusCode) -> Report {
+        let message = format!("{} {} failed with {}", method, path, status);
+        let mut r = Report::new("error", message);
+        r.mount = mount_of(&mounts::decode(&path), &self.mounts);
+        r.backend = self
+            .backend_types
+            .iter()
+            .find(|(m, _)| Some(m) == r.mount.as_ref())
+            .map(|(_, t)| t.clone());
+        r.method = Some(method);
+        r.path = Some(path);
+        r.status = Some(status.as_u16());
+        r
+    }
+
+    /// Request as changed by request filter, Err is response for denied one.
+    async fn filtered<B>(&self, req: Request<B>) -> Result<Request<B>, Response<hyper::Body>> {
+        match self.filter {
+            Some(ref f) => {
+                let user = request_user(&req, self.user_header.as_deref());
+                filter::apply(f, req, user).await
+            }
+            None => Ok(req),
+        }
+    }
+
+    /// False for mounts with locking disabled, nested mount can keep locks
+    /// while outer one has them disabled.
+    fn locking(&self, decoded: &str) -> bool {
+        mount_of(decoded, &self.mounts).map_or(true, |m| !self.no_lock_mounts.contains(&m))
+    }
+
+    /// Checks upload request writing `len` bytes to `dest` as PUT of client
+    /// to it would be: request filter, method rules, locks and free space.
+    /// Filter can't rewrite destination, such uploads are refused.
+    async fn check_upload<B>(
+        &self,
+        req: &Request<B>,
+        dest: &str,
+        len: u64,
+    ) -> Option<Response<hyper::Body>> {
+        let status = |s| {
+            Response::builder()
+                .status(s)
+                .body(hyper::Body::empty())
+                .unwrap()
+        };
+        let mut put = match Request::put(dest).body(()) {
+            Ok(r) => r,
+            Err(_) => return Some(status(StatusCode::BAD_REQUEST)),
+        };
+        *put.headers_mut() = req.headers().clone();
+        put.headers_mut()
+            .insert(CONTENT_LENGTH, HeaderValue::from(len));
+        let put = match self.filtered(put).await {
+            Ok(r) if r.uri().path() == dest => r,
+            Ok(_) => return Some(status(StatusCode::FORBIDDEN)),
+            Err(resp) => return Some(resp),
+        };
+        if let Some(resp) = self.read_only.as_ref().and_then(|m| m.check(put.method())) {
+            return Some(resp);
+        }
+        let decoded = mounts::decode(dest);
+        let methods = methods::rules_for(&decoded, &self.methods);
+        if let Some(resp) = methods.and_then(|m| m.check(put.method())) {
+            return Some(resp);
+        }
+        if self.locking(&decoded) {
+            if let Some(resp) = if_header::check_locks(self.ls.as_ref(), &put) {
+                return Some(resp.map(hyper::Body::wrap_stream));
+            }
+        }
+        insufficient_space(&put, &decoded, &self.disks)
+    }
+
+    /// True if client sending `headers` may GET `href`, for resources request
+    /// reads besides its own path: request filter and method rules.
+    async fn readable(&self, headers: &HeaderMap, href: String) -> bool {
+        let mut get = match Request::get(href.as_str()).body(()) {
+            Ok(r) => r,
+            Err(_) => return false,
+        };
+        *get.headers_mut() = headers.clone();
+        match self.filtered(get).await {
+            Ok(r) if r.uri().path() == href => {}
+            _ => return false,
+        }
+        let decoded = mounts::decode(&href);
+        methods::rules_for(&decoded, &self.methods)
+            .map_or(true, |m| m.check(&Method::GET).is_none())
+    }
+
+    /// Handler of virtual host request is sent to, this one if none matches.
+    fn route<B>(&self, req: &Request<B>) -> &Handler {
+        if self.hosts.is_empty() {
+            return self;
+        }
+        let host = match request_host(req) {
+            Some(h) => h,
+            None => return self,
+        };
+        self.hosts
+            .iter()
+            .find(|(names, _)| names.iter().any(|n| n.eq_ignore_ascii_case(host)))
+            .map_or(self, |(_, h)| h.as_ref())
+    }
+
+    async fn dispatch(&self, req: Request<hyper::Body>) -> Response<hyper::Body> {
+        // readiness is probed by orchestrators, it is never filtered
+        if req.method() == Method::GET && self.ready_path.as_deref() == Some(req.uri().path()) {
+            return metrics::ready(&self.breakers, &self.access);
+        }
+        let req = match self.filtered(req).await {
+            Ok(r) => r,
+            Err(resp) => return resp,
+        };
+        let path = req.uri().path();
+        if req.method() == Method::GET && self.metrics_path.as_deref() == Some(path) {
+            return metrics::response(&self.usage, &self.breakers, &self.access, &self.api_calls);
+        }
+        if req.method() == Method::GET && self.tree_path.as_deref() == Some(path) {
+            return metrics::tree(self.fs.as_ref(), req.uri().query()).await;
+        }
+        if let Some((ref p, ref t)) = self.transfers {
+            if req.method() == Method::GET && p == path {
+                return t.response();
+            }
+        }
+        if let Some(f) = self.find.as_ref().filter(|f| f.handles(&req)) {
+            return f.handle(&req);
+        }
+        if let Some(u) = self.ui.as_ref().filter(|u| u.handles(&req)) {
+            return u.handle();
+        }
+        let path = req.uri().path();
+        // every mount lookup uses decoded path, as filesystems do
+        let decoded = mounts::decode(path).into_owned();
+        if let Some(resp) = self.read_only.as_ref().and_then(|m| m.check(req.method())) {
+            return resp;
+        }
+        let methods = methods::rules_for(&decoded, &self.methods);
+        if let Some(resp) = methods.and_then(|m| m.check(req.method())) {
+            return resp;
+        }
+        if self.macos {
+            if let Some(resp) = compat::finder_probe(req.method(), path) {
+                return resp;
+            }
+        }
+        if let Some(resp) = insufficient_space(&req, &decoded, &self.disks) {
+            return resp;
+        }
+        let fs = &self.fs;
+        let redirect = self.redirects.iter().find(|r| r.applies(&req));
+        let listing = listing::format(&req);
+        let tus = self.tus.as_ref().filter(|t| t.handles(path));
+        let is_tus = tus.is_some();
+        let locking = self.locking(&decoded);
+        let infinity = self.propfind.allow_infinity && propfind::is_infinity_propfind(&req);
+        let max_entries = self.propfind.max_entries;
+        let content_type = file_content_type(&req, &self.content_types);
+        let notifier = &self.notifier;
+        let user_header = self.user_header.as_deref();
+        let user = request_user(&req, user_header);
+        let event = if notifier.is_empty() || is_tus {
+            None
+        } else {
+            change_event(&req, &self.mounts, user_header)
+        };
+        let groupware_mount = innermost_mount(&decoded, &self.groupware_mounts, |m| m.as_str());
+        let groupware = groupware_mount.is_some();
+        let cache = match *req.method() {
+            Method::GET | Method::HEAD => cache_control(&decoded, &self.cache_control).cloned(),
+            _ => None,
+        };
+        let compress = compression::rules_for(&decoded, &self.compression)
+            .and_then(|r| compression::negotiate(&req, r).map(|c| (c, r)));
+
+        let upload = match tus {
+            Some(t) => t.destination(&req).await,
+            None => None,
+        };
+        if let Some((ref dest, len)) = upload {
+            if let Some(resp) = self.check_upload(&req, dest, len).await {
+                return resp;
+            }
+        }
+
+        let req = req.map(|b| timeouts::request_body(b, self.body_timeout));
+        let href = req.uri().path().to_string();
+        let tracking = self
+            .transfers
+            .as_ref()
+            .map(|(_, t)| (t, transfers::client(&req, user.clone())));
+        let mut req = match tracking {
+            Some((t, ref client)) if matches!(*req.method(), Method::PUT | Method::PATCH) => {
+                req.map(|b| t.track(Direction::Upload, &href, client.clone(), b))
+            }
+            _ => req,
+        };
+        let destination = req
+            .headers()
+            .get("Destination")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<hyper::Uri>().ok());
+        let mut written = vec![href.as_str()];
+        if matches!(req.method().as_str(), "MOVE" | "COPY") {
+            written.extend(destination.as_ref().map(|u| u.path()));
+        }
+        // finished upload is written to its destination
+        written.extend(upload.as_ref().map(|(d, _)| d.as_str()));
+        let _write = match req.method().as_str() {
+            "PUT" | "PATCH" | "DELETE" | "MOVE" | "COPY" => {
+                Some(self.path_locks.lock(&written).await)
+            }
+            _ => None,
+        };
+        if let Some(tus) = tus {
+            let (resp, done) = tus.handle(fs.as_ref(), req).await;
+            // event is about destination, not the upload url
+            if let Some(mut e) = done.filter(|_| !notifier.is_empty()) {
+                e.mount = mount_of(&mounts::decode(&e.path), &self.mounts);
+                e.user = user;
+                notifier.notify(e);
+            }
+            return resp.map(hyper::Body::wrap_stream);
+        }
+        if let Some(f) = listing {
+            if let Some(resp) = listing::handle(fs.as_ref(), &href, f).await {
+                return resp.map(hyper::Body::wrap_stream);
+            }
+        }
+        if let Some(r) = redirect {
+            if let Some(resp) = r.handle(fs.as_ref(), &href).await {
+                return resp.map(hyper::Body::wrap_stream);
+            }
+        }
+        propfind::allow_infinity(&mut req, infinity);
+        let method = req.method().clone();
+        let is_options = method == Method::OPTIONS;
+        let is_propfind = req.method().as_str() == "PROPFIND";
+        if groupware {
+            groupware::rewrite_mkcalendar(&mut req);
+        }
+        let deleted = if method == Method::DELETE {
+            let ls = Some(self.ls.as_ref()).filter(|_| locking);
+            delete::handle(fs.clone(), ls, &req).await
+        } else {
+            None
+        };
+        let mut resp = if let Some(resp) = deleted {
+            resp
+        } else if req.method().as_str() == "SEARCH" {
+            search::handle(fs.as_ref(), req, &self.propfind).await
+        } else if groupware && req.method().as_str() == "REPORT" {
+            let headers = req.headers().clone();
+            let mount = groupware_mount.map_or("/", |m| m.as_str());
+            let readable = |href| self.readable(&headers, href);
+            groupware::handle_report(fs.as_ref(), req, mount, readable).await
+        } else if req.method() == Method::PATCH {
+            let locked = Some(self.ls.as_ref())
+                .filter(|_| locking)
+                .and_then(|ls| if_header::check_locks(ls, &req));
+            match locked {
+                Some(resp) => resp,
+                None => patch::handle(fs.as_ref(), req).await,
+            }
+        } else if locking {
+            let handled = self.dav_server.handle(req);
+            aggregate::with_request_path(href.clone(), handled).await
+        } else {
+            let handled = handle_without_locks(self.dav_server.clone(), req);
+            aggregate::with_request_path(href.clone(), handled).await
+        };
+        if is_options {
+            resp.headers_mut()
+                .insert("DASL", HeaderValue::from_static("<DAV:basicsearch>"));
+            patch::advertise(&mut resp);
+            if groupware {
+                groupware::advertise(&mut resp);
+            }
+            if let Some(m) = methods {
+                m.restrict_allow(&mut resp);
+            }
+            if let Some(ref m) = self.read_only {
+                m.restrict_allow(&mut resp);
+            }
+            if let Some(a) = methods::advertised_for(&decoded, &self.advertised) {
+                a.apply(&mut resp);
+            }
+        }
+        if let Some(ct) = content_type {
+            set_content_type(&mut resp, &ct);
+        }
+        if let Some(e) = event {
+            if completed(resp.status()) {
+                notifier.notify(e);
+            }
+        }
+        if let Some(v) = cache {
+            let s = resp.status();
+            if s.is_success() || s == StatusCode::NOT_MODIFIED {
+                resp.headers_mut().insert(CACHE_CONTROL, v);
+            }
+        }
+        let resp = if infinity {
+            resp.map(|b| {
+                hyper::Body::wrap_stream(propfind::limit_responses(b, max_entries, href.clone()))
+            })
+        } else {
+            resp.map(hyper::Body::wrap_stream)
+        };
+        // quota properties are read while PROPFIND body is sent
+        let mut resp = if is_propfind {
+            resp.map(|b| {
+                hyper::Body::wrap_stream(aggregate::stream_with_request_path(href.clone(), b))
+            })
+        } else {
+            resp
+        };
+        if self.windows {
+            if is_options {
+                compat::windows_options(&href, &mut resp);
+            }
+            resp = compat::with_length(&method, resp).await;
+        }
+        if let Some((coding, rules)) = compress {
+            resp = compression::apply(resp, coding, rules);
+        }
+        match tracking {
+            Some((t, client)) if method == Method::GET && resp.status().is_success() => {
+                resp.map(|b| t.track(Direction::Download, &href, client, b))
+            }
+            _ => resp,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::{future::BoxFuture, FutureExt};
+
+    #[test]
+    fn cache_control_by_mount() {
+        let mounts = vec![
+            (
+                "/public".to_string(),
+                HeaderValue::from_static("max-age=86400"),
+            ),
+            (
+                "/public/live".to_string(),
+                HeaderValue::from_static("no-cache"),
+            ),
+        ];
+        let get = |p| cache_control(p, &mounts).map(|v| v.to_str().unwrap().to_string());
+        assert_eq!(get("/public/a.png"), Some("max-age=86400".into()));
+        assert_eq!(get("/public/live/feed.xml"), Some("no-cache".into()));
+        assert_eq!(get("/publicity/a.png"), None);
+        assert_eq!(get("/private/a.png"), None);
+    }
+
+    #[test]
+    fn events() {
+        let mounts = vec!["/fs1".to_string(), "/fs1/nested".to_string()];
+        let req = Request::builder()
+            .method("MOVE")
+            .uri("/fs1/nested/a.txt")
+            .header("Destination", "http://localhost:5000/fs1/b.txt")
+            .header("X-Remote-User", "alice")
+            .body(())
+            .unwrap();
+        let e = change_event(&req, &mounts, Some("X-Remote-User")).unwrap();
+        assert_eq!(e.mount, Some("/fs1/nested".into()));
+        assert_eq!(e.destination, Some("/fs1/b.txt".into()));
+        assert_eq!(e.user, Some("alice".into()));
+        assert_eq!(change_event(&req, &mounts, None).unwrap().user, None);
+
+        let req = Request::builder()
+            .method("PUT")
+            .uri("/fs1/a.txt")
+            .header("Content-Length", "42")
+            .body(())
+            .unwrap();
+        assert_eq!(change_event(&req, &mounts, None).unwrap().size, Some(42));
+
+        let req = Request::builder().uri("/fs1/a.txt").body(()).unwrap();
+        assert!(change_event(&req, &mounts, None).is_none());
+
+        assert!(completed(StatusCode::NO_CONTENT));
+        assert!(!completed(StatusCode::MULTI_STATUS));
+        assert!(!completed(StatusCode::LOCKED));
+    }
+
+    #[test]
+    fn put_exceeding_free_space() {
+        let disks = vec![("/fs".to_string(), std::env::temp_dir())];
+        let put = |uri, len: u64| {
+            Request::builder()
+                .method("PUT")
+                .uri(uri)
+                .header(CONTENT_LENGTH, len.to_string())
+                .body(())
+                .unwrap()
+        };
+        let check = |path, len| insufficient_space(&put(path, len), path, &disks);
+        let resp = check("/fs/big.iso", u64::MAX).unwrap();
+        assert_eq!(resp.status(), StatusCode::INSUFFICIENT_STORAGE);
+        assert!(check("/fs/small.txt", 1).is_none());
+        assert!(check("/mem/big.iso", u64::MAX).is_none());
+    }
+
+    #[tokio::test]
+    async fn embedded_filesystem() -> anyhow::Result<()> {
+        let config: Configuration = serde_yaml::from_str(
+            "app: {host: 127.0.0.1, port: 0}\nfilesystems: [{type: mem, mount_path: /mem}]",
+        )?;
+        let app = ApplicationBuilder::new(config)
+            .add_filesystem("/own", MemFs::new())
+            .build()
+            .await?;
+        let handler = app.handler();
+
+        let req = Request::builder()
+            .method("PUT")
+            .uri("/own/a.txt")
+            .body(hyper::Body::from("data"))?;
+        assert!(handler.handle(req).await.status().is_success());
+        let req = Request::builder()
+            .uri("/own/a.txt")
+            .body(hyper::Body::empty())?;
+        let resp = handler.handle(req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(hyper::body::to_bytes(resp.into_body()).await?, "data");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn virtual_hosts() -> anyhow::Result<()> {
+        let config: Configuration = serde_yaml::from_str(
+            "app: {host: 127.0.0.1, port: 0}
+filesystems: [{type: mem, mount_path: /files}]
+hosts:
+  - names: [dav.family.example]
+    filesystems: [{type: mem, mount_path: /files}]",
+        )?;
+        let handler = Application::build(config).await?.handler();
+        let req = |method, host| {
+            Request::builder()
+                .method(method)
+                .uri("/files/a.txt")
+                .header(HOST, host)
+                .body(match method {
+                    "PUT" => hyper::Body::from("data"),
+                    _ => hyper::Body::empty(),
+                })
+                .unwrap()
+        };
+
+        let resp = handler.handle(req("PUT", "DAV.family.example:8080")).await;
+        assert!(resp.status().is_success());
+        let resp = handler.handle(req("GET", "dav.family.example")).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = handler.handle(req("GET", "dav.me.example")).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_only_mirror() -> anyhow::Result<()> {
+        let config: Configuration = serde_yaml::from_str(
+            "app: {host: 127.0.0.1, port: 0}
+filesystems: [{type: mem, mount_path: /files}]
+metrics: {path: /metrics, transfers_path: /transfers}
+mirror: {port: 0}",
+        )?;
+        let app = Application::build(config).await?;
+        let (addr, mirror) = app.mirror.as_ref().unwrap();
+        assert_eq!(addr, "127.0.0.1:0");
+        let req = |method| {
+            Request::builder()
+                .method(method)
+                .uri("/files/a.txt")
+                .body(match method {
+                    "PUT" => hyper::Body::from("data"),
+                    _ => hyper::Body::empty(),
+                })
+                .unwrap()
+        };
+
+        assert!(app.handler().handle(req("PUT")).await.status().is_success());
+        assert_eq!(mirror.handle(req("GET")).await.status(), StatusCode::OK);
+        for method in ["PUT", "DELETE", "LOCK"].iter() {
+            let resp = mirror.handle(req(*method)).await;
+            assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        }
+        let resp = app.handler().handle(req("GET")).await;
+        assert_eq!(hyper::body::to_bytes(resp.into_body()).await?, "data");
+        for uri in ["/metrics", "/transfers"].iter() {
+            let req = Request::get(*uri).body(hyper::Body::empty())?;
+            assert_eq!(mirror.handle(req).await.status(), StatusCode::NOT_FOUND);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn mount_rules_of_encoded_paths() -> anyhow::Result<()> {
+        let config: Configuration = serde_yaml::from_str(
+            "app: {host: 127.0.0.1, port: 0}
+filesystems:
+  - {type: mem, mount_path: /archive, deny_methods: [DELETE]}
+  - {type: mem, mount_path: /my files, deny_methods: [DELETE]}",
+        )?;
+        let handler = Application::build(config).await?.handler();
+        for uri in ["/%61rchive/x", "/my%20files/x"].iter() {
+            let req = Request::builder()
+                .method("DELETE")
+                .uri(*uri)
+                .body(hyper::Body::empty())?;
+            let resp = handler.handle(req).await;
+            assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED, "{}", uri);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_non_utf8_paths() -> anyhow::Result<()> {
+        let config: Configuration = serde_yaml::from_str(
+            "app: {host: 127.0.0.1, port: 0}
+filesystems: [{type: mem, mount_path: /fs}]",
+        )?;
+        let handler = Application::build(config).await?.handler();
+        let req = Request::get("/fs/%FF").body(hyper::Body::empty())?;
+        assert_eq!(handler.handle(req).await.status(), StatusCode::BAD_REQUEST);
+        let req = Request::builder()
+            .method("MOVE")
+            .uri("/fs/a.txt")
+            .header("Destination", "http://localhost/fs/%FE%FF")
+            .body(hyper::Body::empty())?;
+        assert_eq!(handler.handle(req).await.status(), StatusCode::BAD_REQUEST);
+        let req = Request::get("/fs/%C3%A9").body(hyper::Body::empty())?;
+        assert_eq!(handler.handle(req).await.status(), StatusCode::NOT_FOUND);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_case_insensitive_staging() -> anyhow::Result<()> {
+        let config: Configuration = serde_yaml::from_str(
+            "app: {host: 127.0.0.1, port: 0}
+filesystems: [{type: fs, mount_path: /fs, path: /nonexistent, case_insensitive: true}]",
+        )?;
+        assert!(Application::build(config).await.is_err());
+        Ok(())
+    }
+
+    struct DenyAll;
+
+    impl RequestFilter for DenyAll {
+        fn check<'a>(&'a self, _: &'a filter::FilterRequest) -> BoxFuture<'a, filter::Decision> {
+            futures_util::future::ready(filter::Decision::Deny { status: 403 }).boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn filtered_endpoints() -> anyhow::Result<()> {
+        let config: Configuration = serde_yaml::from_str(
+            "app: {host: 127.0.0.1, port: 0}
+filesystems: [{type: mem, mount_path: /files}]
+metrics:
+  path: /metrics
+  ready_path: /ready
+  transfers_path: /transfers
+  connections_path: /connections",
+        )?;
+        let app = ApplicationBuilder::new(config)
+            .set_request_filter(Arc::new(DenyAll))
+            .build()
+            .await?;
+        let get = |uri| Request::get(uri).body(hyper::Body::empty()).unwrap();
+        for uri in ["/metrics", "/transfers", "/connections"].iter() {
+            let resp = app.handler().handle(get(*uri)).await;
+            assert_eq!(resp.status(), StatusCode::FORBIDDEN, "{}", uri);
+        }
+        let resp = app.handler().handle(get("/ready")).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn upload_destination_checks() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("webdav_ss_tus_dest_{}", std::process::id()));
+        let config: Configuration = serde_yaml::from_str(&format!(
+            "app: {{host: 127.0.0.1, port: 0}}
+filesystems:
+  - {{type: mem, mount_path: /files}}
+  - {{type: mem, mount_path: /archive, deny_methods: [PUT]}}
+uploads: {{path: /.uploads, dir: {}}}",
+            dir.display()
+        ))?;
+        let handler = Application::build(config).await?.handler();
+        let create = |dest: &str| {
+            Request::post("/.uploads")
+                .header("Tus-Resumable", "1.0.0")
+                .header("Upload-Length", 4)
+                .header(
+                    "Upload-Metadata",
+                    format!("destination {}", base64::encode(dest)),
+                )
+                .body(hyper::Body::empty())
+                .unwrap()
+        };
+
+        let resp = handler.handle(create("/%61rchive/a.txt")).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        let lockinfo = "<?xml version=\"1.0\"?><D:lockinfo xmlns:D=\"DAV:\">\
+            <D:lockscope><D:exclusive/></D:lockscope><D:locktype><D:write/></D:locktype>\
+            </D:lockinfo>";
+        let lock = Request::builder()
+            .method("LOCK")
+            .uri("/files/a.txt")
+            .body(hyper::Body::from(lockinfo))?;
+        assert!(handler.handle(lock).await.status().is_success());
+        let resp = handler.handle(create("/files/a.txt")).await;
+        assert_eq!(resp.status(), StatusCode::LOCKED);
+        let resp = handler.handle(create("/files/b.txt")).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn locks_of_nested_mount() -> anyhow::Result<()> {
+        let config: Configuration = serde_yaml::from_str(
+            "app: {host: 127.0.0.1, port: 0}
+lock_storage: {type: none}
+filesystems:
+  - {type: mem, mount_path: /files}
+  - {type: mem, mount_path: /files/shared docs, lock_storage: {type: mem}}",
+        )?;
+        let handler = Application::build(config).await?.handler();
+        let req = |method, path: &str, cond: Option<&str>, body: &str| {
+            let mut b = Request::builder()
+                .method(method)
+                .uri(path)
+                .header(CONTENT_TYPE, patch::PARTIAL_UPDATE)
+                .header("X-Update-Range", "append");
+            if let Some(c) = cond {
+                b = b.header("If", c);
+            }
+            match body {
+                "" => b.body(hyper::Body::empty()).unwrap(),
+                _ => b.body(hyper::Body::from(body.to_string())).unwrap(),
+            }
+        };
+        let lockinfo = "<?xml version=\"1.0\"?><D:lockinfo xmlns:D=\"DAV:\">\
+            <D:lockscope><D:exclusive/></D:lockscope><D:locktype><D:write/></D:locktype>\
+            </D:lockinfo>";
+        let file = "/files/shared%20docs/a.txt";
+
+        let resp = handler.handle(req("PUT", file, None, "data")).await;
+        assert!(resp.status().is_success());
+        let resp = handler.handle(req("LOCK", file, None, lockinfo)).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let token = resp.headers()["Lock-Token"].to_str()?.to_string();
+        let cond = format!("({})", token);
+
+        for method in ["PUT", "PATCH", "DELETE"].iter() {
+            let body = if *method == "DELETE" { "" } else { "more" };
+            let resp = handler.handle(req(*method, file, None, body)).await;
+            assert_eq!(resp.status(), StatusCode::LOCKED, "{}", method);
+        }
+        let resp = handler
+            .handle(req("PATCH", file, Some(&cond), "more"))
+            .await;
+        assert!(resp.status().is_success());
+        let resp = handler.handle(req("PUT", file, Some(&cond), "data")).await;
+        assert!(resp.status().is_success());
+
+        let mut unlock = req("UNLOCK", file, None, "");
+        unlock
+            .headers_mut()
+            .insert("Lock-Token", HeaderValue::from_str(&token)?);
+        assert_eq!(
+            handler.handle(unlock).await.status(),
+            StatusCode::NO_CONTENT
+        );
+        let resp = handler.handle(req("PUT", file, None, "data")).await;
+        assert!(resp.status().is_success());
+
+        // outer mount keeps locking disabled
+        let resp = handler
+            .handle(req("LOCK", "/files/a.txt", None, lockinfo))
+            .await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        Ok(())
     }
 }
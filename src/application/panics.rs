@@ -0,0 +1,96 @@
+use futures_util::{future::poll_fn, stream, Stream};
+use hyper::{Response, StatusCode};
+use std::{
+    future::Future,
+    io,
+    panic::{catch_unwind, AssertUnwindSafe},
+    pin::Pin,
+    sync::atomic::{AtomicU64, Ordering},
+    task::Poll,
+};
+use tracing::error;
+
+static PANICS: AtomicU64 = AtomicU64::new(0);
+
+/// Requests which handling panicked since start.
+pub(crate) fn count() -> u64 {
+    PANICS.load(Ordering::Relaxed)
+}
+
+/// Runs `f`, panic while polling it is answered with 500 instead of
+/// dropping connection. State touched by panicking code is not trusted
+/// further, `f` is not polled again.
+pub(crate) async fn isolate<F>(f: F) -> Response<hyper::Body>
+where
+    F: Future<Output = Response<hyper::Body>>,
+{
+    let mut f = Box::pin(f);
+    let polled = poll_fn(
+        move |cx| match catch_unwind(AssertUnwindSafe(|| f.as_mut().poll(cx))) {
+            Ok(Poll::Ready(resp)) => Poll::Ready(Ok(resp)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(_) => Poll::Ready(Err(())),
+        },
+    )
+    .await;
+    polled.unwrap_or_else(|_| {
+        PANICS.fetch_add(1, Ordering::Relaxed);
+        error!("request handler panicked");
+        Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(hyper::Body::empty())
+            .unwrap()
+    })
+}
+
+/// Response body ending with error when producing it panics, as PROPFIND
+/// one produced lazily by backend calls can.
+pub(crate) fn body(mut body: hyper::Body) -> hyper::Body {
+    let mut failed = false;
+    hyper::Body::wrap_stream(stream::poll_fn(move |cx| {
+        if failed {
+            return Poll::Ready(None);
+        }
+        match catch_unwind(AssertUnwindSafe(|| Pin::new(&mut body).poll_next(cx))) {
+            Ok(p) => p.map(|c| c.map(|r| r.map_err(|e| io::Error::new(io::ErrorKind::Other, e)))),
+            Err(_) => {
+                failed = true;
+                PANICS.fetch_add(1, Ordering::Relaxed);
+                error!("response body panicked");
+                Poll::Ready(Some(Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "response body panicked",
+                ))))
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    async fn failing() -> Response<hyper::Body> {
+        panic!("backend unwrap")
+    }
+
+    #[tokio::test]
+    async fn panics_become_errors() {
+        let before = count();
+        let resp = isolate(failing()).await;
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let resp = isolate(async { Response::new(hyper::Body::from("ok")) }).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let chunks = stream::iter(vec!["a", "b"]).map(|c| {
+            if c == "b" {
+                panic!("lazy body")
+            }
+            Ok::<_, io::Error>(c)
+        });
+        let body = body(hyper::Body::wrap_stream(chunks));
+        assert!(hyper::body::to_bytes(body).await.is_err());
+        assert!(count() >= before + 2);
+    }
+}
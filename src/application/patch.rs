@@ -0,0 +1,223 @@
+use super::errors::error;
+use crate::backend::open_opts;
+use hyper::{
+    header::{HeaderValue, ALLOW, CONTENT_TYPE, ETAG, IF_MATCH},
+    Request, Response, StatusCode,
+};
+use std::io::SeekFrom;
+use webdav_handler::{
+    body::Body,
+    davpath::DavPath,
+    fs::{DavFileSystem, FsError},
+};
+
+/// Content type of SabreDAV partial update requests.
+pub(crate) const PARTIAL_UPDATE: &str = "application/x-sabredav-partialupdate";
+
+/// Value of X-Update-Range header.
+#[derive(Debug, PartialEq)]
+enum UpdateRange {
+    Append,
+    /// `bytes=start-end`, end is inclusive
+    Range(u64, u64),
+    /// `bytes=start-`
+    From(u64),
+    /// `bytes=-count`, replaces last count bytes
+    Last(u64),
+}
+
+fn parse_range(v: &str) -> Option<UpdateRange> {
+    let v = v.trim();
+    if v.eq_ignore_ascii_case("append") {
+        return Some(UpdateRange::Append);
+    }
+    let (start, end) = v.strip_prefix("bytes=")?.split_once('-')?;
+    match (start.trim(), end.trim()) {
+        ("", count) => count.parse().ok().map(UpdateRange::Last),
+        (start, "") => start.parse().ok().map(UpdateRange::From),
+        (start, end) => Some(UpdateRange::Range(start.parse().ok()?, end.parse().ok()?)),
+    }
+}
+
+fn fs_error(e: FsError) -> Response<Body> {
+    match e {
+        FsError::NotFound => error(StatusCode::NOT_FOUND, "not found"),
+        FsError::Forbidden => error(StatusCode::FORBIDDEN, "forbidden"),
+        FsError::NotImplemented => error(StatusCode::NOT_IMPLEMENTED, "not supported by backend"),
+        FsError::InsufficientStorage => error(StatusCode::INSUFFICIENT_STORAGE, "no space"),
+        _ => error(StatusCode::INTERNAL_SERVER_ERROR, "can't update file"),
+    }
+}
+
+fn etag_matches(header: &str, etag: Option<&str>) -> bool {
+    header.split(',').map(|t| t.trim()).any(|t| {
+        t == "*" || etag.map_or(false, |e| t.trim_start_matches("W/").trim_matches('"') == e)
+    })
+}
+
+/// Writes request body over a byte range of existing file, as described by
+/// SabreDAV PartialUpdate plugin. Backends without random writes (s3) read
+/// the whole file and upload it back.
+pub(crate) async fn handle(fs: &dyn DavFileSystem, req: Request<hyper::Body>) -> Response<Body> {
+    let content_type = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok());
+    if content_type != Some(PARTIAL_UPDATE) {
+        return error(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "unsupported patch format",
+        );
+    }
+    let range = match req
+        .headers()
+        .get("X-Update-Range")
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_range)
+    {
+        Some(r) => r,
+        None => return error(StatusCode::BAD_REQUEST, "missing or invalid X-Update-Range"),
+    };
+    let path = match DavPath::new(req.uri().path()) {
+        Ok(p) => p,
+        Err(_) => return error(StatusCode::BAD_REQUEST, "invalid path"),
+    };
+    let if_match = req
+        .headers()
+        .get(IF_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from);
+    let body = match hyper::body::to_bytes(req.into_body()).await {
+        Ok(b) => b,
+        Err(_) => return error(StatusCode::BAD_REQUEST, "can't read body"),
+    };
+
+    let meta = match fs.metadata(&path).await {
+        Ok(m) => m,
+        Err(e) => return fs_error(e),
+    };
+    if meta.is_dir() {
+        return error(StatusCode::METHOD_NOT_ALLOWED, "can't patch collection");
+    }
+    if let Some(ref h) = if_match {
+        if !etag_matches(h, meta.etag().as_deref()) {
+            return error(StatusCode::PRECONDITION_FAILED, "etag does not match");
+        }
+    }
+    let offset = match range {
+        UpdateRange::Append => meta.len(),
+        UpdateRange::From(start) => start,
+        UpdateRange::Range(start, end) if end < start => {
+            return error(
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                "range end is before start",
+            )
+        }
+        UpdateRange::Range(start, end) if end - start + 1 != body.len() as u64 => {
+            return error(StatusCode::BAD_REQUEST, "body length does not match range")
+        }
+        UpdateRange::Range(start, _) => start,
+        UpdateRange::Last(count) if count > meta.len() => {
+            return error(
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                "range is longer than file",
+            )
+        }
+        UpdateRange::Last(count) => meta.len() - count,
+    };
+
+    let written = async {
+        let mut file = fs.open(&path, open_opts::write()).await?;
+        file.seek(SeekFrom::Start(offset)).await?;
+        file.write_bytes(body).await?;
+        file.flush().await?;
+        file.metadata().await
+    };
+    match written.await {
+        Ok(meta) => {
+            let mut resp = Response::builder().status(StatusCode::NO_CONTENT);
+            if let Some(etag) = meta.etag() {
+                resp = resp.header(ETAG, format!("\"{}\"", etag));
+            }
+            resp.body(Body::empty()).unwrap()
+        }
+        Err(e) => fs_error(e),
+    }
+}
+
+/// Adds partial update to OPTIONS response.
+pub(crate) fn advertise<B>(resp: &mut Response<B>) {
+    let headers = resp.headers_mut();
+    let dav = match headers.get("DAV").and_then(|v| v.to_str().ok()) {
+        Some(v) => format!("{}, sabredav-partialupdate", v),
+        None => "1, sabredav-partialupdate".to_string(),
+    };
+    if let Ok(v) = HeaderValue::from_str(&dav) {
+        headers.insert("DAV", v);
+    }
+    if let Some(v) = headers.get(ALLOW).and_then(|v| v.to_str().ok()) {
+        if let Ok(v) = HeaderValue::from_str(&format!("{},PATCH", v)) {
+            headers.insert(ALLOW, v);
+        }
+    }
+    headers.insert("Accept-Patch", HeaderValue::from_static(PARTIAL_UPDATE));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::put;
+    use bytes::Bytes;
+    use webdav_handler::memfs::MemFs;
+
+    #[test]
+    fn update_range() {
+        assert_eq!(parse_range("append"), Some(UpdateRange::Append));
+        assert_eq!(parse_range("bytes=2-5"), Some(UpdateRange::Range(2, 5)));
+        assert_eq!(parse_range("bytes=7-"), Some(UpdateRange::From(7)));
+        assert_eq!(parse_range("bytes=-3"), Some(UpdateRange::Last(3)));
+        assert_eq!(parse_range("bytes=a-"), None);
+        assert_eq!(parse_range("lines=1-2"), None);
+    }
+
+    fn patch(range: &str, body: &'static str) -> Request<hyper::Body> {
+        Request::builder()
+            .method("PATCH")
+            .uri("/a.txt")
+            .header(CONTENT_TYPE, PARTIAL_UPDATE)
+            .header("X-Update-Range", range)
+            .body(body.into())
+            .unwrap()
+    }
+
+    async fn content(fs: &dyn DavFileSystem) -> anyhow::Result<Bytes> {
+        let mut f = fs.open(&DavPath::new("/a.txt")?, open_opts::read()).await?;
+        Ok(f.read_bytes(1024).await?)
+    }
+
+    #[tokio::test]
+    async fn updates_ranges() -> anyhow::Result<()> {
+        let fs = MemFs::new();
+        put(fs.as_ref(), "/a.txt", b"0123456789").await?;
+
+        let resp = handle(fs.as_ref(), patch("bytes=2-4", "abc")).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert!(resp.headers().contains_key(ETAG));
+        assert_eq!(content(fs.as_ref()).await?, "01abc56789");
+
+        handle(fs.as_ref(), patch("bytes=-2", "XY")).await;
+        handle(fs.as_ref(), patch("append", "!")).await;
+        assert_eq!(content(fs.as_ref()).await?, "01abc567XY!");
+
+        let resp = handle(fs.as_ref(), patch("bytes=2-4", "toolong")).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let mut req = patch("bytes=0-0", "z");
+        req.headers_mut()
+            .insert(IF_MATCH, HeaderValue::from_static("\"other\""));
+        assert_eq!(
+            handle(fs.as_ref(), req).await.status(),
+            StatusCode::PRECONDITION_FAILED
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, Weak},
+};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+use webdav_handler::davpath::DavPath;
+
+/// Entries above which released ones are dropped on lock.
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// Serializes requests changing the same path. Handler checks If-Match and
+/// If-None-Match before it writes, so without it two clients sending the
+/// same ETag at once could both pass the check and one change would be lost.
+/// Paths are keyed decoded and normalized, so `/a%20b` and `/a b/` share a
+/// lock.
+#[derive(Clone, Default)]
+pub(crate) struct PathLocks {
+    locks: Arc<Mutex<HashMap<String, Weak<AsyncMutex<()>>>>>,
+}
+
+fn key(href: &str) -> String {
+    match DavPath::new(href) {
+        Ok(p) => p
+            .as_pathbuf()
+            .to_string_lossy()
+            .trim_end_matches('/')
+            .to_string(),
+        Err(_) => href.to_string(),
+    }
+}
+
+impl PathLocks {
+    /// Locks all hrefs, e.g. source and destination of MOVE. They are taken
+    /// in order of keys so two requests over the same paths can't deadlock.
+    pub async fn lock(&self, hrefs: &[&str]) -> Vec<OwnedMutexGuard<()>> {
+        let mut keys = hrefs.iter().map(|h| key(h)).collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+        let mut guards = vec![];
+        for k in keys {
+            guards.push(self.lock_key(k).await);
+        }
+        guards
+    }
+
+    async fn lock_key(&self, key: String) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.locks.lock().unwrap();
+            if locks.len() >= PRUNE_THRESHOLD {
+                locks.retain(|_, l| l.strong_count() > 0);
+            }
+            match locks.get(&key).and_then(|l| l.upgrade()) {
+                Some(l) => l,
+                None => {
+                    let l = Arc::new(AsyncMutex::new(()));
+                    locks.insert(key, Arc::downgrade(&l));
+                    l
+                }
+            }
+        };
+        lock.lock_owned().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn serializes_same_path() {
+        let locks = PathLocks::default();
+        let guard = locks.lock(&["/fs/a.txt"]).await;
+        let _other = locks.lock(&["/fs/b.txt"]).await;
+
+        let same =
+            tokio::time::timeout(Duration::from_millis(50), locks.lock(&["/fs/a.txt"])).await;
+        assert!(same.is_err());
+        drop(guard);
+        let same =
+            tokio::time::timeout(Duration::from_millis(50), locks.lock(&["/fs/a.txt"])).await;
+        assert!(same.is_ok());
+    }
+
+    #[tokio::test]
+    async fn serializes_encoded_and_destination() {
+        let locks = PathLocks::default();
+        let _guard = locks.lock(&["/fs/a%20b.txt"]).await;
+
+        let wait = Duration::from_millis(50);
+        let same = tokio::time::timeout(wait, locks.lock(&["/fs/a b.txt"])).await;
+        assert!(same.is_err());
+        let moved = locks.lock(&["/fs/c.txt", "/fs/%61%20b.txt"]);
+        assert!(tokio::time::timeout(wait, moved).await.is_err());
+        let dir = tokio::time::timeout(wait, locks.lock(&["/fs/d/", "/fs/d"])).await;
+        assert!(dir.is_ok());
+    }
+}
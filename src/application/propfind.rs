@@ -0,0 +1,158 @@
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use hyper::{header::HeaderValue, Request};
+
+const RESPONSE_TAG: &[u8] = b"<D:response>";
+
+/// PROPFIND without Depth header is treated as Depth: infinity.
+pub(crate) fn is_infinity_propfind<B>(req: &Request<B>) -> bool {
+    if req.method().as_str() != "PROPFIND" {
+        return false;
+    }
+    match req.headers().get("Depth") {
+        None => true,
+        Some(v) => v.as_bytes().eq_ignore_ascii_case(b"infinity"),
+    }
+}
+
+/// webdav-handler refuses Depth: infinity unless request comes from litmus.
+/// Header sent by client is dropped, so only configuration decides.
+pub(crate) fn allow_infinity<B>(req: &mut Request<B>, allow: bool) {
+    req.headers_mut().remove("X-Litmus");
+    if allow {
+        req.headers_mut()
+            .insert("X-Litmus", HeaderValue::from_static("propfind-infinity"));
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    if haystack.len() < needle.len() {
+        return None;
+    }
+    (from..=haystack.len() - needle.len()).find(|&i| &haystack[i..i + needle.len()] == needle)
+}
+
+/// Response for `href` of multistatus cut at limit, RFC 4918 section 8.8
+/// and RFC 5323 section 2.3.1.
+pub(crate) fn truncated(href: &str) -> String {
+    format!(
+        "<D:response><D:href>{}</D:href>\
+         <D:status>HTTP/1.1 507 Insufficient Storage</D:status>\
+         <D:error><D:number-of-matches-within-limits/></D:error>\
+         </D:response>",
+        href
+    )
+}
+
+fn truncated_tail(href: &str) -> Bytes {
+    format!("{}</D:multistatus>\n", truncated(href)).into()
+}
+
+/// Passes multistatus body through until `max` responses were sent. After that
+/// body is closed with 507 response for request uri as RFC 4918 suggests for
+/// truncated results.
+pub(crate) fn limit_responses<S, E>(
+    body: S,
+    max: usize,
+    href: String,
+) -> impl Stream<Item = Result<Bytes, E>>
+where
+    S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+    E: Send + 'static,
+{
+    async_stream::stream! {
+        let mut body = Box::pin(body);
+        let mut buf: Vec<u8> = vec![];
+        let mut count = 0;
+        while let Some(chunk) = body.next().await {
+            let chunk = match chunk {
+                Ok(c) => c,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
+            buf.extend_from_slice(&chunk);
+            let mut pos = 0;
+            while let Some(i) = find(&buf, RESPONSE_TAG, pos) {
+                count += 1;
+                if count > max {
+                    yield Ok(Bytes::copy_from_slice(&buf[..i]));
+                    yield Ok(truncated_tail(&href));
+                    return;
+                }
+                pos = i + RESPONSE_TAG.len();
+            }
+            // keep possible beginning of tag split between chunks
+            let keep = std::cmp::min(buf.len() - pos, RESPONSE_TAG.len() - 1);
+            let emit = buf.len() - keep;
+            yield Ok(Bytes::copy_from_slice(&buf[..emit]));
+            buf.drain(..emit);
+        }
+        if !buf.is_empty() {
+            yield Ok(Bytes::from(buf));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::stream;
+
+    async fn collect(chunks: Vec<&'static str>, max: usize) -> String {
+        let s = stream::iter(
+            chunks
+                .into_iter()
+                .map(|c| Ok::<_, std::io::Error>(Bytes::from(c))),
+        );
+        let r = limit_responses(s, max, "/dir/".into())
+            .map(|c| c.unwrap())
+            .collect::<Vec<_>>()
+            .await;
+        String::from_utf8(r.concat()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn limits() {
+        let body = vec![
+            "<D:multistatus><D:resp",
+            "onse>1</D:response><D:response>2</D:response><D:re",
+            "sponse>3</D:response></D:multistatus>",
+        ];
+        let full = body.concat();
+        assert_eq!(collect(body.clone(), 3).await, full);
+
+        let r = collect(body, 2).await;
+        assert!(r.starts_with(
+            "<D:multistatus><D:response>1</D:response><D:response>2</D:response><D:response><D:href>/dir/</D:href>"
+        ));
+        assert!(r.contains("507"));
+        assert!(r.ends_with("</D:multistatus>\n"));
+    }
+
+    #[test]
+    fn depth() {
+        let req = Request::builder().method("PROPFIND").body(()).unwrap();
+        assert!(is_infinity_propfind(&req));
+        let req = Request::builder()
+            .method("PROPFIND")
+            .header("Depth", "1")
+            .body(())
+            .unwrap();
+        assert!(!is_infinity_propfind(&req));
+    }
+
+    #[test]
+    fn client_litmus_header() {
+        let mut req = Request::builder()
+            .method("PROPFIND")
+            .header("X-Litmus", "props: 1 (propfind)")
+            .body(())
+            .unwrap();
+        allow_infinity(&mut req, false);
+        assert!(req.headers().get("X-Litmus").is_none());
+        allow_infinity(&mut req, true);
+        assert_eq!(req.headers()["X-Litmus"], "propfind-infinity");
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{aggregate::Aggregate, backend::lock_systems::aggregate::AggregateLs};
+use std::time::Duration;
+use tracing::{error, info};
+use webdav_handler::ls::DavLockSystem;
+
+/// Entries dropped by one reconciliation.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Dropped {
+    pub props: usize,
+    pub locks: usize,
+}
+
+/// Drops props and locks of paths removed from backends without going
+/// through server, e.g. objects deleted in bucket by another tool.
+pub(crate) async fn reconcile(fs: &Aggregate, ls: &AggregateLs) -> anyhow::Result<Dropped> {
+    let props = fs.drop_stale_props().await?;
+    let mut locks = 0;
+    for path in ls.locked_paths() {
+        if fs.is_removed(&path).await && ls.delete(&path).is_ok() {
+            locks += 1;
+        }
+    }
+    Ok(Dropped { props, locks })
+}
+
+/// Reconciles every `interval`, first time after one interval passes.
+pub(crate) fn spawn(fs: Box<Aggregate>, ls: Box<AggregateLs>, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(interval);
+        ticks.tick().await;
+        loop {
+            ticks.tick().await;
+            match reconcile(&fs, &ls).await {
+                Ok(d) if d != Dropped::default() => info!(
+                    msg = "dropped entries of removed paths",
+                    props = d.props,
+                    locks = d.locks
+                ),
+                Ok(_) => {}
+                Err(e) => error!("can't reconcile props and locks: {:?}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        aggregate::AggregateBuilder, backend::prop_storages::mem::Memory, test_utils::put,
+    };
+    use webdav_handler::{
+        davpath::DavPath,
+        fs::{DavFileSystem, DavProp},
+        memfs::MemFs,
+        memls::MemLs,
+    };
+
+    #[tokio::test]
+    async fn drops_removed() -> anyhow::Result<()> {
+        let data = MemFs::new();
+        let fs = AggregateBuilder::new()
+            .add_route(("/fs", data.clone()))
+            .set_props_storage(Memory::new())
+            .build()?;
+        let mut ls = AggregateLs::new(MemLs::new());
+        ls.add_route("/fs", MemLs::new());
+
+        let path = |p: &str| DavPath::new(p).unwrap();
+        for p in ["/fs/a.txt", "/fs/b.txt"].iter() {
+            put(fs.as_ref(), p, b"").await?;
+            ls.lock(&path(p), None, None, None, false, false).unwrap();
+        }
+        let color = DavProp {
+            name: "color".into(),
+            namespace: Some("urn:test".into()),
+            prefix: None,
+            xml: Some(b"<color xmlns=\"urn:test\">red</color>".to_vec()),
+        };
+        fs.patch_props(&path("/fs/a.txt"), vec![(true, color)])
+            .await?;
+        assert_eq!(reconcile(&fs, &ls).await?, Dropped::default());
+
+        data.remove_file(&path("/a.txt")).await?;
+        let dropped = reconcile(&fs, &ls).await?;
+        assert_eq!(dropped, Dropped { props: 1, locks: 1 });
+        assert!(ls.discover(&path("/fs/a.txt")).is_empty());
+        assert_eq!(ls.discover(&path("/fs/b.txt")).len(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use crate::backend::s3_backend::S3Backend;
+use hyper::{
+    header::{CACHE_CONTROL, LOCATION},
+    Method, Request, Response, StatusCode,
+};
+use std::time::Duration;
+use tracing::{debug, error};
+use webdav_handler::{body::Body, davpath::DavPath, fs::DavFileSystem};
+
+/// S3 mount whose large files are downloaded from bucket directly, so their
+/// content does not pass through server.
+#[derive(Clone)]
+pub(crate) struct Redirect {
+    mount: String,
+    backend: S3Backend,
+    min_size: u64,
+    expiry: Duration,
+}
+
+impl Redirect {
+    pub fn new(mount: &str, backend: S3Backend, min_size: u64, expiry: Duration) -> Self {
+        Redirect {
+            mount: mount.trim_end_matches('/').to_string(),
+            backend,
+            min_size,
+            expiry,
+        }
+    }
+
+    /// Request downloads a file of this mount.
+    pub fn applies<B>(&self, req: &Request<B>) -> bool {
+        let path = req.uri().path();
+        req.method() == Method::GET
+            && path.starts_with(&self.mount)
+            && path[self.mount.len()..].starts_with('/')
+            && !path.ends_with('/')
+    }
+
+    /// Redirect to presigned url if path is a large enough file, None if
+    /// request should be handled as usual.
+    pub async fn handle(&self, fs: &dyn DavFileSystem, path: &str) -> Option<Response<Body>> {
+        let meta = fs.metadata(&DavPath::new(path).ok()?).await.ok()?;
+        if meta.is_dir() || meta.len() < self.min_size {
+            return None;
+        }
+        let key = DavPath::new(&path[self.mount.len()..]).ok()?;
+        let url = match self.backend.presign_get(&key, self.expiry) {
+            Ok(u) => u,
+            Err(e) => {
+                // proxying still works
+                error!("can't redirect download: {:?}", e);
+                return None;
+            }
+        };
+        debug!(msg = "redirecting download", path = path, size = meta.len());
+        Some(
+            Response::builder()
+                .status(StatusCode::TEMPORARY_REDIRECT)
+                .header(LOCATION, url)
+                .header(CACHE_CONTROL, "no-store")
+                .body(Body::empty())
+                .unwrap(),
+        )
+    }
+}
@@ -0,0 +1,220 @@
+use crate::configuration;
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use hyper::{client::HttpConnector, Body, Client, Method, Request, Uri};
+use serde::Serialize;
+use serde_json::json;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Panic or failed request worth telling operator about.
+#[derive(Debug, Clone, Serialize, Default)]
+pub(crate) struct Report {
+    pub kind: &'static str,
+    pub message: String,
+    pub timestamp: String,
+    pub method: Option<String>,
+    pub path: Option<String>,
+    pub mount: Option<String>,
+    pub backend: Option<String>,
+    pub status: Option<u16>,
+}
+
+impl Report {
+    pub fn new(kind: &'static str, message: String) -> Self {
+        Report {
+            kind,
+            message,
+            timestamp: Utc::now().to_rfc3339(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Target {
+    Webhook(Uri),
+    Sentry { store: Uri, auth: String },
+}
+
+/// Sentry store endpoint and auth header of `http://key@host[/prefix]/project`.
+fn parse_dsn(dsn: &str) -> Result<(Uri, String)> {
+    let invalid = || anyhow!("invalid sentry dsn: {}", dsn);
+    let rest = dsn.strip_prefix("http://").ok_or_else(invalid)?;
+    let (key, rest) = rest.split_at(rest.find('@').ok_or_else(invalid)?);
+    let rest = &rest[1..];
+    let slash = rest.rfind('/').ok_or_else(invalid)?;
+    let (host, project) = (&rest[..slash], &rest[slash + 1..]);
+    if key.is_empty() || project.is_empty() || host.is_empty() {
+        return Err(invalid());
+    }
+    let key = key.split(':').next().unwrap_or(key);
+    let store = format!("http://{}/api/{}/store/", host, project).parse()?;
+    let auth = format!(
+        "Sentry sentry_version=7, sentry_client=webdav_ss/{}, sentry_key={}",
+        env!("CARGO_PKG_VERSION"),
+        key
+    );
+    Ok((store, auth))
+}
+
+fn sentry_event(r: &Report) -> serde_json::Value {
+    let mut tags = BTreeMap::new();
+    tags.insert("kind", Some(r.kind.to_string()));
+    tags.insert("mount", r.mount.clone());
+    tags.insert("backend", r.backend.clone());
+    tags.insert("method", r.method.clone());
+    tags.insert("status", r.status.map(|s| s.to_string()));
+    let tags = tags
+        .into_iter()
+        .filter_map(|(k, v)| v.map(|v| (k, v)))
+        .collect::<BTreeMap<_, _>>();
+    json!({
+        "event_id": uuid::Uuid::new_v4().to_simple().to_string(),
+        "timestamp": r.timestamp,
+        "level": if r.kind == "panic" { "fatal" } else { "error" },
+        "platform": "other",
+        "logger": "webdav_ss",
+        "message": r.message,
+        "tags": tags,
+        "extra": { "path": r.path },
+    })
+}
+
+/// Sends reports in background, at most `per_minute` of them so outage
+/// doesn't flood receiver.
+pub(crate) struct Reporter {
+    tx: mpsc::UnboundedSender<Report>,
+    limit: Mutex<(Instant, u32)>,
+    per_minute: u32,
+}
+
+impl Reporter {
+    pub fn new(conf: &configuration::ErrorReports) -> Result<Arc<Reporter>> {
+        let mut targets = vec![];
+        if let Some(ref url) = conf.webhook {
+            let url: Uri = url.parse()?;
+            if url.scheme_str() != Some("http") {
+                return Err(anyhow!("unsupported error report url: {}", url));
+            }
+            targets.push(Target::Webhook(url));
+        }
+        if let Some(ref dsn) = conf.sentry_dsn {
+            let (store, auth) = parse_dsn(dsn)?;
+            targets.push(Target::Sentry { store, auth });
+        }
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(send_reports(targets, rx));
+        Ok(Arc::new(Reporter {
+            tx,
+            limit: Mutex::new((Instant::now(), 0)),
+            per_minute: conf.per_minute,
+        }))
+    }
+
+    pub fn report(&self, r: Report) {
+        {
+            let mut limit = self.limit.lock().unwrap();
+            if limit.0.elapsed() >= Duration::from_secs(60) {
+                *limit = (Instant::now(), 0);
+            }
+            if limit.1 >= self.per_minute {
+                return;
+            }
+            limit.1 += 1;
+        }
+        let _ = self.tx.send(r);
+    }
+
+    /// Reports panics of any thread, previous hook still runs.
+    pub fn install_panic_hook(self: &Arc<Self>) {
+        let reporter = self.clone();
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let payload = info
+                .payload()
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| info.payload().downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "panic".into());
+            let message = match info.location() {
+                Some(l) => format!("{} at {}:{}", payload, l.file(), l.line()),
+                None => payload,
+            };
+            reporter.report(Report::new("panic", message));
+            previous(info);
+        }));
+    }
+}
+
+async fn send_reports(targets: Vec<Target>, mut rx: mpsc::UnboundedReceiver<Report>) {
+    let client: Client<HttpConnector> = Client::new();
+    while let Some(r) = rx.recv().await {
+        for t in &targets {
+            let req = match t {
+                Target::Webhook(url) => Request::builder()
+                    .method(Method::POST)
+                    .uri(url.clone())
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(serde_json::to_vec(&r).unwrap())),
+                Target::Sentry { store, auth } => Request::builder()
+                    .method(Method::POST)
+                    .uri(store.clone())
+                    .header("Content-Type", "application/json")
+                    .header("X-Sentry-Auth", auth.as_str())
+                    .body(Body::from(sentry_event(&r).to_string())),
+            };
+            let sent = match req {
+                Ok(req) => client.request(req).await.map_err(anyhow::Error::from),
+                Err(e) => Err(e.into()),
+            };
+            match sent {
+                Ok(resp) if resp.status().is_success() => {}
+                Ok(resp) => warn!(msg = "error report rejected", status = %resp.status()),
+                Err(e) => warn!(msg = "can't send error report", err = %e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sentry_dsn() -> Result<()> {
+        let (store, auth) = parse_dsn("http://abc123@relay:3000/prefix/42")?;
+        assert_eq!(store, "http://relay:3000/prefix/api/42/store/");
+        assert!(auth.ends_with("sentry_key=abc123"));
+        assert!(parse_dsn("https://abc@sentry.io/1").is_err());
+        assert!(parse_dsn("http://relay/1").is_err());
+
+        let mut r = Report::new("error", "GET failed with 502".into());
+        r.mount = Some("/s3".into());
+        let e = sentry_event(&r);
+        assert_eq!(e["tags"]["mount"], "/s3");
+        assert_eq!(e["level"], "error");
+        assert!(e["tags"].get("backend").is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn limits_reports() -> Result<()> {
+        let conf = configuration::ErrorReports {
+            webhook: None,
+            sentry_dsn: None,
+            per_minute: 2,
+        };
+        let reporter = Reporter::new(&conf)?;
+        for _ in 0..5 {
+            reporter.report(Report::new("error", "failed".into()));
+        }
+        assert_eq!(reporter.limit.lock().unwrap().1, 2);
+        Ok(())
+    }
+}
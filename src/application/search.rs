@@ -0,0 +1,518 @@
+use super::{errors::error, propfind};
+use crate::{
+    backend::{walk::child_path, xml::escape},
+    configuration::Propfind,
+};
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use hyper::{Request, Response, StatusCode};
+use std::{collections::VecDeque, time::SystemTime};
+use webdav_handler::{
+    body::Body,
+    davpath::DavPath,
+    fs::{DavFileSystem, DavMetaData, ReadDirMeta},
+};
+use xmltree::Element;
+
+/// Searchable properties of DAV:basicsearch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Name,
+    Size,
+    Modified,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Cmp {
+    Eq,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Str(String),
+    Num(u64),
+    Time(SystemTime),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Op {
+    And(Vec<Op>),
+    Or(Vec<Op>),
+    Not(Box<Op>),
+    Cmp(Cmp, Field, Value),
+    Like(Field, String),
+    IsCollection,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Query {
+    scope: String,
+    depth: Option<u32>,
+    filter: Option<Op>,
+    limit: Option<usize>,
+}
+
+fn elements(e: &Element) -> impl Iterator<Item = &Element> {
+    e.children.iter().filter_map(|n| n.as_element())
+}
+
+fn text(e: &Element) -> String {
+    e.get_text()
+        .map(|t| t.trim().to_string())
+        .unwrap_or_default()
+}
+
+fn parse_field(e: &Element) -> Result<Field, String> {
+    let prop = e.get_child("prop").ok_or("missing prop")?;
+    let p = elements(prop).next().ok_or("empty prop")?;
+    match p.name.as_str() {
+        "displayname" => Ok(Field::Name),
+        "getcontentlength" => Ok(Field::Size),
+        "getlastmodified" => Ok(Field::Modified),
+        n => Err(format!("unsupported property: {}", n)),
+    }
+}
+
+fn parse_time(s: &str) -> Option<SystemTime> {
+    DateTime::parse_from_rfc2822(s)
+        .or_else(|_| DateTime::parse_from_rfc3339(s))
+        .ok()
+        .map(|t| t.with_timezone(&Utc).into())
+}
+
+fn parse_literal(e: &Element, field: Field) -> Result<Value, String> {
+    let lit = text(e.get_child("literal").ok_or("missing literal")?);
+    match field {
+        Field::Name => Ok(Value::Str(lit)),
+        Field::Size => lit
+            .parse()
+            .map(Value::Num)
+            .map_err(|_| format!("invalid size: {}", lit)),
+        Field::Modified => parse_time(&lit)
+            .map(Value::Time)
+            .ok_or_else(|| format!("invalid date: {}", lit)),
+    }
+}
+
+fn parse_op(e: &Element) -> Result<Op, String> {
+    let cmp = match e.name.as_str() {
+        "and" => {
+            return Ok(Op::And(
+                elements(e).map(parse_op).collect::<Result<_, _>>()?,
+            ))
+        }
+        "or" => return Ok(Op::Or(elements(e).map(parse_op).collect::<Result<_, _>>()?)),
+        "not" => {
+            let inner = elements(e).next().ok_or("empty not")?;
+            return Ok(Op::Not(Box::new(parse_op(inner)?)));
+        }
+        "is-collection" => return Ok(Op::IsCollection),
+        "like" => {
+            let field = parse_field(e)?;
+            let lit = text(e.get_child("literal").ok_or("missing literal")?);
+            return Ok(Op::Like(field, lit));
+        }
+        "eq" => Cmp::Eq,
+        "lt" => Cmp::Lt,
+        "lte" => Cmp::Lte,
+        "gt" => Cmp::Gt,
+        "gte" => Cmp::Gte,
+        n => return Err(format!("unsupported operator: {}", n)),
+    };
+    let field = parse_field(e)?;
+    Ok(Op::Cmp(cmp, field, parse_literal(e, field)?))
+}
+
+/// Strips scheme and host from absolute scope href.
+fn href_path(href: &str) -> String {
+    if let Some(i) = href.find("://") {
+        let rest = &href[i + 3..];
+        return rest
+            .find('/')
+            .map(|j| rest[j..].to_string())
+            .unwrap_or_else(|| "/".into());
+    }
+    href.to_string()
+}
+
+pub(crate) fn parse(body: &[u8]) -> Result<Query, String> {
+    let root = Element::parse(body).map_err(|e| e.to_string())?;
+    if root.name != "searchrequest" {
+        return Err("expected searchrequest".into());
+    }
+    let bs = root
+        .get_child("basicsearch")
+        .ok_or("only basicsearch is supported")?;
+    let scope = bs
+        .get_child("from")
+        .and_then(|f| f.get_child("scope"))
+        .ok_or("missing scope")?;
+    let href = href_path(&text(scope.get_child("href").ok_or("missing href")?));
+    let depth = match scope.get_child("depth").map(text).as_deref() {
+        None | Some("infinity") => None,
+        Some(d) => Some(d.parse().map_err(|_| format!("invalid depth: {}", d))?),
+    };
+    let filter = match bs.get_child("where") {
+        Some(w) => Some(parse_op(elements(w).next().ok_or("empty where")?)?),
+        None => None,
+    };
+    let limit = match bs.get_child("limit").and_then(|l| l.get_child("nresults")) {
+        Some(n) => Some(text(n).parse().map_err(|_| "invalid nresults")?),
+        None => None,
+    };
+    Ok(Query {
+        scope: href,
+        depth,
+        filter,
+        limit,
+    })
+}
+
+/// Case-insensitive SQL-like matching, `%` is any sequence and `_` any char.
+fn like(pattern: &[char], s: &[char]) -> bool {
+    match pattern.split_first() {
+        None => s.is_empty(),
+        Some(('%', rest)) => (0..=s.len()).any(|i| like(rest, &s[i..])),
+        Some((c, rest)) => match s.split_first() {
+            Some((h, tail)) if *c == '_' || c == h => like(rest, tail),
+            _ => false,
+        },
+    }
+}
+
+/// Text of property `like` is matched against, as it is sent in response.
+fn field_text(field: Field, name: &str, meta: &dyn DavMetaData) -> Option<String> {
+    match field {
+        Field::Name => Some(name.to_string()),
+        Field::Size if !meta.is_dir() => Some(meta.len().to_string()),
+        Field::Size => None,
+        Field::Modified => meta.modified().ok().map(http_date),
+    }
+}
+
+fn matches(op: &Op, name: &str, meta: &dyn DavMetaData) -> bool {
+    match op {
+        Op::And(ops) => ops.iter().all(|o| matches(o, name, meta)),
+        Op::Or(ops) => ops.iter().any(|o| matches(o, name, meta)),
+        Op::Not(o) => !matches(o, name, meta),
+        Op::IsCollection => meta.is_dir(),
+        Op::Like(field, p) => match field_text(*field, name, meta) {
+            Some(s) => {
+                let p = p.to_lowercase().chars().collect::<Vec<_>>();
+                let s = s.to_lowercase().chars().collect::<Vec<_>>();
+                like(&p, &s)
+            }
+            None => false,
+        },
+        Op::Cmp(cmp, _, value) => {
+            let ord = match value {
+                Value::Str(v) => name.to_lowercase().cmp(&v.to_lowercase()),
+                Value::Num(v) if !meta.is_dir() => meta.len().cmp(v),
+                Value::Num(_) => return false,
+                Value::Time(v) => match meta.modified() {
+                    Ok(m) => m.cmp(v),
+                    Err(_) => return false,
+                },
+            };
+            match cmp {
+                Cmp::Eq => ord.is_eq(),
+                Cmp::Lt => ord.is_lt(),
+                Cmp::Lte => ord.is_le(),
+                Cmp::Gt => ord.is_gt(),
+                Cmp::Gte => ord.is_ge(),
+            }
+        }
+    }
+}
+
+fn http_date(t: SystemTime) -> String {
+    DateTime::<Utc>::from(t)
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string()
+}
+
+fn response_xml(path: &DavPath, name: &str, meta: &dyn DavMetaData) -> String {
+    let mut props = format!("<D:displayname>{}</D:displayname>", escape(name));
+    if meta.is_dir() {
+        props.push_str("<D:resourcetype><D:collection/></D:resourcetype>");
+    } else {
+        props.push_str("<D:resourcetype/>");
+        props.push_str(&format!(
+            "<D:getcontentlength>{}</D:getcontentlength>",
+            meta.len()
+        ));
+    }
+    if let Ok(m) = meta.modified() {
+        props.push_str(&format!(
+            "<D:getlastmodified>{}</D:getlastmodified>",
+            http_date(m)
+        ));
+    }
+    format!(
+        "<D:response><D:href>{}</D:href><D:propstat><D:prop>{}</D:prop>\
+         <D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>",
+        escape(&path.as_url_string()),
+        props
+    )
+}
+
+fn entry_name(path: &DavPath) -> String {
+    path.as_pathbuf()
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Matching entries of scope, scope itself included.
+struct Found {
+    entries: Vec<String>,
+    /// listing stopped after visiting max entries
+    truncated: bool,
+}
+
+impl Found {
+    /// Adds entry if it matches filter, true once query limit is reached.
+    fn visit(&mut self, query: &Query, path: &DavPath, meta: &dyn DavMetaData) -> bool {
+        let name = entry_name(path);
+        let matched = match &query.filter {
+            Some(f) => matches(f, &name, meta),
+            None => true,
+        };
+        if matched {
+            self.entries.push(response_xml(path, &name, meta));
+        }
+        query.limit.map_or(false, |l| self.entries.len() >= l)
+    }
+}
+
+/// Lists scope up to `depth` levels below it, evaluating filter while going.
+/// Listing stops when `limit` entries match or `max` entries were visited.
+async fn search(
+    fs: &dyn DavFileSystem,
+    root: &DavPath,
+    query: &Query,
+    max: usize,
+) -> Result<Found, StatusCode> {
+    let mut found = Found {
+        entries: vec![],
+        truncated: false,
+    };
+    let meta = fs.metadata(root).await.map_err(|_| StatusCode::NOT_FOUND)?;
+    if found.visit(query, root, meta.as_ref()) || query.depth == Some(0) || !meta.is_dir() {
+        return Ok(found);
+    }
+    let mut visited = 1;
+    let mut dirs = VecDeque::from(vec![(root.clone(), 1)]);
+    while let Some((dir, level)) = dirs.pop_front() {
+        let mut entries = fs
+            .read_dir(&dir, ReadDirMeta::Data)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        while let Some(e) = entries.next().await {
+            let m = match e.metadata().await {
+                Ok(m) => m,
+                Err(_) => continue,
+            };
+            let p = match child_path(&dir, &e.name(), m.is_dir()) {
+                Ok(p) => p,
+                Err(_) => continue,
+            };
+            if visited >= max {
+                found.truncated = true;
+                return Ok(found);
+            }
+            visited += 1;
+            if found.visit(query, &p, m.as_ref()) {
+                return Ok(found);
+            }
+            if m.is_dir() && query.depth.map_or(true, |d| level < d) {
+                dirs.push_back((p, level + 1));
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// Executes DAV:basicsearch (RFC 5323) by walking metadata of scope. Depth
+/// infinity follows PROPFIND settings: it is refused unless allowed and
+/// walks at most `max_entries` entries.
+pub(crate) async fn handle(
+    fs: &dyn DavFileSystem,
+    req: Request<hyper::Body>,
+    conf: &Propfind,
+) -> Response<Body> {
+    let body = match hyper::body::to_bytes(req.into_body()).await {
+        Ok(b) => b,
+        Err(_) => return error(StatusCode::BAD_REQUEST, "can't read body"),
+    };
+    let query = match parse(&body) {
+        Ok(q) => q,
+        Err(e) => return error(StatusCode::UNPROCESSABLE_ENTITY, &e),
+    };
+    let root = match DavPath::new(&query.scope) {
+        Ok(p) => p,
+        Err(_) => return error(StatusCode::BAD_REQUEST, "invalid scope"),
+    };
+    if query.depth.is_none() && !conf.allow_infinity {
+        return error(StatusCode::FORBIDDEN, "depth infinity is not allowed");
+    }
+    let found = match search(fs, &root, &query, conf.max_entries).await {
+        Ok(f) => f,
+        Err(s) => return error(s, "can't list scope"),
+    };
+
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<D:multistatus xmlns:D=\"DAV:\">",
+    );
+    for e in found.entries {
+        xml.push_str(&e);
+    }
+    if found.truncated {
+        xml.push_str(&propfind::truncated(&escape(&root.as_url_string())));
+    }
+    xml.push_str("</D:multistatus>\n");
+
+    Response::builder()
+        .status(StatusCode::MULTI_STATUS)
+        .header("Content-Type", "application/xml; charset=utf-8")
+        .body(Body::from(xml))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::put;
+    use webdav_handler::memfs::MemFs;
+
+    const QUERY: &str = r#"<?xml version="1.0"?>
+<D:searchrequest xmlns:D="DAV:">
+  <D:basicsearch>
+    <D:select><D:prop><D:displayname/></D:prop></D:select>
+    <D:from><D:scope><D:href>http://localhost:5000/docs/</D:href><D:depth>infinity</D:depth></D:scope></D:from>
+    <D:where>
+      <D:and>
+        <D:like><D:prop><D:displayname/></D:prop><D:literal>%.PDF</D:literal></D:like>
+        <D:gt><D:prop><D:getcontentlength/></D:prop><D:literal>2</D:literal></D:gt>
+      </D:and>
+    </D:where>
+    <D:limit><D:nresults>10</D:nresults></D:limit>
+  </D:basicsearch>
+</D:searchrequest>"#;
+
+    #[test]
+    fn parse_query() {
+        let q = parse(QUERY.as_bytes()).unwrap();
+        assert_eq!(q.scope, "/docs/");
+        assert_eq!(q.depth, None);
+        assert_eq!(q.limit, Some(10));
+        assert_eq!(
+            q.filter,
+            Some(Op::And(vec![
+                Op::Like(Field::Name, "%.PDF".into()),
+                Op::Cmp(Cmp::Gt, Field::Size, Value::Num(2)),
+            ]))
+        );
+        assert!(parse(b"<D:propfind xmlns:D=\"DAV:\"/>").is_err());
+    }
+
+    #[test]
+    fn like_patterns() {
+        let m = |p: &str, s: &str| {
+            like(
+                &p.chars().collect::<Vec<_>>(),
+                &s.chars().collect::<Vec<_>>(),
+            )
+        };
+        assert!(m("%.pdf", "report.pdf"));
+        assert!(m("r_port%", "report.pdf"));
+        assert!(!m("%.pdf", "report.pdf.txt"));
+        assert!(m("%", ""));
+    }
+
+    #[tokio::test]
+    async fn search() -> anyhow::Result<()> {
+        let fs = MemFs::new();
+        fs.create_dir(&DavPath::new("/docs/")?).await?;
+        fs.create_dir(&DavPath::new("/docs/sub/")?).await?;
+        for (p, data) in [
+            ("/docs/a.pdf", &b"data"[..]),
+            ("/docs/sub/b.pdf", &b"data"[..]),
+            ("/docs/sub/small.pdf", &b"d"[..]),
+            ("/docs/c.txt", &b"data"[..]),
+        ] {
+            put(fs.as_ref(), p, data).await?;
+        }
+
+        let conf = Propfind {
+            allow_infinity: true,
+            ..Default::default()
+        };
+        let (status, body) = run(fs.as_ref(), QUERY.into(), &conf).await?;
+        assert_eq!(status, StatusCode::MULTI_STATUS);
+        assert!(body.contains("<D:href>/docs/a.pdf</D:href>"));
+        assert!(body.contains("<D:href>/docs/sub/b.pdf</D:href>"));
+        assert!(!body.contains("small.pdf"));
+        assert!(!body.contains("c.txt"));
+
+        // depth 1 covers scope and its members
+        let where_start = QUERY.find("<D:where>").unwrap();
+        let where_end = QUERY.find("</D:where>").unwrap() + "</D:where>".len();
+        let query = QUERY
+            .replace(&QUERY[where_start..where_end], "")
+            .replace("<D:depth>infinity", "<D:depth>1");
+        let (_, body) = run(fs.as_ref(), query, &conf).await?;
+        assert!(body.contains("<D:href>/docs/</D:href>"));
+        assert!(body.contains("<D:href>/docs/sub/</D:href>"));
+        assert!(body.contains("<D:href>/docs/c.txt</D:href>"));
+        assert!(!body.contains("b.pdf"));
+
+        // like matches the field it names
+        let query = QUERY.replace(
+            "<D:like><D:prop><D:displayname/></D:prop><D:literal>%.PDF",
+            "<D:like><D:prop><D:getcontentlength/></D:prop><D:literal>4",
+        );
+        let (_, body) = run(fs.as_ref(), query, &conf).await?;
+        assert!(body.contains("<D:href>/docs/c.txt</D:href>"));
+        assert!(body.contains("<D:href>/docs/sub/b.pdf</D:href>"));
+        assert!(!body.contains("small.pdf"));
+
+        let query = QUERY.replace("<D:nresults>10", "<D:nresults>1");
+        let (_, body) = run(fs.as_ref(), query, &conf).await?;
+        assert_eq!(body.matches("<D:response>").count(), 1);
+
+        let limited = Propfind {
+            max_entries: 2,
+            ..conf.clone()
+        };
+        let (_, body) = run(fs.as_ref(), QUERY.into(), &limited).await?;
+        assert!(body.contains("507 Insufficient Storage"));
+
+        let (status, _) = run(fs.as_ref(), QUERY.into(), &Propfind::default()).await?;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        Ok(())
+    }
+
+    async fn run(
+        fs: &dyn DavFileSystem,
+        query: String,
+        conf: &Propfind,
+    ) -> anyhow::Result<(StatusCode, String)> {
+        let req = Request::builder()
+            .method("SEARCH")
+            .body(hyper::Body::from(query))?;
+        let resp = handle(fs, req, conf).await;
+        let status = resp.status();
+        let body = resp
+            .into_body()
+            .map(|c| c.unwrap())
+            .collect::<Vec<_>>()
+            .await
+            .concat();
+        Ok((status, String::from_utf8(body)?))
+    }
+}
@@ -0,0 +1,273 @@
+use super::connections::{Connections, Tracked};
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use hyper::{Response, StatusCode};
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::{TcpListener, TcpStream},
+    time::{Instant, Sleep},
+};
+use tracing::{error, warn};
+
+/// Deadline for IO on a connection. Between requests it limits receiving of
+/// headers, while request is handled it is its overall deadline if any.
+pub(crate) struct ConnDeadline {
+    header_timeout: Duration,
+    deadline: Mutex<Option<Instant>>,
+    conn: Option<Tracked>,
+}
+
+impl ConnDeadline {
+    fn new(header_timeout: Duration, conn: Option<Tracked>) -> Arc<ConnDeadline> {
+        Arc::new(ConnDeadline {
+            header_timeout,
+            deadline: Mutex::new(Some(Instant::now() + header_timeout)),
+            conn,
+        })
+    }
+
+    fn get(&self) -> Option<Instant> {
+        *self.deadline.lock().unwrap()
+    }
+
+    /// Called when request headers are received.
+    pub fn request_started(self: &Arc<Self>, deadline: Option<Instant>) -> ResponseGuard {
+        *self.deadline.lock().unwrap() = deadline;
+        if let Some(ref c) = self.conn {
+            c.request_started();
+        }
+        ResponseGuard(self.clone())
+    }
+}
+
+/// Starts waiting for headers of the next request once response is sent.
+pub(crate) struct ResponseGuard(Arc<ConnDeadline>);
+
+impl Drop for ResponseGuard {
+    fn drop(&mut self) {
+        *self.0.deadline.lock().unwrap() = Some(Instant::now() + self.0.header_timeout);
+        if let Some(ref c) = self.0.conn {
+            c.request_finished();
+        }
+    }
+}
+
+/// Connection failing reads and writes which are still pending at its deadline,
+/// so stalled clients don't hold it forever.
+pub(crate) struct TimeoutIo<T> {
+    io: T,
+    deadline: Arc<ConnDeadline>,
+    sleep: Pin<Box<Sleep>>,
+}
+
+impl<T> TimeoutIo<T> {
+    /// `conn` is listed among open connections while this is kept.
+    pub fn new(io: T, header_timeout: Duration, conn: Option<Tracked>) -> TimeoutIo<T> {
+        let deadline = ConnDeadline::new(header_timeout, conn);
+        TimeoutIo {
+            io,
+            sleep: Box::pin(tokio::time::sleep_until(deadline.get().unwrap())),
+            deadline,
+        }
+    }
+
+    pub fn deadline(&self) -> Arc<ConnDeadline> {
+        self.deadline.clone()
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.io
+    }
+
+    fn poll_deadline(&mut self, cx: &mut Context<'_>) -> Poll<io::Error> {
+        let deadline = match self.deadline.get() {
+            Some(d) => d,
+            None => return Poll::Pending,
+        };
+        if self.sleep.deadline() != deadline {
+            self.sleep.as_mut().reset(deadline);
+        }
+        match self.sleep.as_mut().poll(cx) {
+            Poll::Ready(()) => Poll::Ready(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "connection deadline exceeded",
+            )),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn with_deadline<R>(
+        &mut self,
+        cx: &mut Context<'_>,
+        r: Poll<io::Result<R>>,
+    ) -> Poll<io::Result<R>> {
+        match r {
+            Poll::Ready(r) => Poll::Ready(r),
+            Poll::Pending => self.poll_deadline(cx).map(Err),
+        }
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for TimeoutIo<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let r = Pin::new(&mut this.io).poll_read(cx, buf);
+        this.with_deadline(cx, r)
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for TimeoutIo<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let r = Pin::new(&mut this.io).poll_write(cx, buf);
+        this.with_deadline(cx, r)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let r = Pin::new(&mut this.io).poll_write_vectored(cx, bufs);
+        this.with_deadline(cx, r)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.io.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let r = Pin::new(&mut this.io).poll_flush(cx);
+        this.with_deadline(cx, r)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().io).poll_shutdown(cx)
+    }
+}
+
+/// Accepted connections wrapped with header timeout. Connections over limit
+/// of their address are closed at once.
+pub(crate) fn incoming(
+    listener: TcpListener,
+    header_timeout: Duration,
+    connections: Arc<Connections>,
+) -> impl Stream<Item = io::Result<TimeoutIo<TcpStream>>> {
+    async_stream::stream! {
+        loop {
+            match listener.accept().await {
+                Ok((stream, peer)) => match connections.open(peer) {
+                    Some(conn) => yield Ok(TimeoutIo::new(stream, header_timeout, Some(conn))),
+                    None => warn!(peer = %peer, "too many connections from address"),
+                },
+                Err(e) => {
+                    // usually out of file descriptors, give others time to close
+                    error!("can't accept connection: {}", e);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+    }
+}
+
+fn timed_out(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::TimedOut, msg)
+}
+
+/// Fails request body when client doesn't send next chunk in time.
+pub(crate) fn request_body(mut body: hyper::Body, timeout: Duration) -> hyper::Body {
+    hyper::Body::wrap_stream::<_, Bytes, io::Error>(async_stream::try_stream! {
+        while let Some(chunk) = tokio::time::timeout(timeout, body.next())
+            .await
+            .map_err(|_| timed_out("request body timed out"))?
+        {
+            let chunk = chunk.map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            yield chunk;
+        }
+    })
+}
+
+/// Keeps `guard` until response is sent and cuts it at `deadline`.
+pub(crate) fn response_body(
+    mut body: hyper::Body,
+    guard: Option<ResponseGuard>,
+    deadline: Option<Instant>,
+) -> hyper::Body {
+    hyper::Body::wrap_stream::<_, Bytes, io::Error>(async_stream::try_stream! {
+        let _guard = guard;
+        loop {
+            let next = match deadline {
+                Some(d) => tokio::time::timeout_at(d, body.next())
+                    .await
+                    .map_err(|_| timed_out("request deadline exceeded"))?,
+                None => body.next().await,
+            };
+            let chunk = match next {
+                Some(chunk) => chunk.map_err(|e| io::Error::new(io::ErrorKind::Other, e))?,
+                None => break,
+            };
+            yield chunk;
+        }
+    })
+}
+
+pub(crate) fn deadline_exceeded() -> Response<hyper::Body> {
+    Response::builder()
+        .status(StatusCode::SERVICE_UNAVAILABLE)
+        .body(hyper::Body::empty())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn header_deadline() -> anyhow::Result<()> {
+        let (mut client, server) = tokio::io::duplex(64);
+        let mut server = TimeoutIo::new(server, Duration::from_millis(50), None);
+        let mut buf = [0u8; 16];
+
+        client.write_all(b"GET").await?;
+        assert_eq!(server.read(&mut buf).await?, 3);
+        let err = server.read(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+        // no deadline while request is handled
+        let guard = server.deadline().request_started(None);
+        let read = tokio::time::timeout(Duration::from_millis(100), server.read(&mut buf)).await;
+        assert!(read.is_err());
+        drop(guard);
+        let err = server.read(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stalled_request_body() {
+        let (mut sender, body) = hyper::Body::channel();
+        let mut body = request_body(body, Duration::from_millis(50));
+        sender.send_data("data".into()).await.unwrap();
+        assert_eq!(body.next().await.unwrap().unwrap(), "data");
+        assert!(body.next().await.unwrap().is_err());
+    }
+}
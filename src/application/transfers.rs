@@ -0,0 +1,194 @@
+use bytes::Bytes;
+use futures_util::StreamExt;
+use hyper::{
+    header::{CONTENT_TYPE, USER_AGENT},
+    Request, Response,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    io,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+
+/// Address of client connection, set on requests by server.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Peer(pub SocketAddr);
+
+/// Peer address, user and user agent of request, whichever are known.
+pub(crate) fn client<B>(req: &Request<B>, user: Option<String>) -> String {
+    let peer = req.extensions().get::<Peer>().map(|p| p.0.to_string());
+    let agent = req
+        .headers()
+        .get(USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from);
+    vec![peer, user, agent]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Direction {
+    Upload,
+    Download,
+}
+
+struct Entry {
+    direction: Direction,
+    path: String,
+    client: String,
+    started: Instant,
+    bytes: Arc<AtomicU64>,
+    /// milliseconds from start to last received or sent chunk
+    active: Arc<AtomicU64>,
+}
+
+/// Request and response bodies being transferred, to see what slow or stuck
+/// clients are doing.
+#[derive(Default)]
+pub(crate) struct Transfers {
+    next: AtomicU64,
+    running: Mutex<BTreeMap<u64, Entry>>,
+}
+
+/// Removes transfer from running ones when dropped with its body.
+struct Tracked {
+    transfers: Arc<Transfers>,
+    id: u64,
+    started: Instant,
+    bytes: Arc<AtomicU64>,
+    active: Arc<AtomicU64>,
+}
+
+impl Tracked {
+    fn add(&self, n: usize) {
+        self.bytes.fetch_add(n as u64, Ordering::Relaxed);
+        let ms = self.started.elapsed().as_millis() as u64;
+        self.active.store(ms, Ordering::Relaxed);
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.transfers.running.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl Transfers {
+    /// Wraps `body`, it is listed until it is sent whole or dropped.
+    pub fn track(
+        self: &Arc<Self>,
+        direction: Direction,
+        path: &str,
+        client: String,
+        mut body: hyper::Body,
+    ) -> hyper::Body {
+        let id = self.next.fetch_add(1, Ordering::Relaxed);
+        let entry = Entry {
+            direction,
+            path: path.to_string(),
+            client,
+            started: Instant::now(),
+            bytes: Arc::new(AtomicU64::new(0)),
+            active: Arc::new(AtomicU64::new(0)),
+        };
+        let tracked = Tracked {
+            transfers: self.clone(),
+            id,
+            started: entry.started,
+            bytes: entry.bytes.clone(),
+            active: entry.active.clone(),
+        };
+        self.running.lock().unwrap().insert(id, entry);
+        hyper::Body::wrap_stream::<_, Bytes, io::Error>(async_stream::try_stream! {
+            let tracked = tracked;
+            while let Some(chunk) = body.next().await {
+                let chunk = chunk.map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+                tracked.add(chunk.len());
+                yield chunk;
+            }
+        })
+    }
+
+    /// One line of `key=value` pairs per transfer, oldest first. Rate is
+    /// average since start, idle is time since last chunk.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for e in self.running.lock().unwrap().values() {
+            let elapsed = e.started.elapsed();
+            let bytes = e.bytes.load(Ordering::Relaxed);
+            let rate = bytes as f64 / elapsed.as_secs_f64().max(0.001);
+            let idle =
+                (elapsed.as_millis() as u64).saturating_sub(e.active.load(Ordering::Relaxed));
+            let direction = match e.direction {
+                Direction::Upload => "upload",
+                Direction::Download => "download",
+            };
+            let _ = writeln!(
+                out,
+                "{} path={:?} bytes={} rate={:.0}B/s elapsed={}s idle={}s client={:?}",
+                direction,
+                e.path,
+                bytes,
+                rate,
+                elapsed.as_secs(),
+                idle / 1000,
+                e.client
+            );
+        }
+        out
+    }
+
+    pub fn response(&self) -> Response<hyper::Body> {
+        Response::builder()
+            .header(CONTENT_TYPE, "text/plain")
+            .body(self.render().into())
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn lists_running_transfers() -> anyhow::Result<()> {
+        let transfers = Arc::new(Transfers::default());
+        let mut body = transfers.track(
+            Direction::Upload,
+            "/fs/a.txt",
+            "127.0.0.1:4000".into(),
+            hyper::Body::from("hello"),
+        );
+        assert!(transfers
+            .render()
+            .starts_with("upload path=\"/fs/a.txt\" bytes=0 "));
+
+        assert_eq!(body.next().await.unwrap()?, "hello");
+        let out = transfers.render();
+        assert!(out.contains(" bytes=5 "));
+        assert!(out.ends_with(" client=\"127.0.0.1:4000\"\n"));
+
+        assert!(body.next().await.is_none());
+        assert_eq!(transfers.render(), "");
+
+        let mut req = Request::get("/fs/a.txt")
+            .header(USER_AGENT, "rclone/v1.57")
+            .body(())?;
+        assert_eq!(client(&req, None), "rclone/v1.57");
+        req.extensions_mut().insert(Peer("10.0.0.2:5000".parse()?));
+        assert_eq!(
+            client(&req, Some("alice".into())),
+            "10.0.0.2:5000 alice rclone/v1.57"
+        );
+        Ok(())
+    }
+}
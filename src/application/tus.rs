@@ -0,0 +1,446 @@
+use super::errors;
+use crate::backend::open_opts;
+use crate::{configuration, events::Event};
+use bytes::BytesMut;
+use futures_util::StreamExt;
+use hyper::{
+    header::{HeaderValue, CACHE_CONTROL, CONTENT_TYPE, LOCATION},
+    Method, Request, Response, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::{debug, error};
+use webdav_handler::{
+    body::Body,
+    davpath::DavPath,
+    fs::{DavFileSystem, FsError, FsResult},
+};
+
+const TUS_VERSION: &str = "1.0.0";
+const OFFSET_STREAM: &str = "application/offset+octet-stream";
+/// Bytes read from upload file at once when it is written to destination.
+const COPY_CHUNK: usize = 4 * 1024 * 1024;
+
+/// Upload saved next to its data.
+#[derive(Debug, Serialize, Deserialize)]
+struct Info {
+    length: u64,
+    destination: String,
+}
+
+/// Resumable uploads with tus protocol (core, creation and termination).
+/// Data is appended to a local file and written to destination path when
+/// all of it arrives, so interrupted uploads continue from what server got.
+#[derive(Debug, Clone)]
+pub(crate) struct Tus {
+    endpoint: String,
+    dir: PathBuf,
+    max_size: Option<u64>,
+}
+
+fn response(status: StatusCode) -> hyper::http::response::Builder {
+    Response::builder()
+        .status(status)
+        .header("Tus-Resumable", TUS_VERSION)
+}
+
+fn error(status: StatusCode, msg: &str) -> Response<Body> {
+    let mut resp = errors::error(status, msg);
+    let headers = resp.headers_mut();
+    headers.insert("Tus-Resumable", HeaderValue::from_static(TUS_VERSION));
+    headers.insert("Tus-Version", HeaderValue::from_static(TUS_VERSION));
+    resp
+}
+
+/// Upload-Metadata is a list of `key base64(value)` pairs.
+fn metadata_value(header: &str, key: &str) -> Option<String> {
+    header.split(',').find_map(|pair| {
+        let mut it = pair.trim().splitn(2, ' ');
+        if it.next()? != key {
+            return None;
+        }
+        let value = base64::decode(it.next()?.trim()).ok()?;
+        String::from_utf8(value).ok()
+    })
+}
+
+fn header<'a, B>(req: &'a Request<B>, name: &str) -> Option<&'a str> {
+    req.headers().get(name).and_then(|v| v.to_str().ok())
+}
+
+/// Directory keeping unfinished uploads.
+pub(crate) fn uploads_dir(conf: &configuration::Uploads) -> PathBuf {
+    match conf.dir {
+        Some(ref d) => PathBuf::from(d),
+        None => std::env::temp_dir().join("webdav_ss_uploads"),
+    }
+}
+
+impl Tus {
+    pub fn new(conf: &configuration::Uploads) -> anyhow::Result<Tus> {
+        let dir = uploads_dir(conf);
+        std::fs::create_dir_all(&dir)?;
+        Ok(Tus {
+            endpoint: conf.path.trim_end_matches('/').to_string(),
+            dir,
+            max_size: conf.max_size,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn handles(&self, path: &str) -> bool {
+        path.starts_with(&self.endpoint)
+            && (path.len() == self.endpoint.len() || path[self.endpoint.len()..].starts_with('/'))
+    }
+
+    /// Upload id from request path, only ids created by server are accepted.
+    fn id(&self, path: &str) -> Option<uuid::Uuid> {
+        let rest = path[self.endpoint.len()..].trim_matches('/');
+        uuid::Uuid::parse_str(rest).ok()
+    }
+
+    fn data_path(&self, id: &uuid::Uuid) -> PathBuf {
+        self.dir.join(format!("{}.part", id))
+    }
+
+    fn info_path(&self, id: &uuid::Uuid) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    async fn info(&self, id: &uuid::Uuid) -> Option<(Info, u64)> {
+        let info = tokio::fs::read(self.info_path(id)).await.ok()?;
+        let info = serde_json::from_slice(&info).ok()?;
+        let len = tokio::fs::metadata(self.data_path(id)).await.ok()?.len();
+        Some((info, len))
+    }
+
+    async fn remove(&self, id: &uuid::Uuid) {
+        let _ = tokio::fs::remove_file(self.info_path(id)).await;
+        let _ = tokio::fs::remove_file(self.data_path(id)).await;
+    }
+
+    /// Destination and length of upload request creates or appends to, handler
+    /// checks them as PUT to destination before request is handled.
+    pub async fn destination<B>(&self, req: &Request<B>) -> Option<(String, u64)> {
+        match (req.method().clone(), self.id(req.uri().path())) {
+            (Method::POST, None) => {
+                let length = header(req, "Upload-Length")?.parse::<u64>().ok()?;
+                let destination = metadata_value(header(req, "Upload-Metadata")?, "destination")?;
+                DavPath::new(&destination).ok()?;
+                Some((destination, length))
+            }
+            (Method::PATCH, Some(id)) => {
+                let (info, _) = self.info(&id).await?;
+                Some((info.destination, info.length))
+            }
+            _ => None,
+        }
+    }
+
+    /// Handles request to endpoint. Returns event for upload written to its
+    /// destination by this request.
+    pub async fn handle(
+        &self,
+        fs: &dyn DavFileSystem,
+        req: Request<hyper::Body>,
+    ) -> (Response<Body>, Option<Event>) {
+        if req.method() == Method::OPTIONS {
+            let mut resp = response(StatusCode::NO_CONTENT)
+                .header("Tus-Version", TUS_VERSION)
+                .header("Tus-Extension", "creation,termination");
+            if let Some(max) = self.max_size {
+                resp = resp.header("Tus-Max-Size", max);
+            }
+            return (resp.body(Body::empty()).unwrap(), None);
+        }
+        if header(&req, "Tus-Resumable") != Some(TUS_VERSION) {
+            let resp = error(StatusCode::PRECONDITION_FAILED, "unsupported tus version");
+            return (resp, None);
+        }
+        let id = self.id(req.uri().path());
+        let resp = match (req.method().clone(), id) {
+            (Method::POST, None) => self.create(req).await,
+            (Method::HEAD, Some(id)) => self.offset(&id).await,
+            (Method::PATCH, Some(id)) => match self.append(fs, &id, req).await {
+                Ok(r) => return r,
+                Err(resp) => resp,
+            },
+            (Method::DELETE, Some(id)) => self.terminate(&id).await,
+            (_, None) if req.uri().path().trim_end_matches('/') != self.endpoint => {
+                error(StatusCode::NOT_FOUND, "unknown upload")
+            }
+            _ => error(StatusCode::METHOD_NOT_ALLOWED, "method not allowed"),
+        };
+        (resp, None)
+    }
+
+    async fn create(&self, req: Request<hyper::Body>) -> Response<Body> {
+        let length = match header(&req, "Upload-Length").and_then(|v| v.parse::<u64>().ok()) {
+            Some(l) => l,
+            None => return error(StatusCode::BAD_REQUEST, "missing or invalid Upload-Length"),
+        };
+        if self.max_size.map_or(false, |m| length > m) {
+            return error(StatusCode::PAYLOAD_TOO_LARGE, "upload is too large");
+        }
+        let destination =
+            match header(&req, "Upload-Metadata").and_then(|v| metadata_value(v, "destination")) {
+                Some(d) if DavPath::new(&d).is_ok() => d,
+                _ => return error(StatusCode::BAD_REQUEST, "missing or invalid destination"),
+            };
+
+        let id = uuid::Uuid::new_v4();
+        let info = Info {
+            length,
+            destination,
+        };
+        let created = async {
+            tokio::fs::write(self.data_path(&id), b"").await?;
+            tokio::fs::write(self.info_path(&id), serde_json::to_vec(&info)?).await?;
+            Ok::<_, anyhow::Error>(())
+        };
+        if let Err(e) = created.await {
+            error!("can't create upload: {:?}", e);
+            self.remove(&id).await;
+            return error(StatusCode::INTERNAL_SERVER_ERROR, "can't create upload");
+        }
+        debug!(msg = "upload created", id = %id, destination = %info.destination);
+        response(StatusCode::CREATED)
+            .header(LOCATION, format!("{}/{}", self.endpoint, id))
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    async fn offset(&self, id: &uuid::Uuid) -> Response<Body> {
+        match self.info(id).await {
+            Some((info, len)) => response(StatusCode::OK)
+                .header("Upload-Offset", len)
+                .header("Upload-Length", info.length)
+                .header(CACHE_CONTROL, "no-store")
+                .body(Body::empty())
+                .unwrap(),
+            None => error(StatusCode::NOT_FOUND, "unknown upload"),
+        }
+    }
+
+    async fn terminate(&self, id: &uuid::Uuid) -> Response<Body> {
+        if self.info(id).await.is_none() {
+            return error(StatusCode::NOT_FOUND, "unknown upload");
+        }
+        self.remove(id).await;
+        response(StatusCode::NO_CONTENT)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    /// Appends body to upload. Received data is kept even if body fails in
+    /// the middle, client asks for offset and continues from it.
+    async fn append(
+        &self,
+        fs: &dyn DavFileSystem,
+        id: &uuid::Uuid,
+        req: Request<hyper::Body>,
+    ) -> Result<(Response<Body>, Option<Event>), Response<Body>> {
+        if header(&req, CONTENT_TYPE.as_str()) != Some(OFFSET_STREAM) {
+            return Err(error(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "unsupported content type",
+            ));
+        }
+        let offset = header(&req, "Upload-Offset")
+            .and_then(|v| v.parse::<u64>().ok())
+            .ok_or_else(|| error(StatusCode::BAD_REQUEST, "missing or invalid Upload-Offset"))?;
+        let (info, mut len) = self
+            .info(id)
+            .await
+            .ok_or_else(|| error(StatusCode::NOT_FOUND, "unknown upload"))?;
+        if offset != len {
+            return Err(error(StatusCode::CONFLICT, "offset does not match upload"));
+        }
+
+        let write_error = |e: std::io::Error| {
+            error!("can't write upload: {:?}", e);
+            error(StatusCode::INTERNAL_SERVER_ERROR, "can't write upload")
+        };
+        let mut file = tokio::fs::OpenOptions::new()
+            .append(true)
+            .open(self.data_path(id))
+            .await
+            .map_err(write_error)?;
+        let mut body = req.into_body();
+        // broken body ends request, data received so far is kept
+        while let Some(Ok(chunk)) = body.next().await {
+            if len + chunk.len() as u64 > info.length {
+                file.flush().await.map_err(write_error)?;
+                return Err(error(
+                    StatusCode::BAD_REQUEST,
+                    "upload is longer than its length",
+                ));
+            }
+            file.write_all(&chunk).await.map_err(write_error)?;
+            len += chunk.len() as u64;
+        }
+        file.flush().await.map_err(write_error)?;
+
+        let mut event = None;
+        if len == info.length {
+            // upload is kept on failure, empty PATCH at its end retries
+            if let Err(e) = self.finish(fs, id, &info).await {
+                error!(msg = "can't write upload", destination = %info.destination, err = ?e);
+                return Err(error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "can't write destination",
+                ));
+            }
+            self.remove(id).await;
+            let mut e = Event::new("PUT", &info.destination);
+            e.size = Some(info.length);
+            event = Some(e);
+        }
+        let resp = response(StatusCode::NO_CONTENT)
+            .header("Upload-Offset", len)
+            .body(Body::empty())
+            .unwrap();
+        Ok((resp, event))
+    }
+
+    async fn finish(&self, fs: &dyn DavFileSystem, id: &uuid::Uuid, info: &Info) -> FsResult<()> {
+        let dest = DavPath::new(&info.destination).map_err(|_| FsError::GeneralFailure)?;
+        let mut opts = open_opts::write();
+        opts.create = true;
+        opts.truncate = true;
+        let mut out = fs.open(&dest, opts).await?;
+        let mut data = tokio::fs::File::open(self.data_path(id))
+            .await
+            .map_err(|_| FsError::GeneralFailure)?;
+        let mut buf = BytesMut::with_capacity(COPY_CHUNK);
+        loop {
+            buf.reserve(COPY_CHUNK);
+            let n = data
+                .read_buf(&mut buf)
+                .await
+                .map_err(|_| FsError::GeneralFailure)?;
+            if n == 0 {
+                break;
+            }
+            if buf.len() >= COPY_CHUNK {
+                out.write_bytes(buf.split().freeze()).await?;
+            }
+        }
+        if !buf.is_empty() {
+            out.write_bytes(buf.freeze()).await?;
+        }
+        out.flush().await
+    }
+}
+
+/// Removes uploads which were not touched for `expire` every hour.
+pub(crate) fn spawn_cleanup(dir: PathBuf, expire: Duration) {
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(Duration::from_secs(3600));
+        loop {
+            ticks.tick().await;
+            if let Err(e) = cleanup(&dir, expire).await {
+                error!("can't remove expired uploads: {:?}", e);
+            }
+        }
+    });
+}
+
+async fn cleanup(dir: &Path, expire: Duration) -> std::io::Result<()> {
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    while let Some(e) = entries.next_entry().await? {
+        let meta = e.metadata().await?;
+        // directories of virtual hosts are cleaned up by their handlers
+        if !meta.is_file() {
+            continue;
+        }
+        let modified = meta.modified()?;
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        if age > expire {
+            debug!(msg = "removing expired upload", path = ?e.path());
+            tokio::fs::remove_file(e.path()).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use webdav_handler::memfs::MemFs;
+
+    fn request(method: &str, uri: &str) -> hyper::http::request::Builder {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("Tus-Resumable", TUS_VERSION)
+    }
+
+    fn patch(uri: &str, offset: u64, data: &'static str) -> Request<hyper::Body> {
+        request("PATCH", uri)
+            .header(CONTENT_TYPE, OFFSET_STREAM)
+            .header("Upload-Offset", offset)
+            .body(data.into())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn resumable_upload() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("webdav_ss_tus_{}", std::process::id()));
+        let tus = Tus::new(&configuration::Uploads {
+            path: "/.uploads/".into(),
+            dir: Some(dir.to_str().unwrap().into()),
+            max_size: Some(100),
+            expire: 24,
+        })?;
+        let fs = MemFs::new();
+        assert!(tus.handles("/.uploads/x"));
+        assert!(!tus.handles("/.uploadsx"));
+
+        let req = request("POST", "/.uploads")
+            .header("Upload-Length", 10)
+            .header(
+                "Upload-Metadata",
+                format!(
+                    "filename {},destination {}",
+                    base64::encode("a"),
+                    base64::encode("/a.txt")
+                ),
+            )
+            .body(hyper::Body::empty())?;
+        let (resp, _) = tus.handle(fs.as_ref(), req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let location = resp.headers()[LOCATION].to_str()?.to_string();
+
+        let (resp, done) = tus.handle(fs.as_ref(), patch(&location, 0, "01234")).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert!(done.is_none());
+        let (resp, _) = tus.handle(fs.as_ref(), patch(&location, 3, "34")).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+        let head = request("HEAD", &location).body(hyper::Body::empty())?;
+        let (resp, _) = tus.handle(fs.as_ref(), head).await;
+        assert_eq!(resp.headers()["Upload-Offset"], "5");
+
+        let (resp, done) = tus.handle(fs.as_ref(), patch(&location, 5, "56789")).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert_eq!(done.unwrap().path, "/a.txt");
+        let mut f = fs.open(&DavPath::new("/a.txt")?, open_opts::read()).await?;
+        assert_eq!(f.read_bytes(100).await?, "0123456789");
+
+        let head = request("HEAD", &location).body(hyper::Body::empty())?;
+        let (resp, _) = tus.handle(fs.as_ref(), head).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
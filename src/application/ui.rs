@@ -0,0 +1,50 @@
+use hyper::{
+    header::{CACHE_CONTROL, CONTENT_TYPE},
+    Method, Request, Response, StatusCode,
+};
+
+const PAGE: &str = include_str!("ui.html");
+
+/// Single page file manager. It browses with JSON listings and changes files
+/// with PUT, MKCOL, MOVE and DELETE, so it has no access of its own.
+pub(crate) struct Ui {
+    path: String,
+}
+
+impl Ui {
+    pub fn new(path: &str) -> Self {
+        Ui {
+            path: path.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn handles<B>(&self, req: &Request<B>) -> bool {
+        let path = req.uri().path();
+        req.method() == Method::GET
+            && (path == self.path || path.strip_suffix('/') == Some(&self.path))
+    }
+
+    pub fn handle(&self) -> Response<hyper::Body> {
+        Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, "text/html; charset=utf-8")
+            .header(CACHE_CONTROL, "no-cache")
+            .body(hyper::Body::from(PAGE))
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serves_page_at_path() -> anyhow::Result<()> {
+        let ui = Ui::new("/_ui/");
+        assert!(ui.handles(&Request::get("/_ui").body(())?));
+        assert!(ui.handles(&Request::get("/_ui/").body(())?));
+        assert!(!ui.handles(&Request::get("/_ui/file").body(())?));
+        assert!(!ui.handles(&Request::put("/_ui").body(())?));
+        Ok(())
+    }
+}
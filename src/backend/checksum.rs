@@ -0,0 +1,423 @@
+use super::{normalized_path::NormalizedPath, prop_storages::PropStorage};
+use bytes::{Buf, BytesMut};
+use futures_util::FutureExt;
+use sha2::{Digest, Sha256};
+use std::io::SeekFrom;
+use tracing::{debug, error};
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{DavFile, DavFileSystem, DavMetaData, DavProp, FsError, FsFuture, FsResult, OpenOptions},
+};
+
+pub const CHECKSUM_NAMESPACE: &str = "urn:webdav_ss:";
+pub const CHECKSUM_NAME: &str = "sha256";
+pub const CHECKSUM_ETAG_NAME: &str = "sha256-etag";
+
+fn prop(name: &str, value: Option<&str>) -> DavProp {
+    DavProp {
+        name: name.into(),
+        namespace: Some(CHECKSUM_NAMESPACE.into()),
+        prefix: None,
+        xml: value.map(|v| {
+            format!(
+                "<{name} xmlns=\"{ns}\">{value}</{name}>",
+                name = name,
+                ns = CHECKSUM_NAMESPACE,
+                value = v
+            )
+            .into_bytes()
+        }),
+    }
+}
+
+/// Dead property used to keep file checksum in prop storage.
+pub fn checksum_prop(value: Option<&str>) -> DavProp {
+    prop(CHECKSUM_NAME, value)
+}
+
+/// Dead property with etag backend reported for the file checksum was
+/// calculated of, tells if the file was changed bypassing server since.
+pub fn checksum_etag_prop(value: Option<&str>) -> DavProp {
+    prop(CHECKSUM_ETAG_NAME, value)
+}
+
+pub fn parse_checksum(xml: &[u8]) -> Option<String> {
+    let s = std::str::from_utf8(xml).ok()?;
+    let start = s.find('>')? + 1;
+    let end = s.rfind("</")?;
+    if start > end {
+        return None;
+    }
+    Some(s[start..end].trim().to_owned())
+}
+
+/// Where checksum property should be stored: global prop storage or
+/// filesystem itself if it keeps dead properties on its own.
+#[derive(Clone)]
+pub enum ChecksumTarget {
+    Storage(Box<dyn PropStorage>, NormalizedPath),
+    Filesystem(Box<dyn DavFileSystem>, DavPath),
+}
+
+impl ChecksumTarget {
+    async fn get(&self, prop: DavProp) -> Option<String> {
+        let xml = match self {
+            ChecksumTarget::Storage(props, path) => props.get_prop(path, prop).await,
+            ChecksumTarget::Filesystem(fs, path) => fs.get_prop(path, prop).await,
+        };
+        parse_checksum(&xml.ok()?)
+    }
+
+    /// Checksum stored by last complete write and etag of the written file,
+    /// if any.
+    pub async fn stored(&self) -> Option<(String, String)> {
+        let checksum = self.get(checksum_prop(None)).await?;
+        let etag = self.get(checksum_etag_prop(None)).await?;
+        Some((checksum, etag))
+    }
+}
+
+impl std::fmt::Debug for ChecksumTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChecksumTarget::Storage(_, p) => write!(f, "Storage({})", p),
+            ChecksumTarget::Filesystem(_, p) => write!(f, "Filesystem({})", p.as_url_string()),
+        }
+    }
+}
+
+/// Wraps file opened for writing and calculates checksum of its content.
+/// Checksum is valid only for sequential writes from the beginning of the file,
+/// any seek or read in between invalidates it and stored checksum is removed on flush.
+#[derive(derivative::Derivative)]
+#[derivative(Debug)]
+pub struct ChecksumFile {
+    inner: Box<dyn DavFile>,
+    target: ChecksumTarget,
+    #[derivative(Debug = "ignore")]
+    hasher: Option<Sha256>,
+    hashed: u64,
+    pos: u64,
+}
+
+impl ChecksumFile {
+    pub fn new(inner: Box<dyn DavFile>, target: ChecksumTarget, truncated: bool) -> Self {
+        ChecksumFile {
+            inner,
+            target,
+            hasher: if truncated { Some(Sha256::new()) } else { None },
+            hashed: 0,
+            pos: 0,
+        }
+    }
+
+    fn update(&mut self, buf: &[u8]) {
+        if self.pos != self.hashed {
+            self.hasher = None;
+        }
+        if let Some(h) = self.hasher.as_mut() {
+            h.update(buf);
+            self.hashed += buf.len() as u64;
+        }
+        self.pos += buf.len() as u64;
+    }
+}
+
+impl DavFile for ChecksumFile {
+    fn metadata<'a>(&'a mut self) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.metadata()
+    }
+
+    fn write_buf<'a>(&'a mut self, mut buf: Box<dyn Buf + Send>) -> FsFuture<()> {
+        let b = buf.copy_to_bytes(buf.remaining());
+        self.write_bytes(b)
+    }
+
+    fn write_bytes<'a>(&'a mut self, buf: bytes::Bytes) -> FsFuture<()> {
+        async move {
+            self.inner.write_bytes(buf.clone()).await?;
+            self.update(&buf);
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn read_bytes<'a>(&'a mut self, count: usize) -> FsFuture<bytes::Bytes> {
+        async move {
+            let b = self.inner.read_bytes(count).await?;
+            self.pos += b.len() as u64;
+            Ok(b)
+        }
+        .boxed()
+    }
+
+    fn seek<'a>(&'a mut self, pos: SeekFrom) -> FsFuture<u64> {
+        async move {
+            self.pos = self.inner.seek(pos).await?;
+            Ok(self.pos)
+        }
+        .boxed()
+    }
+
+    fn flush<'a>(&'a mut self) -> FsFuture<()> {
+        async move {
+            self.inner.flush().await?;
+            let digest = self.hasher.clone().map(|h| format!("{:x}", h.finalize()));
+            let etag = match digest {
+                Some(_) => self.inner.metadata().await.ok().and_then(|m| m.etag()),
+                None => None,
+            };
+            debug!(dest = ?self.target, checksum = ?digest, etag = ?etag);
+            let patch = vec![
+                (digest.is_some(), checksum_prop(digest.as_deref())),
+                (etag.is_some(), checksum_etag_prop(etag.as_deref())),
+            ];
+            let r = match &self.target {
+                ChecksumTarget::Storage(props, path) => {
+                    let mut r = Ok(());
+                    for p in patch {
+                        r = r.and(props.patch_prop(path, p).await.map(|_| ()));
+                    }
+                    r
+                }
+                ChecksumTarget::Filesystem(fs, path) => {
+                    fs.patch_props(path, patch).await.map(|_| ())
+                }
+            };
+            if let Err(e) = r {
+                error!(msg = "can't store checksum", dest = ?self.target, err = ?e);
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+enum Upload {
+    Buffering { buf: BytesMut, hasher: Sha256 },
+    Writing(ChecksumFile),
+    Skipped,
+}
+
+/// Upload replacing file with stored checksum. Content is buffered and
+/// written only if it differs from stored one, so clients uploading
+/// unchanged files again don't cause backend writes. Uploads bigger than
+/// `max` bytes, reads and seeks make it write through instead, so does
+/// a file with etag other than the one stored with checksum.
+#[derive(derivative::Derivative)]
+#[derivative(Debug)]
+pub struct DedupeFile {
+    #[derivative(Debug = "ignore")]
+    fs: Box<dyn DavFileSystem>,
+    path: DavPath,
+    options: OpenOptions,
+    target: ChecksumTarget,
+    #[derivative(Debug = "ignore")]
+    existing: Box<dyn DavMetaData>,
+    stored: String,
+    max: usize,
+    #[derivative(Debug = "ignore")]
+    upload: Upload,
+}
+
+impl DedupeFile {
+    /// Opens `path` of `fs` for truncating write, deduplicated if the file
+    /// exists and is unchanged since its checksum was stored.
+    pub async fn open(
+        fs: Box<dyn DavFileSystem>,
+        path: DavPath,
+        options: OpenOptions,
+        target: ChecksumTarget,
+        max: usize,
+    ) -> FsResult<Box<dyn DavFile>> {
+        let existing = fs.metadata(&path).await.ok().filter(|m| m.is_file());
+        let stored = match &existing {
+            Some(m) => target
+                .stored()
+                .await
+                .filter(|(_, etag)| m.etag().as_ref() == Some(etag))
+                .map(|(checksum, _)| checksum),
+            None => None,
+        };
+        let (existing, stored) = match (existing, stored) {
+            (Some(m), Some(s)) => (m, s),
+            _ => {
+                let file = fs.open(&path, options).await?;
+                return Ok(Box::new(ChecksumFile::new(file, target, true)) as Box<dyn DavFile>);
+            }
+        };
+        Ok(Box::new(DedupeFile {
+            fs,
+            path,
+            options,
+            target,
+            existing,
+            stored,
+            max,
+            upload: Upload::Buffering {
+                buf: BytesMut::new(),
+                hasher: Sha256::new(),
+            },
+        }) as Box<dyn DavFile>)
+    }
+
+    /// Opens the file and writes buffered content into it.
+    async fn write_through(&mut self) -> FsResult<&mut ChecksumFile> {
+        if let Upload::Buffering { buf, .. } = &mut self.upload {
+            let buf = std::mem::take(buf).freeze();
+            let file = self.fs.open(&self.path, self.options).await?;
+            let mut file = ChecksumFile::new(file, self.target.clone(), true);
+            if !buf.is_empty() {
+                file.write_bytes(buf).await?;
+            }
+            self.upload = Upload::Writing(file);
+        }
+        match &mut self.upload {
+            Upload::Writing(f) => Ok(f),
+            _ => Err(FsError::GeneralFailure),
+        }
+    }
+}
+
+impl DavFile for DedupeFile {
+    fn metadata<'a>(&'a mut self) -> FsFuture<Box<dyn DavMetaData>> {
+        match &mut self.upload {
+            Upload::Writing(f) => f.metadata(),
+            _ => {
+                let m = self.existing.clone();
+                async move { Ok(m) }.boxed()
+            }
+        }
+    }
+
+    fn write_buf<'a>(&'a mut self, mut buf: Box<dyn Buf + Send>) -> FsFuture<()> {
+        let b = buf.copy_to_bytes(buf.remaining());
+        self.write_bytes(b)
+    }
+
+    fn write_bytes<'a>(&'a mut self, data: bytes::Bytes) -> FsFuture<()> {
+        async move {
+            if let Upload::Buffering { buf, hasher } = &mut self.upload {
+                if buf.len() + data.len() <= self.max {
+                    hasher.update(&data);
+                    buf.extend_from_slice(&data);
+                    return Ok(());
+                }
+            }
+            self.write_through().await?.write_bytes(data).await
+        }
+        .boxed()
+    }
+
+    fn read_bytes<'a>(&'a mut self, count: usize) -> FsFuture<bytes::Bytes> {
+        async move { self.write_through().await?.read_bytes(count).await }.boxed()
+    }
+
+    fn seek<'a>(&'a mut self, pos: SeekFrom) -> FsFuture<u64> {
+        async move { self.write_through().await?.seek(pos).await }.boxed()
+    }
+
+    fn flush<'a>(&'a mut self) -> FsFuture<()> {
+        async move {
+            if let Upload::Buffering { buf, hasher } = &self.upload {
+                let digest = format!("{:x}", hasher.clone().finalize());
+                if buf.len() as u64 == self.existing.len() && digest == self.stored {
+                    debug!(msg = "content unchanged, write skipped", path = ?self.path);
+                    self.upload = Upload::Skipped;
+                }
+            }
+            match self.upload {
+                Upload::Skipped => Ok(()),
+                _ => self.write_through().await?.flush().await,
+            }
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::open_opts;
+    use crate::{backend::prop_storages::mem::Memory, test_utils::put};
+    use webdav_handler::memfs::MemFs;
+
+    #[test]
+    fn parse_prop() {
+        let p = checksum_prop(Some("abcdef"));
+        assert_eq!(parse_checksum(&p.xml.unwrap()), Some("abcdef".into()));
+    }
+
+    #[tokio::test]
+    async fn checksum_on_flush() -> anyhow::Result<()> {
+        let fs = MemFs::new();
+        let props = Memory::new();
+        let path = DavPath::new("/file.txt").unwrap();
+        let mut opts = open_opts::write();
+        opts.create = true;
+        opts.truncate = true;
+        let f = fs.open(&path, opts).await?;
+        let target = ChecksumTarget::Storage(props.clone(), (&path).into());
+        let mut f = ChecksumFile::new(f, target, true);
+        f.write_bytes("hello ".into()).await?;
+        f.write_bytes("world".into()).await?;
+        f.flush().await?;
+
+        let xml = props.get_prop(&(&path).into(), checksum_prop(None)).await?;
+        assert_eq!(
+            parse_checksum(&xml),
+            Some(format!("{:x}", Sha256::digest(b"hello world")))
+        );
+
+        let f = fs.open(&path, open_opts::write()).await?;
+        let target = ChecksumTarget::Storage(props.clone(), (&path).into());
+        let mut f = ChecksumFile::new(f, target, false);
+        f.write_bytes("j".into()).await?;
+        f.flush().await?;
+        assert!(props
+            .get_prop(&(&path).into(), checksum_prop(None))
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn skips_unchanged_upload() -> anyhow::Result<()> {
+        let fs = MemFs::new();
+        let props = Memory::new();
+        let path = DavPath::new("/file.txt").unwrap();
+        let mut opts = open_opts::write();
+        opts.create = true;
+        opts.truncate = true;
+        let upload = |content: &'static str| {
+            let (fs, props, path) = (fs.clone(), props.clone(), path.clone());
+            async move {
+                let target = ChecksumTarget::Storage(props, (&path).into());
+                let mut f = DedupeFile::open(fs, path, opts, target, 1024).await?;
+                f.write_bytes(content.into()).await?;
+                f.flush().await?;
+                Ok::<_, FsError>(())
+            }
+        };
+        upload("hello").await?;
+        let first = fs.metadata(&path).await?.modified()?;
+
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        upload("hello").await?;
+        assert_eq!(fs.metadata(&path).await?.modified()?, first);
+
+        upload("world").await?;
+        assert_ne!(fs.metadata(&path).await?.modified()?, first);
+        let mut f = fs.open(&path, open_opts::read()).await?;
+        assert_eq!(f.read_bytes(16).await?, "world");
+
+        // file changed bypassing server isn't taken for the stored one
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        put(fs.as_ref(), "/file.txt", b"hello").await?;
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        upload("world").await?;
+        let mut f = fs.open(&path, open_opts::read()).await?;
+        assert_eq!(f.read_bytes(16).await?, "world");
+        Ok(())
+    }
+}
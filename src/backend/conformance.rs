@@ -0,0 +1,377 @@
+//! Checks of `DavFileSystem` behaviour webdav handler relies on, runnable
+//! against any backend.
+use crate::backend::open_opts;
+use crate::backend::walk::child_path;
+use anyhow::{anyhow, ensure, Result};
+use bytes::Bytes;
+use futures_util::StreamExt;
+use std::io::SeekFrom;
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{DavFileSystem, FsError, ReadDirMeta},
+};
+
+/// Names with characters which need escaping in urls or file systems.
+const WEIRD_NAMES: &[&str] = &[
+    "with space",
+    "percent%20sign",
+    "hash#tag",
+    "query?mark",
+    "semi;colon",
+    "plus+amp&eq=",
+    "quote'dquote\"",
+    "ünïcødé",
+    "日本語",
+    "brackets[]{}()",
+    ".hidden",
+    "trailing.",
+];
+
+fn path(base: &DavPath, rel: &str) -> Result<DavPath> {
+    let mut url = base.as_url_string();
+    if !url.ends_with('/') {
+        url.push('/');
+    }
+    url.push_str(rel);
+    DavPath::new(&url).map_err(|_| anyhow!("invalid path: {}", url))
+}
+
+fn child(dir: &DavPath, name: &str, is_dir: bool) -> Result<DavPath> {
+    child_path(dir, name.as_bytes(), is_dir).map_err(|e| anyhow!("invalid name {}: {:?}", name, e))
+}
+
+fn is_not_found<T>(r: Result<T, FsError>) -> bool {
+    matches!(r, Err(FsError::NotFound))
+}
+
+async fn write(fs: &dyn DavFileSystem, p: &DavPath, chunks: &[&[u8]]) -> Result<()> {
+    let mut opts = open_opts::write();
+    opts.create = true;
+    opts.truncate = true;
+    let mut f = fs
+        .open(p, opts)
+        .await
+        .map_err(|e| anyhow!("open {:?} for write: {:?}", p, e))?;
+    for c in chunks {
+        f.write_bytes(Bytes::copy_from_slice(c))
+            .await
+            .map_err(|e| anyhow!("write {:?}: {:?}", p, e))?;
+    }
+    f.flush()
+        .await
+        .map_err(|e| anyhow!("flush {:?}: {:?}", p, e))
+}
+
+async fn read(fs: &dyn DavFileSystem, p: &DavPath) -> Result<Vec<u8>> {
+    let mut f = fs
+        .open(p, open_opts::read())
+        .await
+        .map_err(|e| anyhow!("open {:?} for read: {:?}", p, e))?;
+    let mut data = vec![];
+    loop {
+        let chunk = f
+            .read_bytes(4096)
+            .await
+            .map_err(|e| anyhow!("read {:?}: {:?}", p, e))?;
+        if chunk.is_empty() {
+            return Ok(data);
+        }
+        data.extend_from_slice(&chunk);
+    }
+}
+
+async fn names(fs: &dyn DavFileSystem, dir: &DavPath) -> Result<Vec<String>> {
+    let mut entries = fs
+        .read_dir(dir, ReadDirMeta::Data)
+        .await
+        .map_err(|e| anyhow!("read_dir {:?}: {:?}", dir, e))?;
+    let mut r = vec![];
+    while let Some(e) = entries.next().await {
+        let name = String::from_utf8_lossy(&e.name()).into_owned();
+        r.push(name.trim_end_matches('/').to_string());
+    }
+    r.sort();
+    Ok(r)
+}
+
+async fn create_dir(fs: &dyn DavFileSystem, p: &DavPath) -> Result<()> {
+    fs.create_dir(p)
+        .await
+        .map_err(|e| anyhow!("create_dir {:?}: {:?}", p, e))
+}
+
+async fn dirs(fs: &dyn DavFileSystem, base: &DavPath) -> Result<()> {
+    let dir = path(base, "dir/")?;
+    create_dir(fs, &dir).await?;
+    let meta = fs
+        .metadata(&dir)
+        .await
+        .map_err(|e| anyhow!("metadata {:?}: {:?}", dir, e))?;
+    ensure!(meta.is_dir(), "created collection is not a dir");
+    ensure!(
+        matches!(fs.create_dir(&dir).await, Err(FsError::Exists)),
+        "creating existing dir must fail with Exists"
+    );
+    ensure!(
+        fs.create_dir(&path(base, "missing/child/")?).await.is_err(),
+        "creating dir without parent must fail"
+    );
+    ensure!(
+        is_not_found(fs.metadata(&path(base, "nothing")?).await),
+        "metadata of missing path must fail with NotFound"
+    );
+    Ok(())
+}
+
+async fn files(fs: &dyn DavFileSystem, base: &DavPath) -> Result<()> {
+    let file = path(base, "file.txt")?;
+    write(fs, &file, &[b"hello ", b"world"]).await?;
+    ensure!(read(fs, &file).await? == b"hello world", "content differs");
+    let meta = fs
+        .metadata(&file)
+        .await
+        .map_err(|e| anyhow!("metadata {:?}: {:?}", file, e))?;
+    ensure!(!meta.is_dir(), "file is reported as dir");
+    ensure!(meta.len() == 11, "wrong length {}", meta.len());
+
+    ensure!(
+        is_not_found(
+            fs.open(&path(base, "missing.txt")?, open_opts::read())
+                .await
+        ),
+        "reading missing file must fail with NotFound"
+    );
+    let mut opts = open_opts::write();
+    opts.create_new = true;
+    ensure!(
+        matches!(fs.open(&file, opts).await, Err(FsError::Exists)),
+        "create_new on existing file must fail with Exists"
+    );
+
+    write(fs, &file, &[b"bye"]).await?;
+    ensure!(
+        read(fs, &file).await? == b"bye",
+        "truncated content differs"
+    );
+
+    let empty = path(base, "empty.txt")?;
+    write(fs, &empty, &[]).await?;
+    ensure!(read(fs, &empty).await?.is_empty(), "empty file has content");
+    Ok(())
+}
+
+async fn seek(fs: &dyn DavFileSystem, base: &DavPath) -> Result<()> {
+    let file = path(base, "digits.txt")?;
+    write(fs, &file, &[b"0123456789"]).await?;
+    let mut f = fs
+        .open(&file, open_opts::read())
+        .await
+        .map_err(|e| anyhow!("open {:?}: {:?}", file, e))?;
+    let checks: [(SeekFrom, u64, &[u8]); 3] = [
+        (SeekFrom::Start(3), 3, b"34"),
+        (SeekFrom::Current(1), 6, b"6"),
+        (SeekFrom::End(-2), 8, b"89"),
+    ];
+    for (from, pos, expected) in checks.iter() {
+        let p = f
+            .seek(*from)
+            .await
+            .map_err(|e| anyhow!("seek {:?}: {:?}", from, e))?;
+        ensure!(
+            p == *pos,
+            "seek {:?} returned {}, expected {}",
+            from,
+            p,
+            pos
+        );
+        let data = f
+            .read_bytes(expected.len())
+            .await
+            .map_err(|e| anyhow!("read after seek: {:?}", e))?;
+        ensure!(&data[..] == *expected, "wrong data after seek {:?}", from);
+    }
+    Ok(())
+}
+
+async fn listing(fs: &dyn DavFileSystem, base: &DavPath) -> Result<()> {
+    write(fs, &path(base, "a.txt")?, &[b"a"]).await?;
+    write(fs, &path(base, "b.txt")?, &[b"b"]).await?;
+    create_dir(fs, &path(base, "sub/")?).await?;
+    write(fs, &path(base, "sub/c.txt")?, &[b"c"]).await?;
+    let found = names(fs, base).await?;
+    ensure!(
+        found == ["a.txt", "b.txt", "sub"],
+        "wrong entries: {:?}",
+        found
+    );
+    Ok(())
+}
+
+async fn rename_copy_remove(fs: &dyn DavFileSystem, base: &DavPath) -> Result<()> {
+    let a = path(base, "a.txt")?;
+    let b = path(base, "b.txt")?;
+    write(fs, &a, &[b"data"]).await?;
+    fs.rename(&a, &b)
+        .await
+        .map_err(|e| anyhow!("rename file: {:?}", e))?;
+    ensure!(
+        is_not_found(fs.metadata(&a).await),
+        "renamed file still exists"
+    );
+    ensure!(
+        read(fs, &b).await? == b"data",
+        "renamed file content differs"
+    );
+
+    let dir = path(base, "dir/")?;
+    create_dir(fs, &dir).await?;
+    create_dir(fs, &path(base, "dir/inner/")?).await?;
+    write(fs, &path(base, "dir/inner/f.txt")?, &[b"deep"]).await?;
+    let moved = path(base, "moved/")?;
+    fs.rename(&dir, &moved)
+        .await
+        .map_err(|e| anyhow!("rename dir: {:?}", e))?;
+    ensure!(
+        is_not_found(fs.metadata(&dir).await),
+        "renamed dir still exists"
+    );
+    ensure!(
+        read(fs, &path(base, "moved/inner/f.txt")?).await? == b"deep",
+        "file in renamed dir differs"
+    );
+
+    let c = path(base, "c.txt")?;
+    fs.copy(&b, &c)
+        .await
+        .map_err(|e| anyhow!("copy file: {:?}", e))?;
+    ensure!(read(fs, &b).await? == b"data", "copy source changed");
+    ensure!(read(fs, &c).await? == b"data", "copy content differs");
+
+    fs.remove_file(&c)
+        .await
+        .map_err(|e| anyhow!("remove_file: {:?}", e))?;
+    ensure!(
+        is_not_found(fs.metadata(&c).await),
+        "removed file still exists"
+    );
+    let empty = path(base, "empty/")?;
+    create_dir(fs, &empty).await?;
+    fs.remove_dir(&empty)
+        .await
+        .map_err(|e| anyhow!("remove_dir: {:?}", e))?;
+    ensure!(
+        is_not_found(fs.metadata(&empty).await),
+        "removed dir still exists"
+    );
+    Ok(())
+}
+
+async fn deep_tree(fs: &dyn DavFileSystem, base: &DavPath) -> Result<()> {
+    let mut dir = base.clone();
+    for level in 0..12 {
+        dir = child(&dir, &format!("level{}", level), true)?;
+        create_dir(fs, &dir).await?;
+    }
+    let file = child(&dir, "bottom.txt", false)?;
+    write(fs, &file, &[b"bottom"]).await?;
+    ensure!(
+        read(fs, &file).await? == b"bottom",
+        "deep file content differs"
+    );
+    ensure!(
+        names(fs, &dir).await? == ["bottom.txt"],
+        "wrong entries of deepest dir"
+    );
+    Ok(())
+}
+
+async fn weird_names(fs: &dyn DavFileSystem, base: &DavPath) -> Result<()> {
+    let mut expected = vec![];
+    for name in WEIRD_NAMES {
+        let file = child(base, &format!("{}.txt", name), false)?;
+        write(fs, &file, &[name.as_bytes()]).await?;
+        ensure!(
+            read(fs, &file).await? == name.as_bytes(),
+            "content of {:?} differs",
+            name
+        );
+        let dir = child(base, &format!("{} dir", name), true)?;
+        create_dir(fs, &dir).await?;
+        expected.push(format!("{}.txt", name));
+        expected.push(format!("{} dir", name));
+    }
+    expected.sort();
+    let found = names(fs, base).await?;
+    ensure!(found == expected, "wrong entries: {:?}", found);
+    Ok(())
+}
+
+/// Runs every check in its own collection below `base`, which must exist and
+/// be empty. Returns error describing the first failed check.
+pub async fn check(fs: &dyn DavFileSystem, base: &DavPath) -> Result<()> {
+    macro_rules! run {
+        ($case:ident) => {
+            let dir = path(base, concat!(stringify!($case), "/"))?;
+            create_dir(fs, &dir).await?;
+            $case(fs, &dir)
+                .await
+                .map_err(|e| anyhow!("{}: {}", stringify!($case), e))?;
+        };
+    }
+    run!(dirs);
+    run!(files);
+    run!(seek);
+    run!(listing);
+    run!(rename_copy_remove);
+    run!(deep_tree);
+    run!(weird_names);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        aggregate::AggregateBuilder,
+        backend::wrappers::{
+            case::CaseInsensitiveFs,
+            ignore::{IgnoreAction, IgnoreFs},
+        },
+    };
+    use webdav_handler::{localfs::LocalFs, memfs::MemFs};
+
+    fn root() -> DavPath {
+        DavPath::new("/").unwrap()
+    }
+
+    #[tokio::test]
+    async fn memfs() -> anyhow::Result<()> {
+        check(MemFs::new().as_ref(), &root()).await
+    }
+
+    #[tokio::test]
+    async fn localfs() -> anyhow::Result<()> {
+        let dir =
+            std::env::temp_dir().join(format!("webdav_ss_conformance_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir)?;
+        let r = check(LocalFs::new(&dir, false, false, false).as_ref(), &root()).await;
+        std::fs::remove_dir_all(&dir)?;
+        r
+    }
+
+    #[tokio::test]
+    async fn aggregate() -> anyhow::Result<()> {
+        let fs = AggregateBuilder::new()
+            .add_route(("/m", MemFs::new()))
+            .build()?;
+        check(fs.as_ref(), &DavPath::new("/m/")?).await
+    }
+
+    #[tokio::test]
+    async fn wrappers() -> anyhow::Result<()> {
+        let patterns = vec![".DS_Store".to_string()];
+        let fs = IgnoreFs::new(MemFs::new(), &patterns, IgnoreAction::Reject)?;
+        check(CaseInsensitiveFs::new(fs).as_ref(), &root()).await
+    }
+}
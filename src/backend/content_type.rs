@@ -0,0 +1,48 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+/// Guesses MIME type of files by extension. Configured overrides (extension
+/// to type, case insensitive) win over built-in table.
+#[derive(Debug, Clone, Default)]
+pub struct ContentTypes {
+    overrides: Arc<HashMap<String, String>>,
+}
+
+impl ContentTypes {
+    pub fn new(overrides: HashMap<String, String>) -> Self {
+        let overrides = overrides
+            .into_iter()
+            .map(|(ext, t)| (ext.trim_start_matches('.').to_lowercase(), t))
+            .collect();
+        ContentTypes {
+            overrides: Arc::new(overrides),
+        }
+    }
+
+    pub fn guess<P: AsRef<Path>>(&self, path: P) -> String {
+        let path = path.as_ref();
+        let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase());
+        if let Some(t) = ext.and_then(|e| self.overrides.get(&e)) {
+            return t.clone();
+        }
+        mime_guess::from_path(path)
+            .first_or_octet_stream()
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides() {
+        let mut m = HashMap::new();
+        m.insert(".MD".to_string(), "text/markdown".to_string());
+        m.insert("bin".to_string(), "application/x-firmware".to_string());
+        let types = ContentTypes::new(m);
+        assert_eq!(types.guess("/docs/readme.md"), "text/markdown");
+        assert_eq!(types.guess("/fw/image.BIN"), "application/x-firmware");
+        assert_eq!(types.guess("/img/photo.png"), "image/png");
+        assert_eq!(types.guess("/noext"), "application/octet-stream");
+    }
+}
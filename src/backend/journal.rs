@@ -0,0 +1,283 @@
+use bytes::{Buf, Bytes};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tracing::warn;
+use webdav_handler::fs::{DavFile, DavMetaData, FsFuture};
+
+/// Operation changing both data of a mount and props of its paths, which
+/// are not updated atomically.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum Op {
+    Write { path: String },
+    Remove { path: String },
+    Rename { from: String, to: String },
+    Copy { from: String, to: String },
+}
+
+struct Inner {
+    file: File,
+    next: u64,
+    /// recorded operations and whether they are done
+    entries: BTreeMap<u64, (Op, bool)>,
+}
+
+impl Inner {
+    fn mark_done(&mut self, done: Vec<u64>) {
+        for id in done {
+            if let Some(e) = self.entries.get_mut(&id) {
+                e.1 = true;
+            }
+        }
+    }
+}
+
+/// Write-ahead journal of operations, one JSON line each. Entry is synced
+/// before operation starts and is kept until props it changed are flushed,
+/// so entries left after crash name paths whose props may be stale.
+pub struct Journal {
+    inner: Mutex<Inner>,
+    /// ids of finished operations, kept apart from file so marking them
+    /// does not wait for file io
+    done: Mutex<Vec<u64>>,
+    compacting: AtomicBool,
+    last_compaction: Mutex<Option<Instant>>,
+}
+
+/// Marks its operation done when dropped.
+pub struct Pending {
+    journal: Arc<Journal>,
+    id: u64,
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        self.journal.done.lock().unwrap().push(self.id);
+    }
+}
+
+impl Journal {
+    pub fn open(path: &Path) -> io::Result<Arc<Journal>> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(path)?;
+        Ok(Arc::new(Journal {
+            inner: Mutex::new(Inner {
+                file,
+                next: 0,
+                entries: BTreeMap::new(),
+            }),
+            done: Mutex::new(vec![]),
+            compacting: AtomicBool::new(false),
+            last_compaction: Mutex::new(None),
+        }))
+    }
+
+    /// Operations found in file, done or not. Line torn by crash is skipped.
+    pub fn pending(&self) -> io::Result<Vec<Op>> {
+        let mut inner = self.inner.lock().unwrap();
+        let mut content = String::new();
+        inner.file.seek(SeekFrom::Start(0))?;
+        inner.file.read_to_string(&mut content)?;
+        let mut ops = vec![];
+        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+            match serde_json::from_str(line) {
+                Ok(op) => ops.push(op),
+                Err(e) => warn!("skipping broken journal entry {:?}: {}", line, e),
+            }
+        }
+        Ok(ops)
+    }
+
+    /// Writes and syncs entry of `op`, it is done once returned value is dropped.
+    pub async fn record(self: &Arc<Self>, op: Op) -> io::Result<Pending> {
+        let journal = self.clone();
+        tokio::task::spawn_blocking(move || -> io::Result<Pending> {
+            let mut inner = journal.inner.lock().unwrap();
+            let mut line = serde_json::to_vec(&op)?;
+            line.push(b'\n');
+            inner.file.seek(SeekFrom::End(0))?;
+            inner.file.write_all(&line)?;
+            inner.file.sync_data()?;
+            let id = inner.next;
+            inner.next += 1;
+            inner.entries.insert(id, (op, false));
+            drop(inner);
+            Ok(Pending { journal, id })
+        })
+        .await
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Position to pass to `clear_until` after props are flushed.
+    pub fn checkpoint(&self) -> u64 {
+        self.inner.lock().unwrap().next
+    }
+
+    /// Checkpoint for compaction, None while other one is running or
+    /// previous one started less than `interval` ago.
+    pub fn start_compaction(&self, interval: Duration) -> Option<u64> {
+        if self.compacting.swap(true, Ordering::SeqCst) {
+            return None;
+        }
+        let mut last = self.last_compaction.lock().unwrap();
+        if last.map_or(false, |l| l.elapsed() < interval) {
+            self.compacting.store(false, Ordering::SeqCst);
+            return None;
+        }
+        *last = Some(Instant::now());
+        Some(self.checkpoint())
+    }
+
+    pub fn end_compaction(&self) {
+        self.compacting.store(false, Ordering::SeqCst);
+    }
+
+    /// Removes entries done before `checkpoint` was taken, file is rewritten
+    /// with the rest.
+    pub async fn clear_until(self: &Arc<Self>, checkpoint: u64) -> io::Result<()> {
+        let journal = self.clone();
+        tokio::task::spawn_blocking(move || -> io::Result<()> {
+            let done = std::mem::take(&mut *journal.done.lock().unwrap());
+            let mut inner = journal.inner.lock().unwrap();
+            inner.mark_done(done);
+            inner
+                .entries
+                .retain(|id, (_, done)| *id >= checkpoint || !*done);
+            let mut content = vec![];
+            for (op, _) in inner.entries.values() {
+                content.extend(serde_json::to_vec(op)?);
+                content.push(b'\n');
+            }
+            inner.file.set_len(0)?;
+            inner.file.seek(SeekFrom::Start(0))?;
+            inner.file.write_all(&content)?;
+            inner.file.sync_data()
+        })
+        .await
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+    }
+}
+
+/// File opened for writing, its journal entry is done when it is closed.
+#[derive(derivative::Derivative)]
+#[derivative(Debug)]
+pub struct JournaledFile {
+    inner: Box<dyn DavFile>,
+    #[derivative(Debug = "ignore")]
+    _pending: Pending,
+}
+
+impl JournaledFile {
+    pub fn new(inner: Box<dyn DavFile>, pending: Pending) -> Box<dyn DavFile> {
+        Box::new(JournaledFile {
+            inner,
+            _pending: pending,
+        })
+    }
+}
+
+impl DavFile for JournaledFile {
+    fn metadata<'a>(&'a mut self) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.metadata()
+    }
+
+    fn write_buf<'a>(&'a mut self, buf: Box<dyn Buf + Send>) -> FsFuture<()> {
+        self.inner.write_buf(buf)
+    }
+
+    fn write_bytes<'a>(&'a mut self, buf: Bytes) -> FsFuture<()> {
+        self.inner.write_bytes(buf)
+    }
+
+    fn read_bytes<'a>(&'a mut self, count: usize) -> FsFuture<Bytes> {
+        self.inner.read_bytes(count)
+    }
+
+    fn seek<'a>(&'a mut self, pos: SeekFrom) -> FsFuture<u64> {
+        self.inner.seek(pos)
+    }
+
+    fn flush<'a>(&'a mut self) -> FsFuture<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn keeps_entries_until_cleared() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("webdav_ss_journal_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let journal = Journal::open(&path)?;
+        let write = Op::Write {
+            path: "/fs/a.txt".into(),
+        };
+        let rename = Op::Rename {
+            from: "/fs/a.txt".into(),
+            to: "/fs/b.txt".into(),
+        };
+
+        let done = journal.record(write.clone()).await?;
+        drop(done);
+        let running = journal.record(rename.clone()).await?;
+        assert_eq!(journal.pending()?, vec![write.clone(), rename.clone()]);
+
+        let checkpoint = journal.checkpoint();
+        journal.clear_until(checkpoint).await?;
+        assert_eq!(journal.pending()?, vec![rename.clone()]);
+        drop(running);
+        journal.clear_until(checkpoint).await?;
+        assert!(journal.is_empty());
+
+        // crash while writing entry
+        std::fs::write(
+            &path,
+            "{\"op\":\"remove\",\"path\":\"/fs/c\"}\n{\"op\":\"wri",
+        )?;
+        assert_eq!(
+            Journal::open(&path)?.pending()?,
+            vec![Op::Remove {
+                path: "/fs/c".into()
+            }]
+        );
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn limits_compactions() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("webdav_ss_compact_{}", std::process::id()));
+        let journal = Journal::open(&path)?;
+        let interval = Duration::from_secs(3600);
+        assert!(journal.start_compaction(interval).is_some());
+        assert!(journal.start_compaction(interval).is_none());
+        journal.end_compaction();
+        assert!(journal.start_compaction(interval).is_none());
+        assert!(journal.start_compaction(Duration::from_secs(0)).is_some());
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
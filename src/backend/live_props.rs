@@ -0,0 +1,158 @@
+use super::{content_type::ContentTypes, xml::escape};
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{DavMetaData, DavProp},
+};
+
+pub const DAV_NAMESPACE: &str = "DAV:";
+pub const APACHE_NAMESPACE: &str = "http://apache.org/dav/props/";
+pub const WEBDAV_SS_NAMESPACE: &str = "urn:webdav_ss:";
+
+fn live_prop(ns: &str, name: &str, value: &str, do_content: bool) -> DavProp {
+    DavProp {
+        name: name.into(),
+        namespace: Some(ns.into()),
+        prefix: None,
+        xml: if do_content {
+            Some(
+                format!(
+                    "<{name} xmlns=\"{ns}\">{value}</{name}>",
+                    name = name,
+                    ns = ns,
+                    value = escape(value)
+                )
+                .into_bytes(),
+            )
+        } else {
+            None
+        },
+    }
+}
+
+/// Properties derived from backend metadata.
+pub fn live_props(
+    path: &DavPath,
+    meta: &dyn DavMetaData,
+    types: &ContentTypes,
+    do_content: bool,
+) -> Vec<DavProp> {
+    let mut r = vec![];
+    if !meta.is_dir() {
+        let mime = types.guess(path.as_pathbuf());
+        r.push(live_prop(
+            DAV_NAMESPACE,
+            "getcontenttype",
+            &mime,
+            do_content,
+        ));
+    }
+    if let Some(etag) = meta.etag() {
+        let etag = format!("\"{}\"", etag);
+        r.push(live_prop(DAV_NAMESPACE, "getetag", &etag, do_content));
+    }
+    if let Ok(exec) = meta.executable() {
+        let exec = if exec { "T" } else { "F" };
+        r.push(live_prop(APACHE_NAMESPACE, "executable", exec, do_content));
+    }
+    r
+}
+
+/// Properties describing mount, reported on its root.
+const MOUNT_PROPS: &[&str] = &["backend", "used-bytes", "quota-bytes", "degraded"];
+
+/// Property of mount root: `backend` type, `used-bytes` and `quota-bytes`
+/// as reported by its quota, `degraded` while its backend is failing.
+pub fn mount_prop(name: &str, value: &str, do_content: bool) -> DavProp {
+    live_prop(WEBDAV_SS_NAMESPACE, name, value, do_content)
+}
+
+/// Totals of collection content: `tree-bytes` is size of all files below,
+/// `tree-items` number of files and directories below.
+pub const TREE_PROPS: &[&str] = &["tree-bytes", "tree-items"];
+
+pub fn tree_prop(name: &str, value: u64, do_content: bool) -> DavProp {
+    live_prop(WEBDAV_SS_NAMESPACE, name, &value.to_string(), do_content)
+}
+
+/// Returns true for properties produced by `live_props`.
+pub fn is_live(prop: &DavProp) -> bool {
+    match prop.namespace.as_deref() {
+        Some(DAV_NAMESPACE) => matches!(prop.name.as_str(), "getcontenttype" | "getetag"),
+        Some(APACHE_NAMESPACE) => prop.name == "executable",
+        Some(WEBDAV_SS_NAMESPACE) => {
+            MOUNT_PROPS.contains(&prop.name.as_str()) || TREE_PROPS.contains(&prop.name.as_str())
+        }
+        _ => false,
+    }
+}
+
+/// Merges live and dead properties of one path. Live properties are controlled
+/// by server so dead ones with the same name are dropped.
+pub fn merge_props(live: Vec<DavProp>, dead: Vec<DavProp>) -> Vec<DavProp> {
+    let mut r = live;
+    for p in dead {
+        if !r
+            .iter()
+            .any(|l| l.name == p.name && l.namespace == p.namespace)
+        {
+            r.push(p);
+        }
+    }
+    r
+}
+
+/// Whether dav handler writes `prop` into allprop (`do_content`) or propname
+/// response by itself, so listing it from get_props would repeat it.
+pub fn reported_by_handler(prop: &DavProp, do_content: bool) -> bool {
+    match (prop.namespace.as_deref(), prop.name.as_str()) {
+        (Some(DAV_NAMESPACE), "getcontenttype") | (Some(DAV_NAMESPACE), "getetag") => true,
+        (Some(APACHE_NAMESPACE), "executable") => !do_content,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prop(ns: &str, name: &str) -> DavProp {
+        DavProp {
+            name: name.into(),
+            namespace: Some(ns.into()),
+            prefix: None,
+            xml: Some(b"dead".to_vec()),
+        }
+    }
+
+    #[test]
+    fn live_props_win() {
+        let live = vec![live_prop(DAV_NAMESPACE, "getetag", "\"1\"", true)];
+        let dead = vec![
+            prop(DAV_NAMESPACE, "getetag"),
+            prop("urn:custom", "getetag"),
+        ];
+        let r = merge_props(live, dead);
+        assert_eq!(r.len(), 2);
+        assert_eq!(
+            r[0].xml,
+            Some(b"<getetag xmlns=\"DAV:\">&quot;1&quot;</getetag>".to_vec())
+        );
+        assert_eq!(r[1].namespace, Some("urn:custom".into()));
+    }
+
+    #[test]
+    fn no_content() {
+        let p = live_prop(APACHE_NAMESPACE, "executable", "T", false);
+        assert!(p.xml.is_none());
+    }
+
+    #[test]
+    fn reported_by_handler_only() {
+        let etag = live_prop(DAV_NAMESPACE, "getetag", "\"1\"", true);
+        assert!(reported_by_handler(&etag, true));
+        let exec = live_prop(APACHE_NAMESPACE, "executable", "T", true);
+        assert!(!reported_by_handler(&exec, true));
+        assert!(reported_by_handler(&exec, false));
+        assert!(!reported_by_handler(&prop("urn:custom", "getetag"), true));
+    }
+}
@@ -0,0 +1,283 @@
+use crate::backend::mounts::in_mount;
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::debug;
+use webdav_handler::{
+    davpath::DavPath,
+    ls::{DavLock, DavLockSystem},
+};
+use xmltree::Element;
+
+/// Routes lock requests to lock system of the mount path belongs to.
+/// Paths outside of any mount are served by default lock system. Mounts are
+/// matched against decoded paths, as filesystem aggregate does, so names
+/// with spaces or non-ascii characters reach their own lock system.
+#[derive(Debug, Clone)]
+pub struct AggregateLs {
+    routes: Vec<(String, Box<dyn DavLockSystem>)>,
+    default: Box<dyn DavLockSystem>,
+    /// roots of locks granted by this instance by their tokens
+    granted: Arc<Mutex<HashMap<String, DavPath>>>,
+}
+
+fn decoded(path: &DavPath) -> String {
+    path.as_pathbuf().to_string_lossy().into_owned()
+}
+
+impl AggregateLs {
+    pub fn new(default: Box<dyn DavLockSystem>) -> Box<AggregateLs> {
+        Box::new(AggregateLs {
+            routes: vec![],
+            default,
+            granted: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    pub fn add_route(&mut self, mount: &str, ls: Box<dyn DavLockSystem>) {
+        self.routes.push((mount.trim_end_matches('/').into(), ls));
+        // longest mounts first so nested mounts win
+        self.routes.sort_by_key(|r| Reverse(r.0.len()));
+    }
+
+    fn route(&self, path: &DavPath) -> &dyn DavLockSystem {
+        let p = decoded(path);
+        self.routes
+            .iter()
+            .find(|(m, _)| in_mount(&p, m))
+            .map(|(_, ls)| ls.as_ref())
+            .unwrap_or_else(|| self.default.as_ref())
+    }
+
+    /// Lock systems of outer mounts containing path, they can keep deep locks
+    /// on parents of path.
+    fn outer(&self, path: &DavPath) -> Vec<&dyn DavLockSystem> {
+        let p = decoded(path);
+        let mut r: Vec<&dyn DavLockSystem> = self
+            .routes
+            .iter()
+            .filter(|(m, _)| in_mount(&p, m))
+            .skip(1)
+            .map(|(_, ls)| ls.as_ref())
+            .collect();
+        if self.routes.iter().any(|(m, _)| in_mount(&p, m)) {
+            r.push(self.default.as_ref());
+        }
+        r
+    }
+
+    /// Lock systems of mounts located under path, used for deep operations.
+    fn nested(&self, path: &DavPath) -> Vec<&dyn DavLockSystem> {
+        let p = decoded(path);
+        let p = p.trim_end_matches('/');
+        self.routes
+            .iter()
+            .filter(|(m, _)| m.len() > p.len() && in_mount(m, p))
+            .map(|(_, ls)| ls.as_ref())
+            .collect()
+    }
+
+    /// Roots of locks granted by this instance which are still held, locks of
+    /// other instances sharing storage are left to them.
+    pub fn locked_paths(&self) -> Vec<DavPath> {
+        let mut granted = self.granted.lock().unwrap();
+        granted.retain(|token, path| self.discover(path).iter().any(|l| &l.token == token));
+        let mut paths = granted.values().cloned().collect::<Vec<_>>();
+        paths.sort_by_key(|p| p.as_url_string());
+        paths.dedup_by_key(|p| p.as_url_string());
+        paths
+    }
+
+    fn check_others(
+        &self,
+        path: &DavPath,
+        principal: Option<&str>,
+        ignore_principal: bool,
+        deep: bool,
+        submitted_tokens: &[&str],
+    ) -> Result<(), DavLock> {
+        for ls in self.outer(path) {
+            ls.check(
+                path,
+                principal,
+                ignore_principal,
+                false,
+                submitted_tokens.to_vec(),
+            )?;
+        }
+        if deep {
+            for ls in self.nested(path) {
+                ls.check(
+                    path,
+                    principal,
+                    ignore_principal,
+                    true,
+                    submitted_tokens.to_vec(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DavLockSystem for AggregateLs {
+    fn lock(
+        &self,
+        path: &DavPath,
+        principal: Option<&str>,
+        owner: Option<&Element>,
+        timeout: Option<Duration>,
+        shared: bool,
+        deep: bool,
+    ) -> Result<DavLock, DavLock> {
+        self.check_others(path, principal, false, deep, &[])?;
+        let lock = self
+            .route(path)
+            .lock(path, principal, owner, timeout, shared, deep)?;
+        self.granted
+            .lock()
+            .unwrap()
+            .insert(lock.token.clone(), lock.path.clone());
+        Ok(lock)
+    }
+
+    /// Lock can be held by outer mount when path is a member of its deep
+    /// lock, Request-URI of UNLOCK may be any resource in lock scope.
+    fn unlock(&self, path: &DavPath, token: &str) -> Result<(), ()> {
+        self.route(path).unlock(path, token).or_else(|_| {
+            self.outer(path)
+                .into_iter()
+                .find_map(|ls| ls.unlock(path, token).ok())
+                .ok_or(())
+        })?;
+        self.granted.lock().unwrap().remove(token);
+        Ok(())
+    }
+
+    fn refresh(
+        &self,
+        path: &DavPath,
+        token: &str,
+        timeout: Option<Duration>,
+    ) -> Result<DavLock, ()> {
+        self.route(path).refresh(path, token, timeout).or_else(|_| {
+            self.outer(path)
+                .into_iter()
+                .find_map(|ls| ls.refresh(path, token, timeout).ok())
+                .ok_or(())
+        })
+    }
+
+    fn check(
+        &self,
+        path: &DavPath,
+        principal: Option<&str>,
+        ignore_principal: bool,
+        deep: bool,
+        submitted_tokens: Vec<&str>,
+    ) -> Result<(), DavLock> {
+        self.check_others(path, principal, ignore_principal, deep, &submitted_tokens)?;
+        self.route(path)
+            .check(path, principal, ignore_principal, deep, submitted_tokens)
+    }
+
+    fn discover(&self, path: &DavPath) -> Vec<DavLock> {
+        self.route(path).discover(path)
+    }
+
+    fn delete(&self, path: &DavPath) -> Result<(), ()> {
+        let mut r = self.route(path).delete(path);
+        for ls in self.nested(path) {
+            r = r.and(ls.delete(path));
+        }
+        let p = decoded(path);
+        self.granted
+            .lock()
+            .unwrap()
+            .retain(|_, l| !in_mount(&decoded(l), &p));
+        debug!(path = %path.as_url_string(), result = ?r);
+        r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use webdav_handler::memls::MemLs;
+
+    #[test]
+    fn routing() {
+        let mut ls = AggregateLs::new(MemLs::new());
+        ls.add_route("/fs1", MemLs::new());
+        ls.add_route("/fs1/nested", MemLs::new());
+        let file = DavPath::new("/fs1/nested/file").unwrap();
+        let l = ls.lock(&file, None, None, None, false, false).unwrap();
+
+        // lock is kept by nested mount only
+        assert_eq!(ls.routes[0].1.discover(&file).len(), 1);
+        assert_eq!(ls.routes[1].1.discover(&file).len(), 0);
+        assert_eq!(ls.default.discover(&file).len(), 0);
+
+        // deep lock on parent conflicts with lock in nested mount
+        let root = DavPath::new("/fs1/").unwrap();
+        assert!(ls.lock(&root, None, None, None, false, true).is_err());
+        ls.unlock(&file, &l.token).unwrap();
+        let l = ls.lock(&root, None, None, None, false, true).unwrap();
+
+        // and the other way around
+        assert!(ls.lock(&file, None, None, None, false, false).is_err());
+        assert!(ls
+            .check(&file, None, false, false, vec![l.token.as_str()])
+            .is_ok());
+    }
+
+    #[test]
+    fn encoded_paths() {
+        let mut ls = AggregateLs::new(MemLs::new());
+        ls.add_route("/my docs", MemLs::new());
+        let file = DavPath::new("/my%20docs/file").unwrap();
+        ls.lock(&file, None, None, None, false, false).unwrap();
+        assert_eq!(ls.routes[0].1.discover(&file).len(), 1);
+        assert_eq!(ls.default.discover(&file).len(), 0);
+        assert!(ls.check(&file, None, false, false, vec![]).is_err());
+    }
+
+    #[test]
+    fn unlock_member_of_outer_lock() {
+        let mut ls = AggregateLs::new(MemLs::new());
+        ls.add_route("/fs1", MemLs::new());
+        ls.add_route("/fs1/nested", MemLs::new());
+        let root = DavPath::new("/fs1/").unwrap();
+        let l = ls.lock(&root, None, None, None, false, true).unwrap();
+
+        let file = DavPath::new("/fs1/nested/file").unwrap();
+        assert!(ls.refresh(&file, &l.token, None).is_ok());
+        assert!(ls.unlock(&file, &l.token).is_ok());
+        assert!(ls.lock(&file, None, None, None, false, false).is_ok());
+        assert!(ls.unlock(&file, "opaquelocktoken:unknown").is_err());
+    }
+
+    #[test]
+    fn granted_locks() {
+        let mut ls = AggregateLs::new(MemLs::new());
+        ls.add_route("/fs1", MemLs::new());
+        let a = DavPath::new("/fs1/a").unwrap();
+        let b = DavPath::new("/fs1/dir/b").unwrap();
+        let l = ls.lock(&a, None, None, None, true, false).unwrap();
+        ls.lock(&a, None, None, None, true, false).unwrap();
+        ls.lock(&b, None, None, None, false, false).unwrap();
+        let paths = ls.locked_paths();
+        assert_eq!(paths.len(), 2);
+
+        ls.unlock(&a, &l.token).unwrap();
+        assert_eq!(ls.locked_paths().len(), 2);
+        ls.delete(&DavPath::new("/fs1/dir/").unwrap()).unwrap();
+        assert_eq!(ls.locked_paths(), vec![a.clone()]);
+        // removed from lock system behind aggregate's back
+        ls.routes[0].1.delete(&a).unwrap();
+        assert!(ls.locked_paths().is_empty());
+    }
+}
@@ -0,0 +1,3 @@
+pub mod aggregate;
+pub mod policy;
+pub mod redis;
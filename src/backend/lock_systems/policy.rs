@@ -0,0 +1,125 @@
+use std::time::Duration;
+use tracing::debug;
+use webdav_handler::{
+    davpath::DavPath,
+    ls::{DavLock, DavLockSystem},
+};
+use xmltree::Element;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Applies configured timeout limits to locks before passing them to
+/// underlying lock system.
+#[derive(Debug, Clone)]
+pub struct PolicyLs {
+    inner: Box<dyn DavLockSystem>,
+    default_timeout: Option<Duration>,
+    max_timeout: Option<Duration>,
+    allow_infinite: bool,
+}
+
+impl PolicyLs {
+    pub fn new(
+        inner: Box<dyn DavLockSystem>,
+        default_timeout: Option<Duration>,
+        max_timeout: Option<Duration>,
+        allow_infinite: bool,
+    ) -> Box<PolicyLs> {
+        Box::new(PolicyLs {
+            inner,
+            default_timeout,
+            max_timeout,
+            allow_infinite,
+        })
+    }
+
+    fn timeout(&self, timeout: Option<Duration>) -> Option<Duration> {
+        let t = match timeout {
+            None if self.allow_infinite => return None,
+            None => self.default_timeout.unwrap_or(DEFAULT_TIMEOUT),
+            Some(t) => t,
+        };
+        match self.max_timeout {
+            Some(max) if t > max => Some(max),
+            _ => Some(t),
+        }
+    }
+}
+
+impl DavLockSystem for PolicyLs {
+    fn lock(
+        &self,
+        path: &DavPath,
+        principal: Option<&str>,
+        owner: Option<&Element>,
+        timeout: Option<Duration>,
+        shared: bool,
+        deep: bool,
+    ) -> Result<DavLock, DavLock> {
+        let t = self.timeout(timeout);
+        debug!(requested = ?timeout, timeout = ?t);
+        self.inner.lock(path, principal, owner, t, shared, deep)
+    }
+
+    fn unlock(&self, path: &DavPath, token: &str) -> Result<(), ()> {
+        self.inner.unlock(path, token)
+    }
+
+    fn refresh(
+        &self,
+        path: &DavPath,
+        token: &str,
+        timeout: Option<Duration>,
+    ) -> Result<DavLock, ()> {
+        let t = self.timeout(timeout);
+        debug!(requested = ?timeout, timeout = ?t);
+        self.inner.refresh(path, token, t)
+    }
+
+    fn check(
+        &self,
+        path: &DavPath,
+        principal: Option<&str>,
+        ignore_principal: bool,
+        deep: bool,
+        submitted_tokens: Vec<&str>,
+    ) -> Result<(), DavLock> {
+        self.inner
+            .check(path, principal, ignore_principal, deep, submitted_tokens)
+    }
+
+    fn discover(&self, path: &DavPath) -> Vec<DavLock> {
+        self.inner.discover(path)
+    }
+
+    fn delete(&self, path: &DavPath) -> Result<(), ()> {
+        self.inner.delete(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use webdav_handler::memls::MemLs;
+
+    #[test]
+    fn timeouts() {
+        let path = DavPath::new("/file").unwrap();
+        let ls = PolicyLs::new(
+            MemLs::new(),
+            Some(Duration::from_secs(60)),
+            Some(Duration::from_secs(120)),
+            false,
+        );
+        let l = ls.lock(&path, None, None, None, false, false).unwrap();
+        assert_eq!(l.timeout, Some(Duration::from_secs(60)));
+        let l = ls
+            .refresh(&path, &l.token, Some(Duration::from_secs(1000)))
+            .unwrap();
+        assert_eq!(l.timeout, Some(Duration::from_secs(120)));
+
+        let ls = PolicyLs::new(MemLs::new(), None, Some(Duration::from_secs(120)), true);
+        let l = ls.lock(&path, None, None, None, false, false).unwrap();
+        assert_eq!(l.timeout, None);
+    }
+}
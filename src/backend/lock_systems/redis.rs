@@ -0,0 +1,527 @@
+use futures_util::StreamExt;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+use std::{
+    future::Future,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::runtime::Handle;
+use tracing::{debug, error, span, Level};
+use webdav_handler::{
+    davpath::DavPath,
+    ls::{DavLock, DavLockSystem},
+};
+use xmltree::Element;
+
+use crate::backend::normalized_path::NormalizedPath;
+
+const MUTEX_TTL_MS: u64 = 5000;
+const MUTEX_RETRIES: u32 = 500;
+
+#[derive(Serialize, Deserialize, Debug)]
+struct StoredLock {
+    token: String,
+    path: String,
+    principal: Option<String>,
+    owner: Option<String>,
+    timeout_at: Option<u64>,
+    timeout: Option<u64>,
+    shared: bool,
+    deep: bool,
+}
+
+impl StoredLock {
+    fn from_lock(l: &DavLock) -> Self {
+        let owner = l.owner.as_ref().and_then(|o| {
+            let mut v = vec![];
+            o.write(&mut v).ok()?;
+            String::from_utf8(v).ok()
+        });
+        StoredLock {
+            token: l.token.clone(),
+            path: l.path.as_url_string(),
+            principal: l.principal.clone(),
+            owner,
+            timeout_at: l
+                .timeout_at
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|t| t.as_secs()),
+            timeout: l.timeout.map(|t| t.as_secs()),
+            shared: l.shared,
+            deep: l.deep,
+        }
+    }
+
+    fn into_lock(self) -> Option<DavLock> {
+        Some(DavLock {
+            token: self.token,
+            path: DavPath::new(&self.path).ok()?,
+            principal: self.principal,
+            owner: self.owner.and_then(|o| Element::parse(o.as_bytes()).ok()),
+            timeout_at: self.timeout_at.map(|t| UNIX_EPOCH + Duration::from_secs(t)),
+            timeout: self.timeout.map(Duration::from_secs),
+            shared: self.shared,
+            deep: self.deep,
+        })
+    }
+}
+
+/// Lock system keeping locks in Redis so several server instances can share them.
+///
+/// Every lock is stored under its own key with TTL equal to lock timeout, so
+/// expired locks disappear without any cleanup. Locks are also indexed by path
+/// to find locks on parents and children of requested path.
+///
+/// Redis is queried asynchronously, lock system calls block in place so they
+/// need multi-threaded runtime.
+#[derive(derivative::Derivative, Clone)]
+#[derivative(Debug)]
+pub struct RedisLs {
+    #[derivative(Debug = "ignore")]
+    conn: ConnectionManager,
+    prefix: String,
+    max_timeout: Duration,
+}
+
+impl RedisLs {
+    pub async fn new(
+        url: &str,
+        prefix: &str,
+        max_timeout: Duration,
+    ) -> redis::RedisResult<Box<RedisLs>> {
+        let client = redis::Client::open(url)?;
+        let conn = ConnectionManager::new(client).await?;
+        Ok(Box::new(RedisLs {
+            conn,
+            prefix: prefix.into(),
+            max_timeout,
+        }))
+    }
+
+    fn lock_key(&self, token: &str) -> String {
+        format!("{}:lock:{}", self.prefix, token)
+    }
+
+    fn path_key(&self, path: &str) -> String {
+        format!("{}:path:{}", self.prefix, path)
+    }
+
+    fn mutex_key(&self) -> String {
+        format!("{}:mutex", self.prefix)
+    }
+
+    fn timeout(&self, timeout: Option<Duration>) -> Duration {
+        match timeout {
+            Some(t) if t < self.max_timeout => t,
+            _ => self.max_timeout,
+        }
+    }
+
+    /// Runs `f` while holding lock over whole lock system so check and
+    /// update of locks are atomic between instances.
+    async fn exclusive<T, F>(&self, f: F) -> redis::RedisResult<T>
+    where
+        F: Future<Output = redis::RedisResult<T>>,
+    {
+        let nonce = uuid::Uuid::new_v4().to_string();
+        let mut conn = self.conn.clone();
+        let mut acquired = false;
+        for _ in 0..MUTEX_RETRIES {
+            let r: Option<String> = redis::cmd("SET")
+                .arg(self.mutex_key())
+                .arg(&nonce)
+                .arg("NX")
+                .arg("PX")
+                .arg(MUTEX_TTL_MS)
+                .query_async(&mut conn)
+                .await?;
+            if r.is_some() {
+                acquired = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        if !acquired {
+            return Err((
+                redis::ErrorKind::BusyLoadingError,
+                "can't acquire lock mutex",
+            )
+                .into());
+        }
+        let r = f.await;
+        let release = redis::Script::new(
+            r#"if redis.call("GET", KEYS[1]) == ARGV[1] then return redis.call("DEL", KEYS[1]) else return 0 end"#,
+        );
+        let _: redis::RedisResult<i32> = release
+            .key(self.mutex_key())
+            .arg(&nonce)
+            .invoke_async(&mut conn)
+            .await;
+        r
+    }
+
+    async fn locks_at(&self, path: &str) -> redis::RedisResult<Vec<DavLock>> {
+        let mut conn = self.conn.clone();
+        let pk = self.path_key(path);
+        let tokens: Vec<String> = redis::cmd("SMEMBERS")
+            .arg(&pk)
+            .query_async(&mut conn)
+            .await?;
+        let mut r = vec![];
+        for t in tokens {
+            let data: Option<Vec<u8>> = redis::cmd("GET")
+                .arg(self.lock_key(&t))
+                .query_async(&mut conn)
+                .await?;
+            match data
+                .and_then(|d| bincode::deserialize::<StoredLock>(&d).ok())
+                .and_then(|l| l.into_lock())
+            {
+                Some(l) => r.push(l),
+                None => {
+                    // lock expired, remove it from index
+                    redis::cmd("SREM")
+                        .arg(&pk)
+                        .arg(&t)
+                        .query_async::<_, ()>(&mut conn)
+                        .await?;
+                }
+            }
+        }
+        Ok(r)
+    }
+
+    async fn descendant_paths(&self, path: &str) -> redis::RedisResult<Vec<String>> {
+        let mut conn = self.conn.clone();
+        let base = self.path_key("");
+        let pattern = if path == "/" {
+            format!("{}*", escape_glob(&base))
+        } else {
+            format!("{}/*", escape_glob(&self.path_key(path)))
+        };
+        let mut cmd = redis::cmd("SCAN");
+        cmd.cursor_arg(0).arg("MATCH").arg(&pattern);
+        let keys: Vec<String> = cmd.iter_async(&mut conn).await?.collect().await;
+        Ok(keys
+            .into_iter()
+            .map(|k| k[base.len()..].to_string())
+            .filter(|k| k != path)
+            .collect())
+    }
+
+    /// Locks on path itself and deep locks on its parents.
+    async fn covering_locks(&self, path: &str) -> redis::RedisResult<Vec<DavLock>> {
+        let mut r = vec![];
+        let ancestors = ancestors(path);
+        let last = ancestors.len() - 1;
+        for (i, p) in ancestors.iter().enumerate() {
+            for l in self.locks_at(p).await? {
+                if i == last || l.deep {
+                    r.push(l);
+                }
+            }
+        }
+        Ok(r)
+    }
+
+    async fn relevant_locks(&self, path: &str, deep: bool) -> redis::RedisResult<Vec<DavLock>> {
+        let mut r = self.covering_locks(path).await?;
+        if deep {
+            for p in self.descendant_paths(path).await? {
+                r.extend(self.locks_at(&p).await?);
+            }
+        }
+        Ok(r)
+    }
+
+    async fn store(&self, lock: &DavLock) -> redis::RedisResult<()> {
+        let key = self.lock_key(&lock.token);
+        let data = bincode::serialize(&StoredLock::from_lock(lock)).map_err(|_| {
+            redis::RedisError::from((redis::ErrorKind::TypeError, "can't serialize lock"))
+        })?;
+        let ttl = lock.timeout.unwrap_or(self.max_timeout).as_millis() as u64;
+        let pk = self.path_key(&lock_path(&lock.path));
+        redis::pipe()
+            .atomic()
+            .cmd("SET")
+            .arg(&key)
+            .arg(data)
+            .arg("PX")
+            .arg(ttl)
+            .ignore()
+            .cmd("SADD")
+            .arg(&pk)
+            .arg(&lock.token)
+            .ignore()
+            .query_async(&mut self.conn.clone())
+            .await
+    }
+
+    async fn remove(&self, lock: &DavLock) -> redis::RedisResult<()> {
+        let pk = self.path_key(&lock_path(&lock.path));
+        redis::pipe()
+            .atomic()
+            .cmd("DEL")
+            .arg(self.lock_key(&lock.token))
+            .ignore()
+            .cmd("SREM")
+            .arg(&pk)
+            .arg(&lock.token)
+            .ignore()
+            .query_async(&mut self.conn.clone())
+            .await
+    }
+
+    async fn find_token(&self, path: &str, token: &str) -> redis::RedisResult<Option<DavLock>> {
+        Ok(self
+            .covering_locks(path)
+            .await?
+            .into_iter()
+            .find(|l| l.token == token))
+    }
+}
+
+/// Waits for `f` from sync lock system call, worker thread hands its other
+/// tasks over meanwhile.
+fn block_on<F: Future>(f: F) -> F::Output {
+    tokio::task::block_in_place(|| Handle::current().block_on(f))
+}
+
+fn lock_path(path: &DavPath) -> String {
+    let p: NormalizedPath = path.into();
+    p.as_file().to_string()
+}
+
+/// Returns "/" and every parent of path including path itself.
+fn ancestors(path: &str) -> Vec<String> {
+    let mut r = vec!["/".to_string()];
+    if path == "/" {
+        return r;
+    }
+    let mut cur = String::new();
+    for part in path.split('/').filter(|p| !p.is_empty()) {
+        if !cur.is_empty() {
+            cur.push('/');
+        }
+        cur.push_str(part);
+        r.push(cur.clone());
+    }
+    r
+}
+
+fn escape_glob(s: &str) -> String {
+    let mut r = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            r.push('\\');
+        }
+        r.push(c);
+    }
+    r
+}
+
+/// Failure of lock storage reported as conflict with lock on the path itself.
+fn unavailable(path: &DavPath) -> DavLock {
+    DavLock {
+        token: String::new(),
+        path: path.clone(),
+        principal: None,
+        owner: None,
+        timeout_at: None,
+        timeout: None,
+        shared: false,
+        deep: false,
+    }
+}
+
+fn new_token() -> String {
+    format!("opaquelocktoken:{}", uuid::Uuid::new_v4())
+}
+
+impl DavLockSystem for RedisLs {
+    fn lock(
+        &self,
+        path: &DavPath,
+        principal: Option<&str>,
+        owner: Option<&Element>,
+        timeout: Option<Duration>,
+        shared: bool,
+        deep: bool,
+    ) -> Result<DavLock, DavLock> {
+        let span = span!(Level::INFO, "RedisLs::lock");
+        let _e = span.enter();
+        let lp = lock_path(path);
+        let timeout = self.timeout(timeout);
+        let r = block_on(self.exclusive(async {
+            for l in self.relevant_locks(&lp, deep).await? {
+                if !(shared && l.shared) {
+                    return Ok(Err(l));
+                }
+            }
+            let lock = DavLock {
+                token: new_token(),
+                path: path.clone(),
+                principal: principal.map(|p| p.to_string()),
+                owner: owner.cloned(),
+                timeout_at: Some(SystemTime::now() + timeout),
+                timeout: Some(timeout),
+                shared,
+                deep,
+            };
+            self.store(&lock).await?;
+            Ok(Ok(lock))
+        }));
+        debug!(path = %lp, result = ?r);
+        match r {
+            Ok(r) => r,
+            Err(e) => {
+                error!(msg = "can't lock", err = ?e);
+                Err(unavailable(path))
+            }
+        }
+    }
+
+    fn unlock(&self, path: &DavPath, token: &str) -> Result<(), ()> {
+        let span = span!(Level::INFO, "RedisLs::unlock");
+        let _e = span.enter();
+        let r = block_on(self.exclusive(async {
+            match self.find_token(&lock_path(path), token).await? {
+                Some(l) => self.remove(&l).await.map(|_| true),
+                None => Ok(false),
+            }
+        }));
+        debug!(path = %lock_path(path), token = token, result = ?r);
+        match r {
+            Ok(true) => Ok(()),
+            _ => Err(()),
+        }
+    }
+
+    fn refresh(
+        &self,
+        path: &DavPath,
+        token: &str,
+        timeout: Option<Duration>,
+    ) -> Result<DavLock, ()> {
+        let span = span!(Level::INFO, "RedisLs::refresh");
+        let _e = span.enter();
+        let timeout = self.timeout(timeout);
+        let r = block_on(self.exclusive(async {
+            match self.find_token(&lock_path(path), token).await? {
+                Some(mut l) => {
+                    l.timeout = Some(timeout);
+                    l.timeout_at = Some(SystemTime::now() + timeout);
+                    self.store(&l).await?;
+                    Ok(Some(l))
+                }
+                None => Ok(None),
+            }
+        }));
+        debug!(path = %lock_path(path), token = token, result = ?r);
+        match r {
+            Ok(Some(l)) => Ok(l),
+            _ => Err(()),
+        }
+    }
+
+    fn check(
+        &self,
+        path: &DavPath,
+        principal: Option<&str>,
+        ignore_principal: bool,
+        deep: bool,
+        submitted_tokens: Vec<&str>,
+    ) -> Result<(), DavLock> {
+        let span = span!(Level::INFO, "RedisLs::check");
+        let _e = span.enter();
+        let locks = match block_on(self.relevant_locks(&lock_path(path), deep)) {
+            Ok(k) => k,
+            Err(e) => {
+                // locks can't be verified, so path counts as locked
+                error!(msg = "can't check locks", err = ?e);
+                return Err(unavailable(path));
+            }
+        };
+        let owned = |l: &DavLock| {
+            submitted_tokens.iter().any(|t| *t == l.token)
+                && (ignore_principal || l.principal.as_deref() == principal)
+        };
+        let mut shared_conflict = None;
+        let mut shared_owned = false;
+        for l in locks {
+            if owned(&l) {
+                shared_owned |= l.shared;
+                continue;
+            }
+            if !l.shared {
+                return Err(l);
+            }
+            shared_conflict.get_or_insert(l);
+        }
+        match shared_conflict {
+            Some(l) if !shared_owned => Err(l),
+            _ => Ok(()),
+        }
+    }
+
+    fn discover(&self, path: &DavPath) -> Vec<DavLock> {
+        let span = span!(Level::INFO, "RedisLs::discover");
+        let _e = span.enter();
+        block_on(self.covering_locks(&lock_path(path))).unwrap_or_else(|e| {
+            error!(msg = "can't discover locks", err = ?e);
+            vec![]
+        })
+    }
+
+    fn delete(&self, path: &DavPath) -> Result<(), ()> {
+        let span = span!(Level::INFO, "RedisLs::delete");
+        let _e = span.enter();
+        let lp = lock_path(path);
+        let r = block_on(self.exclusive(async {
+            let mut paths = self.descendant_paths(&lp).await?;
+            paths.push(lp.clone());
+            for p in paths {
+                for l in self.locks_at(&p).await? {
+                    self.remove(&l).await?;
+                }
+            }
+            Ok(())
+        }));
+        debug!(path = %lp, result = ?r);
+        r.map_err(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_ancestors() {
+        assert_eq!(ancestors("/"), vec!["/"]);
+        assert_eq!(
+            ancestors("fs/dir/file"),
+            vec!["/", "fs", "fs/dir", "fs/dir/file"]
+        );
+    }
+
+    #[test]
+    fn stored_lock_roundtrip() {
+        let lock = DavLock {
+            token: new_token(),
+            path: DavPath::new("/fs/dir/").unwrap(),
+            principal: Some("user".into()),
+            owner: Element::parse("<owner xmlns=\"DAV:\">me</owner>".as_bytes()).ok(),
+            timeout_at: Some(UNIX_EPOCH + Duration::from_secs(100)),
+            timeout: Some(Duration::from_secs(10)),
+            shared: true,
+            deep: true,
+        };
+        let l = StoredLock::from_lock(&lock).into_lock().unwrap();
+        assert_eq!(l.token, lock.token);
+        assert_eq!(l.path.as_url_string(), lock.path.as_url_string());
+        assert_eq!(l.timeout_at, lock.timeout_at);
+        assert_eq!(l.owner.unwrap().name, "owner");
+        assert!(l.shared && l.deep);
+    }
+}
@@ -1,3 +1,18 @@
-pub mod s3_backend;
+pub mod checksum;
+pub mod conformance;
+pub mod content_type;
+pub mod journal;
+pub mod live_props;
+pub mod lock_systems;
+pub mod mounts;
 pub mod normalized_path;
-pub mod prop_storages;
\ No newline at end of file
+pub mod open_opts;
+pub mod prop_storages;
+pub mod registry;
+pub mod s3_backend;
+pub mod snapshot;
+pub mod tree_stats;
+pub mod walk;
+pub mod watcher;
+pub mod wrappers;
+pub mod xml;
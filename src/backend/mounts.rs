@@ -0,0 +1,49 @@
+use std::borrow::Cow;
+
+/// Returns true if path belongs to mount.
+pub(crate) fn in_mount(path: &str, mount: &str) -> bool {
+    let mount = mount.trim_end_matches('/');
+    path.starts_with(mount) && (path.len() == mount.len() || path[mount.len()..].starts_with('/'))
+}
+
+/// Request path with percent encoding removed, mounts are matched against it
+/// so `/%61rchive` is the same mount as `/archive`.
+pub(crate) fn decode(path: &str) -> Cow<str> {
+    percent_encoding::percent_decode_str(path).decode_utf8_lossy()
+}
+
+/// False if decoded path is not UTF-8. Props and locks are keyed by strings,
+/// lossy decoding would give such path keys of another one.
+pub(crate) fn is_utf8(path: &str) -> bool {
+    percent_encoding::percent_decode_str(path)
+        .decode_utf8()
+        .is_ok()
+}
+
+/// Innermost of mounts decoded path belongs to.
+pub(crate) fn innermost_mount<'a, T>(
+    path: &str,
+    mounts: &'a [T],
+    mount: impl Fn(&T) -> &str,
+) -> Option<&'a T> {
+    mounts
+        .iter()
+        .filter(|m| in_mount(path, mount(m)))
+        .max_by_key(|m| mount(m).len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn innermost() {
+        let mounts = vec!["/fs", "/fs/nested mount", "/other/"];
+        let of = |p: &str| innermost_mount(&decode(p), &mounts, |m| *m).copied();
+        assert_eq!(of("/fs/a.txt"), Some("/fs"));
+        assert_eq!(of("/f%73/nested%20mount/a.txt"), Some("/fs/nested mount"));
+        assert_eq!(of("/fs/nested mountain"), Some("/fs"));
+        assert_eq!(of("/other"), Some("/other/"));
+        assert_eq!(of("/fsx/a.txt"), None);
+    }
+}
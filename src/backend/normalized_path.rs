@@ -6,14 +6,8 @@ use webdav_handler::davpath::DavPath;
 pub struct NormalizedPath(String);
 
 impl NormalizedPath {
-    fn trim_token(mut token: &str) -> &str {
-        if token.ends_with("/") {
-            token = &token[..token.len() - 1];
-        }
-        if token.starts_with("/") {
-            token = &token[1..];
-        }
-        token
+    fn trim_token(token: &str) -> &str {
+        token.trim_matches('/')
     }
 
     pub fn join_file(&self, mut token: &str) -> NormalizedPath {
@@ -56,8 +50,12 @@ impl NormalizedPath {
             })
     }
 
+    /// Removes `prefix` once, path is returned unchanged if it does not start with it.
     pub fn strip_prefix(&self, prefix: &NormalizedPath) -> NormalizedPath {
-        self.0.trim_start_matches(&prefix.0).into()
+        self.0
+            .strip_prefix(prefix.0.as_str())
+            .unwrap_or(&self.0)
+            .into()
     }
 
     pub fn is_collection(&self) -> bool {
@@ -98,7 +96,7 @@ impl NormalizedPath {
 impl From<String> for NormalizedPath {
     fn from(mut t: String) -> Self {
         if t.starts_with("/") && t.len() > 1 {
-            t = String::from_str(&t[1..]).unwrap();
+            t = String::from_str(t.trim_start_matches('/')).unwrap();
         }
         if t.len() == 0 {
             t = String::from_str("/").unwrap();
@@ -113,6 +111,8 @@ impl From<&str> for NormalizedPath {
     }
 }
 
+/// Paths which are not UTF-8 are converted lossily, requests with them are
+/// refused before reaching filesystems.
 impl From<&DavPath> for NormalizedPath {
     fn from(t: &DavPath) -> Self {
         let col = t.is_collection();
@@ -120,9 +120,8 @@ impl From<&DavPath> for NormalizedPath {
             .as_pathbuf()
             .strip_prefix("/")
             .unwrap_or(t.as_pathbuf().as_path())
-            .to_str()
-            .unwrap()
-            .to_owned();
+            .to_string_lossy()
+            .into_owned();
         let t = if col { format!("{}/", t) } else { t };
         NormalizedPath(t)
     }
@@ -175,6 +174,9 @@ impl std::fmt::Display for NormalizedPath {
 #[cfg(test)]
 mod tests {
     use super::NormalizedPath;
+    use crate::backend::mounts::is_utf8;
+    use percent_encoding::{percent_encode, NON_ALPHANUMERIC};
+    use proptest::prelude::*;
     use webdav_handler::davpath::DavPath;
 
     #[test]
@@ -265,4 +267,124 @@ mod tests {
             NormalizedPath("somekey.txt".into())
         );
     }
+
+    #[test]
+    fn edge_cases() {
+        // repeated prefix is stripped once
+        let p: NormalizedPath = "docs/docs/file.txt".into();
+        assert_eq!(
+            p.strip_prefix(&"docs/".into()),
+            NormalizedPath("docs/file.txt".into())
+        );
+        let p: NormalizedPath = "//file.txt".into();
+        assert_eq!(p, NormalizedPath("file.txt".into()));
+        let p: NormalizedPath = "/".into();
+        assert_eq!(p.join_dir("//dir//"), NormalizedPath("dir/".into()));
+        // invalid utf-8 does not panic
+        must_davpath("/%FF/");
+    }
+
+    fn segment() -> impl Strategy<Value = String> {
+        "[^/\\x00]{1,8}".prop_filter("dot segments", |s| s != "." && s != "..")
+    }
+
+    /// Segment with a byte which never occurs in utf-8.
+    fn invalid_segment() -> impl Strategy<Value = Vec<u8>> {
+        (segment(), any::<prop::sample::Index>(), 0xf8u8..).prop_map(|(s, at, b)| {
+            let mut s = s.into_bytes();
+            s.insert(at.index(s.len() + 1), b);
+            s
+        })
+    }
+
+    fn segments() -> impl Strategy<Value = Vec<String>> {
+        prop::collection::vec(segment(), 0..5)
+    }
+
+    fn from_segments(segments: &[String], collection: bool) -> NormalizedPath {
+        let mut p: NormalizedPath = "/".into();
+        for (i, s) in segments.iter().enumerate() {
+            p = if collection || i + 1 < segments.len() {
+                p.join_dir(s)
+            } else {
+                p.join_file(s)
+            };
+        }
+        p
+    }
+
+    proptest! {
+        #[test]
+        fn davpath_round_trip(segments in segments(), collection: bool) {
+            let collection = collection || segments.is_empty();
+            let mut url: String = segments
+                .iter()
+                .map(|s| format!("/{}", percent_encode(s.as_bytes(), NON_ALPHANUMERIC)))
+                .collect();
+            if collection {
+                url.push('/');
+            }
+            let from_dav: NormalizedPath = DavPath::new(&url).unwrap().into();
+            prop_assert_eq!(&from_dav, &from_segments(&segments, collection));
+            prop_assert_eq!(from_dav.is_collection(), collection);
+            prop_assert_eq!(from_dav.is_root(), segments.is_empty());
+        }
+
+        #[test]
+        fn only_utf8_accepted(
+            valid in prop::collection::vec(segment(), 1..4),
+            invalid in prop::option::of(invalid_segment()),
+            at: prop::sample::Index,
+        ) {
+            let mut segments: Vec<Vec<u8>> = valid.into_iter().map(String::into_bytes).collect();
+            let utf8 = invalid.is_none();
+            if let Some(s) = invalid {
+                segments.insert(at.index(segments.len() + 1), s);
+            }
+            let url: String = segments
+                .iter()
+                .map(|s| format!("/{}", percent_encode(s, NON_ALPHANUMERIC)))
+                .collect();
+            prop_assert_eq!(is_utf8(&url), utf8);
+            if utf8 {
+                // accepted paths convert losslessly
+                let path: NormalizedPath = DavPath::new(&url).unwrap().into();
+                let names: Vec<&str> = segments
+                    .iter()
+                    .map(|s| std::str::from_utf8(s).unwrap())
+                    .collect();
+                prop_assert_eq!(path.as_str(), names.join("/"));
+            }
+        }
+
+        #[test]
+        fn string_round_trip(segments in segments(), collection: bool) {
+            let p = from_segments(&segments, collection);
+            let s: String = p.clone().into();
+            prop_assert_eq!(NormalizedPath::from(s.clone()), p.clone());
+            prop_assert_eq!(NormalizedPath::from(format!("/{}", s)), p);
+        }
+
+        #[test]
+        fn join_parent_laws(segments in segments(), name in segment()) {
+            let dir = from_segments(&segments, true);
+            let file = dir.join_file(&name);
+            let sub = dir.join_dir(&name);
+            prop_assert_eq!(file.parent(), dir.clone());
+            prop_assert_eq!(sub.parent(), dir.clone());
+            prop_assert_eq!(sub.as_file(), file.clone());
+            prop_assert_eq!(file.as_dir(), sub.clone());
+            prop_assert!(!file.is_collection());
+            prop_assert!(sub.is_collection());
+        }
+
+        #[test]
+        fn strip_prefix_laws(segments in segments(), rest in segments()) {
+            prop_assume!(!rest.is_empty());
+            let dir = from_segments(&segments, true);
+            let rel = from_segments(&rest, false);
+            let full = dir.join_file(&rel);
+            prop_assert_eq!(full.strip_prefix(&dir), rel);
+        }
+    }
 }
@@ -0,0 +1,19 @@
+//! `OpenOptions` constructors, the ones of webdav-handler are crate private.
+
+use webdav_handler::fs::OpenOptions;
+
+/// Options opening file for reading.
+pub(crate) fn read() -> OpenOptions {
+    OpenOptions {
+        read: true,
+        ..Default::default()
+    }
+}
+
+/// Options opening file for writing.
+pub(crate) fn write() -> OpenOptions {
+    OpenOptions {
+        write: true,
+        ..Default::default()
+    }
+}
@@ -11,9 +11,12 @@ use std::cell::RefCell;
 use tracing::{debug, span, Instrument, Level};
 use webdav_handler::fs::{DavProp, FsError};
 
+// path -> (prop key -> prop)
+type Props = HashMap<String, HashMap<String, DavProp>>;
+
 #[derive(Clone)]
 pub struct Memory {
-    data: Arc<Mutex<RefCell<HashMap<String, DavProp>>>>,
+    data: Arc<Mutex<RefCell<Props>>>,
 }
 
 impl Memory {
@@ -27,15 +30,54 @@ impl Memory {
         }
     }
 
-    pub fn get_all_props(&self) -> HashMap<String, DavProp> {
+    pub fn get_all_props(&self) -> HashMap<String, Vec<DavProp>> {
         let g = self.data.lock().unwrap();
         let b = g.borrow();
-        b.clone()
+        b.iter()
+            .map(|(k, v)| (k.clone(), v.values().cloned().collect()))
+            .collect()
+    }
+
+    /// Collections and files share same key so props do not depend on
+    /// trailing slash of request path.
+    pub(super) fn get_path_key(path: &NormalizedPath) -> String {
+        path.as_file().to_string()
     }
 
-    fn get_prop_string(path: &NormalizedPath, prop: &DavProp) -> String {
+    fn get_prop_key(prop: &DavProp) -> String {
         let ns = prop.namespace.clone().unwrap_or("".into());
-        format!("{}.{}.{}", path.as_ref(), ns, prop.name)
+        format!("{{{}}}{}", ns, prop.name)
+    }
+
+    fn is_descendant(key: &str, parent: &str) -> bool {
+        if key == parent {
+            return false;
+        }
+        parent == "/" || (key.starts_with(parent) && key[parent.len()..].starts_with('/'))
+    }
+
+    pub(super) fn replace_prefix(key: &str, from: &str, to: &str) -> String {
+        if key == from {
+            return to.to_string();
+        }
+        let rest = if from == "/" {
+            key
+        } else {
+            key[from.len()..].trim_start_matches('/')
+        };
+        if to == "/" {
+            rest.to_string()
+        } else {
+            format!("{}/{}", to, rest)
+        }
+    }
+
+    /// Returns keys of path itself and all its descendants.
+    fn get_tree_keys(b: &Props, key: &str) -> Vec<String> {
+        b.keys()
+            .filter(|k| k.as_str() == key || Memory::is_descendant(k, key))
+            .cloned()
+            .collect()
     }
 
     pub fn add_prop(
@@ -44,19 +86,45 @@ impl Memory {
         (set, prop): (bool, DavProp),
     ) -> PropResult<(StatusCode, DavProp)> {
         let data = self.data.lock().unwrap();
-        let k = Memory::get_prop_string(path, &prop);
+        let mut b = data.borrow_mut();
+        let k = Memory::get_path_key(path);
+        let pk = Memory::get_prop_key(&prop);
         let mut p_c = prop.clone();
         p_c.xml = None;
         if set {
-            let mut b = data.borrow_mut();
-            let v = b.entry(k.clone()).or_insert(prop.clone());
-            *v = prop;
-        } else {
-            data.borrow_mut().remove(&k);
+            b.entry(k).or_default().insert(pk, prop);
+        } else if let Some(props) = b.get_mut(&k) {
+            props.remove(&pk);
+            if props.is_empty() {
+                b.remove(&k);
+            }
         }
 
         Ok((StatusCode::OK, p_c))
     }
+
+    fn move_tree(&self, from: &NormalizedPath, to: &NormalizedPath, keep_source: bool) {
+        let data = self.data.lock().unwrap();
+        let mut b = data.borrow_mut();
+        let from = Memory::get_path_key(from);
+        let to = Memory::get_path_key(to);
+
+        // destination is overwritten
+        for k in Memory::get_tree_keys(&b, &to) {
+            b.remove(&k);
+        }
+
+        for k in Memory::get_tree_keys(&b, &from) {
+            let pp = Memory::replace_prefix(&k, &from, &to);
+            debug!(from = %k, to = %pp);
+            let props = if keep_source {
+                b.get(&k).unwrap().clone()
+            } else {
+                b.remove(&k).unwrap()
+            };
+            b.insert(pp, props);
+        }
+    }
 }
 
 impl PropStorage for Memory {
@@ -65,14 +133,12 @@ impl PropStorage for Memory {
         async move {
             let g = self.data.lock().unwrap();
             let b = g.borrow();
-            for k in b.keys() {
-                if k.starts_with(path.as_ref()) {
-                    debug!(contains = true, path = %path);
-                    return true;
-                }
-            }
-            debug!(contains = false, path = %path);
-            false
+            let contains = b
+                .get(&Memory::get_path_key(path))
+                .map(|p| !p.is_empty())
+                .unwrap_or(false);
+            debug!(contains = contains, path = %path);
+            contains
         }
         .instrument(span)
         .boxed()
@@ -94,10 +160,10 @@ impl PropStorage for Memory {
         let span = span!(Level::INFO, "Memory::get_prop");
         async move {
             let data = self.data.lock().unwrap();
-            let k = Memory::get_prop_string(path, &prop);
-            let r = data
-                .borrow()
-                .get(&k)
+            let b = data.borrow();
+            let r = b
+                .get(&Memory::get_path_key(path))
+                .and_then(|p| p.get(&Memory::get_prop_key(&prop)))
                 .ok_or(FsError::NotFound)
                 .and_then(|e| e.xml.clone().ok_or(FsError::NotFound));
             debug!(path = %path, result = ?r, prop = ?prop);
@@ -115,11 +181,11 @@ impl PropStorage for Memory {
         let span = span!(Level::INFO, "Memory::get_props");
         async move {
             let data = self.data.lock().unwrap();
-            let mut r = vec![];
             let b = data.borrow();
-            for k in b.keys() {
-                if k.starts_with(path.as_ref()) {
-                    let mut v = b.get(k).unwrap().clone();
+            let mut r = vec![];
+            if let Some(props) = b.get(&Memory::get_path_key(path)) {
+                for v in props.values() {
+                    let mut v = v.clone();
                     if !do_content {
                         v.xml = None;
                     }
@@ -134,26 +200,46 @@ impl PropStorage for Memory {
         .boxed()
     }
 
+    fn paths_with_prop<'a>(
+        &'a self,
+        prop: &'a DavProp,
+    ) -> PropFuture<PropResult<Vec<NormalizedPath>>> {
+        let span = span!(Level::INFO, "Memory::paths_with_prop");
+        async move {
+            let pk = Memory::get_prop_key(prop);
+            let data = self.data.lock().unwrap();
+            let b = data.borrow();
+            let r = b
+                .iter()
+                .filter(|(_, v)| v.contains_key(&pk))
+                .map(|(k, _)| k.as_str().into())
+                .collect::<Vec<NormalizedPath>>();
+            debug!(prop = ?prop, result = ?r);
+            Ok(r)
+        }
+        .instrument(span)
+        .boxed()
+    }
+
+    fn get_all<'a>(&'a self) -> PropFuture<PropResult<Vec<(NormalizedPath, Vec<DavProp>)>>> {
+        async move {
+            Ok(self
+                .get_all_props()
+                .into_iter()
+                .map(|(k, v)| (k.into(), v))
+                .collect())
+        }
+        .boxed()
+    }
+
     fn remove_file<'a>(&'a self, path: &'a NormalizedPath) -> PropFuture<PropResult<()>> {
         let span = span!(Level::INFO, "Memory::remove_file");
         async move {
             let data = self.data.lock().unwrap();
             let mut b = data.borrow_mut();
-            let mut rm = None;
-            for k in b.keys() {
-                if k.starts_with(path.as_ref()) {
-                    rm = Some(k.clone());
-                    break;
-                }
-            }
-
             debug!(path = %path);
-            if rm.is_none() {
-                Ok(())
-            } else {
-                b.remove(&rm.unwrap());
-                Ok(())
-            }
+            b.remove(&Memory::get_path_key(path));
+            Ok(())
         }
         .instrument(span)
         .boxed()
@@ -164,21 +250,12 @@ impl PropStorage for Memory {
         async move {
             let data = self.data.lock().unwrap();
             let mut b = data.borrow_mut();
-            let mut rm = None;
-            for k in b.keys() {
-                if k.starts_with(path.as_ref()) {
-                    rm = Some(k.clone());
-                    break;
-                }
-            }
-
-            debug!(path = %path);
-            if rm.is_none() {
-                Ok(())
-            } else {
-                b.remove(&rm.unwrap());
-                Ok(())
+            let keys = Memory::get_tree_keys(&b, &Memory::get_path_key(path));
+            debug!(path = %path, removed = ?keys);
+            for k in keys {
+                b.remove(&k);
             }
+            Ok(())
         }
         .instrument(span)
         .boxed()
@@ -191,22 +268,7 @@ impl PropStorage for Memory {
     ) -> PropFuture<PropResult<()>> {
         let span = span!(Level::INFO, "Memory::rename");
         async move {
-            let data = self.data.lock().unwrap();
-            let mut b = data.borrow_mut();
-            let mut rn = vec![];
-            for k in b.keys() {
-                if k.starts_with(from.as_ref()) {
-                    let pp: NormalizedPath = k.strip_prefix(from.as_ref()).unwrap().into();
-                    let pp = format!("{}{}", to.as_ref(), pp.as_ref());
-                    rn.push((k.clone(), pp));
-                }
-            }
-
-            for (k, pp) in rn {
-                debug!(from = %k, to = %pp);
-                let prop = b.remove(&k).unwrap();
-                b.entry(pp.to_string()).or_insert(prop);
-            }
+            self.move_tree(from, to, false);
             Ok(())
         }
         .instrument(span)
@@ -220,22 +282,7 @@ impl PropStorage for Memory {
     ) -> PropFuture<PropResult<()>> {
         let span = span!(Level::INFO, "Memory::copy");
         async move {
-            let data = self.data.lock().unwrap();
-            let mut b = data.borrow_mut();
-            let mut cp = vec![];
-            for k in b.keys() {
-                if k.starts_with(from.as_ref()) {
-                    let pp: NormalizedPath = k.strip_prefix(from.as_ref()).unwrap().into();
-                    let pp = format!("{}{}", to.as_ref(), pp.as_ref());
-                    cp.push((k.clone(), pp.to_string()));
-                }
-            }
-
-            for (k, pp) in cp {
-                debug!(from = %k, to = %pp);
-                let prop = b.get(&k).unwrap().clone();
-                b.entry(pp.to_string()).or_insert(prop);
-            }
+            self.move_tree(from, to, true);
             Ok(())
         }
         .instrument(span)
@@ -247,6 +294,15 @@ impl PropStorage for Memory {
 mod tests {
     use super::*;
 
+    fn prop(name: &str) -> DavProp {
+        DavProp {
+            name: name.into(),
+            namespace: Some("namespace1".into()),
+            prefix: None,
+            xml: Some([1, 2, 3].into()),
+        }
+    }
+
     #[tokio::test]
     async fn rename() -> anyhow::Result<()> {
         let mem = Memory::new();
@@ -271,4 +327,54 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn siblings_with_common_prefix() -> anyhow::Result<()> {
+        let mem = Memory::new();
+        mem.patch_prop(&"/fs/ab".into(), (true, prop("name1")))
+            .await?;
+        mem.patch_prop(&"/fs/a".into(), (true, prop("name2")))
+            .await?;
+
+        assert_eq!(mem.get_props(&"/fs/a".into(), false).await?.len(), 1);
+        assert!(!mem.have_props(&"/fs/".into()).await);
+
+        mem.remove_file(&"/fs/a".into()).await?;
+        assert!(!mem.have_props(&"/fs/a".into()).await);
+        assert!(mem.have_props(&"/fs/ab".into()).await);
+
+        mem.rename(&"/fs/a".into(), &"/fs/c".into()).await?;
+        assert!(mem.have_props(&"/fs/ab".into()).await);
+        assert!(!mem.have_props(&"/fs/cb".into()).await);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn directory_operations() -> anyhow::Result<()> {
+        let mem = Memory::new();
+        mem.patch_prop(&"/fs/dir/".into(), (true, prop("name1")))
+            .await?;
+        mem.patch_prop(&"/fs/dir/file".into(), (true, prop("name1")))
+            .await?;
+        mem.patch_prop(&"/fs/dir2/file".into(), (true, prop("name1")))
+            .await?;
+
+        assert!(mem.have_props(&"/fs/dir".into()).await);
+
+        mem.copy(&"/fs/dir/".into(), &"/fs/copy/".into()).await?;
+        assert!(mem.have_props(&"/fs/copy/".into()).await);
+        assert!(mem.have_props(&"/fs/copy/file".into()).await);
+        assert!(mem.have_props(&"/fs/dir/file".into()).await);
+
+        mem.remove_dir(&"/fs/dir/".into()).await?;
+        assert!(!mem.have_props(&"/fs/dir/".into()).await);
+        assert!(!mem.have_props(&"/fs/dir/file".into()).await);
+        assert!(mem.have_props(&"/fs/dir2/file".into()).await);
+
+        let paths = mem.paths_with_prop(&prop("name1")).await?;
+        assert_eq!(paths.len(), 3);
+
+        Ok(())
+    }
 }
@@ -1,4 +1,5 @@
 pub mod mem;
+pub mod sqlite;
 pub mod yaml;
 
 use super::normalized_path::NormalizedPath;
@@ -48,6 +49,15 @@ pub trait PropStorage: Send + Sync + BoxClonePropStorage {
         do_content: bool,
     ) -> PropFuture<PropResult<Vec<DavProp>>>;
 
+    /// Returns all paths which have given property set.
+    fn paths_with_prop<'a>(
+        &'a self,
+        prop: &'a DavProp,
+    ) -> PropFuture<PropResult<Vec<NormalizedPath>>>;
+
+    /// Returns all stored properties with content grouped by path.
+    fn get_all<'a>(&'a self) -> PropFuture<PropResult<Vec<(NormalizedPath, Vec<DavProp>)>>>;
+
     fn remove_file<'a>(&'a self, path: &'a NormalizedPath) -> PropFuture<PropResult<()>>;
     fn remove_dir<'a>(&'a self, path: &'a NormalizedPath) -> PropFuture<PropResult<()>>;
     fn rename<'a>(
@@ -60,4 +70,9 @@ pub trait PropStorage: Send + Sync + BoxClonePropStorage {
         from: &'a NormalizedPath,
         to: &'a NormalizedPath,
     ) -> PropFuture<PropResult<()>>;
+
+    /// Writes pending changes to persistent storage if it has any.
+    fn flush<'a>(&'a self) -> PropFuture<PropResult<()>> {
+        Box::pin(async { Ok(()) })
+    }
 }
@@ -0,0 +1,359 @@
+use futures_util::FutureExt;
+use hyper::StatusCode;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+use tracing::{debug, error};
+use webdav_handler::fs::{DavProp, FsError};
+
+use super::{mem::Memory, PropFuture, PropResult, PropStorage};
+use crate::backend::normalized_path::NormalizedPath;
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS props (
+    path TEXT NOT NULL,
+    namespace TEXT NOT NULL,
+    name TEXT NOT NULL,
+    prefix TEXT,
+    xml BLOB,
+    PRIMARY KEY (path, namespace, name)
+)";
+
+/// Rows of path itself and all its descendants, path is bound as `?1`.
+const TREE: &str = "(path = ?1 OR ?1 = '/' OR substr(path, 1, length(?1) + 1) = ?1 || '/')";
+
+/// Keeps props in sqlite database, every change is written immediately.
+/// Queries run on blocking thread pool over one shared connection.
+#[derive(Clone)]
+pub struct Sqlite {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl Sqlite {
+    pub fn new<P: AsRef<Path>>(path: P) -> rusqlite::Result<Box<dyn PropStorage>> {
+        let conn = Connection::open(path)?;
+        conn.execute(SCHEMA, [])?;
+        Ok(Box::new(Sqlite {
+            conn: Arc::new(Mutex::new(conn)),
+        }) as Box<dyn PropStorage>)
+    }
+
+    async fn run<T, F>(&self, f: F) -> PropResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || f(&mut conn.lock().unwrap()))
+            .await
+            .map_err(|_| FsError::GeneralFailure)?
+            .map_err(|e| {
+                error!(msg = "sqlite props query failed", err = %e);
+                FsError::GeneralFailure
+            })
+    }
+
+    fn prop(
+        namespace: String,
+        name: String,
+        prefix: Option<String>,
+        xml: Option<Vec<u8>>,
+    ) -> DavProp {
+        DavProp {
+            name,
+            namespace: Some(namespace).filter(|ns| !ns.is_empty()),
+            prefix,
+            xml,
+        }
+    }
+
+    fn move_tree(
+        conn: &mut Connection,
+        from: &str,
+        to: &str,
+        keep_source: bool,
+    ) -> rusqlite::Result<()> {
+        let tx = conn.transaction()?;
+        // destination is overwritten
+        tx.execute(&format!("DELETE FROM props WHERE {}", TREE), params![to])?;
+        let keys = {
+            let mut stmt =
+                tx.prepare(&format!("SELECT DISTINCT path FROM props WHERE {}", TREE))?;
+            let keys = stmt
+                .query_map(params![from], |r| r.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            keys
+        };
+        let sql = if keep_source {
+            "INSERT OR REPLACE INTO props
+             SELECT ?2, namespace, name, prefix, xml FROM props WHERE path = ?1"
+        } else {
+            "UPDATE OR REPLACE props SET path = ?2 WHERE path = ?1"
+        };
+        for k in keys {
+            let pp = Memory::replace_prefix(&k, from, to);
+            debug!(from = %k, to = %pp);
+            tx.execute(sql, params![k, pp])?;
+        }
+        tx.commit()
+    }
+}
+
+impl PropStorage for Sqlite {
+    fn have_props<'a>(&'a self, path: &'a NormalizedPath) -> PropFuture<bool> {
+        let key = Memory::get_path_key(path);
+        async move {
+            self.run(move |c| {
+                c.query_row(
+                    "SELECT EXISTS (SELECT 1 FROM props WHERE path = ?1)",
+                    params![key],
+                    |r| r.get(0),
+                )
+            })
+            .await
+            .unwrap_or(false)
+        }
+        .boxed()
+    }
+
+    fn patch_prop<'a>(
+        &'a self,
+        path: &'a NormalizedPath,
+        (set, prop): (bool, DavProp),
+    ) -> PropFuture<PropResult<(StatusCode, DavProp)>> {
+        let key = Memory::get_path_key(path);
+        async move {
+            let mut p_c = prop.clone();
+            p_c.xml = None;
+            self.run(move |c| {
+                let ns = prop.namespace.unwrap_or_default();
+                if set {
+                    c.execute(
+                        "INSERT OR REPLACE INTO props VALUES (?1, ?2, ?3, ?4, ?5)",
+                        params![key, ns, prop.name, prop.prefix, prop.xml],
+                    )
+                } else {
+                    c.execute(
+                        "DELETE FROM props WHERE path = ?1 AND namespace = ?2 AND name = ?3",
+                        params![key, ns, prop.name],
+                    )
+                }
+            })
+            .await?;
+            Ok((StatusCode::OK, p_c))
+        }
+        .boxed()
+    }
+
+    fn get_prop<'a>(
+        &'a self,
+        path: &'a NormalizedPath,
+        prop: DavProp,
+    ) -> PropFuture<PropResult<Vec<u8>>> {
+        let key = Memory::get_path_key(path);
+        async move {
+            let xml = self
+                .run(move |c| {
+                    c.query_row(
+                        "SELECT xml FROM props WHERE path = ?1 AND namespace = ?2 AND name = ?3",
+                        params![key, prop.namespace.unwrap_or_default(), prop.name],
+                        |r| r.get::<_, Option<Vec<u8>>>(0),
+                    )
+                    .optional()
+                })
+                .await?;
+            xml.flatten().ok_or(FsError::NotFound)
+        }
+        .boxed()
+    }
+
+    fn get_props<'a>(
+        &'a self,
+        path: &'a NormalizedPath,
+        do_content: bool,
+    ) -> PropFuture<PropResult<Vec<DavProp>>> {
+        let key = Memory::get_path_key(path);
+        async move {
+            self.run(move |c| {
+                let mut stmt =
+                    c.prepare("SELECT namespace, name, prefix, xml FROM props WHERE path = ?1")?;
+                let props = stmt
+                    .query_map(params![key], |r| {
+                        let xml = if do_content { r.get(3)? } else { None };
+                        Ok(Sqlite::prop(r.get(0)?, r.get(1)?, r.get(2)?, xml))
+                    })?
+                    .collect();
+                props
+            })
+            .await
+        }
+        .boxed()
+    }
+
+    fn paths_with_prop<'a>(
+        &'a self,
+        prop: &'a DavProp,
+    ) -> PropFuture<PropResult<Vec<NormalizedPath>>> {
+        let ns = prop.namespace.clone().unwrap_or_default();
+        let name = prop.name.clone();
+        async move {
+            self.run(move |c| {
+                let mut stmt =
+                    c.prepare("SELECT path FROM props WHERE namespace = ?1 AND name = ?2")?;
+                let paths = stmt
+                    .query_map(params![ns, name], |r| r.get::<_, String>(0))?
+                    .map(|p| p.map(NormalizedPath::from))
+                    .collect();
+                paths
+            })
+            .await
+        }
+        .boxed()
+    }
+
+    fn get_all<'a>(&'a self) -> PropFuture<PropResult<Vec<(NormalizedPath, Vec<DavProp>)>>> {
+        async move {
+            self.run(|c| {
+                let mut stmt = c.prepare(
+                    "SELECT path, namespace, name, prefix, xml FROM props ORDER BY path",
+                )?;
+                let mut rows = stmt.query([])?;
+                let mut all: Vec<(String, Vec<DavProp>)> = vec![];
+                while let Some(r) = rows.next()? {
+                    let path: String = r.get(0)?;
+                    let prop = Sqlite::prop(r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?);
+                    match all.last_mut() {
+                        Some((p, props)) if *p == path => props.push(prop),
+                        _ => all.push((path, vec![prop])),
+                    }
+                }
+                Ok(all.into_iter().map(|(p, v)| (p.into(), v)).collect())
+            })
+            .await
+        }
+        .boxed()
+    }
+
+    fn remove_file<'a>(&'a self, path: &'a NormalizedPath) -> PropFuture<PropResult<()>> {
+        let key = Memory::get_path_key(path);
+        async move {
+            self.run(move |c| c.execute("DELETE FROM props WHERE path = ?1", params![key]))
+                .await?;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn remove_dir<'a>(&'a self, path: &'a NormalizedPath) -> PropFuture<PropResult<()>> {
+        let key = Memory::get_path_key(path);
+        async move {
+            self.run(move |c| {
+                c.execute(&format!("DELETE FROM props WHERE {}", TREE), params![key])
+            })
+            .await?;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn rename<'a>(
+        &'a self,
+        from: &'a NormalizedPath,
+        to: &'a NormalizedPath,
+    ) -> PropFuture<PropResult<()>> {
+        let (from, to) = (Memory::get_path_key(from), Memory::get_path_key(to));
+        async move {
+            self.run(move |c| Sqlite::move_tree(c, &from, &to, false))
+                .await
+        }
+        .boxed()
+    }
+
+    fn copy<'a>(
+        &'a self,
+        from: &'a NormalizedPath,
+        to: &'a NormalizedPath,
+    ) -> PropFuture<PropResult<()>> {
+        let (from, to) = (Memory::get_path_key(from), Memory::get_path_key(to));
+        async move {
+            self.run(move |c| Sqlite::move_tree(c, &from, &to, true))
+                .await
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prop(name: &str) -> DavProp {
+        DavProp {
+            name: name.into(),
+            namespace: Some("urn:test".into()),
+            prefix: None,
+            xml: Some(format!("<{0}>1</{0}>", name).into_bytes()),
+        }
+    }
+
+    #[tokio::test]
+    async fn keeps_props_across_opens() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("webdav_ss_sqlite_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let file: NormalizedPath = "/dir/file".into();
+
+        let storage = Sqlite::new(&path)?;
+        storage.patch_prop(&file, (true, prop("color"))).await?;
+        storage.patch_prop(&file, (true, prop("size"))).await?;
+        storage.patch_prop(&file, (false, prop("size"))).await?;
+
+        let storage = Sqlite::new(&path)?;
+        assert!(storage.have_props(&file).await);
+        assert_eq!(
+            storage.get_prop(&file, prop("color")).await?,
+            prop("color").xml.unwrap()
+        );
+        assert!(storage.get_prop(&file, prop("size")).await.is_err());
+        let all = storage.get_all().await?;
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].0, file);
+        assert_eq!(all[0].1.len(), 1);
+        assert_eq!(all[0].1[0].namespace, prop("color").namespace);
+        assert_eq!(all[0].1[0].xml, prop("color").xml);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn moves_trees() -> anyhow::Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("webdav_ss_sqlite_tree_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let storage = Sqlite::new(&path)?;
+        for p in ["/a/", "/a/b", "/ab"].iter() {
+            storage.patch_prop(&(*p).into(), (true, prop("x"))).await?;
+        }
+
+        storage.copy(&"/a/".into(), &"/c/".into()).await?;
+        storage.rename(&"/a/".into(), &"/d/".into()).await?;
+        let mut paths: Vec<String> = storage
+            .paths_with_prop(&prop("x"))
+            .await?
+            .into_iter()
+            .map(|p| p.as_file().to_string())
+            .collect();
+        paths.sort();
+        let expected: Vec<String> = vec!["/ab", "/c", "/c/b", "/d", "/d/b"]
+            .into_iter()
+            .map(|p| NormalizedPath::from(p).as_file().to_string())
+            .collect();
+        assert_eq!(paths, expected);
+
+        storage.remove_dir(&"/d/".into()).await?;
+        assert!(!storage.have_props(&"/d/b".into()).await);
+        assert!(storage.have_props(&"/ab".into()).await);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
@@ -1,7 +1,17 @@
 use futures_util::FutureExt;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, path::PathBuf};
-use webdav_handler::fs::DavProp;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::Mutex;
+use tracing::{debug, error, info};
+use webdav_handler::fs::{DavProp, FsError};
 
 use super::{mem::Memory, PropStorage};
 
@@ -13,79 +23,170 @@ struct Prop {
     name: String,
 }
 
+/// Keeps props in memory and dumps them to yaml file. Changes are not written
+/// immediately: file is rewritten at most once per `dump_interval` and on flush.
+/// Dumps run on blocking thread pool one at a time.
 #[derive(Clone)]
 pub struct Yaml {
     filepath: PathBuf,
     mem: Memory,
+    dirty: Arc<AtomicBool>,
+    dumping: Arc<Mutex<()>>,
 }
 
 impl Yaml {
-    pub fn new(fp: PathBuf) -> Box<dyn PropStorage> {
+    pub fn new(fp: PathBuf, dump_interval: Duration) -> Box<dyn PropStorage> {
         let mut m = Yaml {
             filepath: fp,
             mem: Memory::new_unboxed(),
+            dirty: Arc::new(AtomicBool::new(false)),
+            dumping: Arc::new(Mutex::new(())),
         };
 
         if std::fs::metadata(&m.filepath).is_ok() {
-            m.load().expect("can't load yaml props");
+            if let Err(e) = m.load() {
+                m.set_aside(e);
+            }
+        }
+
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let y = m.clone();
+            handle.spawn(async move {
+                let mut interval = tokio::time::interval(dump_interval);
+                loop {
+                    interval.tick().await;
+                    let _ = y.flush_dirty().await;
+                }
+            });
         }
         Box::new(m) as Box<dyn PropStorage>
     }
 
+    fn mark_dirty(&self) {
+        self.dirty.store(true, Ordering::SeqCst);
+    }
+
+    async fn flush_dirty(&self) -> super::PropResult<()> {
+        // flag is checked under lock so flush waits for running dump
+        let _dumping = self.dumping.lock().await;
+        if !self.dirty.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let data = self.snapshot();
+        let path = self.filepath.clone();
+        let r = tokio::task::spawn_blocking(move || Yaml::dump(&path, &data))
+            .await
+            .unwrap_or(Err(FsError::GeneralFailure));
+        if let Err(ref e) = r {
+            error!(msg = "can't dump yaml props", path = ?self.filepath, err = ?e);
+            self.mark_dirty();
+        }
+        r
+    }
+
     fn load(&mut self) -> std::io::Result<()> {
         use std::io::{Error, ErrorKind};
 
-        let mut f = std::fs::OpenOptions::new();
-        let f = f.read(true).open(&self.filepath)?;
-        let data: HashMap<String, Prop> =
-            serde_yaml::from_reader(f).map_err(|e| Error::new(ErrorKind::Other, e))?;
-
-        for (k, v) in &data {
-            let xml = v.value.clone()
-                .map(|v| 
-                    base64::decode(&v)
-                    .map(|k| Some(k)).unwrap_or(None)
-                ).unwrap_or(None);
-            let _ = self
-                .mem
-                .add_prop(
-                    &k.clone().into(),
-                    (
-                        true,
-                        DavProp {
-                            name: v.name.clone(),
-                            namespace: v.namespace.clone(),
-                            prefix: v.prefix.clone(),
-                            xml,
-                        },
-                    ),
-                )
-                .map_err(|e| Error::new(ErrorKind::Other, e))?;
+        let content = std::fs::read_to_string(&self.filepath)?;
+        let data: HashMap<String, Vec<Prop>> = match serde_yaml::from_str(&content) {
+            Ok(k) => k,
+            Err(_) => Yaml::convert_flat(
+                serde_yaml::from_str(&content).map_err(|e| Error::new(ErrorKind::Other, e))?,
+            ),
+        };
+
+        info!(msg = "yaml props loaded", path = ?self.filepath, paths = data.len());
+        for (k, props) in &data {
+            for v in props {
+                let xml = v
+                    .value
+                    .clone()
+                    .map(|v| base64::decode(&v).map(|k| Some(k)).unwrap_or(None))
+                    .unwrap_or(None);
+                let _ = self
+                    .mem
+                    .add_prop(
+                        &k.clone().into(),
+                        (
+                            true,
+                            DavProp {
+                                name: v.name.clone(),
+                                namespace: v.namespace.clone(),
+                                prefix: v.prefix.clone(),
+                                xml,
+                            },
+                        ),
+                    )
+                    .map_err(|e| Error::new(ErrorKind::Other, e))?;
+            }
         }
 
         Ok(())
     }
 
-    fn dump(&self) -> super::PropResult<()> {
-        let data = self.mem.get_all_props();
-        let data: HashMap<_, _> = data
+    /// Moves unreadable storage to `<file>.corrupt` and starts with no props,
+    /// so server still starts and the file is kept for manual recovery.
+    fn set_aside(&mut self, e: std::io::Error) {
+        let mut aside = self.filepath.as_os_str().to_owned();
+        aside.push(".corrupt");
+        let aside = PathBuf::from(aside);
+        error!(
+            msg = "can't load yaml props, starting empty",
+            path = ?self.filepath,
+            moved_to = ?aside,
+            err = %e
+        );
+        if let Err(e) = std::fs::rename(&self.filepath, &aside) {
+            error!(msg = "can't move corrupted yaml props", path = ?self.filepath, err = %e);
+        }
+        self.mem = Memory::new_unboxed();
+    }
+
+    /// Converts storage written by previous versions where every prop was
+    /// keyed by `path.namespace.name`.
+    fn convert_flat(data: HashMap<String, Prop>) -> HashMap<String, Vec<Prop>> {
+        let mut r: HashMap<String, Vec<Prop>> = HashMap::new();
+        for (k, v) in data {
+            let suffix = format!(".{}.{}", v.namespace.clone().unwrap_or("".into()), v.name);
+            let path = k.strip_suffix(&suffix).unwrap_or(&k).to_string();
+            r.entry(path).or_default().push(v);
+        }
+        r
+    }
+
+    fn snapshot(&self) -> HashMap<String, Vec<Prop>> {
+        self.mem
+            .get_all_props()
             .into_iter()
             .map(|(k, v)| {
                 (
                     k,
-                    Prop {
-                        namespace: v.namespace,
-                        name: v.name,
-                        prefix: v.prefix,
-                        value: v.xml.map(|m| base64::encode(m)),
-                    },
+                    v.into_iter()
+                        .map(|v| Prop {
+                            namespace: v.namespace,
+                            name: v.name,
+                            prefix: v.prefix,
+                            value: v.xml.map(|m| base64::encode(m)),
+                        })
+                        .collect::<Vec<_>>(),
                 )
             })
-            .collect();
-        let mut opts = std::fs::OpenOptions::new();
-        let f = opts.create(true).write(true).open(&self.filepath)?;
+            .collect()
+    }
 
-        serde_yaml::to_writer(f, &data).map_err(|_| webdav_handler::fs::FsError::GeneralFailure)
+    fn dump(filepath: &Path, data: &HashMap<String, Vec<Prop>>) -> super::PropResult<()> {
+        // write to temporary file first so crash in the middle of dump
+        // does not leave corrupted storage
+        let mut tmp = filepath.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        let mut opts = std::fs::OpenOptions::new();
+        let f = opts.create(true).write(true).truncate(true).open(&tmp)?;
+        serde_yaml::to_writer(&f, data).map_err(|_| FsError::GeneralFailure)?;
+        f.sync_all()?;
+        std::fs::rename(&tmp, filepath)?;
+        debug!(path = ?filepath, msg = "yaml props dumped");
+        Ok(())
     }
 }
 
@@ -109,7 +210,7 @@ impl PropStorage for Yaml {
     {
         async move {
             let r = self.mem.patch_prop(path, patch).await?;
-            self.dump()?;
+            self.mark_dirty();
             Ok(r)
         }
         .boxed()
@@ -120,12 +221,7 @@ impl PropStorage for Yaml {
         path: &'a crate::backend::normalized_path::NormalizedPath,
         prop: webdav_handler::fs::DavProp,
     ) -> super::PropFuture<super::PropResult<Vec<u8>>> {
-        async move {
-            let r = self.mem.get_prop(path, prop).await?;
-            self.dump()?;
-            Ok(r)
-        }
-        .boxed()
+        async move { self.mem.get_prop(path, prop).await }.boxed()
     }
 
     fn get_props<'a>(
@@ -133,12 +229,28 @@ impl PropStorage for Yaml {
         path: &'a crate::backend::normalized_path::NormalizedPath,
         do_content: bool,
     ) -> super::PropFuture<super::PropResult<Vec<webdav_handler::fs::DavProp>>> {
-        async move {
-            let r = self.mem.get_props(path, do_content).await?;
-            self.dump()?;
-            Ok(r)
-        }
-        .boxed()
+        async move { self.mem.get_props(path, do_content).await }.boxed()
+    }
+
+    fn paths_with_prop<'a>(
+        &'a self,
+        prop: &'a webdav_handler::fs::DavProp,
+    ) -> super::PropFuture<super::PropResult<Vec<crate::backend::normalized_path::NormalizedPath>>>
+    {
+        async move { self.mem.paths_with_prop(prop).await }.boxed()
+    }
+
+    fn get_all<'a>(
+        &'a self,
+    ) -> super::PropFuture<
+        super::PropResult<
+            Vec<(
+                crate::backend::normalized_path::NormalizedPath,
+                Vec<webdav_handler::fs::DavProp>,
+            )>,
+        >,
+    > {
+        async move { self.mem.get_all().await }.boxed()
     }
 
     fn remove_file<'a>(
@@ -147,7 +259,7 @@ impl PropStorage for Yaml {
     ) -> super::PropFuture<super::PropResult<()>> {
         async move {
             let r = self.mem.remove_file(path).await?;
-            self.dump()?;
+            self.mark_dirty();
             Ok(r)
         }
         .boxed()
@@ -159,7 +271,7 @@ impl PropStorage for Yaml {
     ) -> super::PropFuture<super::PropResult<()>> {
         async move {
             let r = self.mem.remove_dir(path).await?;
-            self.dump()?;
+            self.mark_dirty();
             Ok(r)
         }
         .boxed()
@@ -172,7 +284,7 @@ impl PropStorage for Yaml {
     ) -> super::PropFuture<super::PropResult<()>> {
         async move {
             let r = self.mem.rename(from, to).await?;
-            self.dump()?;
+            self.mark_dirty();
             Ok(r)
         }
         .boxed()
@@ -185,9 +297,61 @@ impl PropStorage for Yaml {
     ) -> super::PropFuture<super::PropResult<()>> {
         async move {
             let r = self.mem.copy(from, to).await?;
-            self.dump()?;
+            self.mark_dirty();
             Ok(r)
         }
         .boxed()
     }
+
+    fn flush<'a>(&'a self) -> super::PropFuture<super::PropResult<()>> {
+        async move { self.flush_dirty().await }.boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::normalized_path::NormalizedPath;
+
+    #[tokio::test]
+    async fn dump_and_load() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("webdav_ss_yaml_{}.yml", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let file: NormalizedPath = "/dir/file".into();
+        let prop = DavProp {
+            name: "color".into(),
+            namespace: Some("urn:test".into()),
+            prefix: None,
+            xml: Some(b"<color>red</color>".to_vec()),
+        };
+
+        let storage = Yaml::new(path.clone(), Duration::from_secs(3600));
+        storage.patch_prop(&file, (true, prop.clone())).await?;
+        assert!(!path.exists());
+        storage.flush().await?;
+        assert!(path.exists());
+
+        let storage = Yaml::new(path.clone(), Duration::from_secs(3600));
+        assert_eq!(
+            storage.get_prop(&file, prop.clone()).await?,
+            prop.xml.clone().unwrap()
+        );
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn corrupted_file_set_aside() -> anyhow::Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("webdav_ss_yaml_corrupt_{}.yml", std::process::id()));
+        let aside = path.with_extension("yml.corrupt");
+        std::fs::write(&path, "/dir/file: [{name: ")?;
+
+        let storage = Yaml::new(path.clone(), Duration::from_secs(3600));
+        assert!(storage.get_all().await?.is_empty());
+        assert!(!path.exists());
+        assert_eq!(std::fs::read_to_string(&aside)?, "/dir/file: [{name: ");
+        std::fs::remove_file(&aside)?;
+        Ok(())
+    }
 }
@@ -0,0 +1,78 @@
+use anyhow::anyhow;
+use futures_util::future::BoxFuture;
+use std::{collections::HashMap, sync::Arc};
+use webdav_handler::fs::DavFileSystem;
+
+/// Creates backend from options of its mount.
+pub type Factory = Arc<
+    dyn Fn(&serde_yaml::Value) -> BoxFuture<'static, anyhow::Result<Box<dyn DavFileSystem>>>
+        + Send
+        + Sync,
+>;
+
+/// Backends provided by programs embedding the server. They are mounted with
+/// `type: plugin` and `backend: <name>`, other fields of mount are passed to
+/// factory registered under that name.
+#[derive(Clone, Default)]
+pub struct Registry {
+    factories: HashMap<String, Factory>,
+}
+
+impl Registry {
+    pub fn register(&mut self, name: &str, factory: Factory) {
+        self.factories.insert(name.to_string(), factory);
+    }
+
+    pub async fn create(
+        &self,
+        name: &str,
+        options: &serde_yaml::Value,
+    ) -> anyhow::Result<Box<dyn DavFileSystem>> {
+        let factory = self
+            .factories
+            .get(name)
+            .ok_or_else(|| anyhow!("backend is not registered: {}", name))?;
+        factory(options).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configuration::Filesystem;
+    use futures_util::FutureExt;
+    use webdav_handler::{davpath::DavPath, memfs::MemFs};
+
+    #[tokio::test]
+    async fn creates_registered_backend() -> anyhow::Result<()> {
+        let mut registry = Registry::default();
+        registry.register(
+            "prefilled",
+            Arc::new(|options: &serde_yaml::Value| {
+                let dir = options["dir"].as_str().map(String::from);
+                async move {
+                    let fs = MemFs::new();
+                    if let Some(dir) = dir {
+                        fs.create_dir(&DavPath::new(&dir)?).await?;
+                    }
+                    Ok::<_, anyhow::Error>(fs as Box<dyn DavFileSystem>)
+                }
+                .boxed()
+            }),
+        );
+
+        let conf: Filesystem = serde_yaml::from_str("type: plugin\nbackend: prefilled\ndir: /a/")?;
+        assert_eq!(conf.type_name(), "prefilled");
+        let fs = match conf {
+            Filesystem::Plugin {
+                ref backend,
+                ref options,
+            } => registry.create(backend, options).await?,
+            _ => unreachable!(),
+        };
+        assert!(fs.metadata(&DavPath::new("/a/")?).await?.is_dir());
+        let none = serde_yaml::Value::Null;
+        assert!(registry.create("other", &none).await.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use crate::configuration::Pricing;
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Kind of S3 API request, as providers bill them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Call {
+    Get,
+    Head,
+    Put,
+    Copy,
+    List,
+    Delete,
+    Multipart,
+    Tagging,
+}
+
+impl Call {
+    pub const ALL: [Call; 8] = [
+        Call::Get,
+        Call::Head,
+        Call::Put,
+        Call::Copy,
+        Call::List,
+        Call::Delete,
+        Call::Multipart,
+        Call::Tagging,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Call::Get => "get",
+            Call::Head => "head",
+            Call::Put => "put",
+            Call::Copy => "copy",
+            Call::List => "list",
+            Call::Delete => "delete",
+            Call::Multipart => "multipart",
+            Call::Tagging => "tagging",
+        }
+    }
+
+    /// Requests billed at write rate (class A), reads are class B and
+    /// deletes are usually free.
+    fn is_write(self) -> bool {
+        matches!(
+            self,
+            Call::Put | Call::Copy | Call::List | Call::Multipart | Call::Tagging
+        )
+    }
+}
+
+const MONTH: Duration = Duration::from_secs(30 * 24 * 3600);
+
+/// Requests sent to bucket of a mount and bytes downloaded from it.
+#[derive(Debug)]
+pub struct ApiCalls {
+    counts: [AtomicU64; 8],
+    egress: AtomicU64,
+    since: Instant,
+}
+
+impl Default for ApiCalls {
+    fn default() -> Self {
+        ApiCalls {
+            counts: Default::default(),
+            egress: AtomicU64::new(0),
+            since: Instant::now(),
+        }
+    }
+}
+
+impl ApiCalls {
+    pub(super) fn record(&self, call: Call) {
+        self.counts[call as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn downloaded(&self, bytes: u64) {
+        self.egress.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn count(&self, call: Call) -> u64 {
+        self.counts[call as usize].load(Ordering::Relaxed)
+    }
+
+    pub fn egress(&self) -> u64 {
+        self.egress.load(Ordering::Relaxed)
+    }
+
+    /// Cost of a month at the rate seen since start.
+    pub fn monthly_cost(&self, pricing: &Pricing) -> f64 {
+        self.cost(pricing) * MONTH.as_secs_f64() / self.since.elapsed().as_secs_f64().max(1.0)
+    }
+
+    fn cost(&self, pricing: &Pricing) -> f64 {
+        let (writes, reads) =
+            Call::ALL
+                .iter()
+                .filter(|c| **c != Call::Delete)
+                .fold((0, 0), |(w, r), c| {
+                    if c.is_write() {
+                        (w + self.count(*c), r)
+                    } else {
+                        (w, r + self.count(*c))
+                    }
+                });
+        writes as f64 / 1000.0 * pricing.per_1000_writes
+            + reads as f64 / 1000.0 * pricing.per_1000_reads
+            + self.egress() as f64 / 1e9 * pricing.per_gb_egress
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_cost() {
+        let calls = ApiCalls::default();
+        for _ in 0..2000 {
+            calls.record(Call::Put);
+        }
+        calls.record(Call::Delete);
+        for _ in 0..1000 {
+            calls.record(Call::Head);
+        }
+        calls.downloaded(2_000_000_000);
+        assert_eq!(calls.count(Call::Put), 2000);
+        let pricing = Pricing {
+            per_1000_writes: 0.005,
+            per_1000_reads: 0.0004,
+            per_gb_egress: 0.09,
+        };
+        assert!((calls.cost(&pricing) - (0.01 + 0.0004 + 0.18)).abs() < 1e-9);
+        assert!(calls.monthly_cost(&pricing) >= calls.cost(&pricing));
+    }
+}
@@ -0,0 +1,71 @@
+use super::calls::{ApiCalls, Call};
+use crate::backend::normalized_path::NormalizedPath;
+use s3::Bucket;
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+use tracing::debug;
+
+/// Directory markers are rewritten after their content changes, so
+/// Last-Modified of marker reported as modification time of directory
+/// follows changes. Changes within `delay` are written by one request.
+/// Root has no marker, its time is kept in memory since start.
+#[derive(Clone)]
+pub(super) struct DirTimes {
+    client: Bucket,
+    calls: Arc<ApiCalls>,
+    delay: Duration,
+    pending: Arc<Mutex<HashSet<String>>>,
+    root: Arc<Mutex<SystemTime>>,
+}
+
+impl DirTimes {
+    pub fn new(client: Bucket, calls: Arc<ApiCalls>, delay: Duration) -> DirTimes {
+        DirTimes {
+            client,
+            calls,
+            delay,
+            pending: Arc::new(Mutex::new(HashSet::new())),
+            root: Arc::new(Mutex::new(SystemTime::now())),
+        }
+    }
+
+    /// Modification time of root directory.
+    pub fn root(&self) -> SystemTime {
+        *self.root.lock().unwrap()
+    }
+
+    /// Called once `path` is written, created or removed.
+    pub fn changed(&self, path: &str) {
+        let dir = NormalizedPath::from(path).parent();
+        if dir.is_root() {
+            *self.root.lock().unwrap() = SystemTime::now();
+            return;
+        }
+        let dir = dir.to_string();
+        if !self.pending.lock().unwrap().insert(dir.clone()) {
+            return;
+        }
+        let this = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(this.delay).await;
+            // directory was removed meanwhile
+            if !this.pending.lock().unwrap().remove(&dir) {
+                return;
+            }
+            let marker = format!("{}.dir", dir);
+            this.calls.record(Call::Put);
+            let r = this.client.put_object(&marker, &[]).await;
+            debug!(msg = "touched directory marker", dir = %dir, result = ?r.as_ref().map(|(_, code)| *code));
+        });
+    }
+
+    /// Drops pending update of removed directory `dir`, so its marker is
+    /// not written back.
+    pub fn removed(&self, dir: &str) {
+        let dir = NormalizedPath::from(dir).as_dir().to_string();
+        self.pending.lock().unwrap().remove(&dir);
+    }
+}
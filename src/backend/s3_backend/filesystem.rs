@@ -1,36 +1,89 @@
 use super::{
-    entries::S3DirEntry, metadata::S3MetaData, partial_open_file::PartialOpenFile,
+    calls::{ApiCalls, Call},
+    dir_times::DirTimes,
+    entries::S3DirEntry,
+    metadata::S3MetaData,
+    negative_cache::NegativeCache,
+    partial_open_file::PartialOpenFile,
+    props::SidecarProp,
+    ranged_file::{RangeFetch, RangedReadFile},
+    recent::RecentWrites,
+    region::discover as discover_location,
     simple_open_file::S3SimpleOpenFile,
+    single_flight::SingleFlight,
+    spool::Spool,
+    write_failure,
 };
 use crate::{
-    backend::normalized_path::NormalizedPath,
+    backend::{content_type::ContentTypes, normalized_path::NormalizedPath},
     configuration::{Filesystem, S3AuthFile, S3Authentication},
 };
 use anyhow::{anyhow, Result};
 use futures_util::{FutureExt, StreamExt};
+use hyper::StatusCode;
 use s3::{creds::Credentials, region::Region, Bucket};
 use s3::{serde_types::HeadObjectResult, BucketConfiguration};
+use std::collections::{BTreeSet, HashSet};
 use std::io::{BufReader, Read};
-use tracing::{debug, error, instrument, span, Instrument, Level};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, info, instrument, span, warn, Instrument, Level};
 use webdav_handler::memfs::MemFs;
 use webdav_handler::{
     davpath::DavPath,
     fs::{
-        DavDirEntry, DavFile, DavFileSystem, DavMetaData, FsError, FsFuture, FsStream, OpenOptions,
-        ReadDirMeta,
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, DavProp, FsError, FsFuture, FsStream,
+        OpenOptions, ReadDirMeta,
     },
 };
 
+pub(super) const PROPS_SUFFIX: &str = ".davprops";
+
+/// Changes to directory within it are written to its marker at once.
+const DIR_TOUCH_DELAY: Duration = Duration::from_secs(2);
+
 #[derive(Clone)]
 pub struct S3Backend {
     memfs: Box<MemFs>,
-    client: Bucket,
+    pub(super) client: Bucket,
+    sidecar_props: bool,
+    content_types: ContentTypes,
+    pub(super) missing: NegativeCache,
+    recent: RecentWrites,
+    dirs: DirTimes,
+    pub(super) calls: Arc<ApiCalls>,
+    deep_listing: bool,
+    lookups: SingleFlight<Result<Box<S3MetaData>, FsError>>,
+    spool_threshold: usize,
+    fetch: RangeFetch,
 }
 
 impl S3Backend {
+    pub async fn new(
+        config: Filesystem,
+        content_types: ContentTypes,
+    ) -> Result<Box<dyn DavFileSystem>> {
+        let fs = S3Backend::new_unboxed(config, content_types).await?;
+        Ok(Box::new(fs) as Box<dyn DavFileSystem>)
+    }
+
     #[instrument(level = "info", err)]
-    pub async fn new(config: Filesystem) -> Result<Box<dyn DavFileSystem>> {
-        let (bucket, region, url, path_style, ensure_bucket, auth) = match config {
+    pub async fn new_unboxed(config: Filesystem, content_types: ContentTypes) -> Result<S3Backend> {
+        let (
+            bucket,
+            region,
+            url,
+            path_style,
+            ensure_bucket,
+            auth,
+            sidecar_props,
+            negative_cache_ttl,
+            spool_threshold,
+            discover_region,
+            fetch,
+            listing_cache,
+            deep_listing,
+        ) = match config {
             Filesystem::S3 {
                 bucket,
                 region,
@@ -38,13 +91,44 @@ impl S3Backend {
                 path_style,
                 ensure_bucket,
                 auth,
-            } => (bucket, region, url, path_style, ensure_bucket, auth),
+                sidecar_props,
+                negative_cache_ttl,
+                spool_threshold,
+                discover_region,
+                download_range,
+                download_parallelism,
+                listing_cache,
+                deep_listing,
+                ..
+            } => (
+                bucket,
+                region,
+                url,
+                path_style,
+                ensure_bucket,
+                auth,
+                sidecar_props,
+                negative_cache_ttl,
+                spool_threshold,
+                discover_region,
+                RangeFetch {
+                    range: download_range,
+                    parallelism: download_parallelism,
+                },
+                listing_cache,
+                deep_listing,
+            ),
             _ => return Err(anyhow!("unsupported config")),
         };
         let url = url.to_owned();
-        let region = Region::Custom {
+        // rust-s3 is built with no-verify-ssl, client accepts any certificate
+        if url.starts_with("https://") {
+            warn!(msg = "certificate of S3 endpoint is not verified", url = %url);
+        }
+        let region_name: String = region.parse()?;
+        let mut region = Region::Custom {
             endpoint: url.clone(),
-            region: region.parse()?,
+            region: region_name.clone(),
         };
 
         let creds = match auth {
@@ -74,11 +158,35 @@ impl S3Backend {
             } => Credentials::new(Some(&access_key), Some(&secret_key), None, None, None)?,
         };
         let bucket_name = bucket.to_owned();
-        let bucket = if path_style {
-            Bucket::new_with_path_style(&bucket_name, region.clone(), creds.clone())?
-        } else {
-            Bucket::new(&bucket_name, region.clone(), creds.clone())?
+        let new_bucket = |region: Region| {
+            if path_style {
+                Bucket::new_with_path_style(&bucket_name, region, creds.clone())
+            } else {
+                Bucket::new(&bucket_name, region, creds.clone())
+            }
         };
+        let mut bucket = new_bucket(region.clone())?;
+
+        if discover_region {
+            let found = discover_location(&bucket).await;
+            let endpoint = match found.endpoint {
+                // keep scheme of configured url
+                Some(e) => match url.find("://") {
+                    Some(i) => format!("{}{}", &url[..i + 3], e),
+                    None => e,
+                },
+                None => url.clone(),
+            };
+            let name = found.region.unwrap_or_else(|| region_name.clone());
+            if endpoint != url || name != region_name {
+                info!(msg = "using discovered location", region = %name, endpoint = %endpoint);
+                region = Region::Custom {
+                    endpoint,
+                    region: name,
+                };
+                bucket = new_bucket(region.clone())?;
+            }
+        }
 
         if ensure_bucket {
             let mut config = BucketConfiguration::private();
@@ -109,10 +217,112 @@ impl S3Backend {
             }
         }
 
-        Ok(Box::new(S3Backend {
+        // rust-s3 pages listings with ListObjectsV2 continuation tokens, owners
+        // are not fetched
+        info!(
+            msg = "listing objects with ListObjectsV2",
+            bucket = %bucket_name,
+            delimiter = !deep_listing
+        );
+        let calls = Arc::new(ApiCalls::default());
+        Ok(S3Backend {
+            dirs: DirTimes::new(bucket.clone(), calls.clone(), DIR_TOUCH_DELAY),
             client: bucket,
             memfs: MemFs::new(),
-        }) as Box<dyn DavFileSystem>)
+            sidecar_props,
+            content_types,
+            missing: NegativeCache::new(Duration::from_millis(negative_cache_ttl)),
+            recent: RecentWrites::new(Duration::from_millis(listing_cache)),
+            calls,
+            deep_listing,
+            lookups: SingleFlight::new(),
+            spool_threshold,
+            fetch,
+        })
+    }
+
+    /// Counts requests into `calls`, shared with metrics.
+    pub fn with_calls(mut self, calls: Arc<ApiCalls>) -> S3Backend {
+        self.dirs = DirTimes::new(self.client.clone(), calls.clone(), DIR_TOUCH_DELAY);
+        self.calls = calls;
+        self
+    }
+
+    /// Short-lived url to download object directly from bucket.
+    pub fn presign_get(&self, path: &DavPath, expiry: Duration) -> Result<String> {
+        let path: NormalizedPath = path.into();
+        self.client
+            .presign_get(path.as_ref(), expiry.as_secs() as u32)
+            .map_err(|e| anyhow!("can't presign {}: {}", path.as_ref(), e))
+    }
+
+    /// Key of sidecar object with dead properties. Collections keep them
+    /// next to `.dir` stub.
+    fn props_key(path: &NormalizedPath) -> String {
+        let key = if path.is_collection() {
+            path.join_file(".dir")
+        } else {
+            path.clone()
+        };
+        format!("{}{}", key.as_ref(), PROPS_SUFFIX)
+    }
+
+    fn reserved(&self, path: &NormalizedPath) -> bool {
+        self.sidecar_props && is_props_name(path.as_ref())
+    }
+
+    #[instrument(level = "debug", skip(self), err)]
+    async fn load_props(&self, path: &NormalizedPath) -> Result<Vec<SidecarProp>, FsError> {
+        self.calls.record(Call::Get);
+        let (data, code) = self
+            .client
+            .get_object(S3Backend::props_key(path))
+            .await
+            .map_err(|_| FsError::GeneralFailure)?;
+        match code {
+            200 => bincode::deserialize(&data).map_err(|_| FsError::GeneralFailure),
+            404 => Ok(vec![]),
+            _ => {
+                error!(msg = "can't get props object", code = code);
+                Err(FsError::GeneralFailure)
+            }
+        }
+    }
+
+    #[instrument(level = "debug", skip(self, props), err)]
+    async fn save_props(
+        &self,
+        path: &NormalizedPath,
+        props: &[SidecarProp],
+    ) -> Result<(), FsError> {
+        let key = S3Backend::props_key(path);
+        let (_, code) = if props.is_empty() {
+            self.calls.record(Call::Delete);
+            self.client.delete_object(&key).await
+        } else {
+            let data = bincode::serialize(props).map_err(|_| FsError::GeneralFailure)?;
+            self.calls.record(Call::Put);
+            self.client.put_object(&key, &data).await
+        }
+        .map_err(|_| FsError::GeneralFailure)?;
+
+        if code != 200 && code != 204 {
+            error!(msg = "can't save props object", code = code);
+            return Err(write_failure(code));
+        }
+        Ok(())
+    }
+
+    /// Copies sidecar props object between two object keys if it exists.
+    async fn copy_props(&self, from: &NormalizedPath, to: &NormalizedPath) {
+        if !self.sidecar_props {
+            return;
+        }
+        let from = format!("{}{}", from.as_ref(), PROPS_SUFFIX);
+        let to = format!("{}{}", to.as_ref(), PROPS_SUFFIX);
+        self.calls.record(Call::Copy);
+        let r = self.client.copy_object(from, to).await;
+        debug!(msg = "copy props object", result = ?r.as_ref().map(|(_, code)| *code));
     }
 
     async fn check_bucket(bucket: &Bucket) -> Result<()> {
@@ -121,23 +331,33 @@ impl S3Backend {
         Ok(())
     }
 
-    #[instrument(level = "debug", skip(self), err)]
+    /// Concurrent lookups of the same path (e.g. from PROPFIND fan out) share
+    /// one request.
     async fn metadata_info(&self, path: NormalizedPath) -> Result<Box<S3MetaData>, FsError> {
+        let key = path.to_string();
+        self.lookups.run(&key, || self.lookup_metadata(path)).await
+    }
+
+    #[instrument(level = "debug", skip(self), err)]
+    async fn lookup_metadata(&self, path: NormalizedPath) -> Result<Box<S3MetaData>, FsError> {
         // root dir always exist
         if path.starts_with("/") && path.ends_with("/") {
-            return Ok(Box::new(S3MetaData::extract_from_tags(
-                0,
-                "".into(),
-                true,
-                None,
-                None,
-            )));
+            let mut meta = S3MetaData::extract_from_tags(0, "".into(), true, None, None);
+            meta.modified = self.dirs.root();
+            meta.created = meta.modified;
+            return Ok(Box::new(meta));
+        }
+
+        if self.missing.is_missing(path.as_str()) {
+            debug!(msg = "not found (cached)", path = ?path);
+            return Err(FsError::NotFound);
         }
 
         let mut is_col = false;
         let mut head: Option<(HeadObjectResult, NormalizedPath)> = None;
         // check if it dir or file
         for prefix in [path.join_file(".dir"), path.clone()] {
+            self.calls.record(Call::Head);
             let (resp, code) = self.client.head_object(prefix.clone()).await.unwrap();
             if code != 200 {
                 continue;
@@ -151,6 +371,7 @@ impl S3Backend {
 
         if head.is_none() {
             debug!(msg = "not found", path = ?path);
+            self.missing.insert(path.as_str());
             return Err(FsError::NotFound);
         }
 
@@ -187,57 +408,126 @@ impl S3Backend {
         };
 
         debug!(path_to_prefix = %path);
-        let prefix = if path.ends_with("/") && path.len() == 1 {
+        let prefix: String = if path.ends_with("/") && path.len() == 1 {
             "".into()
         } else {
             path.clone().into()
         };
-        let objects = self.client.list(prefix, Some("/".into())).await.unwrap();
+        let recent = self.recent.children(&prefix);
+        let delimiter = if self.deep_listing {
+            None
+        } else {
+            Some("/".into())
+        };
+        // one request per page, rust-s3 doesn't tell how many were made
+        self.calls.record(Call::List);
+        let objects = self.client.list(prefix.clone(), delimiter).await.unwrap();
 
         debug!(msg = "received entries", entries = ?objects);
+        let (dirs, keys) = if self.deep_listing {
+            let keys = objects.into_iter().flat_map(|e| e.contents).map(|c| c.key);
+            deep_children(&prefix, keys)
+        } else {
+            let mut dirs = vec![];
+            let mut keys = vec![];
+            for e in objects {
+                dirs.extend(e.common_prefixes.into_iter().flatten().map(|d| d.prefix));
+                keys.extend(e.contents.into_iter().map(|c| c.key));
+            }
+            (dirs, keys)
+        };
         let fs = self.clone();
         let s = stream! {
-            for e in objects {
-                if let Some(v) = e.common_prefixes {
-                    for d in v {
-                        let m = fs.metadata_info(d.prefix.clone().into()).await;
-                        if let Err(_) = m {
-                            continue;
-                        }
-                        let p: NormalizedPath = d.prefix.clone().into();
-                        let p = p.strip_prefix(&path);
-                        debug!(msg = "generating entry for dir", prefix = ?p);
-                        yield Box::new(S3DirEntry {
-                            metadata: m.unwrap(),
-                            name: p.into(),
-                        }) as Box<dyn DavDirEntry>;
-                    }
+            let mut listed = HashSet::new();
+            for d in dirs {
+                listed.insert(d.clone());
+                let m = fs.metadata_info(d.clone().into()).await;
+                if let Err(_) = m {
+                    continue;
                 }
+                let p: NormalizedPath = d.into();
+                let p = p.strip_prefix(&path);
+                debug!(msg = "generating entry for dir", prefix = ?p);
+                yield Box::new(S3DirEntry {
+                    metadata: m.unwrap(),
+                    name: p.into(),
+                }) as Box<dyn DavDirEntry>;
+            }
 
-                for c in e.contents {
-                    let prefix: NormalizedPath = c.key.into();
-                    if prefix.ends_with(".dir") {
-                        continue;
-                    }
-                    let meta = fs.metadata_info(prefix.clone().into()).await;
-                    if let Err(_) = meta {
-                        debug!(msg = "error metadata for entry", prefix = ?prefix);
-                        continue;
-                    }
-                    let prefix = prefix.strip_prefix(&path);
-                    debug!(msg = "generating entry for", prefix = ?prefix);
-                    let entry = Box::new(S3DirEntry {
-                        metadata: meta.unwrap(),
-                        name: prefix.into(),
-                    }) as Box<dyn DavDirEntry>;
-                    yield entry;
+            for key in keys {
+                listed.insert(key.clone());
+                let prefix: NormalizedPath = key.into();
+                if prefix.ends_with(".dir") {
+                    continue;
+                }
+                if fs.sidecar_props && prefix.ends_with(PROPS_SUFFIX) {
+                    continue;
+                }
+                let meta = fs.metadata_info(prefix.clone().into()).await;
+                if let Err(_) = meta {
+                    debug!(msg = "error metadata for entry", prefix = ?prefix);
+                    continue;
                 }
+                let prefix = prefix.strip_prefix(&path);
+                debug!(msg = "generating entry for", prefix = ?prefix);
+                let entry = Box::new(S3DirEntry {
+                    metadata: meta.unwrap(),
+                    name: prefix.into(),
+                }) as Box<dyn DavDirEntry>;
+                yield entry;
+            }
+
+            // own writes store does not list yet
+            for p in recent.into_iter().filter(|p| !listed.contains(p)) {
+                let p: NormalizedPath = p.into();
+                let meta = match fs.metadata_info(p.clone()).await {
+                    Ok(m) => m,
+                    Err(_) => continue,
+                };
+                debug!(msg = "adding recently written entry", path = ?p);
+                yield Box::new(S3DirEntry {
+                    metadata: meta,
+                    name: p.strip_prefix(&path).into(),
+                }) as Box<dyn DavDirEntry>;
             }
         };
 
         Ok(Box::pin(s) as FsStream<Box<dyn DavDirEntry>>)
     }
 
+    /// Objects bigger than spool threshold are fetched by ranges straight
+    /// to disk, several ranges at once.
+    async fn download(&self, path: &NormalizedPath, len: u64) -> Result<Spool, FsError> {
+        if len <= self.spool_threshold as u64 {
+            self.calls.record(Call::Get);
+            let (obj, code) = self
+                .client
+                .get_object(path.as_ref())
+                .await
+                .map_err(|_| FsError::GeneralFailure)?;
+
+            if code != 200 {
+                error!(msg = "cant get object", code = code);
+                return Err(FsError::GeneralFailure);
+            }
+
+            debug!(msg = "received data", length = obj.len());
+            self.calls.downloaded(obj.len() as u64);
+            return Ok(Spool::from_vec(obj, self.spool_threshold));
+        }
+
+        let mut spool = Spool::new(self.spool_threshold);
+        let mut ranges = self
+            .fetch
+            .stream(&self.client, &self.calls, path.as_ref(), 0, len);
+        while let Some(obj) = ranges.next().await {
+            spool.write_all(&obj?).await?;
+        }
+        debug!(msg = "received data to disk", length = len);
+        spool.seek(std::io::SeekFrom::Start(0)).await?;
+        Ok(spool)
+    }
+
     #[instrument(level = "debug", err, skip(self))]
     async fn remove_file_impl(&self, path: NormalizedPath, dir_check: bool) -> Result<(), FsError> {
         debug!(path = ?path, dir_check = dir_check);
@@ -252,12 +542,20 @@ impl S3Backend {
                 }
             }
         };
+        self.calls.record(Call::Delete);
         let (_, code) = self.client.delete_object(path.as_ref()).await.unwrap();
 
         debug!(code = code);
         if code != 204 {
-            return Err(FsError::GeneralFailure);
+            return Err(write_failure(code));
+        }
+
+        if self.sidecar_props {
+            let key = format!("{}{}", path.as_ref(), PROPS_SUFFIX);
+            self.calls.record(Call::Delete);
+            let _ = self.client.delete_object(&key).await;
         }
+        self.dirs.changed(path.as_str());
 
         Ok(())
     }
@@ -278,59 +576,47 @@ impl S3Backend {
 
         let dir_file = path.join_file(".dir");
         self.remove_file_impl(dir_file, false).await?;
+        self.dirs.removed(path.as_str());
+        self.dirs.changed(path.as_dir().as_str());
 
         Ok(())
     }
 
     #[instrument(level = "debug", err, skip(self))]
     async fn create_dir_impl(&self, path: NormalizedPath) -> Result<(), FsError> {
-        let meta = self.metadata_info(path.clone()).await;
-        if let Ok(m) = meta {
-            if m.is_dir() {
-                debug!(msg = "dir already exist", path = ?path);
-                return Err(FsError::Exists);
-            }
+        let dir = path.as_dir();
+        if dir.is_root() || self.has_objects(&dir).await? {
+            debug!(msg = "dir already exist", path = ?path);
+            return Err(FsError::Exists);
         }
 
-        if path.is_collection() {
-            let p = path.strip_suffix("/").unwrap();
-            let meta = self.metadata_info(p.clone().into()).await;
-            if let Ok(_) = meta {
-                return Err(FsError::Forbidden);
-            }
-        }
-
-        let prefix_dir = path.join_file(".dir");
-        if path.ends_with("/") && path.starts_with("/") {
-            let (resp, code) = self
+        let file = dir.as_file();
+        if !self.missing.is_missing(file.as_str()) {
+            self.calls.record(Call::Head);
+            let (_, code) = self
                 .client
-                .put_object(prefix_dir.clone(), &[])
+                .head_object(file.clone())
                 .await
-                .unwrap();
-
-            debug!(msg = "creating stub dir file", resp = ?resp, code = code, prefix = ?path);
-            if code != 200 {
-                return Err(FsError::GeneralFailure);
+                .map_err(|_| FsError::GeneralFailure)?;
+            if code == 200 {
+                debug!(msg = "file with the same name exists", path = ?file);
+                return Err(FsError::Forbidden);
             }
-            return Ok(());
         }
 
-        // let pb = prefix_dir.as_pathbuf();
-        let parent = prefix_dir.dirs_parent();
-        let meta = self.metadata_info(parent.clone().into()).await;
-        match meta {
-            Err(e) => {
-                debug!(msg = "parent folder does not exist", parent = ?parent, err = %e);
-                return Err(e);
-            }
-            Ok(k) => {
-                if k.is_file() {
-                    debug!(msg = "tried to create subfolder in file", parent = ?parent);
-                    return Err(FsError::Forbidden);
-                }
-            }
-        };
+        // parent created by us lately is known without asking the store
+        let parent = dir.parent();
+        if !parent.is_root()
+            && !self.recent.contains(parent.as_str())
+            && !self.has_objects(&parent).await?
+        {
+            debug!(msg = "parent folder does not exist", parent = ?parent);
+            return Err(FsError::NotFound);
+        }
 
+        let prefix_dir = dir.join_file(".dir");
+        self.missing.invalidate(dir.as_str());
+        self.calls.record(Call::Put);
         let (resp, code) = self
             .client
             .put_object(prefix_dir.clone(), &[])
@@ -339,12 +625,30 @@ impl S3Backend {
 
         debug!(msg = "creating stub dir file", resp = ?resp, code = code, prefix = ?prefix_dir);
         if code != 200 {
-            return Err(FsError::GeneralFailure);
+            return Err(write_failure(code));
         }
+        self.recent.written(dir.as_str());
+        self.dirs.changed(dir.as_str());
 
         Ok(())
     }
 
+    /// Whether collection `dir` has its marker or anything else stored
+    /// under it, by listing a single key. Directories made by other tools
+    /// often have content but no marker.
+    async fn has_objects(&self, dir: &NormalizedPath) -> Result<bool, FsError> {
+        self.calls.record(Call::List);
+        let (page, code) = self
+            .client
+            .list_page(dir.as_str().to_string(), None, None, None, Some(1))
+            .await
+            .map_err(|_| FsError::GeneralFailure)?;
+        if code != 200 {
+            return Err(write_failure(code));
+        }
+        Ok(!page.contents.is_empty())
+    }
+
     #[instrument(level = "debug", err, skip(self))]
     async fn copy_impl(
         &self,
@@ -368,16 +672,28 @@ impl S3Backend {
             self.create_dir_impl(to.parent()).await?;
         }
 
+        self.missing.invalidate(to.as_str());
+        self.missing.invalidate(to.parent().as_str());
+        self.calls.record(Call::Copy);
         let (_, code) = self
             .client
-            .copy_object(from.into(), to.into())
+            .copy_object(from.clone().into(), to.clone().into())
             .await
             .unwrap();
 
         if code != 200 {
-            return Err(FsError::GeneralFailure);
+            return Err(write_failure(code));
+        }
+        if to.ends_with(".dir") {
+            self.recent.written(to.parent().as_str());
+            self.dirs.changed(to.parent().as_str());
+        } else {
+            self.recent.written(to.as_str());
+            self.dirs.changed(to.as_str());
         }
 
+        self.copy_props(&from, &to).await;
+
         Ok(())
     }
 
@@ -397,7 +713,7 @@ impl S3Backend {
 
         if !from.is_collection() && !to.is_collection() {
             self.copy_impl(from.clone(), to).await?;
-            self.remove_file_impl(from, true).await?;
+            self.remove_moved(from, false).await;
             return Ok(());
         }
 
@@ -410,6 +726,7 @@ impl S3Backend {
         let mut paths = vec![];
         let mut dirs_to_remove = vec![];
         let mut dirs_to_create = vec![to.clone()];
+        let mut dir_props = vec![(from.clone(), to.clone())];
 
         while !dirs.is_empty() {
             let path = dirs.pop().unwrap();
@@ -427,6 +744,7 @@ impl S3Backend {
                 if obj.is_dir().await? {
                     dirs.push(path.join_dir(&suffix));
                     dirs_to_create.push(to.join_dir(&suffix));
+                    dir_props.push((path.join_dir(&suffix), to.join_dir(&suffix)));
                 } else {
                     let to = to.join_file(&suffix);
                     paths.push((path.join_file(&suffix), to))
@@ -434,21 +752,70 @@ impl S3Backend {
             }
         }
 
+        // whole tree is copied before anything is removed, so failure leaves
+        // source as it was and copies are rolled back
+        let mut created = vec![];
         for dir in dirs_to_create {
-            self.create_dir_impl(dir).await?;
+            if let Err(e) = self.create_dir_impl(dir.clone()).await {
+                self.roll_back_move(vec![], created).await;
+                return Err(e);
+            }
+            created.push(dir);
         }
 
-        for (from, to) in paths {
-            self.copy_impl(from.clone(), to).await?;
-            self.remove_file_impl(from, true).await?;
+        for (from, to) in &dir_props {
+            self.copy_props(&from.join_file(".dir"), &to.join_file(".dir"))
+                .await;
+        }
+
+        let mut copied = vec![];
+        for (from, to) in &paths {
+            if let Err(e) = self.copy_impl(from.clone(), to.clone()).await {
+                self.roll_back_move(copied, created).await;
+                return Err(e);
+            }
+            copied.push(to.clone());
+        }
+
+        for (from, _) in paths {
+            self.remove_moved(from, false).await;
         }
 
         for dir in dirs_to_remove {
-            self.remove_dir_impl(dir).await?;
+            self.remove_moved(dir, true).await;
         }
 
         Ok(())
     }
+
+    /// Removes source of move once its target is complete. Failure leaves
+    /// an extra copy, which is logged instead of failing the move: client
+    /// retrying it would replace complete target with what is left.
+    async fn remove_moved(&self, path: NormalizedPath, is_dir: bool) {
+        let r = if is_dir {
+            self.remove_dir_impl(path.clone()).await
+        } else {
+            self.remove_file_impl(path.clone(), true).await
+        };
+        if let Err(e) = r {
+            warn!(msg = "moved path left at source", path = ?path, err = ?e);
+        }
+    }
+
+    /// Removes files and directories copied by move which failed, deepest
+    /// directories first.
+    async fn roll_back_move(&self, files: Vec<NormalizedPath>, dirs: Vec<NormalizedPath>) {
+        for file in files {
+            if let Err(e) = self.remove_file_impl(file.clone(), true).await {
+                error!(msg = "can't roll back moved file", path = ?file, err = ?e);
+            }
+        }
+        for dir in dirs.into_iter().rev() {
+            if let Err(e) = self.remove_dir_impl(dir.clone()).await {
+                error!(msg = "can't roll back moved directory", path = ?dir, err = ?e);
+            }
+        }
+    }
 }
 
 impl DavFileSystem for S3Backend {
@@ -456,6 +823,13 @@ impl DavFileSystem for S3Backend {
         let span = span!(Level::INFO, "S3Backend::open");
         async move {
             let path: NormalizedPath = path.into();
+            if self.reserved(&path) {
+                return Err(if options.write || options.create {
+                    FsError::Forbidden
+                } else {
+                    FsError::NotFound
+                });
+            }
             let meta = self.metadata_info(path.clone()).await;
             match meta {
                 Ok(k) => {
@@ -474,49 +848,65 @@ impl DavFileSystem for S3Backend {
                 Err(e) => return Err(e),
             }
 
-            let mut buf = vec![];
+            self.calls.record(Call::Head);
             let (head, code) = self
                 .client
                 .head_object(path.as_ref())
                 .await
                 .map_err(|_| FsError::GeneralFailure)?;
 
-            if code == 200 {
-                let (obj, code) = self
-                    .client
-                    .get_object(path.as_ref())
-                    .await
-                    .map_err(|_| FsError::GeneralFailure)?;
-
-                if code != 200 {
-                    error!(msg = "cant get object", code = code);
-                    return Err(FsError::GeneralFailure);
-                }
-
-                debug!(msg = "received data", length = obj.len());
-                buf = obj;
-            }
-
-            debug!(is_new = %options.create, path = ?path);
-
             let len = head.content_length.unwrap_or(0i64) as u64;
             let etag = head.e_tag;
             let modified = head.last_modified;
             let metadata =
                 S3MetaData::extract_from_tags(len, path.clone().into(), false, etag, modified);
 
+            // large downloads are streamed while ranges arrive
+            if code == 200 && !options.write && len > self.fetch.range {
+                return Ok(Box::new(RangedReadFile::new(
+                    metadata,
+                    path.into(),
+                    self.client.clone(),
+                    self.calls.clone(),
+                    self.fetch,
+                )) as Box<dyn DavFile>);
+            }
+
+            let mut spool = Spool::new(self.spool_threshold);
+            if code == 200 && !options.create && !options.truncate {
+                spool = self.download(&path, len).await?;
+            }
+
+            debug!(is_new = %options.create, path = ?path);
+
+            let content_type = self.content_types.guess(path.as_str());
+
             if options.create {
                 Ok(Box::new(
-                    PartialOpenFile::new(metadata, options, path.into(), self.client.clone())
-                        .await?,
+                    PartialOpenFile::new(
+                        metadata,
+                        options,
+                        path.into(),
+                        self.client.clone(),
+                        &content_type,
+                        self.missing.clone(),
+                        self.recent.clone(),
+                        self.dirs.clone(),
+                        self.calls.clone(),
+                    )
+                    .await?,
                 ) as Box<dyn DavFile>)
             } else {
                 Ok(Box::new(S3SimpleOpenFile::new(
                     metadata,
-                    buf,
+                    spool,
                     options,
                     path.into(),
                     self.client.clone(),
+                    content_type,
+                    self.missing.clone(),
+                    self.dirs.clone(),
+                    self.calls.clone(),
                 )) as Box<dyn DavFile>)
             }
         }
@@ -540,15 +930,24 @@ impl DavFileSystem for S3Backend {
 
     fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
         let span = span!(Level::INFO, "S3Backend::metadata");
-        async move { Ok(self.metadata_info(path.into()).await? as Box<dyn DavMetaData>) }
-            .instrument(span)
-            .boxed()
+        async move {
+            let path: NormalizedPath = path.into();
+            if self.reserved(&path) {
+                return Err(FsError::NotFound);
+            }
+            Ok(self.metadata_info(path).await? as Box<dyn DavMetaData>)
+        }
+        .instrument(span)
+        .boxed()
     }
 
     fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
         let span = span!(Level::INFO, "S3Backend::create_dir");
         async move {
             let path: NormalizedPath = path.into();
+            if self.reserved(&path) {
+                return Err(FsError::Forbidden);
+            }
             Ok(self.create_dir_impl(path).await?)
         }
         .instrument(span)
@@ -559,6 +958,9 @@ impl DavFileSystem for S3Backend {
         let span = span!(Level::INFO, "S3Backend::remove_file");
         async move {
             let path: NormalizedPath = path.into();
+            if self.reserved(&path) {
+                return Err(FsError::NotFound);
+            }
             Ok(self.remove_file_impl(path, true).await.unwrap())
         }
         .instrument(span)
@@ -569,6 +971,9 @@ impl DavFileSystem for S3Backend {
         let span = span!(Level::INFO, "S3Backend::remove_dir");
         async move {
             let path: NormalizedPath = path.into();
+            if self.reserved(&path) {
+                return Err(FsError::NotFound);
+            }
             Ok(self.remove_dir_impl(path).await?)
         }
         .instrument(span)
@@ -580,6 +985,12 @@ impl DavFileSystem for S3Backend {
         async move {
             let from: NormalizedPath = from.into();
             let to: NormalizedPath = to.into();
+            if self.reserved(&from) {
+                return Err(FsError::NotFound);
+            }
+            if self.reserved(&to) {
+                return Err(FsError::Forbidden);
+            }
             Ok(self.rename_impl(from, to).await?)
         }
         .instrument(span)
@@ -591,6 +1002,12 @@ impl DavFileSystem for S3Backend {
         async move {
             let from: NormalizedPath = from.into();
             let to: NormalizedPath = to.into();
+            if self.reserved(&from) {
+                return Err(FsError::NotFound);
+            }
+            if self.reserved(&to) {
+                return Err(FsError::Forbidden);
+            }
             debug!(method = "copy", from = ?from, to = ?to);
             Ok(self.copy_impl(from, to).await?)
         }
@@ -600,48 +1017,151 @@ impl DavFileSystem for S3Backend {
 
     fn have_props<'a>(
         &'a self,
-        _: &'a DavPath,
+        path: &'a DavPath,
     ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
         let span = span!(Level::INFO, "S3Backend::have_props");
-        async move { true }.instrument(span).boxed()
+        async move {
+            if !self.sidecar_props {
+                return true;
+            }
+            let path: NormalizedPath = path.into();
+            self.load_props(&path)
+                .await
+                .map(|p| !p.is_empty())
+                .unwrap_or(false)
+        }
+        .instrument(span)
+        .boxed()
     }
 
     fn patch_props<'a>(
         &'a self,
-        _path: &'a DavPath,
-        _patch: Vec<(bool, webdav_handler::fs::DavProp)>,
-    ) -> FsFuture<Vec<(hyper::StatusCode, webdav_handler::fs::DavProp)>> {
+        path: &'a DavPath,
+        patch: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(StatusCode, DavProp)>> {
         let span = span!(Level::INFO, "S3Backend::patch_props");
         async move {
-            return Err(FsError::NotImplemented);
+            if !self.sidecar_props {
+                return Err(FsError::NotImplemented);
+            }
+            let path: NormalizedPath = path.into();
+            let mut props = self.load_props(&path).await?;
+            let mut r = vec![];
+            for (set, prop) in patch {
+                props.retain(|p| !p.same(&prop));
+                let mut p_c = prop.clone();
+                p_c.xml = None;
+                if set {
+                    props.push(prop.into());
+                }
+                r.push((StatusCode::OK, p_c));
+            }
+            self.save_props(&path, &props).await?;
+            Ok(r)
         }
         .instrument(span)
         .boxed()
     }
 
-    fn get_prop<'a>(
-        &'a self,
-        _path: &'a DavPath,
-        _prop: webdav_handler::fs::DavProp,
-    ) -> FsFuture<Vec<u8>> {
+    fn get_prop<'a>(&'a self, path: &'a DavPath, prop: DavProp) -> FsFuture<Vec<u8>> {
         let span = span!(Level::INFO, "S3Backend::get_prop");
         async move {
-            return Err(FsError::NotImplemented);
+            if !self.sidecar_props {
+                return Err(FsError::NotImplemented);
+            }
+            let path: NormalizedPath = path.into();
+            self.load_props(&path)
+                .await?
+                .into_iter()
+                .find(|p| p.same(&prop))
+                .and_then(|p| p.xml)
+                .ok_or(FsError::NotFound)
         }
         .instrument(span)
         .boxed()
     }
 
-    fn get_props<'a>(
-        &'a self,
-        _path: &'a DavPath,
-        _do_content: bool,
-    ) -> FsFuture<Vec<webdav_handler::fs::DavProp>> {
-        let span = span!(Level::INFO, "S3Backend::get_prop");
+    fn get_props<'a>(&'a self, path: &'a DavPath, do_content: bool) -> FsFuture<Vec<DavProp>> {
+        let span = span!(Level::INFO, "S3Backend::get_props");
         async move {
-            return Err(FsError::NotImplemented);
+            if !self.sidecar_props {
+                return Err(FsError::NotImplemented);
+            }
+            let path: NormalizedPath = path.into();
+            Ok(self
+                .load_props(&path)
+                .await?
+                .into_iter()
+                .map(|p| {
+                    let mut p: DavProp = p.into();
+                    if !do_content {
+                        p.xml = None;
+                    }
+                    p
+                })
+                .collect())
         }
         .instrument(span)
         .boxed()
     }
 }
+
+/// Direct children of collection `prefix` found in keys of the whole subtree,
+/// as listing with delimiter would return them: collection prefixes and keys
+/// of files.
+fn deep_children(prefix: &str, keys: impl Iterator<Item = String>) -> (Vec<String>, Vec<String>) {
+    let mut dirs = BTreeSet::new();
+    let mut files = vec![];
+    for key in keys {
+        let rest = match key.strip_prefix(prefix) {
+            Some(r) if !r.is_empty() => r,
+            _ => continue,
+        };
+        match rest.find('/') {
+            Some(i) => {
+                dirs.insert(format!("{}{}", prefix, &rest[..=i]));
+            }
+            None => files.push(key),
+        }
+    }
+    (dirs.into_iter().collect(), files)
+}
+
+/// Whether `path` names a sidecar props object, such names are reserved on
+/// mounts keeping props in sidecars.
+fn is_props_name(path: &str) -> bool {
+    path.trim_end_matches('/').ends_with(PROPS_SUFFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn children_of_deep_listing() {
+        let keys = [
+            "a/.dir",
+            "a/x.txt",
+            "a/b/.dir",
+            "a/b/c/.dir",
+            "a/b/c/y.txt",
+            "a/d/z.txt",
+        ];
+        let (dirs, files) = deep_children("a/", keys.iter().map(|k| k.to_string()));
+        assert_eq!(dirs, vec!["a/b/", "a/d/"]);
+        assert_eq!(files, vec!["a/.dir", "a/x.txt"]);
+
+        let (dirs, files) = deep_children("", keys.iter().map(|k| k.to_string()));
+        assert_eq!(dirs, vec!["a/"]);
+        assert!(files.is_empty());
+    }
+
+    #[test]
+    fn props_names() {
+        assert!(is_props_name("a/b.txt.davprops"));
+        assert!(is_props_name("a/.dir.davprops"));
+        assert!(is_props_name("a/x.davprops/"));
+        assert!(!is_props_name("a/b.txt"));
+        assert!(!is_props_name("a/davprops"));
+    }
+}
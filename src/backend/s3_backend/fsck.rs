@@ -0,0 +1,144 @@
+use super::{
+    filesystem::{S3Backend, PROPS_SUFFIX},
+    snapshot::SNAPSHOT_PREFIX,
+};
+use anyhow::{anyhow, Result};
+use std::collections::{BTreeSet, HashSet};
+use tracing::{debug, error};
+
+const DIR_MARKER: &str = ".dir";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Issue {
+    /// objects exist under prefix but directory has no `.dir` marker, so it
+    /// is invisible through webdav
+    MissingMarker(String),
+    /// both file object and directory marker exist for the same name
+    FileDirConflict(String),
+    /// sidecar props object without object it belongs to
+    OrphanProps(String),
+}
+
+impl Issue {
+    pub fn repairable(&self) -> bool {
+        !matches!(self, Issue::FileDirConflict(_))
+    }
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Issue::MissingMarker(d) => write!(f, "missing dir marker: {}", d),
+            Issue::FileDirConflict(p) => write!(f, "file and dir with same name: {}", p),
+            Issue::OrphanProps(k) => write!(f, "orphaned props object: {}", k),
+        }
+    }
+}
+
+/// Finds inconsistencies in flat list of object keys.
+pub(crate) fn check_keys(keys: &[String]) -> Vec<Issue> {
+    let all: HashSet<&str> = keys.iter().map(|k| k.as_str()).collect();
+    let mut dirs = BTreeSet::new();
+    let mut issues = vec![];
+
+    for key in keys {
+        let mut parts: Vec<&str> = key.split('/').collect();
+        parts.pop();
+        for i in 1..=parts.len() {
+            dirs.insert(format!("{}/", parts[..i].join("/")));
+        }
+
+        if let Some(base) = key.strip_suffix(PROPS_SUFFIX) {
+            if !all.contains(base) {
+                issues.push(Issue::OrphanProps(key.clone()));
+            }
+        }
+    }
+
+    for dir in dirs {
+        if !all.contains(format!("{}{}", dir, DIR_MARKER).as_str()) {
+            issues.push(Issue::MissingMarker(dir.clone()));
+        }
+        if all.contains(dir.trim_end_matches('/')) {
+            issues.push(Issue::FileDirConflict(dir.trim_end_matches('/').into()));
+        }
+    }
+    issues
+}
+
+impl S3Backend {
+    /// Scans whole bucket for inconsistencies left by interrupted operations
+    /// and optionally repairs them.
+    pub async fn fsck(&self, repair: bool) -> Result<Vec<Issue>> {
+        let results = self
+            .client
+            .list("".into(), None)
+            .await
+            .map_err(|e| anyhow!("can't list bucket: {}", e))?;
+        let keys: Vec<String> = results
+            .into_iter()
+            .flat_map(|r| r.contents.into_iter().map(|c| c.key))
+            .filter(|k| !k.starts_with(SNAPSHOT_PREFIX))
+            .collect();
+        debug!(objects = keys.len());
+
+        let issues = check_keys(&keys);
+        if !repair {
+            return Ok(issues);
+        }
+        for issue in &issues {
+            let r = match issue {
+                Issue::MissingMarker(dir) => self
+                    .client
+                    .put_object(format!("{}{}", dir, DIR_MARKER), &[])
+                    .await
+                    .map(|(_, code)| code),
+                Issue::OrphanProps(key) => {
+                    self.client.delete_object(key).await.map(|(_, code)| code)
+                }
+                Issue::FileDirConflict(_) => continue,
+            };
+            match r {
+                Ok(200) | Ok(204) => {}
+                r => {
+                    error!(msg = "can't repair", issue = %issue, result = ?r);
+                    return Err(anyhow!("can't repair {}", issue));
+                }
+            }
+        }
+        self.missing.clear();
+        Ok(issues)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn issues() {
+        let keys = [
+            "docs/.dir",
+            "docs/a.txt",
+            "docs/a.txt.davprops",
+            "docs/gone.txt.davprops",
+            "docs/sub/b.txt",
+            "photos",
+            "photos/.dir",
+            "top.txt",
+        ]
+        .iter()
+        .map(|k| k.to_string())
+        .collect::<Vec<_>>();
+        let issues = check_keys(&keys);
+        assert_eq!(
+            issues,
+            vec![
+                Issue::OrphanProps("docs/gone.txt.davprops".into()),
+                Issue::MissingMarker("docs/sub/".into()),
+                Issue::FileDirConflict("photos".into()),
+            ]
+        );
+        assert!(!issues[2].repairable());
+    }
+}
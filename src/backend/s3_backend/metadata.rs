@@ -1,4 +1,5 @@
 use chrono::DateTime;
+use s3::Bucket;
 use serde::{Deserialize, Serialize};
 use std::time::SystemTime;
 use webdav_handler::fs::{DavMetaData, FsResult};
@@ -48,10 +49,21 @@ impl S3MetaData {
         metadata.is_dir = is_dir;
         metadata.etag = etag;
         metadata.modified = m.unwrap_or(SystemTime::now());
+        // storage keeps no creation time, last write is the best known
+        metadata.created = metadata.modified;
 
         metadata
     }
 
+    /// Takes etag storage assigned to written object, so response to PUT
+    /// carries the same ETag as later GET and HEAD.
+    pub async fn refresh_etag(&mut self, client: &Bucket, path: &str) {
+        self.etag = match client.head_object(path).await {
+            Ok((head, 200)) => head.e_tag,
+            _ => None,
+        };
+    }
+
     fn as_unixtime(t: SystemTime) -> String {
         if let Ok(n) = t.duration_since(std::time::UNIX_EPOCH) {
             n.as_secs().to_string()
@@ -90,6 +102,13 @@ impl DavMetaData for S3MetaData {
     }
 
     fn etag(&self) -> Option<String> {
+        // strong etag from storage, handler adds quotes by itself
+        if let Some(ref etag) = self.etag {
+            let etag = etag.trim_start_matches("W/").trim_matches('"');
+            if !etag.is_empty() {
+                return Some(etag.to_string());
+            }
+        }
         if let Ok(t) = self.modified() {
             if let Ok(t) = t.duration_since(std::time::UNIX_EPOCH) {
                 let t = t.as_secs() * 1000000 + t.subsec_nanos() as u64 / 1000;
@@ -104,3 +123,24 @@ impl DavMetaData for S3MetaData {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backend_etag() {
+        let m = S3MetaData::extract_from_tags(
+            4,
+            "file".into(),
+            false,
+            Some("\"9b2cf535f27731c974343645a3985328\"".into()),
+            Some("Wed, 12 Oct 2009 17:50:00 GMT".into()),
+        );
+        assert_eq!(m.etag(), Some("9b2cf535f27731c974343645a3985328".into()));
+        assert_eq!(m.created().unwrap(), m.modified().unwrap());
+
+        let m = S3MetaData::extract_from_tags(4, "file".into(), false, None, None);
+        assert!(m.etag().unwrap().starts_with("4-"));
+    }
+}
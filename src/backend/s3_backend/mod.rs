@@ -1,7 +1,31 @@
+mod calls;
+mod dir_times;
+mod entries;
 mod filesystem;
+mod fsck;
 mod metadata;
-mod entries;
-mod simple_open_file;
+mod negative_cache;
 mod partial_open_file;
+mod props;
+mod ranged_file;
+mod recent;
+mod region;
+mod simple_open_file;
+mod single_flight;
+mod snapshot;
+mod spool;
+
+pub use calls::{ApiCalls, Call};
+pub use filesystem::S3Backend;
+pub use fsck::Issue;
+
+use webdav_handler::fs::FsError;
 
-pub use filesystem::S3Backend;
\ No newline at end of file
+/// Error for unsuccessful write response, bucket denying writes is reported
+/// as forbidden so it is not mistaken for outage.
+pub(crate) fn write_failure(code: u16) -> FsError {
+    match code {
+        403 => FsError::Forbidden,
+        _ => FsError::GeneralFailure,
+    }
+}
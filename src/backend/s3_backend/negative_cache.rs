@@ -0,0 +1,99 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Entries above which expired ones are dropped on insert.
+const PRUNE_THRESHOLD: usize = 4096;
+
+/// Remembers paths which were not found for a short time, so clients probing
+/// for lock files and `.DS_Store` don't cost two HEAD requests every time.
+/// Anything creating a path must invalidate it.
+#[derive(Clone)]
+pub(super) struct NegativeCache {
+    ttl: Duration,
+    entries: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+impl NegativeCache {
+    /// Zero `ttl` disables caching.
+    pub fn new(ttl: Duration) -> NegativeCache {
+        NegativeCache {
+            ttl,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// File and collection variants of path share an entry because both are
+    /// checked by a single metadata lookup.
+    fn key(path: &str) -> &str {
+        path.trim_end_matches('/')
+    }
+
+    pub fn is_missing(&self, path: &str) -> bool {
+        if self.ttl.is_zero() {
+            return false;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(Self::key(path)) {
+            Some(expires) if *expires > Instant::now() => true,
+            Some(_) => {
+                entries.remove(Self::key(path));
+                false
+            }
+            None => false,
+        }
+    }
+
+    pub fn insert(&self, path: &str) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= PRUNE_THRESHOLD {
+            entries.retain(|_, expires| *expires > now);
+        }
+        entries.insert(Self::key(path).to_string(), now + self.ttl);
+    }
+
+    pub fn invalidate(&self, path: &str) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        self.entries.lock().unwrap().remove(Self::key(path));
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expires_and_invalidates() {
+        let cache = NegativeCache::new(Duration::from_millis(50));
+        cache.insert("/dir/.DS_Store");
+        assert!(cache.is_missing("/dir/.DS_Store"));
+        assert!(!cache.is_missing("/dir/other"));
+
+        cache.insert("/dir/sub/");
+        assert!(cache.is_missing("/dir/sub"));
+        cache.invalidate("/dir/sub");
+        assert!(!cache.is_missing("/dir/sub/"));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(!cache.is_missing("/dir/.DS_Store"));
+    }
+
+    #[test]
+    fn disabled() {
+        let cache = NegativeCache::new(Duration::from_secs(0));
+        cache.insert("/file");
+        assert!(!cache.is_missing("/file"));
+    }
+}
@@ -1,12 +1,19 @@
-use super::metadata::S3MetaData;
+use super::{
+    calls::{ApiCalls, Call},
+    dir_times::DirTimes,
+    metadata::S3MetaData,
+    negative_cache::NegativeCache,
+    recent::RecentWrites,
+    write_failure,
+};
 use crate::backend::normalized_path::NormalizedPath;
-use bytes::Buf;
+use bytes::{BufMut, Bytes, BytesMut};
 use futures_util::FutureExt;
 use s3::serde_types::Part;
 use s3::Bucket;
-use std::io::Write;
+use std::convert::TryInto;
+use std::sync::Arc;
 use std::time::SystemTime;
-use std::{convert::TryInto, io::Cursor};
 use tracing::{debug, error, instrument};
 use webdav_handler::fs::{DavFile, DavMetaData, FsError, FsFuture, FsResult, OpenOptions};
 
@@ -20,17 +27,36 @@ pub struct PartialOpenFile {
     metadata: S3MetaData,
     etags: Vec<String>,
     upload_id: String,
-    cursor: Cursor<Vec<u8>>,
+    #[derivative(Debug = "ignore")]
+    buf: BytesMut,
+    #[derivative(Debug = "ignore")]
+    missing: NegativeCache,
+    #[derivative(Debug = "ignore")]
+    recent: RecentWrites,
+    #[derivative(Debug = "ignore")]
+    dirs: DirTimes,
+    #[derivative(Debug = "ignore")]
+    calls: Arc<ApiCalls>,
 }
 
 impl PartialOpenFile {
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         metadata: S3MetaData,
         opts: OpenOptions,
         path: NormalizedPath,
         client: Bucket,
+        content_type: &str,
+        missing: NegativeCache,
+        recent: RecentWrites,
+        dirs: DirTimes,
+        calls: Arc<ApiCalls>,
     ) -> FsResult<Self> {
-        let (id, code) = match client.create_multipart_upload(path.as_ref()).await {
+        // content type of multipart object is set when upload is created
+        let mut create_client = client.clone();
+        create_client.add_header("Content-Type", content_type);
+        calls.record(Call::Multipart);
+        let (id, code) = match create_client.create_multipart_upload(path.as_ref()).await {
             Ok(k) => k,
             Err(e) => {
                 error!(msg = "can't create multipart upload", err = ?e);
@@ -43,7 +69,7 @@ impl PartialOpenFile {
                 msg = "unsuccessful create multipart upload code",
                 code = code
             );
-            return Err(FsError::GeneralFailure);
+            return Err(write_failure(code));
         }
 
         Ok(PartialOpenFile {
@@ -53,66 +79,85 @@ impl PartialOpenFile {
             client,
             etags: vec![],
             upload_id: id.upload_id,
-            cursor: Cursor::new(vec![]),
+            buf: BytesMut::new(),
+            missing,
+            recent,
+            dirs,
+            calls,
         })
     }
 }
 
-const CHUNK_SIZE: u64 = 10 * 1024 * 1024;
+pub(super) const CHUNK_SIZE: usize = 10 * 1024 * 1024;
 
 impl PartialOpenFile {
     async fn write_chunk<'a, S: bytes::Buf>(&'a mut self, buf: S) -> FsResult<()> {
-        let b = buf.chunk();
-        let _ = self.cursor.write(b)?;
-        if self.cursor.position() < CHUNK_SIZE {
+        if self.buf.capacity() == 0 {
+            self.buf.reserve(CHUNK_SIZE);
+        }
+        self.buf.put(buf);
+        if self.buf.len() < CHUNK_SIZE {
             return Ok(());
         }
         self.upload_current().await
     }
 
+    /// Whole chunks are uploaded right away without copying them into buffer.
+    async fn write_bytes_impl(&mut self, buf: Bytes) -> FsResult<()> {
+        if self.buf.is_empty() && buf.len() >= CHUNK_SIZE {
+            return self.upload(&buf).await;
+        }
+        self.write_chunk(buf).await
+    }
+
     async fn upload_current<'a>(&'a mut self) -> FsResult<()> {
-        self.cursor.set_position(0);
+        let buf = std::mem::take(&mut self.buf);
+        let r = self.upload(&buf).await;
+        // keep allocation for the next part
+        self.buf = buf;
+        self.buf.clear();
+        r
+    }
 
+    async fn upload(&mut self, b: &[u8]) -> FsResult<()> {
+        self.calls.record(Call::Multipart);
+        let (resp, code) = match self
+            .client
+            .upload_part(
+                &self.path,
+                &self.upload_id,
+                (self.etags.len() + 1).try_into().unwrap(),
+                b,
+            )
+            .await
         {
-            let b = self.cursor.chunk();
-
-            let (resp, code) = match self
-                .client
-                .upload_part(
-                    &self.path,
-                    &self.upload_id,
-                    (self.etags.len() + 1).try_into().unwrap(),
-                    b,
-                )
-                .await
-            {
-                Ok(k) => k,
-                Err(e) => {
-                    error!("{:?}", e);
-                    // TODO: retrying?
-                    let _ = self
-                        .client
-                        .abort_multipart_upload(&self.path, &self.upload_id)
-                        .await;
-                    return Err(FsError::GeneralFailure);
-                }
-            };
-
-            if code != 200 {
-                error!(msg = "can't upload part", code = code);
+            Ok(k) => k,
+            Err(e) => {
+                error!("{:?}", e);
+                // TODO: retrying?
+                self.calls.record(Call::Delete);
                 let _ = self
                     .client
                     .abort_multipart_upload(&self.path, &self.upload_id)
                     .await;
                 return Err(FsError::GeneralFailure);
             }
+        };
 
-            self.etags.push(resp);
-            self.metadata.len += b.len() as u64;
+        if code != 200 {
+            error!(msg = "can't upload part", code = code);
+            self.calls.record(Call::Delete);
+            let _ = self
+                .client
+                .abort_multipart_upload(&self.path, &self.upload_id)
+                .await;
+            return Err(write_failure(code));
         }
 
-        self.cursor = Cursor::new(vec![]);
+        self.etags.push(resp);
+        self.metadata.len += b.len() as u64;
         self.metadata.modified = SystemTime::now();
+        self.metadata.etag = None;
         Ok(())
     }
 }
@@ -128,7 +173,7 @@ impl DavFile for PartialOpenFile {
     }
 
     fn write_bytes<'a>(&'a mut self, buf: bytes::Bytes) -> FsFuture<()> {
-        async move { self.write_chunk(buf).await }.boxed()
+        async move { self.write_bytes_impl(buf).await }.boxed()
     }
 
     fn read_bytes<'a>(&'a mut self, _: usize) -> FsFuture<bytes::Bytes> {
@@ -156,6 +201,7 @@ impl DavFile for PartialOpenFile {
                     part_number: i as u32 + 1,
                 })
                 .collect::<Vec<Part>>();
+            self.calls.record(Call::Multipart);
             let (_, code) = match self
                 .client
                 .complete_multipart_upload(&self.path, &self.upload_id, parts)
@@ -170,6 +216,7 @@ impl DavFile for PartialOpenFile {
 
             if code != 200 {
                 error!(reason = "multipart object unsuccessful", code = code);
+                self.calls.record(Call::Delete);
                 let code = match self
                     .client
                     .abort_multipart_upload(&self.path, &self.upload_id)
@@ -187,6 +234,11 @@ impl DavFile for PartialOpenFile {
                 return Err(FsError::GeneralFailure);
             }
 
+            self.missing.invalidate(&self.path);
+            self.recent.written(&self.path);
+            self.dirs.changed(&self.path);
+            self.calls.record(Call::Head);
+            self.metadata.refresh_etag(&self.client, &self.path).await;
             Ok(())
         }
         .boxed()
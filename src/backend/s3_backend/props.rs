@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use webdav_handler::fs::DavProp;
+
+/// Dead property as it stored in sidecar object.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SidecarProp {
+    pub name: String,
+    pub prefix: Option<String>,
+    pub namespace: Option<String>,
+    pub xml: Option<Vec<u8>>,
+}
+
+impl SidecarProp {
+    pub fn same(&self, prop: &DavProp) -> bool {
+        self.name == prop.name && self.namespace == prop.namespace
+    }
+}
+
+impl From<DavProp> for SidecarProp {
+    fn from(p: DavProp) -> Self {
+        SidecarProp {
+            name: p.name,
+            prefix: p.prefix,
+            namespace: p.namespace,
+            xml: p.xml,
+        }
+    }
+}
+
+impl From<SidecarProp> for DavProp {
+    fn from(p: SidecarProp) -> Self {
+        DavProp {
+            name: p.name,
+            prefix: p.prefix,
+            namespace: p.namespace,
+            xml: p.xml,
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use super::{
+    calls::{ApiCalls, Call},
+    metadata::S3MetaData,
+};
+use bytes::{Buf, Bytes};
+use futures_util::{FutureExt, Stream, StreamExt};
+use s3::Bucket;
+use std::{
+    io::SeekFrom,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+use tracing::{debug, error};
+use webdav_handler::fs::{DavFile, DavMetaData, FsError, FsFuture, FsResult};
+
+pub(super) type Ranges = Pin<Box<dyn Stream<Item = FsResult<Bytes>> + Send>>;
+
+/// How objects are fetched: `parallelism` ranges of `range` bytes are
+/// requested at once and returned in order.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct RangeFetch {
+    pub range: u64,
+    pub parallelism: usize,
+}
+
+impl RangeFetch {
+    /// Content of object from `start` to `len`.
+    pub fn stream(
+        &self,
+        client: &Bucket,
+        calls: &Arc<ApiCalls>,
+        path: &str,
+        start: u64,
+        len: u64,
+    ) -> Ranges {
+        let range = self.range.max(1);
+        let client = client.clone();
+        let calls = calls.clone();
+        let path = path.to_string();
+        let starts = futures_util::stream::iter((start..len).step_by(range as usize));
+        let ranges = starts
+            .map(move |s| {
+                let client = client.clone();
+                let calls = calls.clone();
+                let path = path.clone();
+                let end = (s + range).min(len) - 1;
+                async move {
+                    calls.record(Call::Get);
+                    let (obj, code) = client
+                        .get_object_range(&path, s, Some(end))
+                        .await
+                        .map_err(|_| FsError::GeneralFailure)?;
+                    if (code != 206 && code != 200) || obj.len() as u64 != end - s + 1 {
+                        error!(msg = "cant get object range", code = code, start = s);
+                        return Err(FsError::GeneralFailure);
+                    }
+                    calls.downloaded(obj.len() as u64);
+                    Ok(Bytes::from(obj))
+                }
+            })
+            .buffered(self.parallelism.max(1));
+        Box::pin(ranges)
+    }
+}
+
+/// Object opened for reading. Content is fetched by ranges as it is read
+/// instead of downloading it whole first, so large downloads start at once.
+#[derive(derivative::Derivative)]
+#[derivative(Debug)]
+pub struct RangedReadFile {
+    path: String,
+    metadata: S3MetaData,
+    #[derivative(Debug = "ignore")]
+    client: Bucket,
+    #[derivative(Debug = "ignore")]
+    calls: Arc<ApiCalls>,
+    fetch: RangeFetch,
+    pos: u64,
+    // content at pos, next ranges follow it
+    buf: Bytes,
+    // mutex only makes file Sync, it is accessed through &mut self
+    #[derivative(Debug = "ignore")]
+    ranges: Mutex<Option<Ranges>>,
+}
+
+impl RangedReadFile {
+    pub(super) fn new(
+        metadata: S3MetaData,
+        path: String,
+        client: Bucket,
+        calls: Arc<ApiCalls>,
+        fetch: RangeFetch,
+    ) -> Self {
+        RangedReadFile {
+            path,
+            metadata,
+            client,
+            calls,
+            fetch,
+            pos: 0,
+            buf: Bytes::new(),
+            ranges: Mutex::new(None),
+        }
+    }
+}
+
+impl DavFile for RangedReadFile {
+    fn metadata<'a>(&'a mut self) -> FsFuture<Box<dyn DavMetaData>> {
+        async move { Ok(Box::new(self.metadata.clone()) as Box<dyn DavMetaData>) }.boxed()
+    }
+
+    fn write_buf<'a>(&'a mut self, _: Box<dyn Buf + Send>) -> FsFuture<()> {
+        async move { Err(FsError::Forbidden) }.boxed()
+    }
+
+    fn write_bytes<'a>(&'a mut self, _: Bytes) -> FsFuture<()> {
+        async move { Err(FsError::Forbidden) }.boxed()
+    }
+
+    fn read_bytes<'a>(&'a mut self, count: usize) -> FsFuture<Bytes> {
+        async move {
+            let len = self.metadata.len;
+            if self.buf.is_empty() {
+                if self.pos >= len {
+                    return Ok(Bytes::new());
+                }
+                let ranges = self.ranges.get_mut().unwrap();
+                if ranges.is_none() {
+                    debug!(msg = "fetching ranges", path = %self.path, start = self.pos);
+                    let stream =
+                        self.fetch
+                            .stream(&self.client, &self.calls, &self.path, self.pos, len);
+                    *ranges = Some(stream);
+                }
+                match ranges.as_mut().unwrap().next().await {
+                    Some(r) => self.buf = r?,
+                    None => return Ok(Bytes::new()),
+                }
+            }
+            let data = self.buf.split_to(count.min(self.buf.len()));
+            self.pos += data.len() as u64;
+            Ok(data)
+        }
+        .boxed()
+    }
+
+    fn seek<'a>(&'a mut self, pos: SeekFrom) -> FsFuture<u64> {
+        async move {
+            let len = self.metadata.len as i64;
+            let new = match pos {
+                SeekFrom::Start(p) => p as i64,
+                SeekFrom::End(d) => len + d,
+                SeekFrom::Current(d) => self.pos as i64 + d,
+            };
+            if new < 0 {
+                return Err(FsError::GeneralFailure);
+            }
+            let new = new as u64;
+            if new >= self.pos && new - self.pos <= self.buf.len() as u64 {
+                self.buf.advance((new - self.pos) as usize);
+            } else {
+                // ranges ahead belong to old position
+                self.buf = Bytes::new();
+                *self.ranges.get_mut().unwrap() = None;
+            }
+            self.pos = new;
+            Ok(new)
+        }
+        .boxed()
+    }
+
+    fn flush<'a>(&'a mut self) -> FsFuture<()> {
+        async move { Ok(()) }.boxed()
+    }
+}
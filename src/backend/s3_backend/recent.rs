@@ -0,0 +1,93 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Entries above which expired ones are dropped on insert.
+const PRUNE_THRESHOLD: usize = 4096;
+
+/// Remembers paths written for a while, so listings of eventually consistent
+/// stores include them even before the store lists them itself. Collections
+/// are kept with trailing slash.
+#[derive(Clone)]
+pub(super) struct RecentWrites {
+    window: Duration,
+    entries: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+impl RecentWrites {
+    /// Zero `window` disables remembering.
+    pub fn new(window: Duration) -> RecentWrites {
+        RecentWrites {
+            window,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn written(&self, path: &str) {
+        if self.window.is_zero() {
+            return;
+        }
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= PRUNE_THRESHOLD {
+            entries.retain(|_, expires| *expires > now);
+        }
+        entries.insert(path.to_string(), now + self.window);
+    }
+
+    /// Whether `path` was written within window.
+    pub fn contains(&self, path: &str) -> bool {
+        let entries = self.entries.lock().unwrap();
+        entries.get(path).map_or(false, |e| *e > Instant::now())
+    }
+
+    /// Paths written directly under collection `dir` within window.
+    pub fn children(&self, dir: &str) -> Vec<String> {
+        if self.window.is_zero() {
+            return vec![];
+        }
+        let now = Instant::now();
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .filter(|(_, expires)| **expires > now)
+            .map(|(p, _)| p)
+            .filter(|p| match p.strip_prefix(dir) {
+                Some(rest) => !rest.is_empty() && !rest.trim_end_matches('/').contains('/'),
+                None => false,
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_children_within_window() {
+        let recent = RecentWrites::new(Duration::from_millis(50));
+        recent.written("dir/a.txt");
+        recent.written("dir/sub/");
+        recent.written("dir/sub/b.txt");
+        recent.written("other.txt");
+        let mut children = recent.children("dir/");
+        children.sort();
+        assert_eq!(children, vec!["dir/a.txt", "dir/sub/"]);
+        // root is listed with empty prefix
+        assert_eq!(recent.children(""), vec!["other.txt"]);
+        assert!(recent.contains("dir/sub/"));
+        assert!(!recent.contains("dir/"));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(recent.children("dir/").is_empty());
+        assert!(!recent.contains("dir/sub/"));
+
+        let disabled = RecentWrites::new(Duration::from_secs(0));
+        disabled.written("dir/a.txt");
+        assert!(disabled.children("dir/").is_empty());
+    }
+}
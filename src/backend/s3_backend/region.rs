@@ -0,0 +1,102 @@
+use s3::Bucket;
+use tracing::{debug, warn};
+
+/// Object requested to learn region from error response, it does not have to exist.
+const PROBE_KEY: &str = "/.webdav_ss_region_probe";
+
+/// Where bucket has to be reached, as reported by S3.
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct Location {
+    pub region: Option<String>,
+    /// host without bucket name, set when S3 redirects to another endpoint
+    pub endpoint: Option<String>,
+}
+
+fn is_region_name(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+fn xml_value<'a>(body: &'a str, tag: &str) -> Option<&'a str> {
+    let start = body.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = start + body[start..].find(&format!("</{}>", tag))?;
+    Some(body[start..end].trim())
+}
+
+/// Reads region and endpoint from error of request signed for wrong region
+/// (AuthorizationHeaderMalformed) or sent to wrong endpoint (PermanentRedirect).
+pub(super) fn parse_error(body: &[u8], bucket: &str) -> Location {
+    let body = String::from_utf8_lossy(body);
+    let region = xml_value(&body, "Region")
+        .filter(|r| is_region_name(r))
+        .map(String::from);
+    let endpoint = xml_value(&body, "Endpoint").map(|e| {
+        let prefix = format!("{}.", bucket);
+        e.strip_prefix(&prefix).unwrap_or(e).to_string()
+    });
+    Location { region, endpoint }
+}
+
+/// Asks bucket location and falls back to probing an object when provider
+/// does not answer GetBucketLocation with a region.
+pub(super) async fn discover(bucket: &Bucket) -> Location {
+    match bucket.location().await {
+        Ok((region, 200)) => {
+            let region = region.to_string();
+            // empty location constraint is the default region
+            let region = if region.is_empty() {
+                "us-east-1".to_string()
+            } else {
+                region
+            };
+            if is_region_name(&region) {
+                return Location {
+                    region: Some(region),
+                    endpoint: None,
+                };
+            }
+        }
+        Ok((_, code)) => debug!(msg = "bucket location is not available", code = code),
+        Err(e) => debug!(msg = "bucket location is not available", err = ?e),
+    }
+    match bucket.get_object(PROBE_KEY).await {
+        Ok((body, 301)) | Ok((body, 400)) => parse_error(&body, &bucket.name),
+        Ok(_) => Location::default(),
+        Err(e) => {
+            warn!(msg = "can't probe bucket region", err = ?e);
+            Location::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_responses() {
+        let malformed = br#"<?xml version="1.0" encoding="UTF-8"?>
+<Error><Code>AuthorizationHeaderMalformed</Code><Message>the region 'us-east-1' is wrong; expecting 'eu-west-1'</Message><Region>eu-west-1</Region></Error>"#;
+        assert_eq!(
+            parse_error(malformed, "files"),
+            Location {
+                region: Some("eu-west-1".into()),
+                endpoint: None
+            }
+        );
+
+        let redirect = br#"<Error><Code>PermanentRedirect</Code><Bucket>files</Bucket><Endpoint>files.s3.eu-central-1.amazonaws.com</Endpoint></Error>"#;
+        assert_eq!(
+            parse_error(redirect, "files"),
+            Location {
+                region: None,
+                endpoint: Some("s3.eu-central-1.amazonaws.com".into())
+            }
+        );
+        assert_eq!(
+            parse_error(b"<Error><Region>Bad Region</Region></Error>", "files"),
+            Location::default()
+        );
+    }
+}
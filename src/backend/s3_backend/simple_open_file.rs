@@ -1,102 +1,325 @@
-use super::metadata::S3MetaData;
+use super::{
+    calls::{ApiCalls, Call},
+    dir_times::DirTimes,
+    metadata::S3MetaData,
+    negative_cache::NegativeCache,
+    partial_open_file::CHUNK_SIZE,
+    spool::Spool,
+    write_failure,
+};
 use crate::backend::normalized_path::NormalizedPath;
 use bytes::Buf;
 use futures_util::FutureExt;
-use s3::Bucket;
-use std::io::{Cursor, SeekFrom};
+use s3::{serde_types::Part, Bucket};
+use std::io::SeekFrom;
+use std::sync::Arc;
 use std::time::SystemTime;
-use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
-use tracing::{debug, instrument};
-use webdav_handler::fs::{DavFile, DavMetaData, FsError, FsFuture, OpenOptions};
+use tracing::{debug, error, instrument};
+use webdav_handler::fs::{DavFile, DavMetaData, FsError, FsFuture, FsResult, OpenOptions};
+
+/// Writes into empty file going strictly from its start are uploaded with
+/// multipart as soon as a part is collected, so large overwrites are not kept
+/// whole in memory.
+enum Stream {
+    Possible,
+    Off,
+    Uploading {
+        upload_id: String,
+        parts: Vec<Part>,
+        uploaded: u64,
+    },
+    Done,
+}
 
 #[derive(derivative::Derivative)]
 #[derivative(Debug)]
 pub struct S3SimpleOpenFile {
     path: String,
     options: OpenOptions,
-    cursor: Cursor<Vec<u8>>,
+    #[derivative(Debug = "ignore")]
+    spool: Spool,
+    #[derivative(Debug = "ignore")]
+    stream: Stream,
     #[derivative(Debug = "ignore")]
     client: Bucket,
     metadata: S3MetaData,
+    content_type: String,
+    #[derivative(Debug = "ignore")]
+    missing: NegativeCache,
+    #[derivative(Debug = "ignore")]
+    dirs: DirTimes,
+    #[derivative(Debug = "ignore")]
+    calls: Arc<ApiCalls>,
 }
 
 impl S3SimpleOpenFile {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         metadata: S3MetaData,
-        buf: Vec<u8>,
+        spool: Spool,
         opts: OpenOptions,
         path: NormalizedPath,
         client: Bucket,
+        content_type: String,
+        missing: NegativeCache,
+        dirs: DirTimes,
+        calls: Arc<ApiCalls>,
     ) -> Self {
+        let stream = if spool.len() == 0 {
+            Stream::Possible
+        } else {
+            Stream::Off
+        };
         S3SimpleOpenFile {
             metadata,
-            cursor: Cursor::new(buf),
+            spool,
+            stream,
             options: opts,
             path: path.to_string(),
             client,
+            content_type,
+            missing,
+            dirs,
+            calls,
         }
     }
 }
 
+impl S3SimpleOpenFile {
+    fn written(&mut self) {
+        self.metadata.etag = None;
+        self.metadata.modified = SystemTime::now();
+        self.metadata.len = self.spool.len();
+        if let Stream::Uploading { uploaded, .. } = self.stream {
+            self.metadata.len += uploaded;
+        }
+    }
+
+    /// Random access is not possible once parts are uploaded.
+    fn random_access(&mut self) -> FsResult<()> {
+        match self.stream {
+            Stream::Possible => self.stream = Stream::Off,
+            Stream::Off => {}
+            Stream::Uploading { .. } | Stream::Done => return Err(FsError::NotImplemented),
+        }
+        Ok(())
+    }
+
+    async fn start_multipart(&self) -> FsResult<String> {
+        let mut create_client = self.client.clone();
+        create_client.add_header("Content-Type", &self.content_type);
+        self.calls.record(Call::Multipart);
+        match create_client.create_multipart_upload(&self.path).await {
+            Ok((id, 200)) => Ok(id.upload_id),
+            Ok((_, code)) => {
+                error!(msg = "can't create multipart upload", code = code);
+                Err(FsError::GeneralFailure)
+            }
+            Err(e) => {
+                error!(msg = "can't create multipart upload", err = ?e);
+                Err(FsError::GeneralFailure)
+            }
+        }
+    }
+
+    async fn upload_part(&self, upload_id: &str, part_number: u32, data: &[u8]) -> FsResult<Part> {
+        self.calls.record(Call::Multipart);
+        match self
+            .client
+            .upload_part(&self.path, upload_id, part_number, data)
+            .await
+        {
+            Ok((etag, 200)) => Ok(Part { etag, part_number }),
+            r => {
+                error!(msg = "can't upload part", result = ?r);
+                self.calls.record(Call::Delete);
+                let _ = self
+                    .client
+                    .abort_multipart_upload(&self.path, upload_id)
+                    .await;
+                Err(FsError::GeneralFailure)
+            }
+        }
+    }
+
+    async fn complete_multipart(&self, upload_id: &str, parts: Vec<Part>) -> FsResult<()> {
+        self.calls.record(Call::Multipart);
+        match self
+            .client
+            .complete_multipart_upload(&self.path, upload_id, parts)
+            .await
+        {
+            Ok((_, 200)) => Ok(()),
+            r => {
+                error!(msg = "can't complete multipart upload", err = ?r.as_ref().err());
+                self.calls.record(Call::Delete);
+                let _ = self
+                    .client
+                    .abort_multipart_upload(&self.path, upload_id)
+                    .await;
+                Err(FsError::GeneralFailure)
+            }
+        }
+    }
+
+    /// Uploads collected data as next part when streaming and there is
+    /// enough of it, or `last` is set.
+    async fn stream_part(&mut self, last: bool) -> FsResult<()> {
+        let len = self.spool.len();
+        if !matches!(self.stream, Stream::Possible | Stream::Uploading { .. })
+            || (len < CHUNK_SIZE as u64 && !last)
+        {
+            return Ok(());
+        }
+        if let Stream::Possible = self.stream {
+            debug!(msg = "streaming write with multipart", path = %self.path);
+            self.stream = Stream::Uploading {
+                upload_id: self.start_multipart().await?,
+                parts: vec![],
+                uploaded: 0,
+            };
+        }
+        let (upload_id, part_number) = match &self.stream {
+            Stream::Uploading {
+                upload_id, parts, ..
+            } => (upload_id.clone(), parts.len() as u32 + 1),
+            _ => unreachable!(),
+        };
+
+        // the last part may be empty only when it is the only one
+        if len > 0 || part_number == 1 {
+            let spilled = if self.spool.as_slice().is_none() {
+                self.spool.seek(SeekFrom::Start(0)).await?;
+                Some(self.spool.read_bytes(len as usize).await?)
+            } else {
+                None
+            };
+            let data = match &spilled {
+                Some(b) => &b[..],
+                None => self.spool.as_slice().unwrap(),
+            };
+            let part = self.upload_part(&upload_id, part_number, data).await?;
+            if let Stream::Uploading {
+                parts, uploaded, ..
+            } = &mut self.stream
+            {
+                parts.push(part);
+                *uploaded += len;
+            }
+            self.spool.reset();
+        }
+
+        if last {
+            if let Stream::Uploading { parts, .. } =
+                std::mem::replace(&mut self.stream, Stream::Done)
+            {
+                self.complete_multipart(&upload_id, parts).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn upload_whole(&mut self) -> FsResult<()> {
+        if let Some(data) = self.spool.as_slice() {
+            self.calls.record(Call::Put);
+            let (_, code) = self
+                .client
+                .put_object_with_content_type(self.path.to_string(), data, &self.content_type)
+                .await
+                .unwrap();
+
+            if code != 200 {
+                debug!(msg = "put object unsuccessful", code = code);
+                return Err(write_failure(code));
+            }
+            return Ok(());
+        }
+        self.upload_spooled().await
+    }
+
+    /// Contents spilled to disk are uploaded in parts to not read them back
+    /// into memory at once.
+    async fn upload_spooled(&mut self) -> FsResult<()> {
+        let upload_id = self.start_multipart().await?;
+
+        let pos = self.spool.seek(SeekFrom::Current(0)).await?;
+        self.spool.seek(SeekFrom::Start(0)).await?;
+        let mut parts = vec![];
+        loop {
+            let b = self.spool.read_bytes(CHUNK_SIZE).await?;
+            if b.is_empty() && !parts.is_empty() {
+                break;
+            }
+            let part_number = parts.len() as u32 + 1;
+            parts.push(self.upload_part(&upload_id, part_number, &b).await?);
+            if b.len() < CHUNK_SIZE {
+                break;
+            }
+        }
+        self.spool.seek(SeekFrom::Start(pos)).await?;
+
+        self.complete_multipart(&upload_id, parts).await
+    }
+}
+
 impl DavFile for S3SimpleOpenFile {
     fn metadata<'a>(&'a mut self) -> FsFuture<Box<dyn DavMetaData>> {
         async move { Ok(Box::new(self.metadata.clone()) as Box<dyn DavMetaData>) }.boxed()
     }
 
-    fn write_buf<'a>(&'a mut self, buf: Box<dyn bytes::Buf + Send>) -> FsFuture<()> {
+    fn write_buf<'a>(&'a mut self, mut buf: Box<dyn bytes::Buf + Send>) -> FsFuture<()> {
         async move {
-            let b = buf.chunk();
-            self.cursor.write(b).await.unwrap();
-            self.metadata.modified = SystemTime::now();
-            self.metadata.len += b.len() as u64;
-            Ok(())
+            while buf.has_remaining() {
+                let n = buf.chunk().len();
+                self.spool.write_all(buf.chunk()).await?;
+                buf.advance(n);
+            }
+            self.written();
+            self.stream_part(false).await
         }
         .boxed()
     }
 
     fn write_bytes<'a>(&'a mut self, buf: bytes::Bytes) -> FsFuture<()> {
         async move {
-            self.cursor.write(buf.chunk()).await.unwrap();
-            self.metadata.modified = SystemTime::now();
-            self.metadata.len += buf.len() as u64;
-            Ok(())
+            self.spool.write_all(&buf).await?;
+            self.written();
+            self.stream_part(false).await
         }
         .boxed()
     }
 
     fn read_bytes<'a>(&'a mut self, count: usize) -> FsFuture<bytes::Bytes> {
         async move {
-            let mut b = Vec::with_capacity(count);
-            b.resize(count, 0);
-            self.cursor.read(b.as_mut()).await.unwrap();
-            Ok(bytes::Bytes::from(b))
+            self.random_access()?;
+            Ok(self.spool.read_bytes(count).await?)
         }
         .boxed()
     }
 
     fn seek<'a>(&'a mut self, pos: std::io::SeekFrom) -> FsFuture<u64> {
-        async move { Ok(self.cursor.seek(pos).await.unwrap()) }.boxed()
+        async move {
+            self.random_access()?;
+            Ok(self.spool.seek(pos).await?)
+        }
+        .boxed()
     }
 
     #[instrument(level = "debug", skip(self))]
     fn flush<'a>(&'a mut self) -> FsFuture<()> {
-        let mut data = self.cursor.clone();
         debug!(path = %self.path, length = self.metadata.len);
 
         async move {
-            data.seek(SeekFrom::Start(0)).await.unwrap();
-            let (_, code) = self
-                .client
-                .put_object(self.path.to_string(), data.chunk())
-                .await
-                .unwrap();
-
-            if code != 200 {
-                debug!(msg = "put object unsuccessful", code = code);
-                return Err(FsError::GeneralFailure);
+            match self.stream {
+                Stream::Done => return Ok(()),
+                Stream::Uploading { .. } => self.stream_part(true).await?,
+                Stream::Possible | Stream::Off => self.upload_whole().await?,
             }
+            self.missing.invalidate(&self.path);
+            self.dirs.changed(&self.path);
 
             let tags = self.metadata.as_metadata();
+            self.calls.record(Call::Tagging);
 
             let (_, code) = self
                 .client
@@ -105,8 +328,10 @@ impl DavFile for S3SimpleOpenFile {
                 .unwrap();
             if code != 200 {
                 debug!(msg = "tag object unsuccessful", code = code);
-                return Err(FsError::GeneralFailure);
+                return Err(write_failure(code));
             }
+            self.calls.record(Call::Head);
+            self.metadata.refresh_etag(&self.client, &self.path).await;
             Ok(())
         }
         .boxed()
@@ -0,0 +1,71 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::OnceCell;
+
+/// Runs only one call per key at a time, concurrent callers with the same key
+/// wait for it and share its result.
+#[derive(Clone)]
+pub(super) struct SingleFlight<T> {
+    calls: Arc<Mutex<HashMap<String, Arc<OnceCell<T>>>>>,
+}
+
+impl<T: Clone> SingleFlight<T> {
+    pub fn new() -> SingleFlight<T> {
+        SingleFlight {
+            calls: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub async fn run<F, Fut>(&self, key: &str, f: F) -> T
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let cell = self
+            .calls
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_insert_with(|| Arc::new(OnceCell::new()))
+            .clone();
+        // if the running call is cancelled one of waiters takes over
+        let r = cell.get_or_init(f).await.clone();
+
+        let mut calls = self.calls.lock().unwrap();
+        if calls.get(key).map_or(false, |c| Arc::ptr_eq(c, &cell)) {
+            calls.remove(key);
+        }
+        r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn coalesces_concurrent_calls() {
+        let flight = SingleFlight::new();
+        let calls = &AtomicUsize::new(0);
+        let call = |key: &'static str| {
+            flight.run(key, move || async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                key.len()
+            })
+        };
+
+        let r = tokio::join!(call("a"), call("a"), call("a"), call("bb"));
+        assert_eq!(r, (1, 1, 1, 2));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // finished calls are not cached
+        assert_eq!(call("a").await, 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
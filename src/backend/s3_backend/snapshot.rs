@@ -0,0 +1,131 @@
+use super::filesystem::S3Backend;
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+use tracing::{debug, error};
+
+/// Prefix of snapshot copies. It has no `.dir` marker so it is never listed
+/// through webdav.
+pub(crate) const SNAPSHOT_PREFIX: &str = ".snapshots/";
+
+fn check(code: u16, op: &str, key: &str) -> Result<()> {
+    match code {
+        200 | 204 => Ok(()),
+        code => {
+            error!(
+                msg = "snapshot operation failed",
+                op = op,
+                key = key,
+                code = code
+            );
+            Err(anyhow!("can't {} {}: status {}", op, key, code))
+        }
+    }
+}
+
+impl S3Backend {
+    async fn keys(&self, prefix: &str) -> Result<Vec<String>> {
+        let results = self
+            .client
+            .list(prefix.into(), None)
+            .await
+            .map_err(|e| anyhow!("can't list bucket: {}", e))?;
+        Ok(results
+            .into_iter()
+            .flat_map(|r| r.contents.into_iter().map(|c| c.key))
+            .collect())
+    }
+
+    /// Live objects of the mount, snapshot copies excluded.
+    async fn live_keys(&self) -> Result<Vec<String>> {
+        Ok(self
+            .keys("")
+            .await?
+            .into_iter()
+            .filter(|k| !k.starts_with(SNAPSHOT_PREFIX))
+            .collect())
+    }
+
+    /// Copies every object of the bucket under snapshot prefix. Copies are
+    /// done by server so object data is not transferred.
+    pub async fn create_snapshot(&self, name: &str) -> Result<()> {
+        for key in self.live_keys().await? {
+            let to = format!("{}{}/{}", SNAPSHOT_PREFIX, name, key);
+            let (_, code) = self
+                .client
+                .copy_object(key.clone(), to)
+                .await
+                .map_err(|e| anyhow!("can't copy {}: {}", key, e))?;
+            check(code, "copy", &key)?;
+        }
+        Ok(())
+    }
+
+    pub async fn list_snapshots(&self) -> Result<Vec<String>> {
+        let results = self
+            .client
+            .list(SNAPSHOT_PREFIX.into(), Some("/".into()))
+            .await
+            .map_err(|e| anyhow!("can't list snapshots: {}", e))?;
+        let mut names: Vec<String> = results
+            .into_iter()
+            .flat_map(|r| r.common_prefixes.unwrap_or_default())
+            .filter_map(|p| {
+                p.prefix
+                    .strip_prefix(SNAPSHOT_PREFIX)
+                    .map(|n| n.trim_end_matches('/').to_string())
+            })
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    pub async fn remove_snapshot(&self, name: &str) -> Result<()> {
+        for key in self.keys(&format!("{}{}/", SNAPSHOT_PREFIX, name)).await? {
+            let (_, code) = self
+                .client
+                .delete_object(&key)
+                .await
+                .map_err(|e| anyhow!("can't delete {}: {}", key, e))?;
+            check(code, "delete", &key)?;
+        }
+        Ok(())
+    }
+
+    /// Makes bucket content equal to snapshot: objects created after it are
+    /// deleted and the rest is copied back.
+    pub async fn restore_snapshot(&self, name: &str) -> Result<()> {
+        let prefix = format!("{}{}/", SNAPSHOT_PREFIX, name);
+        let saved = self.keys(&prefix).await?;
+        if saved.is_empty() {
+            return Err(anyhow!("snapshot not found: {}", name));
+        }
+        let restored: HashSet<&str> = saved
+            .iter()
+            .filter_map(|k| k.strip_prefix(prefix.as_str()))
+            .collect();
+
+        for key in self.live_keys().await? {
+            if restored.contains(key.as_str()) {
+                continue;
+            }
+            debug!(msg = "removing object missing in snapshot", key = %key);
+            let (_, code) = self
+                .client
+                .delete_object(&key)
+                .await
+                .map_err(|e| anyhow!("can't delete {}: {}", key, e))?;
+            check(code, "delete", &key)?;
+        }
+        for key in &saved {
+            let to = key[prefix.len()..].to_string();
+            let (_, code) = self
+                .client
+                .copy_object(key.clone(), to)
+                .await
+                .map_err(|e| anyhow!("can't copy {}: {}", key, e))?;
+            check(code, "copy", key)?;
+        }
+        self.missing.clear();
+        Ok(())
+    }
+}
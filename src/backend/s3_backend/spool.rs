@@ -0,0 +1,197 @@
+use bytes::Bytes;
+use std::{
+    io::{Cursor, Result, SeekFrom},
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use tokio::{
+    fs::{File, OpenOptions},
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+};
+
+static NEXT_SPOOL: AtomicUsize = AtomicUsize::new(0);
+
+enum Inner {
+    Mem(Cursor<Vec<u8>>),
+    File { file: File, path: PathBuf },
+}
+
+/// Buffer of open file contents kept in memory until it grows beyond
+/// threshold, then moved to temporary file.
+pub(super) struct Spool {
+    inner: Inner,
+    threshold: usize,
+    pos: u64,
+    len: u64,
+}
+
+impl Spool {
+    pub fn new(threshold: usize) -> Spool {
+        Spool::from_vec(vec![], threshold)
+    }
+
+    pub fn from_vec(buf: Vec<u8>, threshold: usize) -> Spool {
+        Spool {
+            len: buf.len() as u64,
+            inner: Inner::Mem(Cursor::new(buf)),
+            threshold,
+            pos: 0,
+        }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whole contents when they are kept in memory.
+    pub fn as_slice(&self) -> Option<&[u8]> {
+        match &self.inner {
+            Inner::Mem(c) => Some(c.get_ref()),
+            Inner::File { .. } => None,
+        }
+    }
+
+    pub async fn read_bytes(&mut self, count: usize) -> Result<Bytes> {
+        let b = match &mut self.inner {
+            Inner::Mem(c) => {
+                let data = c.get_ref();
+                let start = (self.pos as usize).min(data.len());
+                let end = (start + count).min(data.len());
+                let b = Bytes::copy_from_slice(&data[start..end]);
+                c.set_position(end as u64);
+                b
+            }
+            Inner::File { file, .. } => {
+                let mut b = Vec::with_capacity(count);
+                file.take(count as u64).read_to_end(&mut b).await?;
+                Bytes::from(b)
+            }
+        };
+        self.pos += b.len() as u64;
+        Ok(b)
+    }
+
+    pub async fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        match &mut self.inner {
+            Inner::Mem(c) => c.write_all(buf).await?,
+            Inner::File { file, .. } => file.write_all(buf).await?,
+        }
+        self.pos += buf.len() as u64;
+        self.len = self.len.max(self.pos);
+        if self.len > self.threshold as u64 {
+            self.spill().await?;
+        }
+        Ok(())
+    }
+
+    pub async fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        self.pos = match &mut self.inner {
+            Inner::Mem(c) => c.seek(pos).await?,
+            Inner::File { file, .. } => file.seek(pos).await?,
+        };
+        Ok(self.pos)
+    }
+
+    /// Drops contents keeping threshold.
+    pub fn reset(&mut self) {
+        let old = std::mem::replace(&mut self.inner, Inner::Mem(Cursor::new(vec![])));
+        remove(old);
+        self.pos = 0;
+        self.len = 0;
+    }
+
+    async fn spill(&mut self) -> Result<()> {
+        let data = match &self.inner {
+            Inner::Mem(c) => c.get_ref(),
+            Inner::File { .. } => return Ok(()),
+        };
+        let path = std::env::temp_dir().join(format!(
+            "webdav_ss_spool_{}_{}",
+            std::process::id(),
+            NEXT_SPOOL.fetch_add(1, Ordering::SeqCst)
+        ));
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await?;
+        file.write_all(data).await?;
+        file.seek(SeekFrom::Start(self.pos)).await?;
+        self.inner = Inner::File { file, path };
+        Ok(())
+    }
+}
+
+/// Temporary file is removed off the runtime threads when there is one.
+fn remove(inner: Inner) {
+    if let Inner::File { file, path } = inner {
+        drop(file);
+        match tokio::runtime::Handle::try_current() {
+            Ok(h) => {
+                h.spawn_blocking(move || std::fs::remove_file(path));
+            }
+            Err(_) => {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+}
+
+impl Drop for Spool {
+    fn drop(&mut self) {
+        let inner = std::mem::replace(&mut self.inner, Inner::Mem(Cursor::new(vec![])));
+        remove(inner);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn spills_over_threshold() -> anyhow::Result<()> {
+        let mut spool = Spool::from_vec(b"0123".to_vec(), 8);
+        spool.seek(SeekFrom::End(0)).await?;
+        spool.write_all(b"4567").await?;
+        assert!(spool.as_slice().is_some());
+
+        spool.write_all(b"89").await?;
+        assert!(spool.as_slice().is_none());
+        let path = match &spool.inner {
+            Inner::File { path, .. } => path.clone(),
+            Inner::Mem(_) => unreachable!(),
+        };
+        assert!(path.exists());
+        assert_eq!(spool.len(), 10);
+
+        spool.seek(SeekFrom::Start(2)).await?;
+        spool.write_all(b"ab").await?;
+        assert_eq!(spool.len(), 10);
+        spool.seek(SeekFrom::Start(0)).await?;
+        assert_eq!(&spool.read_bytes(6).await?[..], b"01ab45");
+        assert_eq!(&spool.read_bytes(10).await?[..], b"6789");
+
+        spool.reset();
+        for _ in 0..100 {
+            if !path.exists() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(!path.exists());
+        assert_eq!(spool.len(), 0);
+        spool.write_all(b"x").await?;
+        assert!(spool.as_slice().is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reads_in_memory() -> anyhow::Result<()> {
+        let mut spool = Spool::from_vec(b"hello".to_vec(), 1024);
+        assert_eq!(&spool.read_bytes(3).await?[..], b"hel");
+        assert_eq!(&spool.read_bytes(10).await?[..], b"lo");
+        assert!(spool.read_bytes(10).await?.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,364 @@
+use crate::backend::s3_backend::S3Backend;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
+use std::path::{Path, PathBuf};
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+/// Subset of cron syntax: `minute hour day month weekday` where every field
+/// is `*`, number, range `a-b`, step `*/n` or `a-b/n` and comma separated
+/// lists of them. `@hourly`, `@daily` and `@weekly` are accepted too. Time is UTC.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut mask = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((r, s)) => (
+                r,
+                s.parse::<u32>()
+                    .map_err(|_| format!("bad step: {}", item))?,
+            ),
+            None => (item, 1),
+        };
+        if step == 0 {
+            return Err(format!("bad step: {}", item));
+        }
+        let (from, to) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            let a = a.parse().map_err(|_| format!("bad range: {}", item))?;
+            let b = b.parse().map_err(|_| format!("bad range: {}", item))?;
+            (a, b)
+        } else {
+            let v = range.parse().map_err(|_| format!("bad value: {}", item))?;
+            (v, v)
+        };
+        if from < min || to > max || from > to {
+            return Err(format!("value out of range {}-{}: {}", min, max, item));
+        }
+        for v in (from..=to).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+impl Schedule {
+    pub fn parse(s: &str) -> Result<Schedule, String> {
+        let s = match s.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            s => s,
+        };
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!("expected 5 fields in schedule: {}", s));
+        }
+        let weekdays = parse_field(fields[4], 0, 7)?;
+        // both 0 and 7 are sunday
+        let weekdays = (weekdays | weekdays >> 7) & 0x7f;
+        Ok(Schedule {
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)?,
+            days: parse_field(fields[2], 1, 31)?,
+            months: parse_field(fields[3], 1, 12)?,
+            weekdays,
+            any_day: fields[2] == "*",
+            any_weekday: fields[4] == "*",
+        })
+    }
+
+    fn matches(&self, t: &DateTime<Utc>) -> bool {
+        let day = self.days & (1 << t.day()) != 0;
+        let weekday = self.weekdays & (1 << t.weekday().num_days_from_sunday()) != 0;
+        // like in cron, restricted day and weekday match if any of them does
+        let day_ok = match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        };
+        self.minutes & (1 << t.minute()) != 0
+            && self.hours & (1 << t.hour()) != 0
+            && self.months & (1 << t.month()) != 0
+            && day_ok
+    }
+
+    /// First matching minute after `t`, searched up to four years ahead.
+    pub fn next_after(&self, t: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut next = t.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        for _ in 0..4 * 366 * 24 * 60 {
+            if self.matches(&next) {
+                return Some(next);
+            }
+            next = next + Duration::minutes(1);
+        }
+        None
+    }
+}
+
+/// Snapshots of one mount, named by creation time so names sort oldest first.
+#[derive(Clone)]
+pub enum Snapshots {
+    /// hard-linked copies of directory tree
+    Fs { root: PathBuf, dir: PathBuf },
+    /// server side copies of objects under separate prefix
+    S3(S3Backend),
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(std::fs::read_link(from)?, to)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(_from: &Path, _to: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Recreates directory tree of `from` in `to` with files hard linked. `skip`
+/// is not descended into, so snapshot dir may be placed inside the mount.
+fn link_tree(from: &Path, to: &Path, skip: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let src = entry.path();
+        if src == skip {
+            continue;
+        }
+        let dst = to.join(entry.file_name());
+        let ft = entry.file_type()?;
+        if ft.is_dir() {
+            link_tree(&src, &dst, skip)?;
+        } else if ft.is_symlink() {
+            copy_symlink(&src, &dst)?;
+        } else {
+            std::fs::hard_link(&src, &dst)?;
+        }
+    }
+    Ok(())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut s = path.as_os_str().to_owned();
+    s.push(suffix);
+    PathBuf::from(s)
+}
+
+async fn blocking<F>(f: F) -> Result<()>
+where
+    F: FnOnce() -> std::io::Result<()> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f).await??;
+    Ok(())
+}
+
+impl Snapshots {
+    /// Default place of fs snapshots is directory next to mount one.
+    pub fn fs(root: &str, dir: Option<&str>) -> Snapshots {
+        let root = PathBuf::from(root.trim_end_matches('/'));
+        let dir = match dir {
+            Some(d) => PathBuf::from(d),
+            None => with_suffix(&root, ".snapshots"),
+        };
+        Snapshots::Fs { root, dir }
+    }
+
+    pub async fn create(&self) -> Result<String> {
+        let name = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        match self {
+            Snapshots::Fs { root, dir } => {
+                let (root, dir) = (root.clone(), dir.clone());
+                let target = dir.join(&name);
+                // built under hidden name, so half-done snapshot is never listed
+                let tmp = dir.join(format!(".{}", name));
+                blocking(move || {
+                    let _ = std::fs::remove_dir_all(&tmp);
+                    link_tree(&root, &tmp, &dir)?;
+                    std::fs::rename(&tmp, &target)
+                })
+                .await?;
+            }
+            Snapshots::S3(s3) => s3.create_snapshot(&name).await?,
+        }
+        Ok(name)
+    }
+
+    pub async fn list(&self) -> Result<Vec<String>> {
+        match self {
+            Snapshots::Fs { dir, .. } => {
+                let mut names = vec![];
+                let entries = match std::fs::read_dir(dir) {
+                    Ok(e) => e,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(names),
+                    Err(e) => return Err(e.into()),
+                };
+                for entry in entries {
+                    let name = entry?.file_name().to_string_lossy().into_owned();
+                    if !name.starts_with('.') {
+                        names.push(name);
+                    }
+                }
+                names.sort();
+                Ok(names)
+            }
+            Snapshots::S3(s3) => s3.list_snapshots().await,
+        }
+    }
+
+    pub async fn remove(&self, name: &str) -> Result<()> {
+        match self {
+            Snapshots::Fs { dir, .. } => {
+                let path = dir.join(name);
+                blocking(move || std::fs::remove_dir_all(path)).await
+            }
+            Snapshots::S3(s3) => s3.remove_snapshot(name).await,
+        }
+    }
+
+    /// Replaces mount content with snapshot. Snapshot itself is kept.
+    pub async fn restore(&self, name: &str) -> Result<()> {
+        if !self.list().await?.iter().any(|n| n == name) {
+            return Err(anyhow!("snapshot not found: {}", name));
+        }
+        match self {
+            Snapshots::Fs { root, dir } => {
+                let (root, snapshot) = (root.clone(), dir.join(name));
+                let new = with_suffix(&root, ".restore");
+                let old = with_suffix(&root, ".old");
+                let inner = dir.strip_prefix(&root).is_ok();
+                let dir = dir.clone();
+                blocking(move || {
+                    let _ = std::fs::remove_dir_all(&new);
+                    link_tree(&snapshot, &new, &dir)?;
+                    let _ = std::fs::remove_dir_all(&old);
+                    std::fs::rename(&root, &old)?;
+                    std::fs::rename(&new, &root)?;
+                    if inner {
+                        // snapshot dir lives inside the mount, move it over
+                        let rel = dir.strip_prefix(&root).unwrap();
+                        std::fs::rename(old.join(rel), root.join(rel))?;
+                    }
+                    std::fs::remove_dir_all(&old)
+                })
+                .await
+            }
+            Snapshots::S3(s3) => s3.restore_snapshot(name).await,
+        }
+    }
+
+    /// Removes all but `keep` newest snapshots, returns removed names.
+    pub async fn prune(&self, keep: usize) -> Result<Vec<String>> {
+        let names = self.list().await?;
+        let count = names.len().saturating_sub(keep);
+        let old: Vec<String> = names.into_iter().take(count).collect();
+        for name in &old {
+            self.remove(name).await?;
+        }
+        Ok(old)
+    }
+}
+
+/// Takes snapshots on schedule and keeps `keep` newest ones.
+pub fn spawn(
+    mount: String,
+    snapshots: Snapshots,
+    schedule: Schedule,
+    keep: usize,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let now = Utc::now();
+            let next = match schedule.next_after(now) {
+                Some(n) => n,
+                None => {
+                    error!(msg = "snapshot schedule never fires", mount = %mount);
+                    return;
+                }
+            };
+            let wait = (next - now).to_std().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+            match snapshots.create().await {
+                Ok(name) => info!(msg = "snapshot created", mount = %mount, name = %name),
+                Err(e) => error!(msg = "can't create snapshot", mount = %mount, err = %e),
+            }
+            match snapshots.prune(keep).await {
+                Ok(removed) if !removed.is_empty() => {
+                    info!(msg = "old snapshots removed", mount = %mount, names = ?removed)
+                }
+                Ok(_) => {}
+                Err(e) => error!(msg = "can't remove old snapshots", mount = %mount, err = %e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn schedule() {
+        let s = Schedule::parse("*/15 2-3 * * *").unwrap();
+        let t = Utc.ymd(2021, 6, 1).and_hms(1, 59, 30);
+        assert_eq!(s.next_after(t), Some(Utc.ymd(2021, 6, 1).and_hms(2, 0, 0)));
+        let t = Utc.ymd(2021, 6, 1).and_hms(3, 45, 0);
+        assert_eq!(s.next_after(t), Some(Utc.ymd(2021, 6, 2).and_hms(2, 0, 0)));
+
+        // 2021-06-01 is tuesday
+        let weekly = Schedule::parse("@weekly").unwrap();
+        assert_eq!(
+            weekly.next_after(t),
+            Some(Utc.ymd(2021, 6, 6).and_hms(0, 0, 0))
+        );
+        assert_eq!(Schedule::parse("0 0 * * 7").unwrap(), weekly);
+
+        assert!(Schedule::parse("0 0 * *").is_err());
+        assert!(Schedule::parse("60 * * * *").is_err());
+        assert!(Schedule::parse("*/0 * * * *").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn fs_snapshots() -> anyhow::Result<()> {
+        let base = std::env::temp_dir().join(format!("webdav_ss_snap_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(base.join("root/docs"))?;
+        std::fs::write(base.join("root/docs/a.txt"), b"a")?;
+
+        let snapshots = Snapshots::fs(base.join("root").to_str().unwrap(), None);
+        let name = snapshots.create().await?;
+        assert!(base
+            .join("root.snapshots")
+            .join(&name)
+            .join("docs/a.txt")
+            .exists());
+
+        std::fs::remove_file(base.join("root/docs/a.txt"))?;
+        std::fs::write(base.join("root/b.txt"), b"b")?;
+        snapshots.restore(&name).await?;
+        assert_eq!(std::fs::read(base.join("root/docs/a.txt"))?, b"a");
+        assert!(!base.join("root/b.txt").exists());
+
+        std::fs::create_dir_all(base.join("root.snapshots/20000101T000000Z"))?;
+        assert_eq!(
+            snapshots.prune(1).await?,
+            vec!["20000101T000000Z".to_string()]
+        );
+        assert_eq!(snapshots.list().await?, vec![name]);
+        std::fs::remove_dir_all(&base)?;
+        Ok(())
+    }
+}
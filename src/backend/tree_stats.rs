@@ -0,0 +1,108 @@
+use super::walk::walk;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{DavFileSystem, FsResult},
+};
+
+/// Cached totals above which expired ones are dropped.
+const PRUNE_ABOVE: usize = 10000;
+
+/// Size of files and number of files and directories below collection.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Tree {
+    pub bytes: u64,
+    pub items: u64,
+}
+
+/// Totals of collections computed by walking them when asked for. They are
+/// kept for `ttl` or until something below is changed through aggregate,
+/// changes made to backends directly are seen after `ttl`.
+pub struct TreeStats {
+    ttl: Duration,
+    cache: Mutex<HashMap<String, (Instant, Tree)>>,
+}
+
+fn key(path: &DavPath) -> String {
+    let mut key = path.as_url_string();
+    if !key.ends_with('/') {
+        key.push('/');
+    }
+    key
+}
+
+impl TreeStats {
+    pub fn new(ttl: Duration) -> Arc<TreeStats> {
+        Arc::new(TreeStats {
+            ttl,
+            cache: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Totals of collection `dir` of `fs`.
+    pub async fn get(&self, fs: &dyn DavFileSystem, dir: &DavPath) -> FsResult<Tree> {
+        let key = key(dir);
+        if let Some((at, tree)) = self.cache.lock().unwrap().get(&key) {
+            if at.elapsed() < self.ttl {
+                return Ok(*tree);
+            }
+        }
+        let mut tree = Tree::default();
+        for (_, meta) in walk(fs, dir).await? {
+            tree.items += 1;
+            if !meta.is_dir() {
+                tree.bytes += meta.len();
+            }
+        }
+        let mut cache = self.cache.lock().unwrap();
+        if cache.len() > PRUNE_ABOVE {
+            let ttl = self.ttl;
+            cache.retain(|_, (at, _)| at.elapsed() < ttl);
+        }
+        cache.insert(key, (Instant::now(), tree));
+        Ok(tree)
+    }
+
+    /// Drops totals of collections containing `path`.
+    pub fn changed(&self, path: &DavPath) {
+        let path = path.as_url_string();
+        self.cache
+            .lock()
+            .unwrap()
+            .retain(|dir, _| !path.starts_with(dir.as_str()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::put;
+    use webdav_handler::memfs::MemFs;
+
+    fn path(p: &str) -> DavPath {
+        DavPath::new(p).unwrap()
+    }
+
+    #[tokio::test]
+    async fn counts_until_changed() -> anyhow::Result<()> {
+        let fs = MemFs::new();
+        fs.create_dir(&path("/dir/")).await?;
+        fs.create_dir(&path("/dir/sub/")).await?;
+        put(fs.as_ref(), "/dir/sub/a.txt", b"data").await?;
+
+        let stats = TreeStats::new(Duration::from_secs(60));
+        let tree = stats.get(fs.as_ref(), &path("/dir")).await?;
+        assert_eq!(tree, Tree { bytes: 4, items: 2 });
+
+        fs.remove_file(&path("/dir/sub/a.txt")).await?;
+        assert_eq!(stats.get(fs.as_ref(), &path("/dir/")).await?, tree);
+        stats.changed(&path("/dir/sub/a.txt"));
+        let tree = stats.get(fs.as_ref(), &path("/dir/")).await?;
+        assert_eq!(tree, Tree { bytes: 0, items: 1 });
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use futures_util::StreamExt;
+use percent_encoding::{percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{DavFileSystem, DavMetaData, FsError, FsResult, ReadDirMeta},
+};
+
+const ENC: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'.')
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Builds path of directory entry `name` inside `parent`. Some backends (s3)
+/// return collection names with trailing slash so it is trimmed here.
+pub fn child_path(parent: &DavPath, mut name: &[u8], is_dir: bool) -> FsResult<DavPath> {
+    while name.ends_with(b"/") {
+        name = &name[..name.len() - 1];
+    }
+    let mut base = parent.as_url_string();
+    if !base.ends_with('/') {
+        base.push('/');
+    }
+    let mut path = format!("{}{}", base, percent_encode(name, ENC));
+    if is_dir {
+        path.push('/');
+    }
+    DavPath::new(&path).map_err(|_| FsError::GeneralFailure)
+}
+
+/// Recursively lists everything below `root` (root itself is not included).
+pub async fn walk(
+    fs: &dyn DavFileSystem,
+    root: &DavPath,
+) -> FsResult<Vec<(DavPath, Box<dyn DavMetaData>)>> {
+    let mut result = vec![];
+    let mut dirs = vec![root.clone()];
+    while let Some(dir) = dirs.pop() {
+        let mut entries = fs.read_dir(&dir, ReadDirMeta::Data).await?;
+        while let Some(entry) = entries.next().await {
+            let meta = entry.metadata().await?;
+            let path = child_path(&dir, &entry.name(), meta.is_dir())?;
+            if meta.is_dir() {
+                dirs.push(path.clone());
+            }
+            result.push((path, meta));
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,128 @@
+use super::{normalized_path::NormalizedPath, prop_storages::PropStorage};
+use notify::{watcher, DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+use tokio::runtime::Handle;
+use tracing::{debug, error};
+
+/// Path in prop storage for file changed under mount root.
+fn storage_path(root: &Path, mount: &str, path: &Path) -> Option<NormalizedPath> {
+    let rel = path.strip_prefix(root).ok()?.to_str()?;
+    let p: NormalizedPath = format!("{}/{}", mount.trim_end_matches('/'), rel).into();
+    Some(p)
+}
+
+async fn removed(props: &dyn PropStorage, path: NormalizedPath) {
+    // entry is gone so it is unknown whether it was a directory
+    let _ = props.remove_file(&path).await;
+    let _ = props.remove_dir(&path.as_dir()).await;
+}
+
+async fn renamed(props: &dyn PropStorage, from: NormalizedPath, to: NormalizedPath, dir: bool) {
+    let (from, to) = if dir {
+        (from.as_dir(), to.as_dir())
+    } else {
+        (from, to)
+    };
+    // renames made through webdav already moved props
+    if !props.have_props(&from).await {
+        return;
+    }
+    if let Err(e) = props.rename(&from, &to).await {
+        error!(msg = "can't move props", from = %from, to = %to, err = ?e);
+    }
+}
+
+/// Watches local filesystem mount for changes made outside of webdav and
+/// keeps dead properties in sync: props of removed files are dropped and
+/// props of renamed ones are moved. Returned watcher must be kept alive.
+pub fn watch(
+    root: PathBuf,
+    mount: String,
+    props: Box<dyn PropStorage>,
+) -> notify::Result<RecommendedWatcher> {
+    let (tx, rx) = mpsc::channel();
+    let mut w = watcher(tx, Duration::from_secs(1))?;
+    w.watch(&root, RecursiveMode::Recursive)?;
+    // canonical path is used in events
+    let root = root.canonicalize().unwrap_or(root);
+    let handle = Handle::current();
+
+    std::thread::spawn(move || {
+        for event in rx {
+            debug!(?event);
+            match event {
+                // file may be recreated, e.g. by editor saving through
+                // temporary file, before debounced event is delivered
+                DebouncedEvent::Remove(p) if std::fs::symlink_metadata(&p).is_err() => {
+                    if let Some(p) = storage_path(&root, &mount, &p) {
+                        handle.block_on(removed(props.as_ref(), p));
+                    }
+                }
+                DebouncedEvent::Rename(from, to) => {
+                    let dir = to.is_dir();
+                    if let (Some(from), Some(to)) = (
+                        storage_path(&root, &mount, &from),
+                        storage_path(&root, &mount, &to),
+                    ) {
+                        handle.block_on(renamed(props.as_ref(), from, to, dir));
+                    }
+                }
+                DebouncedEvent::Error(e, p) => {
+                    error!(msg = "watch error", err = %e, path = ?p);
+                }
+                _ => {}
+            }
+        }
+    });
+    Ok(w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::prop_storages::mem::Memory;
+    use webdav_handler::fs::DavProp;
+
+    fn prop() -> DavProp {
+        DavProp {
+            name: "color".into(),
+            namespace: Some("urn:test".into()),
+            prefix: None,
+            xml: Some(b"<color xmlns=\"urn:test\">red</color>".to_vec()),
+        }
+    }
+
+    #[test]
+    fn paths() {
+        let root = Path::new("/data/fs");
+        assert_eq!(
+            storage_path(root, "/fs1/", Path::new("/data/fs/dir/a.txt")),
+            Some("fs1/dir/a.txt".into())
+        );
+        assert_eq!(storage_path(root, "/fs1", Path::new("/other/a.txt")), None);
+    }
+
+    #[tokio::test]
+    async fn sync_props() -> anyhow::Result<()> {
+        let props = Memory::new();
+        let a: NormalizedPath = "fs1/a.txt".into();
+        let b: NormalizedPath = "fs1/b.txt".into();
+        props.patch_prop(&a, (true, prop())).await?;
+
+        renamed(props.as_ref(), a.clone(), b.clone(), false).await;
+        assert!(!props.have_props(&a).await);
+        assert!(props.have_props(&b).await);
+
+        // second rename event for the same move does not touch destination
+        renamed(props.as_ref(), a.clone(), b.clone(), false).await;
+        assert!(props.have_props(&b).await);
+
+        removed(props.as_ref(), b.clone()).await;
+        assert!(!props.have_props(&b).await);
+        Ok(())
+    }
+}
@@ -0,0 +1,227 @@
+use bytes::{Buf, Bytes};
+use futures_util::FutureExt;
+use std::{
+    io::SeekFrom,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::SystemTime,
+};
+use tracing::{info, warn};
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, DavProp, FsError, FsFuture, FsResult,
+        FsStream, OpenOptions, ReadDirMeta,
+    },
+};
+
+/// Whether storage under mount refuses writes, e.g. read-only remount of
+/// local filesystem or bucket policy without put permission.
+#[derive(Debug, Default)]
+pub struct Access {
+    read_only: AtomicBool,
+}
+
+impl Access {
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::Relaxed)
+    }
+
+    /// Denied write marks storage read-only, any successful one clears it.
+    fn record<T>(&self, r: &FsResult<T>) {
+        match r {
+            Err(FsError::Forbidden) => {
+                if !self.read_only.swap(true, Ordering::Relaxed) {
+                    warn!("storage denies writes, mount is read-only");
+                }
+            }
+            Ok(_) => {
+                if self.read_only.swap(false, Ordering::Relaxed) {
+                    info!("storage accepts writes again");
+                }
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+fn record<'a, T: Send + 'a>(access: Arc<Access>, f: FsFuture<'a, T>) -> FsFuture<'a, T> {
+    async move {
+        let r = f.await;
+        access.record(&r);
+        r
+    }
+    .boxed()
+}
+
+/// Tracks whether backend accepts writes, errors are passed through so denied
+/// ones are still answered with 403.
+#[derive(Clone)]
+pub struct AccessFs {
+    inner: Box<dyn DavFileSystem>,
+    access: Arc<Access>,
+}
+
+impl AccessFs {
+    pub fn new(inner: Box<dyn DavFileSystem>, access: Arc<Access>) -> Box<dyn DavFileSystem> {
+        Box::new(AccessFs { inner, access }) as Box<dyn DavFileSystem>
+    }
+
+    fn write<'a, T: Send + 'a>(&self, f: FsFuture<'a, T>) -> FsFuture<'a, T> {
+        record(self.access.clone(), f)
+    }
+}
+
+#[derive(Debug)]
+struct AccessFile {
+    inner: Box<dyn DavFile>,
+    access: Arc<Access>,
+}
+
+impl DavFile for AccessFile {
+    fn metadata<'a>(&'a mut self) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.metadata()
+    }
+
+    fn write_buf<'a>(&'a mut self, buf: Box<dyn Buf + Send>) -> FsFuture<()> {
+        let access = self.access.clone();
+        record(access, self.inner.write_buf(buf))
+    }
+
+    fn write_bytes<'a>(&'a mut self, buf: Bytes) -> FsFuture<()> {
+        let access = self.access.clone();
+        record(access, self.inner.write_bytes(buf))
+    }
+
+    fn read_bytes<'a>(&'a mut self, count: usize) -> FsFuture<Bytes> {
+        self.inner.read_bytes(count)
+    }
+
+    fn seek<'a>(&'a mut self, pos: SeekFrom) -> FsFuture<u64> {
+        self.inner.seek(pos)
+    }
+
+    fn flush<'a>(&'a mut self) -> FsFuture<()> {
+        let access = self.access.clone();
+        record(access, self.inner.flush())
+    }
+}
+
+impl DavFileSystem for AccessFs {
+    fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
+        async move {
+            let writing = options.write || options.append || options.create || options.truncate;
+            let r = self.inner.open(path, options).await;
+            if writing {
+                self.access.record(&r);
+            }
+            let inner = r?;
+            if !writing {
+                return Ok(inner);
+            }
+            Ok(Box::new(AccessFile {
+                inner,
+                access: self.access.clone(),
+            }) as Box<dyn DavFile>)
+        }
+        .boxed()
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        meta: ReadDirMeta,
+    ) -> FsFuture<FsStream<Box<dyn DavDirEntry>>> {
+        self.inner.read_dir(path, meta)
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.metadata(path)
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.symlink_metadata(path)
+    }
+
+    fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.write(self.inner.create_dir(path))
+    }
+
+    fn remove_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.write(self.inner.remove_dir(path))
+    }
+
+    fn remove_file<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.write(self.inner.remove_file(path))
+    }
+
+    fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        self.write(self.inner.rename(from, to))
+    }
+
+    fn copy<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        self.write(self.inner.copy(from, to))
+    }
+
+    fn set_accessed<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_accessed(path, tm)
+    }
+
+    fn set_modified<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_modified(path, tm)
+    }
+
+    fn have_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
+        self.inner.have_props(path)
+    }
+
+    fn patch_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+        patch: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(hyper::StatusCode, DavProp)>> {
+        self.inner.patch_props(path, patch)
+    }
+
+    fn get_props<'a>(&'a self, path: &'a DavPath, do_content: bool) -> FsFuture<Vec<DavProp>> {
+        self.inner.get_props(path, do_content)
+    }
+
+    fn get_prop<'a>(&'a self, path: &'a DavPath, prop: DavProp) -> FsFuture<Vec<u8>> {
+        self.inner.get_prop(path, prop)
+    }
+
+    fn get_quota<'a>(&'a self) -> FsFuture<(u64, Option<u64>)> {
+        self.inner.get_quota()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::wrappers::read_only::ReadOnlyFs;
+    use webdav_handler::memfs::MemFs;
+
+    #[tokio::test]
+    async fn tracks_denied_writes() -> anyhow::Result<()> {
+        let access = Arc::new(Access::default());
+        let fs = AccessFs::new(ReadOnlyFs::new(MemFs::new()), access.clone());
+        assert!(fs.metadata(&DavPath::new("/")?).await?.is_dir());
+        assert!(!access.is_read_only());
+        assert!(matches!(
+            fs.create_dir(&DavPath::new("/a/")?).await,
+            Err(FsError::Forbidden)
+        ));
+        assert!(access.is_read_only());
+
+        let fs = AccessFs::new(MemFs::new(), access.clone());
+        fs.create_dir(&DavPath::new("/a/")?).await?;
+        assert!(!access.is_read_only());
+        Ok(())
+    }
+}
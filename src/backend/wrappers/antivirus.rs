@@ -0,0 +1,515 @@
+use crate::backend::open_opts;
+use anyhow::anyhow;
+use bytes::{Buf, Bytes};
+use futures_util::FutureExt;
+use std::{fmt, io::SeekFrom, path::PathBuf, time::SystemTime};
+use tokio::{
+    io::{
+        AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWrite, AsyncWriteExt,
+        BufReader,
+    },
+    net::TcpStream,
+};
+use tracing::{debug, error, warn};
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, DavProp, FsError, FsFuture, FsResult,
+        FsStream, OpenOptions, ReadDirMeta,
+    },
+};
+
+/// Bytes of file sent to clamd in one chunk.
+const CHUNK: usize = 64 * 1024;
+
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Clean,
+    /// name of found signature
+    Infected(String),
+}
+
+/// clamd listening on unix socket (absolute path) or `host:port`.
+#[derive(Debug, Clone)]
+pub struct Clamd {
+    address: String,
+}
+
+impl Clamd {
+    pub fn new(address: &str) -> Self {
+        Clamd {
+            address: address.to_string(),
+        }
+    }
+
+    /// Streams `content` to clamd with INSTREAM command.
+    pub async fn scan<R: AsyncRead + Unpin>(&self, content: R) -> anyhow::Result<Verdict> {
+        #[cfg(unix)]
+        {
+            if self.address.starts_with('/') {
+                let conn = tokio::net::UnixStream::connect(&self.address).await?;
+                return instream(conn, content).await;
+            }
+        }
+        let conn = TcpStream::connect(&self.address).await?;
+        instream(conn, content).await
+    }
+}
+
+async fn instream<S: AsyncRead + AsyncWrite + Unpin, R: AsyncRead + Unpin>(
+    mut conn: S,
+    mut content: R,
+) -> anyhow::Result<Verdict> {
+    conn.write_all(b"zINSTREAM\0").await?;
+    let mut data = vec![0; CHUNK];
+    loop {
+        let n = content.read(&mut data).await?;
+        if n == 0 {
+            break;
+        }
+        conn.write_all(&(n as u32).to_be_bytes()).await?;
+        conn.write_all(&data[..n]).await?;
+    }
+    conn.write_all(&[0; 4]).await?;
+    conn.flush().await?;
+    let mut reply = vec![];
+    BufReader::new(conn).read_until(0, &mut reply).await?;
+    parse_reply(&reply)
+}
+
+/// Reply is `stream: OK`, `stream: <signature> FOUND` or `<message> ERROR`.
+fn parse_reply(reply: &[u8]) -> anyhow::Result<Verdict> {
+    let reply = String::from_utf8_lossy(reply);
+    let reply = reply.trim_end_matches(&['\0', '\n'][..]);
+    let result = reply.strip_prefix("stream: ").unwrap_or(reply);
+    if result == "OK" {
+        Ok(Verdict::Clean)
+    } else if let Some(name) = result.strip_suffix(" FOUND") {
+        Ok(Verdict::Infected(name.to_string()))
+    } else {
+        Err(anyhow!("clamd error: {}", result))
+    }
+}
+
+/// Scans uploads before they reach the backend. Written content is kept in
+/// local temporary file and replaces target on flush only if it is clean.
+/// Infected one is stored under quarantine directory of mount or dropped, and
+/// upload fails with 403. Upload which can't be scanned fails leaving target
+/// as it was.
+#[derive(Clone)]
+pub struct AntivirusFs {
+    inner: Box<dyn DavFileSystem>,
+    clamd: Clamd,
+    quarantine: Option<String>,
+}
+
+impl fmt::Debug for AntivirusFs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AntivirusFs")
+            .field("clamd", &self.clamd)
+            .field("quarantine", &self.quarantine)
+            .finish()
+    }
+}
+
+impl AntivirusFs {
+    pub fn new(
+        inner: Box<dyn DavFileSystem>,
+        clamd: Clamd,
+        quarantine: Option<String>,
+    ) -> Box<dyn DavFileSystem> {
+        Box::new(AntivirusFs {
+            inner,
+            clamd,
+            quarantine: quarantine.map(|q| q.trim_matches('/').to_string()),
+        }) as Box<dyn DavFileSystem>
+    }
+
+    /// Fails like opening `path` with `options` would, before anything is
+    /// written.
+    async fn check_open(&self, path: &DavPath, options: &OpenOptions) -> FsResult<bool> {
+        match self.inner.metadata(path).await {
+            Ok(m) if m.is_dir() => Err(FsError::Forbidden),
+            Ok(_) if options.create_new => Err(FsError::Exists),
+            Ok(_) => Ok(true),
+            Err(FsError::NotFound) if options.create || options.create_new => {
+                let url = path.as_url_string();
+                let parent = DavPath::new(&url[..=url.rfind('/').unwrap_or(0)])
+                    .map_err(|_| FsError::GeneralFailure)?;
+                match self.inner.metadata(&parent).await {
+                    Ok(m) if m.is_dir() => Ok(false),
+                    _ => Err(FsError::NotFound),
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn check(&self, path: &DavPath, staged: &mut Staged) -> FsResult<()> {
+        let scanned = match staged.rewind().await {
+            Ok(()) => self.clamd.scan(&mut staged.file).await,
+            Err(e) => Err(e.into()),
+        };
+        match scanned {
+            Ok(Verdict::Clean) => {
+                debug!(msg = "file is clean", path = %path.as_url_string());
+                staged.copy_to(self.inner.as_ref(), path).await
+            }
+            Ok(Verdict::Infected(name)) => {
+                warn!(msg = "infected upload", path = %path.as_url_string(), signature = %name);
+                if let Err(e) = self.isolate(path, staged).await {
+                    error!(msg = "can't quarantine file", err = ?e);
+                }
+                Err(FsError::Forbidden)
+            }
+            Err(e) => {
+                error!(msg = "can't scan file", path = %path.as_url_string(), err = %e);
+                Err(FsError::GeneralFailure)
+            }
+        }
+    }
+
+    async fn isolate(&self, path: &DavPath, staged: &mut Staged) -> FsResult<()> {
+        let dir = match self.quarantine {
+            Some(ref d) => d,
+            None => return Ok(()),
+        };
+        let dest = format!("/{}{}", dir, path.as_url_string());
+        // parents of destination, file name is not followed by slash
+        for (i, _) in dest.match_indices('/').skip(1) {
+            let p = DavPath::new(&dest[..=i]).map_err(|_| FsError::GeneralFailure)?;
+            if self.inner.metadata(&p).await.is_err() {
+                self.inner.create_dir(&p).await?;
+            }
+        }
+        let dest = DavPath::new(&dest).map_err(|_| FsError::GeneralFailure)?;
+        staged.copy_to(self.inner.as_ref(), &dest).await
+    }
+}
+
+fn io_error(e: std::io::Error) -> FsError {
+    debug!(msg = "staged upload failed", err = %e);
+    FsError::GeneralFailure
+}
+
+/// Local temporary copy of file being uploaded.
+#[derive(Debug)]
+struct Staged {
+    file: tokio::fs::File,
+    path: PathBuf,
+}
+
+impl Staged {
+    async fn new() -> FsResult<Staged> {
+        let path = std::env::temp_dir().join(format!("webdav_ss_scan_{}", uuid::Uuid::new_v4()));
+        let file = tokio::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await
+            .map_err(io_error)?;
+        Ok(Staged { file, path })
+    }
+
+    async fn rewind(&mut self) -> std::io::Result<()> {
+        self.file.flush().await?;
+        self.file.seek(SeekFrom::Start(0)).await?;
+        Ok(())
+    }
+
+    /// Writes whole content to `path` of `fs`, keeping current position.
+    async fn copy_to(&mut self, fs: &dyn DavFileSystem, path: &DavPath) -> FsResult<()> {
+        let pos = self.file.stream_position().await.map_err(io_error)?;
+        self.rewind().await.map_err(io_error)?;
+        let mut options = open_opts::write();
+        options.create = true;
+        options.truncate = true;
+        let mut out = fs.open(path, options).await?;
+        let mut buf = vec![0; CHUNK];
+        loop {
+            let n = self.file.read(&mut buf).await.map_err(io_error)?;
+            if n == 0 {
+                break;
+            }
+            out.write_bytes(Bytes::copy_from_slice(&buf[..n])).await?;
+        }
+        out.flush().await?;
+        self.file
+            .seek(SeekFrom::Start(pos))
+            .await
+            .map_err(io_error)?;
+        Ok(())
+    }
+}
+
+impl Drop for Staged {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[derive(Debug)]
+struct AntivirusFile {
+    staged: Staged,
+    fs: AntivirusFs,
+    path: DavPath,
+    written: bool,
+}
+
+impl DavFile for AntivirusFile {
+    fn metadata<'a>(&'a mut self) -> FsFuture<Box<dyn DavMetaData>> {
+        self.fs.inner.metadata(&self.path)
+    }
+
+    fn write_buf<'a>(&'a mut self, mut buf: Box<dyn Buf + Send>) -> FsFuture<()> {
+        let data = buf.copy_to_bytes(buf.remaining());
+        self.write_bytes(data)
+    }
+
+    fn write_bytes<'a>(&'a mut self, buf: Bytes) -> FsFuture<()> {
+        self.written = true;
+        async move { self.staged.file.write_all(&buf).await.map_err(io_error) }.boxed()
+    }
+
+    fn read_bytes<'a>(&'a mut self, count: usize) -> FsFuture<Bytes> {
+        async move {
+            let mut buf = vec![0; count];
+            let n = self.staged.file.read(&mut buf).await.map_err(io_error)?;
+            buf.truncate(n);
+            Ok(Bytes::from(buf))
+        }
+        .boxed()
+    }
+
+    fn seek<'a>(&'a mut self, pos: SeekFrom) -> FsFuture<u64> {
+        async move { self.staged.file.seek(pos).await.map_err(io_error) }.boxed()
+    }
+
+    fn flush<'a>(&'a mut self) -> FsFuture<()> {
+        async move {
+            if self.written {
+                self.written = false;
+                self.fs.check(&self.path, &mut self.staged).await?;
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+impl DavFileSystem for AntivirusFs {
+    fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
+        async move {
+            if !options.write {
+                return self.inner.open(path, options).await;
+            }
+            let exists = self.check_open(path, &options).await?;
+            let mut staged = Staged::new().await?;
+            let mut written = false;
+            if exists && !options.truncate {
+                // partial write changes previous content
+                let mut f = self.inner.open(path, open_opts::read()).await?;
+                loop {
+                    let data = f.read_bytes(CHUNK).await?;
+                    if data.is_empty() {
+                        break;
+                    }
+                    staged.file.write_all(&data).await.map_err(io_error)?;
+                }
+                if !options.append {
+                    staged.rewind().await.map_err(io_error)?;
+                }
+            } else {
+                // truncated or created file is written even if empty
+                written = true;
+            }
+            Ok(Box::new(AntivirusFile {
+                staged,
+                fs: self.clone(),
+                path: path.clone(),
+                written,
+            }) as Box<dyn DavFile>)
+        }
+        .boxed()
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        meta: ReadDirMeta,
+    ) -> FsFuture<FsStream<Box<dyn DavDirEntry>>> {
+        self.inner.read_dir(path, meta)
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.metadata(path)
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.symlink_metadata(path)
+    }
+
+    fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.create_dir(path)
+    }
+
+    fn remove_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.remove_dir(path)
+    }
+
+    fn remove_file<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.remove_file(path)
+    }
+
+    fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        self.inner.rename(from, to)
+    }
+
+    fn copy<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        self.inner.copy(from, to)
+    }
+
+    fn set_accessed<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_accessed(path, tm)
+    }
+
+    fn set_modified<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_modified(path, tm)
+    }
+
+    fn have_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
+        self.inner.have_props(path)
+    }
+
+    fn patch_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+        patch: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(hyper::StatusCode, DavProp)>> {
+        self.inner.patch_props(path, patch)
+    }
+
+    fn get_props<'a>(&'a self, path: &'a DavPath, do_content: bool) -> FsFuture<Vec<DavProp>> {
+        self.inner.get_props(path, do_content)
+    }
+
+    fn get_prop<'a>(&'a self, path: &'a DavPath, prop: DavProp) -> FsFuture<Vec<u8>> {
+        self.inner.get_prop(path, prop)
+    }
+
+    fn get_quota<'a>(&'a self) -> FsFuture<(u64, Option<u64>)> {
+        self.inner.get_quota()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::write_opts;
+    use tokio::{io::AsyncReadExt, net::TcpListener};
+    use webdav_handler::memfs::MemFs;
+
+    /// Answers like clamd, content with "EICAR" is infected.
+    async fn fake_clamd() -> anyhow::Result<String> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?.to_string();
+        tokio::spawn(async move {
+            while let Ok((mut conn, _)) = listener.accept().await {
+                let mut cmd = [0u8; 10];
+                conn.read_exact(&mut cmd).await.unwrap();
+                let mut content = vec![];
+                loop {
+                    let len = conn.read_u32().await.unwrap() as usize;
+                    if len == 0 {
+                        break;
+                    }
+                    let mut chunk = vec![0; len];
+                    conn.read_exact(&mut chunk).await.unwrap();
+                    content.extend(chunk);
+                }
+                let reply: &[u8] = if String::from_utf8_lossy(&content).contains("EICAR") {
+                    b"stream: Eicar-Test-Signature FOUND\0"
+                } else {
+                    b"stream: OK\0"
+                };
+                conn.write_all(reply).await.unwrap();
+            }
+        });
+        Ok(addr)
+    }
+
+    async fn upload(fs: &dyn DavFileSystem, path: &str, data: &'static [u8]) -> FsResult<()> {
+        let mut f = fs.open(&DavPath::new(path).unwrap(), write_opts()).await?;
+        f.write_bytes(Bytes::from_static(data)).await?;
+        f.flush().await
+    }
+
+    #[tokio::test]
+    async fn quarantines_infected() -> anyhow::Result<()> {
+        let clamd = Clamd::new(&fake_clamd().await?);
+        let fs = AntivirusFs::new(MemFs::new(), clamd, Some("/.quarantine/".into()));
+        fs.create_dir(&DavPath::new("/dir/")?).await?;
+
+        upload(fs.as_ref(), "/dir/a.txt", b"hello").await?;
+        assert!(fs.metadata(&DavPath::new("/dir/a.txt")?).await.is_ok());
+
+        let res = upload(fs.as_ref(), "/dir/b.txt", b"X5O EICAR test").await;
+        assert!(matches!(res, Err(FsError::Forbidden)));
+        assert!(fs.metadata(&DavPath::new("/dir/b.txt")?).await.is_err());
+        assert!(fs
+            .metadata(&DavPath::new("/.quarantine/dir/b.txt")?)
+            .await
+            .is_ok());
+        Ok(())
+    }
+
+    async fn content(fs: &dyn DavFileSystem, path: &str) -> anyhow::Result<Bytes> {
+        let mut f = fs.open(&DavPath::new(path)?, open_opts::read()).await?;
+        Ok(f.read_bytes(1024).await?)
+    }
+
+    #[tokio::test]
+    async fn keeps_previous_content() -> anyhow::Result<()> {
+        let clamd = Clamd::new(&fake_clamd().await?);
+        let mem = MemFs::new();
+        let fs = AntivirusFs::new(mem.clone(), clamd, None);
+        upload(fs.as_ref(), "/a.txt", b"hello").await?;
+
+        let res = upload(fs.as_ref(), "/a.txt", b"X5O EICAR test").await;
+        assert!(matches!(res, Err(FsError::Forbidden)));
+        assert_eq!(content(fs.as_ref(), "/a.txt").await?, "hello");
+
+        // partial write is scanned with the rest of file
+        let mut f = fs
+            .open(&DavPath::new("/a.txt")?, open_opts::write())
+            .await?;
+        f.seek(SeekFrom::Start(5)).await?;
+        f.write_bytes(Bytes::from_static(b" world")).await?;
+        f.flush().await?;
+        assert_eq!(content(fs.as_ref(), "/a.txt").await?, "hello world");
+
+        // clamd is down
+        let addr = TcpListener::bind("127.0.0.1:0").await?.local_addr()?;
+        let fs = AntivirusFs::new(mem.clone(), Clamd::new(&addr.to_string()), None);
+        let res = upload(fs.as_ref(), "/a.txt", b"new").await;
+        assert!(matches!(res, Err(FsError::GeneralFailure)));
+        assert_eq!(content(fs.as_ref(), "/a.txt").await?, "hello world");
+        assert!(upload(fs.as_ref(), "/b.txt", b"new").await.is_err());
+        assert!(mem.metadata(&DavPath::new("/b.txt")?).await.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn replies() {
+        assert_eq!(parse_reply(b"stream: OK\0").unwrap(), Verdict::Clean);
+        assert_eq!(
+            parse_reply(b"stream: Win.Test FOUND\0").unwrap(),
+            Verdict::Infected("Win.Test".into())
+        );
+        assert!(parse_reply(b"INSTREAM size limit exceeded. ERROR\0").is_err());
+    }
+}
@@ -0,0 +1,355 @@
+use super::read_only::ReadOnlyFs;
+use bytes::{Buf, Bytes};
+use futures_util::FutureExt;
+use std::{
+    io::SeekFrom,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+use tracing::{info, warn};
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, DavProp, FsError, FsFuture, FsResult,
+        FsStream, OpenOptions, ReadDirMeta,
+    },
+};
+
+#[derive(Debug, Default)]
+struct State {
+    failures: u32,
+    // set while degraded, last time backend was tried
+    tried: Option<Instant>,
+}
+
+/// Counts consecutive backend failures. After `threshold` of them mount is
+/// degraded and backend is tried once per `cooldown` until a call succeeds.
+#[derive(Debug)]
+pub struct Breaker {
+    threshold: u32,
+    cooldown: Duration,
+    state: Mutex<State>,
+}
+
+impl Breaker {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Breaker {
+            threshold: threshold.max(1),
+            cooldown,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.state.lock().unwrap().tried.is_some()
+    }
+
+    /// Returns true if backend should be called now.
+    fn allow(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        match state.tried {
+            None => true,
+            Some(t) if t.elapsed() >= self.cooldown => {
+                state.tried = Some(Instant::now());
+                true
+            }
+            Some(_) => false,
+        }
+    }
+
+    fn record<T>(&self, r: &FsResult<T>) {
+        let mut state = self.state.lock().unwrap();
+        if !is_failure(r) {
+            if state.tried.is_some() {
+                info!("backend recovered");
+            }
+            *state = State::default();
+            return;
+        }
+        state.failures = state.failures.saturating_add(1);
+        if state.failures == self.threshold {
+            warn!(
+                failures = state.failures,
+                "backend is failing, mount degraded"
+            );
+            state.tried = Some(Instant::now());
+        }
+    }
+}
+
+/// Other errors are answers of a working backend.
+fn is_failure<T>(r: &FsResult<T>) -> bool {
+    matches!(r, Err(FsError::GeneralFailure))
+}
+
+/// Stops calling backend which keeps failing, e.g. unreachable s3 endpoint,
+/// so requests fail fast or are served by failover backend meanwhile.
+/// Failover only serves reads, changes made to it would be lost for the
+/// primary backend.
+#[derive(Clone)]
+pub struct BreakerFs {
+    inner: Box<dyn DavFileSystem>,
+    failover: Option<Box<dyn DavFileSystem>>,
+    breaker: Arc<Breaker>,
+}
+
+impl BreakerFs {
+    pub fn new(
+        inner: Box<dyn DavFileSystem>,
+        failover: Option<Box<dyn DavFileSystem>>,
+        breaker: Arc<Breaker>,
+    ) -> Box<dyn DavFileSystem> {
+        Box::new(BreakerFs {
+            inner,
+            failover: failover.map(ReadOnlyFs::new),
+            breaker,
+        }) as Box<dyn DavFileSystem>
+    }
+
+    fn call<'a, T, F>(&'a self, f: F) -> FsFuture<'a, T>
+    where
+        T: Send + 'a,
+        F: Fn(&'a dyn DavFileSystem) -> FsFuture<'a, T> + Send + 'a,
+    {
+        async move {
+            if self.breaker.allow() {
+                let r = f(self.inner.as_ref()).await;
+                self.breaker.record(&r);
+                if !is_failure(&r) || self.failover.is_none() {
+                    return r;
+                }
+            }
+            match self.failover {
+                Some(ref fs) => f(fs.as_ref()).await,
+                None => Err(FsError::GeneralFailure),
+            }
+        }
+        .boxed()
+    }
+}
+
+/// File of primary backend, failures while reading or writing it count too.
+#[derive(Debug)]
+struct BreakerFile {
+    inner: Box<dyn DavFile>,
+    breaker: Arc<Breaker>,
+}
+
+fn record<'a, T: Send + 'a>(breaker: Arc<Breaker>, f: FsFuture<'a, T>) -> FsFuture<'a, T> {
+    async move {
+        let r = f.await;
+        breaker.record(&r);
+        r
+    }
+    .boxed()
+}
+
+impl DavFile for BreakerFile {
+    fn metadata<'a>(&'a mut self) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.metadata()
+    }
+
+    fn write_buf<'a>(&'a mut self, buf: Box<dyn Buf + Send>) -> FsFuture<()> {
+        let breaker = self.breaker.clone();
+        let f = self.inner.write_buf(buf);
+        record(breaker, f)
+    }
+
+    fn write_bytes<'a>(&'a mut self, buf: Bytes) -> FsFuture<()> {
+        let breaker = self.breaker.clone();
+        let f = self.inner.write_bytes(buf);
+        record(breaker, f)
+    }
+
+    fn read_bytes<'a>(&'a mut self, count: usize) -> FsFuture<Bytes> {
+        let breaker = self.breaker.clone();
+        let f = self.inner.read_bytes(count);
+        record(breaker, f)
+    }
+
+    fn seek<'a>(&'a mut self, pos: SeekFrom) -> FsFuture<u64> {
+        self.inner.seek(pos)
+    }
+
+    fn flush<'a>(&'a mut self) -> FsFuture<()> {
+        let breaker = self.breaker.clone();
+        let f = self.inner.flush();
+        record(breaker, f)
+    }
+}
+
+impl DavFileSystem for BreakerFs {
+    fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
+        async move {
+            if self.breaker.allow() {
+                let r = self.inner.open(path, options).await;
+                self.breaker.record(&r);
+                match r {
+                    Ok(inner) => {
+                        return Ok(Box::new(BreakerFile {
+                            inner,
+                            breaker: self.breaker.clone(),
+                        }) as Box<dyn DavFile>)
+                    }
+                    Err(FsError::GeneralFailure) if self.failover.is_some() => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            match self.failover {
+                Some(ref fs) => fs.open(path, options).await,
+                None => Err(FsError::GeneralFailure),
+            }
+        }
+        .boxed()
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        meta: ReadDirMeta,
+    ) -> FsFuture<FsStream<Box<dyn DavDirEntry>>> {
+        self.call(move |fs| fs.read_dir(path, meta))
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.call(move |fs| fs.metadata(path))
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.call(move |fs| fs.symlink_metadata(path))
+    }
+
+    fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.call(move |fs| fs.create_dir(path))
+    }
+
+    fn remove_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.call(move |fs| fs.remove_dir(path))
+    }
+
+    fn remove_file<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.call(move |fs| fs.remove_file(path))
+    }
+
+    fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        self.call(move |fs| fs.rename(from, to))
+    }
+
+    fn copy<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        self.call(move |fs| fs.copy(from, to))
+    }
+
+    fn set_accessed<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.call(move |fs| fs.set_accessed(path, tm))
+    }
+
+    fn set_modified<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.call(move |fs| fs.set_modified(path, tm))
+    }
+
+    fn have_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
+        match self.failover {
+            Some(ref fs) if self.breaker.is_degraded() => fs.have_props(path),
+            _ => self.inner.have_props(path),
+        }
+    }
+
+    fn patch_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+        patch: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(hyper::StatusCode, DavProp)>> {
+        self.call(move |fs| fs.patch_props(path, patch.clone()))
+    }
+
+    fn get_props<'a>(&'a self, path: &'a DavPath, do_content: bool) -> FsFuture<Vec<DavProp>> {
+        self.call(move |fs| fs.get_props(path, do_content))
+    }
+
+    fn get_prop<'a>(&'a self, path: &'a DavPath, prop: DavProp) -> FsFuture<Vec<u8>> {
+        self.call(move |fs| fs.get_prop(path, prop.clone()))
+    }
+
+    fn get_quota<'a>(&'a self) -> FsFuture<(u64, Option<u64>)> {
+        self.call(move |fs| fs.get_quota())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::open_opts;
+    use crate::test_utils::{put, write_opts};
+    use webdav_handler::memfs::MemFs;
+
+    /// Backend which is down.
+    #[derive(Debug, Clone)]
+    struct Down;
+
+    impl DavFileSystem for Down {
+        fn open<'a>(&'a self, _: &'a DavPath, _: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
+            async { Err(FsError::GeneralFailure) }.boxed()
+        }
+
+        fn read_dir<'a>(
+            &'a self,
+            _: &'a DavPath,
+            _: ReadDirMeta,
+        ) -> FsFuture<FsStream<Box<dyn DavDirEntry>>> {
+            async { Err(FsError::GeneralFailure) }.boxed()
+        }
+
+        fn metadata<'a>(&'a self, _: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+            async { Err(FsError::GeneralFailure) }.boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn degrades_and_fails_over() -> anyhow::Result<()> {
+        let root = DavPath::new("/")?;
+        let breaker = Arc::new(Breaker::new(2, Duration::from_secs(3600)));
+        let fs = BreakerFs::new(Box::new(Down), None, breaker.clone());
+        assert!(fs.metadata(&root).await.is_err());
+        assert!(!breaker.is_degraded());
+        assert!(fs.metadata(&root).await.is_err());
+        assert!(breaker.is_degraded());
+
+        let breaker = Arc::new(Breaker::new(1, Duration::from_secs(3600)));
+        let fs = BreakerFs::new(Box::new(Down), Some(MemFs::new()), breaker.clone());
+        assert!(fs.metadata(&root).await?.is_dir());
+        assert!(breaker.is_degraded());
+        // served by failover without trying primary until cooldown passes
+        assert!(fs.metadata(&root).await?.is_dir());
+
+        let breaker = Arc::new(Breaker::new(1, Duration::from_secs(0)));
+        let fs = BreakerFs::new(MemFs::new(), None, breaker.clone());
+        breaker.record::<()>(&Err(FsError::GeneralFailure));
+        assert!(breaker.is_degraded());
+        assert!(fs.metadata(&root).await?.is_dir());
+        assert!(!breaker.is_degraded());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn failover_is_read_only() -> anyhow::Result<()> {
+        let replica = MemFs::new();
+        put(replica.as_ref(), "/a.txt", b"data").await?;
+        let breaker = Arc::new(Breaker::new(1, Duration::from_secs(3600)));
+        let fs = BreakerFs::new(Box::new(Down), Some(replica.clone()), breaker);
+
+        let (old, new) = (DavPath::new("/a.txt")?, DavPath::new("/b.txt")?);
+        let mut f = fs.open(&old, open_opts::read()).await?;
+        assert_eq!(f.read_bytes(16).await?, "data");
+        assert!(matches!(
+            fs.open(&new, write_opts()).await,
+            Err(FsError::Forbidden)
+        ));
+        assert!(fs.remove_file(&old).await.is_err());
+        assert!(replica.metadata(&new).await.is_err());
+        assert!(replica.metadata(&old).await.is_ok());
+        Ok(())
+    }
+}
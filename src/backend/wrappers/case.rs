@@ -0,0 +1,241 @@
+use super::path_map::ENC;
+use futures_util::{FutureExt, StreamExt};
+use percent_encoding::percent_encode;
+use std::time::SystemTime;
+use tracing::debug;
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, DavProp, FsError, FsFuture, FsResult,
+        FsStream, OpenOptions, ReadDirMeta,
+    },
+};
+
+/// Filesystem wrapper resolving path components ignoring case, so clients
+/// which change case of names between requests still find their files.
+/// Exact matches are preferred, components which do not exist are passed as is.
+#[derive(Clone)]
+pub struct CaseInsensitiveFs {
+    inner: Box<dyn DavFileSystem>,
+}
+
+impl CaseInsensitiveFs {
+    pub fn new(inner: Box<dyn DavFileSystem>) -> Box<dyn DavFileSystem> {
+        Box::new(CaseInsensitiveFs { inner }) as Box<dyn DavFileSystem>
+    }
+
+    async fn find_in_dir(&self, dir: &str, name: &str) -> Option<String> {
+        let dir = DavPath::new(dir).ok()?;
+        let lower = name.to_lowercase();
+        let mut entries = self.inner.read_dir(&dir, ReadDirMeta::None).await.ok()?;
+        while let Some(e) = entries.next().await {
+            let n = String::from_utf8_lossy(&e.name())
+                .trim_end_matches('/')
+                .to_string();
+            if n.to_lowercase() == lower {
+                return Some(n);
+            }
+        }
+        None
+    }
+
+    async fn resolve(&self, path: &DavPath) -> FsResult<DavPath> {
+        if path.as_url_string() == "/" || self.inner.metadata(path).await.is_ok() {
+            return Ok(path.clone());
+        }
+        let col = path.is_collection();
+        let pb = path.as_pathbuf();
+        let s = pb.to_str().ok_or(FsError::NotFound)?;
+        let names = s.split('/').filter(|n| !n.is_empty()).collect::<Vec<_>>();
+
+        let mut cur = String::from("/");
+        let mut rest = &names[..];
+        // parent spelled as requested exists, as for most new files, so only
+        // leaf is looked up instead of listing every ancestor
+        if names.len() > 1 {
+            let parent = names[..names.len() - 1]
+                .iter()
+                .map(|n| format!("{}/", percent_encode(n.as_bytes(), ENC)))
+                .collect::<String>();
+            let parent = format!("/{}", parent);
+            if let Ok(p) = DavPath::new(&parent) {
+                if self.inner.metadata(&p).await.is_ok() {
+                    cur = parent;
+                    rest = &names[names.len() - 1..];
+                }
+            }
+        }
+        let mut found = true;
+        for (i, name) in rest.iter().enumerate() {
+            let last = i == rest.len() - 1;
+            let name = if found {
+                match self.find_in_dir(&cur, name).await {
+                    Some(n) => n,
+                    None => {
+                        found = false;
+                        name.to_string()
+                    }
+                }
+            } else {
+                name.to_string()
+            };
+            cur.push_str(&percent_encode(name.as_bytes(), ENC).to_string());
+            if !last || col {
+                cur.push('/');
+            }
+        }
+        debug!(from = %path.as_url_string(), to = %cur);
+        DavPath::new(&cur).map_err(|_| FsError::NotFound)
+    }
+}
+
+impl DavFileSystem for CaseInsensitiveFs {
+    fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
+        async move { self.inner.open(&self.resolve(path).await?, options).await }.boxed()
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        meta: ReadDirMeta,
+    ) -> FsFuture<FsStream<Box<dyn DavDirEntry>>> {
+        async move { self.inner.read_dir(&self.resolve(path).await?, meta).await }.boxed()
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        async move { self.inner.metadata(&self.resolve(path).await?).await }.boxed()
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        async move {
+            self.inner
+                .symlink_metadata(&self.resolve(path).await?)
+                .await
+        }
+        .boxed()
+    }
+
+    fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        async move { self.inner.create_dir(&self.resolve(path).await?).await }.boxed()
+    }
+
+    fn remove_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        async move { self.inner.remove_dir(&self.resolve(path).await?).await }.boxed()
+    }
+
+    fn remove_file<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        async move { self.inner.remove_file(&self.resolve(path).await?).await }.boxed()
+    }
+
+    fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        async move {
+            let from = self.resolve(from).await?;
+            let to = self.resolve(to).await?;
+            self.inner.rename(&from, &to).await
+        }
+        .boxed()
+    }
+
+    fn copy<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        async move {
+            let from = self.resolve(from).await?;
+            let to = self.resolve(to).await?;
+            self.inner.copy(&from, &to).await
+        }
+        .boxed()
+    }
+
+    fn set_accessed<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        async move {
+            self.inner
+                .set_accessed(&self.resolve(path).await?, tm)
+                .await
+        }
+        .boxed()
+    }
+
+    fn set_modified<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        async move {
+            self.inner
+                .set_modified(&self.resolve(path).await?, tm)
+                .await
+        }
+        .boxed()
+    }
+
+    fn have_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
+        async move {
+            match self.resolve(path).await {
+                Ok(p) => self.inner.have_props(&p).await,
+                Err(_) => false,
+            }
+        }
+        .boxed()
+    }
+
+    fn patch_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+        patch: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(hyper::StatusCode, DavProp)>> {
+        async move {
+            self.inner
+                .patch_props(&self.resolve(path).await?, patch)
+                .await
+        }
+        .boxed()
+    }
+
+    fn get_props<'a>(&'a self, path: &'a DavPath, do_content: bool) -> FsFuture<Vec<DavProp>> {
+        async move {
+            self.inner
+                .get_props(&self.resolve(path).await?, do_content)
+                .await
+        }
+        .boxed()
+    }
+
+    fn get_prop<'a>(&'a self, path: &'a DavPath, prop: DavProp) -> FsFuture<Vec<u8>> {
+        async move { self.inner.get_prop(&self.resolve(path).await?, prop).await }.boxed()
+    }
+
+    fn get_quota<'a>(&'a self) -> FsFuture<(u64, Option<u64>)> {
+        self.inner.get_quota()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::write_opts;
+    use webdav_handler::memfs::MemFs;
+
+    fn path(s: &str) -> DavPath {
+        DavPath::new(s).unwrap()
+    }
+
+    #[tokio::test]
+    async fn resolves_case() -> anyhow::Result<()> {
+        let mem = MemFs::new();
+        mem.create_dir(&path("/Docs/")).await?;
+        mem.open(&path("/Docs/Report%20One.docx"), write_opts())
+            .await?;
+
+        let fs = CaseInsensitiveFs::new(mem.clone());
+        assert!(fs.metadata(&path("/docs/report%20one.DOCX")).await.is_ok());
+        assert!(fs.metadata(&path("/DOCS/")).await?.is_dir());
+        assert!(fs.metadata(&path("/docs/missing")).await.is_err());
+
+        // new file is created in existing dir keeping requested name
+        fs.open(&path("/docs/~%24New.tmp"), write_opts()).await?;
+        assert!(mem.metadata(&path("/Docs/~%24New.tmp")).await.is_ok());
+        // existing file of other case is opened in existing dir
+        let other = path("/Docs/report%20one.DOCX");
+        fs.open(&other, write_opts()).await?;
+        assert!(mem.metadata(&other).await.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,339 @@
+use bytes::{Buf, Bytes};
+use futures_util::FutureExt;
+use std::{
+    io::SeekFrom,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime},
+};
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, DavProp, FsError, FsFuture, FsResult,
+        FsStream, OpenOptions, ReadDirMeta,
+    },
+};
+
+/// Faults injected into backend calls, rates are chances from 0.0 to 1.0.
+#[derive(Debug, Clone, Default)]
+pub struct Faults {
+    /// delay added to calls picked by `latency_rate`
+    pub latency: Duration,
+    pub latency_rate: f64,
+    /// calls failing with GeneralFailure, as unreachable backend does
+    pub error_rate: f64,
+    /// file reads ending early, later reads of the file return nothing
+    pub truncate_rate: f64,
+}
+
+/// Decides which calls get faults. Random numbers come from `seed`, so the
+/// same sequence of calls gets the same faults in every run.
+#[derive(Debug)]
+pub struct Chaos {
+    faults: Mutex<Faults>,
+    rng: Mutex<u64>,
+    injected: AtomicU64,
+}
+
+impl Chaos {
+    pub fn new(faults: Faults, seed: u64) -> Arc<Chaos> {
+        Arc::new(Chaos {
+            faults: Mutex::new(faults),
+            // xorshift never leaves zero
+            rng: Mutex::new(seed.max(1)),
+            injected: AtomicU64::new(0),
+        })
+    }
+
+    /// Replaces faults, e.g. to let backend recover in the middle of a test.
+    pub fn set_faults(&self, faults: Faults) {
+        *self.faults.lock().unwrap() = faults;
+    }
+
+    /// Number of faults injected so far.
+    pub fn injected(&self) -> u64 {
+        self.injected.load(Ordering::SeqCst)
+    }
+
+    fn roll(&self, rate: f64) -> bool {
+        if rate <= 0.0 {
+            return false;
+        }
+        let mut x = self.rng.lock().unwrap();
+        *x ^= *x << 13;
+        *x ^= *x >> 7;
+        *x ^= *x << 17;
+        let sample = (*x >> 11) as f64 / (1u64 << 53) as f64;
+        let hit = sample < rate;
+        if hit {
+            self.injected.fetch_add(1, Ordering::SeqCst);
+        }
+        hit
+    }
+
+    /// Delays and fails call according to faults.
+    async fn call(&self) -> FsResult<()> {
+        let faults = self.faults.lock().unwrap().clone();
+        if self.roll(faults.latency_rate) {
+            tokio::time::sleep(faults.latency).await;
+        }
+        if self.roll(faults.error_rate) {
+            return Err(FsError::GeneralFailure);
+        }
+        Ok(())
+    }
+
+    fn truncates(&self) -> bool {
+        let rate = self.faults.lock().unwrap().truncate_rate;
+        self.roll(rate)
+    }
+}
+
+/// Injects latency, errors and truncated reads into calls of wrapped
+/// backend, for testing how retries, circuit breaker and clients cope with
+/// failing storage. Props and quota are passed through.
+#[derive(Clone)]
+pub struct ChaosFs {
+    inner: Box<dyn DavFileSystem>,
+    chaos: Arc<Chaos>,
+}
+
+impl ChaosFs {
+    pub fn new(inner: Box<dyn DavFileSystem>, chaos: Arc<Chaos>) -> Box<dyn DavFileSystem> {
+        Box::new(ChaosFs { inner, chaos }) as Box<dyn DavFileSystem>
+    }
+
+    fn call<'a, T, F>(&'a self, f: F) -> FsFuture<'a, T>
+    where
+        T: Send + 'a,
+        F: FnOnce(&'a dyn DavFileSystem) -> FsFuture<'a, T> + Send + 'a,
+    {
+        async move {
+            self.chaos.call().await?;
+            f(self.inner.as_ref()).await
+        }
+        .boxed()
+    }
+}
+
+#[derive(Debug)]
+struct ChaosFile {
+    inner: Box<dyn DavFile>,
+    chaos: Arc<Chaos>,
+    truncated: bool,
+}
+
+impl DavFile for ChaosFile {
+    fn metadata<'a>(&'a mut self) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.metadata()
+    }
+
+    fn write_buf<'a>(&'a mut self, buf: Box<dyn Buf + Send>) -> FsFuture<()> {
+        async move {
+            self.chaos.call().await?;
+            self.inner.write_buf(buf).await
+        }
+        .boxed()
+    }
+
+    fn write_bytes<'a>(&'a mut self, buf: Bytes) -> FsFuture<()> {
+        async move {
+            self.chaos.call().await?;
+            self.inner.write_bytes(buf).await
+        }
+        .boxed()
+    }
+
+    fn read_bytes<'a>(&'a mut self, count: usize) -> FsFuture<Bytes> {
+        async move {
+            self.chaos.call().await?;
+            if self.truncated {
+                return Ok(Bytes::new());
+            }
+            let data = self.inner.read_bytes(count).await?;
+            if !data.is_empty() && self.chaos.truncates() {
+                self.truncated = true;
+                return Ok(data.slice(..data.len() / 2));
+            }
+            Ok(data)
+        }
+        .boxed()
+    }
+
+    fn seek<'a>(&'a mut self, pos: SeekFrom) -> FsFuture<u64> {
+        self.inner.seek(pos)
+    }
+
+    fn flush<'a>(&'a mut self) -> FsFuture<()> {
+        async move {
+            self.chaos.call().await?;
+            self.inner.flush().await
+        }
+        .boxed()
+    }
+}
+
+impl DavFileSystem for ChaosFs {
+    fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
+        async move {
+            self.chaos.call().await?;
+            let inner = self.inner.open(path, options).await?;
+            Ok(Box::new(ChaosFile {
+                inner,
+                chaos: self.chaos.clone(),
+                truncated: false,
+            }) as Box<dyn DavFile>)
+        }
+        .boxed()
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        meta: ReadDirMeta,
+    ) -> FsFuture<FsStream<Box<dyn DavDirEntry>>> {
+        self.call(move |fs| fs.read_dir(path, meta))
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.call(move |fs| fs.metadata(path))
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.call(move |fs| fs.symlink_metadata(path))
+    }
+
+    fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.call(move |fs| fs.create_dir(path))
+    }
+
+    fn remove_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.call(move |fs| fs.remove_dir(path))
+    }
+
+    fn remove_file<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.call(move |fs| fs.remove_file(path))
+    }
+
+    fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        self.call(move |fs| fs.rename(from, to))
+    }
+
+    fn copy<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        self.call(move |fs| fs.copy(from, to))
+    }
+
+    fn set_accessed<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_accessed(path, tm)
+    }
+
+    fn set_modified<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_modified(path, tm)
+    }
+
+    fn have_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
+        self.inner.have_props(path)
+    }
+
+    fn patch_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+        patch: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(hyper::StatusCode, DavProp)>> {
+        self.inner.patch_props(path, patch)
+    }
+
+    fn get_props<'a>(&'a self, path: &'a DavPath, do_content: bool) -> FsFuture<Vec<DavProp>> {
+        self.inner.get_props(path, do_content)
+    }
+
+    fn get_prop<'a>(&'a self, path: &'a DavPath, prop: DavProp) -> FsFuture<Vec<u8>> {
+        self.inner.get_prop(path, prop)
+    }
+
+    fn get_quota<'a>(&'a self) -> FsFuture<(u64, Option<u64>)> {
+        self.inner.get_quota()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::open_opts;
+    use crate::{
+        backend::wrappers::breaker::{Breaker, BreakerFs},
+        test_utils::put,
+    };
+    use webdav_handler::memfs::MemFs;
+
+    async fn outcomes(seed: u64) -> Vec<bool> {
+        let faults = Faults {
+            error_rate: 0.5,
+            ..Faults::default()
+        };
+        let fs = ChaosFs::new(MemFs::new(), Chaos::new(faults, seed));
+        let root = DavPath::new("/").unwrap();
+        let mut r = vec![];
+        for _ in 0..32 {
+            r.push(fs.metadata(&root).await.is_ok());
+        }
+        r
+    }
+
+    #[tokio::test]
+    async fn same_seed_same_faults() {
+        let first = outcomes(7).await;
+        assert_eq!(first, outcomes(7).await);
+        assert!(first.contains(&true) && first.contains(&false));
+        assert_ne!(first, outcomes(8).await);
+    }
+
+    #[tokio::test]
+    async fn truncates_reads() -> anyhow::Result<()> {
+        let inner = MemFs::new();
+        let path = DavPath::new("/a.txt")?;
+        put(inner.as_ref(), "/a.txt", b"0123456789").await?;
+
+        let faults = Faults {
+            truncate_rate: 1.0,
+            ..Faults::default()
+        };
+        let chaos = Chaos::new(faults, 1);
+        let fs = ChaosFs::new(inner, chaos.clone());
+        let mut file = fs.open(&path, open_opts::read()).await?;
+        assert_eq!(file.read_bytes(10).await?, "01234");
+        assert!(file.read_bytes(10).await?.is_empty());
+        assert_eq!(chaos.injected(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn trips_breaker() -> anyhow::Result<()> {
+        let faults = Faults {
+            error_rate: 1.0,
+            ..Faults::default()
+        };
+        let chaos = Chaos::new(faults, 1);
+        let breaker = Arc::new(Breaker::new(3, Duration::from_secs(0)));
+        let fs = BreakerFs::new(
+            ChaosFs::new(MemFs::new(), chaos.clone()),
+            None,
+            breaker.clone(),
+        );
+        let root = DavPath::new("/")?;
+        for _ in 0..3 {
+            assert!(fs.metadata(&root).await.is_err());
+        }
+        assert!(breaker.is_degraded());
+
+        chaos.set_faults(Faults::default());
+        assert!(fs.metadata(&root).await?.is_dir());
+        assert!(!breaker.is_degraded());
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+use futures_util::FutureExt;
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, DavProp, FsError, FsFuture, FsStream,
+        OpenOptions, ReadDirMeta,
+    },
+};
+
+/// Size of filesystem holding a directory, in bytes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Space {
+    pub total: u64,
+    /// free space usable by unprivileged process
+    pub available: u64,
+}
+
+#[cfg(unix)]
+pub fn space(path: &Path) -> io::Result<Space> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    // SAFETY: path is nul terminated and stat is plain data filled by call
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let block = stat.f_frsize as u64;
+    Ok(Space {
+        total: stat.f_blocks as u64 * block,
+        available: stat.f_bavail as u64 * block,
+    })
+}
+
+#[cfg(not(unix))]
+pub fn space(_: &Path) -> io::Result<Space> {
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "free space is known on unix only",
+    ))
+}
+
+/// Whether `len` more bytes fit into filesystem of `root`. Unknown space is
+/// assumed to fit so the write itself decides.
+pub fn fits(root: &Path, len: u64) -> bool {
+    space(root).map_or(true, |s| len <= s.available)
+}
+
+/// Reports size of local filesystem as quota, so clients see free space.
+/// Used bytes are of the whole filesystem, UsageFs above counts mount only.
+#[derive(Clone)]
+pub struct DiskSpaceFs {
+    inner: Box<dyn DavFileSystem>,
+    root: PathBuf,
+}
+
+impl DiskSpaceFs {
+    pub fn new(inner: Box<dyn DavFileSystem>, root: impl Into<PathBuf>) -> Box<dyn DavFileSystem> {
+        Box::new(DiskSpaceFs {
+            inner,
+            root: root.into(),
+        }) as Box<dyn DavFileSystem>
+    }
+}
+
+impl DavFileSystem for DiskSpaceFs {
+    fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
+        self.inner.open(path, options)
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        meta: ReadDirMeta,
+    ) -> FsFuture<FsStream<Box<dyn DavDirEntry>>> {
+        self.inner.read_dir(path, meta)
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.metadata(path)
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.symlink_metadata(path)
+    }
+
+    fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.create_dir(path)
+    }
+
+    fn remove_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.remove_dir(path)
+    }
+
+    fn remove_file<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.remove_file(path)
+    }
+
+    fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        self.inner.rename(from, to)
+    }
+
+    fn copy<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        self.inner.copy(from, to)
+    }
+
+    fn set_accessed<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_accessed(path, tm)
+    }
+
+    fn set_modified<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_modified(path, tm)
+    }
+
+    fn have_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
+        self.inner.have_props(path)
+    }
+
+    fn patch_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+        patch: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(hyper::StatusCode, DavProp)>> {
+        self.inner.patch_props(path, patch)
+    }
+
+    fn get_props<'a>(&'a self, path: &'a DavPath, do_content: bool) -> FsFuture<Vec<DavProp>> {
+        self.inner.get_props(path, do_content)
+    }
+
+    fn get_prop<'a>(&'a self, path: &'a DavPath, prop: DavProp) -> FsFuture<Vec<u8>> {
+        self.inner.get_prop(path, prop)
+    }
+
+    fn get_quota<'a>(&'a self) -> FsFuture<(u64, Option<u64>)> {
+        async move {
+            let s = space(&self.root).map_err(|_| FsError::GeneralFailure)?;
+            Ok((s.total.saturating_sub(s.available), Some(s.total)))
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use webdav_handler::memfs::MemFs;
+
+    #[tokio::test]
+    async fn reports_free_space() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir();
+        let s = space(&dir)?;
+        assert!(s.total >= s.available);
+        assert!(fits(&dir, 0));
+        assert!(!fits(&dir, s.total + 1));
+
+        let fs = DiskSpaceFs::new(MemFs::new(), dir);
+        let (used, total) = fs.get_quota().await?;
+        assert!(used <= total.unwrap());
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+use futures_util::FutureExt;
+use std::time::SystemTime;
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, DavProp, FsError, FsFuture, FsStream,
+        OpenOptions, ReadDirMeta,
+    },
+};
+
+/// Attempts to find free name before giving up.
+const MAX_ATTEMPTS: usize = 1000;
+
+/// Upload-only inbox: new files can be created, but nothing can be listed,
+/// read, changed or removed. Existing files are hidden, uploads with taken
+/// names are stored as `name (1).ext`, `name (2).ext` and so on.
+#[derive(Clone)]
+pub struct DropBoxFs {
+    inner: Box<dyn DavFileSystem>,
+}
+
+impl DropBoxFs {
+    pub fn new(inner: Box<dyn DavFileSystem>) -> Box<dyn DavFileSystem> {
+        Box::new(DropBoxFs { inner }) as Box<dyn DavFileSystem>
+    }
+
+    async fn free_path(&self, path: &DavPath) -> Result<DavPath, FsError> {
+        if self.inner.metadata(path).await.is_err() {
+            return Ok(path.clone());
+        }
+        for n in 1..=MAX_ATTEMPTS {
+            let p = numbered(path, n)?;
+            if self.inner.metadata(&p).await.is_err() {
+                return Ok(p);
+            }
+        }
+        Err(FsError::Exists)
+    }
+}
+
+/// `/dir/name.ext` to `/dir/name (n).ext`, extension is after last dot not
+/// at the start of name.
+fn numbered(path: &DavPath, n: usize) -> Result<DavPath, FsError> {
+    let url = path.as_url_string();
+    let slash = url.rfind('/').unwrap_or(0) + 1;
+    let (dir, name) = url.split_at(slash);
+    let (stem, ext) = match name.rfind('.') {
+        Some(i) if i > 0 => name.split_at(i),
+        _ => (name, ""),
+    };
+    DavPath::new(&format!("{}{}%20({}){}", dir, stem, n, ext)).map_err(|_| FsError::GeneralFailure)
+}
+
+fn forbidden<'a, T: Send + 'a>() -> FsFuture<'a, T> {
+    async { Err(FsError::Forbidden) }.boxed()
+}
+
+impl DavFileSystem for DropBoxFs {
+    fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
+        if !options.create || options.append || options.read {
+            return forbidden();
+        }
+        async move {
+            let path = self.free_path(path).await?;
+            self.inner.open(&path, options).await
+        }
+        .boxed()
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        _: &'a DavPath,
+        _: ReadDirMeta,
+    ) -> FsFuture<FsStream<Box<dyn DavDirEntry>>> {
+        forbidden()
+    }
+
+    /// Only collections are visible, so uploads are never reported as
+    /// overwrites and don't reveal what is already there.
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        async move {
+            let meta = self.inner.metadata(path).await?;
+            if meta.is_dir() {
+                Ok(meta)
+            } else {
+                Err(FsError::NotFound)
+            }
+        }
+        .boxed()
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.metadata(path)
+    }
+
+    fn create_dir<'a>(&'a self, _: &'a DavPath) -> FsFuture<()> {
+        forbidden()
+    }
+
+    fn remove_dir<'a>(&'a self, _: &'a DavPath) -> FsFuture<()> {
+        forbidden()
+    }
+
+    fn remove_file<'a>(&'a self, _: &'a DavPath) -> FsFuture<()> {
+        forbidden()
+    }
+
+    fn rename<'a>(&'a self, _: &'a DavPath, _: &'a DavPath) -> FsFuture<()> {
+        forbidden()
+    }
+
+    fn copy<'a>(&'a self, _: &'a DavPath, _: &'a DavPath) -> FsFuture<()> {
+        forbidden()
+    }
+
+    fn set_accessed<'a>(&'a self, _: &'a DavPath, _: SystemTime) -> FsFuture<()> {
+        forbidden()
+    }
+
+    fn set_modified<'a>(&'a self, _: &'a DavPath, _: SystemTime) -> FsFuture<()> {
+        forbidden()
+    }
+
+    fn have_props<'a>(
+        &'a self,
+        _: &'a DavPath,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
+        async { false }.boxed()
+    }
+
+    fn patch_props<'a>(
+        &'a self,
+        _: &'a DavPath,
+        _: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(hyper::StatusCode, DavProp)>> {
+        forbidden()
+    }
+
+    fn get_props<'a>(&'a self, _: &'a DavPath, _: bool) -> FsFuture<Vec<DavProp>> {
+        forbidden()
+    }
+
+    fn get_prop<'a>(&'a self, _: &'a DavPath, _: DavProp) -> FsFuture<Vec<u8>> {
+        forbidden()
+    }
+
+    fn get_quota<'a>(&'a self) -> FsFuture<(u64, Option<u64>)> {
+        self.inner.get_quota()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::open_opts;
+    use crate::test_utils::put as upload;
+    use webdav_handler::memfs::MemFs;
+
+    #[tokio::test]
+    async fn accepts_only_new_files() -> anyhow::Result<()> {
+        let mem = MemFs::new();
+        mem.create_dir(&DavPath::new("/in/")?).await?;
+        let fs = DropBoxFs::new(mem.clone());
+
+        upload(fs.as_ref(), "/in/report.pdf", b"first").await?;
+        upload(fs.as_ref(), "/in/report.pdf", b"second").await?;
+        upload(fs.as_ref(), "/in/report.pdf", b"third").await?;
+        for p in &[
+            "/in/report.pdf",
+            "/in/report%20(1).pdf",
+            "/in/report%20(2).pdf",
+        ] {
+            assert!(mem.metadata(&DavPath::new(p)?).await.is_ok());
+        }
+        assert_eq!(
+            mem.metadata(&DavPath::new("/in/report.pdf")?).await?.len(),
+            5
+        );
+
+        let file = DavPath::new("/in/report.pdf")?;
+        assert!(fs.metadata(&file).await.is_err());
+        assert!(fs.open(&file, open_opts::read()).await.is_err());
+        assert!(fs
+            .read_dir(&DavPath::new("/in/")?, ReadDirMeta::None)
+            .await
+            .is_err());
+        assert!(fs.remove_file(&file).await.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn numbered_names() {
+        let n = |p: &str| {
+            numbered(&DavPath::new(p).unwrap(), 3)
+                .unwrap()
+                .as_url_string()
+        };
+        assert_eq!(n("/a/b.tar.gz"), "/a/b.tar%20%283%29.gz");
+        assert_eq!(n("/a/.profile"), "/a/.profile%20%283%29");
+        assert_eq!(n("/notes"), "/notes%20%283%29");
+    }
+}
@@ -0,0 +1,238 @@
+use futures_util::FutureExt;
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use tracing::debug;
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, DavProp, FsError, FsFuture, FsStream,
+        OpenOptions, ReadDirMeta,
+    },
+};
+
+/// Local filesystem wrapper for mounts with snapshots. Snapshots share data
+/// with the mount through hard links, so a file with more than one link is
+/// replaced by its own copy before it is opened for writing.
+#[derive(Clone)]
+pub struct BreakLinksFs {
+    inner: Box<dyn DavFileSystem>,
+    root: PathBuf,
+    case_insensitive: bool,
+}
+
+impl BreakLinksFs {
+    pub fn new(
+        inner: Box<dyn DavFileSystem>,
+        root: PathBuf,
+        case_insensitive: bool,
+    ) -> Box<dyn DavFileSystem> {
+        Box::new(BreakLinksFs {
+            inner,
+            root,
+            case_insensitive,
+        }) as Box<dyn DavFileSystem>
+    }
+
+    fn local_path(&self, path: &DavPath) -> PathBuf {
+        let pb = path.as_pathbuf();
+        let rel = pb.strip_prefix("/").unwrap_or(&pb);
+        if self.case_insensitive {
+            on_disk(&self.root, rel)
+        } else {
+            self.root.join(rel)
+        }
+    }
+}
+
+/// Path of file which case insensitive local backend opens for `rel`: every
+/// segment missing as is takes name of first entry differing only in case.
+fn on_disk(root: &Path, rel: &Path) -> PathBuf {
+    let mut path = root.to_path_buf();
+    for seg in rel.iter() {
+        let exact = path.join(seg);
+        if std::fs::symlink_metadata(&exact).is_ok() {
+            path = exact;
+            continue;
+        }
+        let name = seg.to_string_lossy().to_lowercase();
+        let found = std::fs::read_dir(&path).ok().and_then(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .find(|e| e.file_name().to_string_lossy().to_lowercase() == name)
+        });
+        path = match found {
+            Some(e) => e.path(),
+            None => exact,
+        };
+    }
+    path
+}
+
+#[cfg(unix)]
+fn break_link(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let meta = match std::fs::symlink_metadata(path) {
+        Ok(m) => m,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if !meta.is_file() || meta.nlink() < 2 {
+        return Ok(());
+    }
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = path.with_file_name(format!(".{}.cow", name));
+    std::fs::copy(path, &tmp)?;
+    std::fs::rename(&tmp, path)
+}
+
+#[cfg(not(unix))]
+fn break_link(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+impl DavFileSystem for BreakLinksFs {
+    fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
+        async move {
+            if options.write {
+                let fs = self.clone();
+                let dav = path.clone();
+                tokio::task::spawn_blocking(move || {
+                    let local = fs.local_path(&dav);
+                    debug!(msg = "breaking hard link", path = ?local);
+                    break_link(&local)
+                })
+                .await
+                .map_err(|_| FsError::GeneralFailure)?
+                .map_err(|_| FsError::GeneralFailure)?;
+            }
+            self.inner.open(path, options).await
+        }
+        .boxed()
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        meta: ReadDirMeta,
+    ) -> FsFuture<FsStream<Box<dyn DavDirEntry>>> {
+        self.inner.read_dir(path, meta)
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.metadata(path)
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.symlink_metadata(path)
+    }
+
+    fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.create_dir(path)
+    }
+
+    fn remove_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.remove_dir(path)
+    }
+
+    fn remove_file<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.remove_file(path)
+    }
+
+    fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        self.inner.rename(from, to)
+    }
+
+    fn copy<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        self.inner.copy(from, to)
+    }
+
+    fn set_accessed<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_accessed(path, tm)
+    }
+
+    fn set_modified<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_modified(path, tm)
+    }
+
+    fn have_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
+        self.inner.have_props(path)
+    }
+
+    fn patch_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+        patch: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(hyper::StatusCode, DavProp)>> {
+        self.inner.patch_props(path, patch)
+    }
+
+    fn get_props<'a>(&'a self, path: &'a DavPath, do_content: bool) -> FsFuture<Vec<DavProp>> {
+        self.inner.get_props(path, do_content)
+    }
+
+    fn get_prop<'a>(&'a self, path: &'a DavPath, prop: DavProp) -> FsFuture<Vec<u8>> {
+        self.inner.get_prop(path, prop)
+    }
+
+    fn get_quota<'a>(&'a self) -> FsFuture<(u64, Option<u64>)> {
+        self.inner.get_quota()
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_utils::put;
+    use webdav_handler::localfs::LocalFs;
+
+    #[tokio::test]
+    async fn write_does_not_change_link() -> anyhow::Result<()> {
+        let base = std::env::temp_dir().join(format!("webdav_ss_cow_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(base.join("root"))?;
+        std::fs::create_dir_all(base.join("snap"))?;
+        std::fs::write(base.join("root/a.txt"), b"old")?;
+        std::fs::hard_link(base.join("root/a.txt"), base.join("snap/a.txt"))?;
+
+        let fs = BreakLinksFs::new(
+            LocalFs::new(base.join("root"), false, false, false),
+            base.join("root"),
+            false,
+        );
+        put(fs.as_ref(), "/a.txt", b"new").await?;
+
+        assert_eq!(std::fs::read(base.join("root/a.txt"))?, b"new");
+        assert_eq!(std::fs::read(base.join("snap/a.txt"))?, b"old");
+        std::fs::remove_dir_all(&base)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn breaks_link_of_other_case() -> anyhow::Result<()> {
+        let base = std::env::temp_dir().join(format!("webdav_ss_cow_ci_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(base.join("root/Docs"))?;
+        std::fs::create_dir_all(base.join("snap"))?;
+        std::fs::write(base.join("root/Docs/A.txt"), b"old")?;
+        std::fs::hard_link(base.join("root/Docs/A.txt"), base.join("snap/A.txt"))?;
+
+        let fs = BreakLinksFs::new(
+            LocalFs::new(base.join("root"), false, true, false),
+            base.join("root"),
+            true,
+        );
+        put(fs.as_ref(), "/docs/a.txt", b"new").await?;
+
+        assert_eq!(std::fs::read(base.join("root/Docs/A.txt"))?, b"new");
+        assert_eq!(std::fs::read(base.join("snap/A.txt"))?, b"old");
+        std::fs::remove_dir_all(&base)?;
+        Ok(())
+    }
+}
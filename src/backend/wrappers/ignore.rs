@@ -0,0 +1,310 @@
+use bytes::{Buf, Bytes};
+use futures_util::{FutureExt, StreamExt};
+use glob::Pattern;
+use serde::Deserialize;
+use std::{io::SeekFrom, time::SystemTime};
+use tracing::debug;
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, DavProp, FsError, FsFuture, FsResult,
+        FsStream, OpenOptions, ReadDirMeta,
+    },
+};
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum IgnoreAction {
+    /// return 403 for writes
+    Reject,
+    /// pretend write succeeded and drop data
+    Swallow,
+}
+
+/// Filesystem wrapper hiding files which names match ignore patterns.
+/// Existing matching files are not listed and can't be opened, new ones are
+/// rejected or silently dropped depending on action.
+#[derive(Clone)]
+pub struct IgnoreFs {
+    inner: Box<dyn DavFileSystem>,
+    patterns: Vec<Pattern>,
+    action: IgnoreAction,
+}
+
+impl IgnoreFs {
+    pub fn new(
+        inner: Box<dyn DavFileSystem>,
+        patterns: &[String],
+        action: IgnoreAction,
+    ) -> Result<Box<dyn DavFileSystem>, glob::PatternError> {
+        let patterns = patterns
+            .iter()
+            .map(|p| Pattern::new(p))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Box::new(IgnoreFs {
+            inner,
+            patterns,
+            action,
+        }) as Box<dyn DavFileSystem>)
+    }
+
+    fn is_ignored_name(&self, name: &[u8]) -> bool {
+        let name = String::from_utf8_lossy(name);
+        let name = name.trim_end_matches('/');
+        self.patterns.iter().any(|p| p.matches(name))
+    }
+
+    fn is_ignored(&self, path: &DavPath) -> bool {
+        let pb = path.as_pathbuf();
+        match pb.file_name() {
+            Some(n) => self.is_ignored_name(n.to_string_lossy().as_bytes()),
+            None => false,
+        }
+    }
+
+    /// Result of write operation on ignored path.
+    fn write_result(&self, path: &DavPath) -> FsResult<()> {
+        debug!(msg = "ignored write", path = %path.as_url_string(), action = ?self.action);
+        match self.action {
+            IgnoreAction::Reject => Err(FsError::Forbidden),
+            IgnoreAction::Swallow => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DiscardMetaData {
+    len: u64,
+    modified: SystemTime,
+}
+
+impl DavMetaData for DiscardMetaData {
+    fn len(&self) -> u64 {
+        self.len
+    }
+
+    fn modified(&self) -> FsResult<SystemTime> {
+        Ok(self.modified)
+    }
+
+    fn is_dir(&self) -> bool {
+        false
+    }
+}
+
+/// Accepts writes and throws data away.
+#[derive(Debug)]
+struct DiscardFile {
+    meta: DiscardMetaData,
+}
+
+impl DavFile for DiscardFile {
+    fn metadata<'a>(&'a mut self) -> FsFuture<Box<dyn DavMetaData>> {
+        async move { Ok(Box::new(self.meta.clone()) as Box<dyn DavMetaData>) }.boxed()
+    }
+
+    fn write_buf<'a>(&'a mut self, buf: Box<dyn Buf + Send>) -> FsFuture<()> {
+        self.meta.len += buf.remaining() as u64;
+        async move { Ok(()) }.boxed()
+    }
+
+    fn write_bytes<'a>(&'a mut self, buf: Bytes) -> FsFuture<()> {
+        self.meta.len += buf.len() as u64;
+        async move { Ok(()) }.boxed()
+    }
+
+    fn read_bytes<'a>(&'a mut self, _count: usize) -> FsFuture<Bytes> {
+        async move { Ok(Bytes::new()) }.boxed()
+    }
+
+    fn seek<'a>(&'a mut self, _pos: SeekFrom) -> FsFuture<u64> {
+        async move { Ok(0) }.boxed()
+    }
+
+    fn flush<'a>(&'a mut self) -> FsFuture<()> {
+        async move { Ok(()) }.boxed()
+    }
+}
+
+impl DavFileSystem for IgnoreFs {
+    fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
+        async move {
+            if !self.is_ignored(path) {
+                return self.inner.open(path, options).await;
+            }
+            if !options.write {
+                return Err(FsError::NotFound);
+            }
+            self.write_result(path)?;
+            Ok(Box::new(DiscardFile {
+                meta: DiscardMetaData {
+                    len: 0,
+                    modified: SystemTime::now(),
+                },
+            }) as Box<dyn DavFile>)
+        }
+        .boxed()
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        meta: ReadDirMeta,
+    ) -> FsFuture<FsStream<Box<dyn DavDirEntry>>> {
+        async move {
+            let entries = self.inner.read_dir(path, meta).await?;
+            let this = self.clone();
+            let entries = entries.filter(move |e| {
+                let hide = this.is_ignored_name(&e.name());
+                futures_util::future::ready(!hide)
+            });
+            Ok(Box::pin(entries) as FsStream<Box<dyn DavDirEntry>>)
+        }
+        .boxed()
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        async move {
+            if self.is_ignored(path) {
+                return Err(FsError::NotFound);
+            }
+            self.inner.metadata(path).await
+        }
+        .boxed()
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        async move {
+            if self.is_ignored(path) {
+                return Err(FsError::NotFound);
+            }
+            self.inner.symlink_metadata(path).await
+        }
+        .boxed()
+    }
+
+    fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        async move {
+            if self.is_ignored(path) {
+                return self.write_result(path);
+            }
+            self.inner.create_dir(path).await
+        }
+        .boxed()
+    }
+
+    fn remove_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.remove_dir(path)
+    }
+
+    fn remove_file<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        async move {
+            if self.is_ignored(path) {
+                // clean up files created before the rule was added
+                let _ = self.inner.remove_file(path).await;
+                return Ok(());
+            }
+            self.inner.remove_file(path).await
+        }
+        .boxed()
+    }
+
+    fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        async move {
+            if self.is_ignored(to) || self.is_ignored(from) {
+                return Err(FsError::Forbidden);
+            }
+            self.inner.rename(from, to).await
+        }
+        .boxed()
+    }
+
+    fn copy<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        async move {
+            if self.is_ignored(to) || self.is_ignored(from) {
+                return Err(FsError::Forbidden);
+            }
+            self.inner.copy(from, to).await
+        }
+        .boxed()
+    }
+
+    fn set_accessed<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_accessed(path, tm)
+    }
+
+    fn set_modified<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_modified(path, tm)
+    }
+
+    fn have_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
+        self.inner.have_props(path)
+    }
+
+    fn patch_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+        patch: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(hyper::StatusCode, DavProp)>> {
+        self.inner.patch_props(path, patch)
+    }
+
+    fn get_props<'a>(&'a self, path: &'a DavPath, do_content: bool) -> FsFuture<Vec<DavProp>> {
+        self.inner.get_props(path, do_content)
+    }
+
+    fn get_prop<'a>(&'a self, path: &'a DavPath, prop: DavProp) -> FsFuture<Vec<u8>> {
+        self.inner.get_prop(path, prop)
+    }
+
+    fn get_quota<'a>(&'a self) -> FsFuture<(u64, Option<u64>)> {
+        self.inner.get_quota()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::write_opts;
+    use webdav_handler::memfs::MemFs;
+
+    fn path(s: &str) -> DavPath {
+        DavPath::new(s).unwrap()
+    }
+
+    #[tokio::test]
+    async fn swallow_and_hide() -> anyhow::Result<()> {
+        let mem = MemFs::new();
+        mem.open(&path("/._old"), write_opts()).await?;
+
+        let patterns = vec![".DS_Store".to_string(), "._*".to_string()];
+        let fs = IgnoreFs::new(mem.clone(), &patterns, IgnoreAction::Swallow)?;
+        let mut f = fs.open(&path("/.DS_Store"), write_opts()).await?;
+        f.write_bytes(Bytes::from_static(b"junk")).await?;
+        f.flush().await?;
+        fs.open(&path("/file.txt"), write_opts()).await?;
+
+        assert!(mem.metadata(&path("/.DS_Store")).await.is_err());
+        assert!(fs.metadata(&path("/._old")).await.is_err());
+        let names = fs
+            .read_dir(&path("/"), ReadDirMeta::None)
+            .await?
+            .map(|e| e.name())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(names, vec![b"file.txt".to_vec()]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reject() -> anyhow::Result<()> {
+        let patterns = vec!["Thumbs.db".to_string()];
+        let fs = IgnoreFs::new(MemFs::new(), &patterns, IgnoreAction::Reject)?;
+        assert!(fs.open(&path("/Thumbs.db"), write_opts()).await.is_err());
+        assert!(fs.create_dir(&path("/Thumbs.db/")).await.is_err());
+        Ok(())
+    }
+}
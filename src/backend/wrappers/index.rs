@@ -0,0 +1,431 @@
+use crate::backend::open_opts;
+use crate::backend::{content_type::ContentTypes, walk::walk};
+use bytes::{Buf, Bytes};
+use futures_util::FutureExt;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::SeekFrom,
+    sync::{Arc, RwLock},
+    time::SystemTime,
+};
+use tracing::{debug, error, info};
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, DavProp, FsFuture, FsStream, OpenOptions,
+        ReadDirMeta,
+    },
+};
+
+#[derive(Debug, Clone)]
+struct Entry {
+    /// lowercase decoded name
+    name: String,
+    is_dir: bool,
+    size: u64,
+    /// lowercase text of small text files
+    content: Option<String>,
+}
+
+/// Found file or directory.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Hit {
+    pub href: String,
+    pub is_dir: bool,
+    pub size: u64,
+}
+
+/// Names and text content of files of all mounts, keyed by href.
+#[derive(Debug)]
+pub struct Index {
+    entries: RwLock<BTreeMap<String, Entry>>,
+    types: ContentTypes,
+    content_limit: u64,
+}
+
+fn name_of(href: &str) -> String {
+    let name = href.trim_end_matches('/').rsplit('/').next().unwrap_or("");
+    percent_encoding::percent_decode_str(name)
+        .decode_utf8_lossy()
+        .to_lowercase()
+}
+
+fn is_text(mime: &str) -> bool {
+    mime.starts_with("text/") || mime == "application/json" || mime == "application/xml"
+}
+
+impl Index {
+    /// Text files up to `content_limit` bytes are searchable by content, 0
+    /// indexes names only.
+    pub fn new(types: ContentTypes, content_limit: u64) -> Self {
+        Index {
+            entries: RwLock::new(BTreeMap::new()),
+            types,
+            content_limit,
+        }
+    }
+
+    /// Entries under `scope` whose name or content contains every word of query.
+    pub fn search(&self, query: &str, scope: &str, limit: usize) -> Vec<Hit> {
+        let words = query
+            .split_whitespace()
+            .map(|w| w.to_lowercase())
+            .collect::<Vec<_>>();
+        if words.is_empty() {
+            return vec![];
+        }
+        let entries = self.entries.read().unwrap();
+        entries
+            .range(scope.to_string()..)
+            .take_while(|(k, _)| k.starts_with(scope))
+            .filter(|(_, e)| {
+                words.iter().all(|w| {
+                    e.name.contains(w.as_str())
+                        || e.content.as_ref().map_or(false, |c| c.contains(w.as_str()))
+                })
+            })
+            .take(limit)
+            .map(|(k, e)| Hit {
+                href: k.clone(),
+                is_dir: e.is_dir,
+                size: e.size,
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    async fn entry(&self, fs: &dyn DavFileSystem, path: &DavPath, meta: &dyn DavMetaData) -> Entry {
+        let href = path.as_url_string();
+        let mut content = None;
+        if !meta.is_dir()
+            && meta.len() <= self.content_limit
+            && is_text(&self.types.guess(path.as_pathbuf()))
+        {
+            content = read_text(fs, path).await;
+        }
+        Entry {
+            name: name_of(&href),
+            is_dir: meta.is_dir(),
+            size: if meta.is_dir() { 0 } else { meta.len() },
+            content,
+        }
+    }
+
+    /// Indexes path again after it changed.
+    async fn update(&self, fs: &dyn DavFileSystem, path: &DavPath) {
+        let meta = match fs.metadata(path).await {
+            Ok(m) => m,
+            Err(_) => return self.remove(&path.as_url_string()),
+        };
+        let entry = self.entry(fs, path, meta.as_ref()).await;
+        self.entries
+            .write()
+            .unwrap()
+            .insert(path.as_url_string(), entry);
+    }
+
+    /// Indexes directory and everything below it.
+    async fn update_tree(&self, fs: &dyn DavFileSystem, root: &DavPath) {
+        self.update(fs, root).await;
+        if let Ok(found) = walk(fs, root).await {
+            for (path, meta) in found {
+                let entry = self.entry(fs, &path, meta.as_ref()).await;
+                self.entries
+                    .write()
+                    .unwrap()
+                    .insert(path.as_url_string(), entry);
+            }
+        }
+    }
+
+    /// Removes path and, for directories, everything below it.
+    fn remove(&self, href: &str) {
+        let dir = format!("{}/", href.trim_end_matches('/'));
+        let mut entries = self.entries.write().unwrap();
+        entries.remove(href.trim_end_matches('/'));
+        let below = entries
+            .range(dir.clone()..)
+            .take_while(|(k, _)| k.starts_with(&dir))
+            .map(|(k, _)| k.clone())
+            .collect::<Vec<_>>();
+        for k in below {
+            entries.remove(&k);
+        }
+    }
+
+    fn rename(&self, from: &str, to: &str) {
+        let from_dir = from.trim_end_matches('/');
+        let to_dir = to.trim_end_matches('/');
+        let mut entries = self.entries.write().unwrap();
+        let moved = entries
+            .range(from_dir.to_string()..)
+            .take_while(|(k, _)| k.starts_with(from_dir))
+            .filter(|(k, _)| k.len() == from_dir.len() || k[from_dir.len()..].starts_with('/'))
+            .map(|(k, _)| k.clone())
+            .collect::<Vec<_>>();
+        for k in moved {
+            if let Some(mut e) = entries.remove(&k) {
+                let new = format!("{}{}", to_dir, &k[from_dir.len()..]);
+                e.name = name_of(&new);
+                entries.insert(new, e);
+            }
+        }
+    }
+}
+
+async fn read_text(fs: &dyn DavFileSystem, path: &DavPath) -> Option<String> {
+    let mut f = fs.open(path, open_opts::read()).await.ok()?;
+    let mut data = vec![];
+    loop {
+        let chunk = f.read_bytes(64 * 1024).await.ok()?;
+        if chunk.is_empty() {
+            break;
+        }
+        data.extend_from_slice(&chunk);
+    }
+    String::from_utf8(data).ok().map(|s| s.to_lowercase())
+}
+
+/// Fills index with current content of `fs`.
+pub fn spawn_build(fs: Box<dyn DavFileSystem>, index: Arc<Index>) {
+    tokio::spawn(async move {
+        let root = DavPath::new("/").unwrap();
+        match walk(fs.as_ref(), &root).await {
+            Ok(found) => {
+                for (path, meta) in found {
+                    let entry = index.entry(fs.as_ref(), &path, meta.as_ref()).await;
+                    index
+                        .entries
+                        .write()
+                        .unwrap()
+                        .insert(path.as_url_string(), entry);
+                }
+                info!(msg = "search index built", entries = index.len());
+            }
+            Err(e) => error!(msg = "can't build search index", err = ?e),
+        }
+    });
+}
+
+/// Keeps index up to date with changes made through it.
+#[derive(Clone)]
+pub struct IndexFs {
+    inner: Box<dyn DavFileSystem>,
+    index: Arc<Index>,
+}
+
+impl fmt::Debug for IndexFs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndexFs").finish()
+    }
+}
+
+impl IndexFs {
+    pub fn new(inner: Box<dyn DavFileSystem>, index: Arc<Index>) -> Box<dyn DavFileSystem> {
+        Box::new(IndexFs { inner, index }) as Box<dyn DavFileSystem>
+    }
+}
+
+#[derive(Debug)]
+struct IndexFile {
+    inner: Box<dyn DavFile>,
+    fs: IndexFs,
+    path: DavPath,
+}
+
+impl DavFile for IndexFile {
+    fn metadata<'a>(&'a mut self) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.metadata()
+    }
+
+    fn write_buf<'a>(&'a mut self, buf: Box<dyn Buf + Send>) -> FsFuture<()> {
+        self.inner.write_buf(buf)
+    }
+
+    fn write_bytes<'a>(&'a mut self, buf: Bytes) -> FsFuture<()> {
+        self.inner.write_bytes(buf)
+    }
+
+    fn read_bytes<'a>(&'a mut self, count: usize) -> FsFuture<Bytes> {
+        self.inner.read_bytes(count)
+    }
+
+    fn seek<'a>(&'a mut self, pos: SeekFrom) -> FsFuture<u64> {
+        self.inner.seek(pos)
+    }
+
+    fn flush<'a>(&'a mut self) -> FsFuture<()> {
+        async move {
+            self.inner.flush().await?;
+            debug!(msg = "reindexing", path = %self.path.as_url_string());
+            self.fs
+                .index
+                .update(self.fs.inner.as_ref(), &self.path)
+                .await;
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+impl DavFileSystem for IndexFs {
+    fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
+        async move {
+            let write = options.write;
+            let inner = self.inner.open(path, options).await?;
+            if !write {
+                return Ok(inner);
+            }
+            Ok(Box::new(IndexFile {
+                inner,
+                fs: self.clone(),
+                path: path.clone(),
+            }) as Box<dyn DavFile>)
+        }
+        .boxed()
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        meta: ReadDirMeta,
+    ) -> FsFuture<FsStream<Box<dyn DavDirEntry>>> {
+        self.inner.read_dir(path, meta)
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.metadata(path)
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.symlink_metadata(path)
+    }
+
+    fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        async move {
+            self.inner.create_dir(path).await?;
+            self.index.update(self.inner.as_ref(), path).await;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn remove_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        async move {
+            self.inner.remove_dir(path).await?;
+            self.index.remove(&path.as_url_string());
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn remove_file<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        async move {
+            self.inner.remove_file(path).await?;
+            self.index.remove(&path.as_url_string());
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        async move {
+            self.inner.rename(from, to).await?;
+            // replaced destination goes away first
+            self.index.remove(&to.as_url_string());
+            self.index
+                .rename(&from.as_url_string(), &to.as_url_string());
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn copy<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        async move {
+            self.inner.copy(from, to).await?;
+            self.index.update_tree(self.inner.as_ref(), to).await;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn set_accessed<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_accessed(path, tm)
+    }
+
+    fn set_modified<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_modified(path, tm)
+    }
+
+    fn have_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
+        self.inner.have_props(path)
+    }
+
+    fn patch_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+        patch: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(hyper::StatusCode, DavProp)>> {
+        self.inner.patch_props(path, patch)
+    }
+
+    fn get_props<'a>(&'a self, path: &'a DavPath, do_content: bool) -> FsFuture<Vec<DavProp>> {
+        self.inner.get_props(path, do_content)
+    }
+
+    fn get_prop<'a>(&'a self, path: &'a DavPath, prop: DavProp) -> FsFuture<Vec<u8>> {
+        self.inner.get_prop(path, prop)
+    }
+
+    fn get_quota<'a>(&'a self) -> FsFuture<(u64, Option<u64>)> {
+        self.inner.get_quota()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::put as write;
+    use webdav_handler::memfs::MemFs;
+
+    #[tokio::test]
+    async fn follows_changes() -> anyhow::Result<()> {
+        let index = Arc::new(Index::new(ContentTypes::default(), 1024));
+        let fs = IndexFs::new(MemFs::new(), index.clone());
+        fs.create_dir(&DavPath::new("/docs/")?).await?;
+        write(fs.as_ref(), "/docs/Tax%20Report.txt", b"Invoice for 2021").await?;
+        write(fs.as_ref(), "/docs/photo.jpg", b"invoice").await?;
+
+        let hrefs = |q: &str| {
+            index
+                .search(q, "/", 10)
+                .into_iter()
+                .map(|h| h.href)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(hrefs("tax report"), vec!["/docs/Tax%20Report.txt"]);
+        // content of binary files is not indexed
+        assert_eq!(hrefs("invoice"), vec!["/docs/Tax%20Report.txt"]);
+        assert!(index.search("invoice", "/other/", 10).is_empty());
+
+        fs.rename(&DavPath::new("/docs/")?, &DavPath::new("/archive/")?)
+            .await?;
+        assert_eq!(hrefs("2021"), vec!["/archive/Tax%20Report.txt"]);
+        assert_eq!(hrefs("docs"), Vec::<String>::new());
+
+        fs.remove_file(&DavPath::new("/archive/photo.jpg")?).await?;
+        assert!(hrefs("photo").is_empty());
+        Ok(())
+    }
+}
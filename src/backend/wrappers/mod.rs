@@ -0,0 +1,19 @@
+pub mod access;
+pub mod antivirus;
+pub mod breaker;
+pub mod case;
+pub mod chaos;
+pub mod disk_space;
+pub mod drop_box;
+pub mod hardlinks;
+pub mod ignore;
+pub mod index;
+pub mod path_map;
+pub mod read_only;
+pub mod rewrite;
+pub mod staging;
+pub mod symlinks;
+pub mod throttle;
+pub mod unicode;
+pub mod usage;
+pub mod versions;
@@ -0,0 +1,185 @@
+use futures_util::FutureExt;
+use percent_encoding::{percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use std::{cmp::Reverse, fmt::Debug, sync::Arc, time::SystemTime};
+use tracing::debug;
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, DavProp, FsError, FsFuture, FsResult,
+        FsStream, OpenOptions, ReadDirMeta,
+    },
+};
+
+pub(crate) const ENC: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'/')
+    .remove(b'.')
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Rewrites decoded request path (with leading slash, without trailing one)
+/// before it reaches filesystem.
+pub trait PathMapper: Debug + Send + Sync {
+    fn map(&self, path: &str) -> String;
+}
+
+/// Applies mappers in order.
+#[derive(Debug)]
+pub struct ChainMapper(pub Vec<Arc<dyn PathMapper>>);
+
+impl PathMapper for ChainMapper {
+    fn map(&self, path: &str) -> String {
+        self.0.iter().fold(path.to_string(), |acc, m| m.map(&acc))
+    }
+}
+
+/// Applies mappers only to paths inside their mounts, mount prefix itself is
+/// left as is.
+#[derive(Debug, Default)]
+pub struct MountMapper {
+    mounts: Vec<(String, Arc<dyn PathMapper>)>,
+}
+
+impl MountMapper {
+    pub fn add(&mut self, mount: &str, mapper: Arc<dyn PathMapper>) {
+        self.mounts
+            .push((mount.trim_end_matches('/').to_string(), mapper));
+        self.mounts.sort_by_key(|m| Reverse(m.0.len()));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mounts.is_empty()
+    }
+}
+
+impl PathMapper for MountMapper {
+    fn map(&self, path: &str) -> String {
+        for (mount, mapper) in &self.mounts {
+            if !path.starts_with(mount.as_str()) {
+                continue;
+            }
+            let rest = &path[mount.len()..];
+            if rest.is_empty() {
+                return path.to_string();
+            }
+            if rest.starts_with('/') {
+                return format!("{}{}", mount, mapper.map(rest));
+            }
+        }
+        path.to_string()
+    }
+}
+
+/// Filesystem wrapper which passes every path through `PathMapper`.
+#[derive(Clone)]
+pub struct PathMapFs {
+    inner: Box<dyn DavFileSystem>,
+    mapper: Arc<dyn PathMapper>,
+}
+
+impl PathMapFs {
+    pub fn new(
+        inner: Box<dyn DavFileSystem>,
+        mapper: Arc<dyn PathMapper>,
+    ) -> Box<dyn DavFileSystem> {
+        Box::new(PathMapFs { inner, mapper }) as Box<dyn DavFileSystem>
+    }
+
+    fn map(&self, path: &DavPath) -> FsResult<DavPath> {
+        let col = path.is_collection();
+        let pb = path.as_pathbuf();
+        let s = pb.to_str().ok_or(FsError::NotFound)?;
+        let mapped = self.mapper.map(s);
+        let mut enc = percent_encode(mapped.as_bytes(), ENC).to_string();
+        if !enc.starts_with('/') {
+            enc.insert(0, '/');
+        }
+        if col && !enc.ends_with('/') {
+            enc.push('/');
+        }
+        debug!(from = %path.as_url_string(), to = %enc);
+        DavPath::new(&enc).map_err(|_| FsError::NotFound)
+    }
+}
+
+impl DavFileSystem for PathMapFs {
+    fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
+        async move { self.inner.open(&self.map(path)?, options).await }.boxed()
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        meta: ReadDirMeta,
+    ) -> FsFuture<FsStream<Box<dyn DavDirEntry>>> {
+        async move { self.inner.read_dir(&self.map(path)?, meta).await }.boxed()
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        async move { self.inner.metadata(&self.map(path)?).await }.boxed()
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        async move { self.inner.symlink_metadata(&self.map(path)?).await }.boxed()
+    }
+
+    fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        async move { self.inner.create_dir(&self.map(path)?).await }.boxed()
+    }
+
+    fn remove_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        async move { self.inner.remove_dir(&self.map(path)?).await }.boxed()
+    }
+
+    fn remove_file<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        async move { self.inner.remove_file(&self.map(path)?).await }.boxed()
+    }
+
+    fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        async move { self.inner.rename(&self.map(from)?, &self.map(to)?).await }.boxed()
+    }
+
+    fn copy<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        async move { self.inner.copy(&self.map(from)?, &self.map(to)?).await }.boxed()
+    }
+
+    fn set_accessed<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        async move { self.inner.set_accessed(&self.map(path)?, tm).await }.boxed()
+    }
+
+    fn set_modified<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        async move { self.inner.set_modified(&self.map(path)?, tm).await }.boxed()
+    }
+
+    fn have_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
+        Box::pin(async move {
+            match self.map(path) {
+                Ok(p) => self.inner.have_props(&p).await,
+                Err(_) => false,
+            }
+        })
+    }
+
+    fn patch_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+        patch: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(hyper::StatusCode, DavProp)>> {
+        async move { self.inner.patch_props(&self.map(path)?, patch).await }.boxed()
+    }
+
+    fn get_props<'a>(&'a self, path: &'a DavPath, do_content: bool) -> FsFuture<Vec<DavProp>> {
+        async move { self.inner.get_props(&self.map(path)?, do_content).await }.boxed()
+    }
+
+    fn get_prop<'a>(&'a self, path: &'a DavPath, prop: DavProp) -> FsFuture<Vec<u8>> {
+        async move { self.inner.get_prop(&self.map(path)?, prop).await }.boxed()
+    }
+
+    fn get_quota<'a>(&'a self) -> FsFuture<(u64, Option<u64>)> {
+        self.inner.get_quota()
+    }
+}
@@ -0,0 +1,125 @@
+use futures_util::FutureExt;
+use std::time::SystemTime;
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, DavProp, FsError, FsFuture, FsStream,
+        OpenOptions, ReadDirMeta,
+    },
+};
+
+/// Rejects every change of mount content and properties.
+#[derive(Clone)]
+pub struct ReadOnlyFs {
+    inner: Box<dyn DavFileSystem>,
+}
+
+impl ReadOnlyFs {
+    pub fn new(inner: Box<dyn DavFileSystem>) -> Box<dyn DavFileSystem> {
+        Box::new(ReadOnlyFs { inner }) as Box<dyn DavFileSystem>
+    }
+}
+
+fn forbidden<'a, T: Send + 'a>() -> FsFuture<'a, T> {
+    async { Err(FsError::Forbidden) }.boxed()
+}
+
+impl DavFileSystem for ReadOnlyFs {
+    fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
+        if options.write || options.append || options.create || options.truncate {
+            return forbidden();
+        }
+        self.inner.open(path, options)
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        meta: ReadDirMeta,
+    ) -> FsFuture<FsStream<Box<dyn DavDirEntry>>> {
+        self.inner.read_dir(path, meta)
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.metadata(path)
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.symlink_metadata(path)
+    }
+
+    fn create_dir<'a>(&'a self, _: &'a DavPath) -> FsFuture<()> {
+        forbidden()
+    }
+
+    fn remove_dir<'a>(&'a self, _: &'a DavPath) -> FsFuture<()> {
+        forbidden()
+    }
+
+    fn remove_file<'a>(&'a self, _: &'a DavPath) -> FsFuture<()> {
+        forbidden()
+    }
+
+    fn rename<'a>(&'a self, _: &'a DavPath, _: &'a DavPath) -> FsFuture<()> {
+        forbidden()
+    }
+
+    fn copy<'a>(&'a self, _: &'a DavPath, _: &'a DavPath) -> FsFuture<()> {
+        forbidden()
+    }
+
+    fn set_accessed<'a>(&'a self, _: &'a DavPath, _: SystemTime) -> FsFuture<()> {
+        forbidden()
+    }
+
+    fn set_modified<'a>(&'a self, _: &'a DavPath, _: SystemTime) -> FsFuture<()> {
+        forbidden()
+    }
+
+    fn have_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
+        self.inner.have_props(path)
+    }
+
+    fn patch_props<'a>(
+        &'a self,
+        _: &'a DavPath,
+        _: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(hyper::StatusCode, DavProp)>> {
+        forbidden()
+    }
+
+    fn get_props<'a>(&'a self, path: &'a DavPath, do_content: bool) -> FsFuture<Vec<DavProp>> {
+        self.inner.get_props(path, do_content)
+    }
+
+    fn get_prop<'a>(&'a self, path: &'a DavPath, prop: DavProp) -> FsFuture<Vec<u8>> {
+        self.inner.get_prop(path, prop)
+    }
+
+    fn get_quota<'a>(&'a self) -> FsFuture<(u64, Option<u64>)> {
+        self.inner.get_quota()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::write_opts;
+    use webdav_handler::memfs::MemFs;
+
+    #[tokio::test]
+    async fn rejects_changes() -> anyhow::Result<()> {
+        let mem = MemFs::new();
+        mem.create_dir(&DavPath::new("/dir/")?).await?;
+        let fs = ReadOnlyFs::new(mem);
+
+        assert!(fs.metadata(&DavPath::new("/dir/")?).await?.is_dir());
+        let created = fs.open(&DavPath::new("/a.txt")?, write_opts()).await;
+        assert!(created.is_err());
+        assert!(fs.remove_dir(&DavPath::new("/dir/")?).await.is_err());
+        Ok(())
+    }
+}
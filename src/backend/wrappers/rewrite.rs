@@ -0,0 +1,95 @@
+use super::path_map::PathMapper;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RewriteRule {
+    /// removes leading folder, e.g. one created by some client
+    StripPrefix {
+        prefix: String,
+    },
+    /// prepends folder to every path
+    AddPrefix {
+        prefix: String,
+    },
+    Lowercase,
+    /// replaces every occurrence of substring
+    Replace {
+        from: String,
+        to: String,
+    },
+}
+
+impl RewriteRule {
+    fn apply(&self, path: String) -> String {
+        match self {
+            RewriteRule::StripPrefix { prefix } => {
+                let prefix = format!("/{}", prefix.trim_matches('/'));
+                match path.strip_prefix(prefix.as_str()) {
+                    Some("") => "/".into(),
+                    Some(rest) if rest.starts_with('/') => rest.into(),
+                    _ => path,
+                }
+            }
+            RewriteRule::AddPrefix { prefix } => {
+                let prefix = prefix.trim_matches('/');
+                if prefix.is_empty() {
+                    return path;
+                }
+                format!("/{}{}", prefix, path)
+            }
+            RewriteRule::Lowercase => path.to_lowercase(),
+            RewriteRule::Replace { from, to } => {
+                if from.is_empty() {
+                    return path;
+                }
+                path.replace(from.as_str(), to)
+            }
+        }
+    }
+}
+
+/// Applies rewrite rules one after another.
+#[derive(Debug)]
+pub struct Rewriter {
+    rules: Vec<RewriteRule>,
+}
+
+impl Rewriter {
+    pub fn new(rules: Vec<RewriteRule>) -> Self {
+        Rewriter { rules }
+    }
+}
+
+impl PathMapper for Rewriter {
+    fn map(&self, path: &str) -> String {
+        self.rules
+            .iter()
+            .fold(path.to_string(), |acc, r| r.apply(acc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules() {
+        let r = Rewriter::new(vec![
+            RewriteRule::StripPrefix {
+                prefix: "/Legacy/".into(),
+            },
+            RewriteRule::Lowercase,
+            RewriteRule::Replace {
+                from: " ".into(),
+                to: "_".into(),
+            },
+            RewriteRule::AddPrefix {
+                prefix: "v2".into(),
+            },
+        ]);
+        assert_eq!(r.map("/Legacy/My Docs/A.txt"), "/v2/my_docs/a.txt");
+        assert_eq!(r.map("/LegacyDocs/a"), "/v2/legacydocs/a");
+        assert_eq!(r.map("/Legacy"), "/v2/");
+    }
+}
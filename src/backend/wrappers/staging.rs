@@ -0,0 +1,360 @@
+use bytes::{Buf, Bytes};
+use futures_util::FutureExt;
+use std::{
+    fs::Metadata,
+    io::{self, SeekFrom},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tracing::{debug, warn};
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, DavProp, FsError, FsFuture, FsResult,
+        FsStream, OpenOptions, ReadDirMeta,
+    },
+};
+
+/// Whole-file writes of local filesystem go to a temporary file in staging
+/// directory which replaces target by rename on flush, so interrupted uploads
+/// never leave half-written files visible. Partial writes, e.g. ranged PUT,
+/// change files in place.
+#[derive(Clone)]
+pub struct StagingFs {
+    inner: Box<dyn DavFileSystem>,
+    root: PathBuf,
+    dir: PathBuf,
+    public: bool,
+}
+
+impl StagingFs {
+    /// Staging `dir` is created and emptied of leftovers of interrupted
+    /// uploads, it has to be on the same filesystem as `root`.
+    pub fn new(
+        inner: Box<dyn DavFileSystem>,
+        root: PathBuf,
+        dir: PathBuf,
+        public: bool,
+    ) -> io::Result<Box<dyn DavFileSystem>> {
+        std::fs::create_dir_all(&dir)?;
+        check_same_device(&root, &dir)?;
+        for e in std::fs::read_dir(&dir)? {
+            let p = e?.path();
+            if let Err(e) = std::fs::remove_file(&p) {
+                warn!(msg = "can't remove staged file", path = ?p, err = %e);
+            }
+        }
+        Ok(Box::new(StagingFs {
+            inner,
+            root,
+            dir,
+            public,
+        }) as Box<dyn DavFileSystem>)
+    }
+
+    fn local_path(&self, path: &DavPath) -> PathBuf {
+        let pb = path.as_pathbuf();
+        match pb.strip_prefix("/") {
+            Ok(rel) => self.root.join(rel),
+            Err(_) => self.root.join(pb),
+        }
+    }
+
+    /// Creates temporary file for `target`, None if it should be written in
+    /// place.
+    fn stage(&self, target: &Path, options: &OpenOptions) -> io::Result<Option<StagedFile>> {
+        match std::fs::symlink_metadata(target) {
+            Ok(m) if m.file_type().is_symlink() => return Ok(None),
+            Ok(m) if m.is_dir() => return Err(io::ErrorKind::PermissionDenied.into()),
+            Ok(_) if options.create_new => return Err(io::ErrorKind::AlreadyExists.into()),
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound && options.create => {
+                let parent = target.parent().map(std::fs::metadata);
+                if !matches!(parent, Some(Ok(ref m)) if m.is_dir()) {
+                    return Err(io::ErrorKind::NotFound.into());
+                }
+            }
+            Err(e) => return Err(e),
+        }
+        let tmp = self.dir.join(format!("{}.upload", uuid::Uuid::new_v4()));
+        let mut opts = std::fs::OpenOptions::new();
+        opts.read(true).write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            opts.mode(if self.public { 0o644 } else { 0o600 });
+        }
+        let file = opts.open(&tmp)?;
+        Ok(Some(StagedFile {
+            file: tokio::fs::File::from_std(file),
+            tmp,
+            target: target.to_path_buf(),
+            renamed: false,
+        }))
+    }
+}
+
+#[cfg(unix)]
+fn check_same_device(root: &Path, dir: &Path) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    if std::fs::metadata(root)?.dev() != std::fs::metadata(dir)?.dev() {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            "staging directory is on another filesystem than mount",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_same_device(_root: &Path, _dir: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn errno_error(e: &io::Error) -> Option<FsError> {
+    match e.raw_os_error()? {
+        libc::ENOSPC | libc::EDQUOT => Some(FsError::InsufficientStorage),
+        libc::EROFS => Some(FsError::Forbidden),
+        _ => None,
+    }
+}
+
+#[cfg(not(unix))]
+fn errno_error(_e: &io::Error) -> Option<FsError> {
+    None
+}
+
+fn fs_error(e: io::Error) -> FsError {
+    if let Some(err) = errno_error(&e) {
+        return err;
+    }
+    match e.kind() {
+        io::ErrorKind::NotFound => FsError::NotFound,
+        io::ErrorKind::PermissionDenied => FsError::Forbidden,
+        io::ErrorKind::AlreadyExists => FsError::Exists,
+        _ => FsError::GeneralFailure,
+    }
+}
+
+#[derive(Debug, Clone)]
+struct StagedMetaData(Metadata);
+
+impl DavMetaData for StagedMetaData {
+    fn len(&self) -> u64 {
+        self.0.len()
+    }
+
+    fn modified(&self) -> FsResult<SystemTime> {
+        self.0.modified().map_err(fs_error)
+    }
+
+    fn is_dir(&self) -> bool {
+        false
+    }
+}
+
+/// Temporary file, renamed over target on first flush. Later writes go to the
+/// same file under its new name.
+#[derive(Debug)]
+struct StagedFile {
+    file: tokio::fs::File,
+    tmp: PathBuf,
+    target: PathBuf,
+    renamed: bool,
+}
+
+impl Drop for StagedFile {
+    fn drop(&mut self) {
+        if !self.renamed {
+            debug!(msg = "discarding unfinished upload", path = ?self.target);
+            let _ = std::fs::remove_file(&self.tmp);
+        }
+    }
+}
+
+impl DavFile for StagedFile {
+    fn metadata<'a>(&'a mut self) -> FsFuture<Box<dyn DavMetaData>> {
+        async move {
+            let meta = self.file.metadata().await.map_err(fs_error)?;
+            Ok(Box::new(StagedMetaData(meta)) as Box<dyn DavMetaData>)
+        }
+        .boxed()
+    }
+
+    fn write_buf<'a>(&'a mut self, mut buf: Box<dyn Buf + Send>) -> FsFuture<()> {
+        let data = buf.copy_to_bytes(buf.remaining());
+        self.write_bytes(data)
+    }
+
+    fn write_bytes<'a>(&'a mut self, buf: Bytes) -> FsFuture<()> {
+        async move { self.file.write_all(&buf).await.map_err(fs_error) }.boxed()
+    }
+
+    fn read_bytes<'a>(&'a mut self, count: usize) -> FsFuture<Bytes> {
+        async move {
+            let mut buf = vec![0; count];
+            let n = self.file.read(&mut buf).await.map_err(fs_error)?;
+            buf.truncate(n);
+            Ok(Bytes::from(buf))
+        }
+        .boxed()
+    }
+
+    fn seek<'a>(&'a mut self, pos: SeekFrom) -> FsFuture<u64> {
+        async move { self.file.seek(pos).await.map_err(fs_error) }.boxed()
+    }
+
+    fn flush<'a>(&'a mut self) -> FsFuture<()> {
+        async move {
+            self.file.flush().await.map_err(fs_error)?;
+            self.file.sync_all().await.map_err(fs_error)?;
+            if !self.renamed {
+                tokio::fs::rename(&self.tmp, &self.target)
+                    .await
+                    .map_err(fs_error)?;
+                self.renamed = true;
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+impl DavFileSystem for StagingFs {
+    fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
+        async move {
+            if !options.write || !options.truncate {
+                return self.inner.open(path, options).await;
+            }
+            let fs = self.clone();
+            let target = self.local_path(path);
+            let opts = options;
+            let staged = tokio::task::spawn_blocking(move || fs.stage(&target, &opts))
+                .await
+                .map_err(|_| FsError::GeneralFailure)?
+                .map_err(fs_error)?;
+            match staged {
+                Some(f) => Ok(Box::new(f) as Box<dyn DavFile>),
+                None => self.inner.open(path, options).await,
+            }
+        }
+        .boxed()
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        meta: ReadDirMeta,
+    ) -> FsFuture<FsStream<Box<dyn DavDirEntry>>> {
+        self.inner.read_dir(path, meta)
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.metadata(path)
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.symlink_metadata(path)
+    }
+
+    fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.create_dir(path)
+    }
+
+    fn remove_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.remove_dir(path)
+    }
+
+    fn remove_file<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.remove_file(path)
+    }
+
+    fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        self.inner.rename(from, to)
+    }
+
+    fn copy<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        self.inner.copy(from, to)
+    }
+
+    fn set_accessed<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_accessed(path, tm)
+    }
+
+    fn set_modified<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_modified(path, tm)
+    }
+
+    fn have_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
+        self.inner.have_props(path)
+    }
+
+    fn patch_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+        patch: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(hyper::StatusCode, DavProp)>> {
+        self.inner.patch_props(path, patch)
+    }
+
+    fn get_props<'a>(&'a self, path: &'a DavPath, do_content: bool) -> FsFuture<Vec<DavProp>> {
+        self.inner.get_props(path, do_content)
+    }
+
+    fn get_prop<'a>(&'a self, path: &'a DavPath, prop: DavProp) -> FsFuture<Vec<u8>> {
+        self.inner.get_prop(path, prop)
+    }
+
+    fn get_quota<'a>(&'a self) -> FsFuture<(u64, Option<u64>)> {
+        self.inner.get_quota()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::write_opts;
+    use webdav_handler::localfs::LocalFs;
+
+    #[tokio::test]
+    async fn replaces_file_on_flush() -> anyhow::Result<()> {
+        let base = std::env::temp_dir().join(format!("webdav_ss_staging_{}", uuid::Uuid::new_v4()));
+        let (root, dir) = (base.join("fs"), base.join("fs.staging"));
+        std::fs::create_dir_all(&root)?;
+        std::fs::write(root.join("a.txt"), b"old")?;
+        let fs = StagingFs::new(
+            LocalFs::new(&root, false, false, false),
+            root.clone(),
+            dir.clone(),
+            false,
+        )?;
+
+        let opts = write_opts();
+        let mut f = fs.open(&DavPath::new("/a.txt")?, opts).await?;
+        f.write_bytes(Bytes::from_static(b"new data")).await?;
+        assert_eq!(std::fs::read(root.join("a.txt"))?, b"old");
+        f.flush().await?;
+        assert_eq!(std::fs::read(root.join("a.txt"))?, b"new data");
+        assert_eq!(f.metadata().await?.len(), 8);
+
+        // interrupted upload leaves nothing behind
+        let mut f = fs.open(&DavPath::new("/b.txt")?, opts).await?;
+        f.write_bytes(Bytes::from_static(b"partial")).await?;
+        drop(f);
+        assert!(!root.join("b.txt").exists());
+        assert_eq!(std::fs::read_dir(&dir)?.count(), 0);
+
+        assert!(fs
+            .open(&DavPath::new("/missing/c.txt")?, opts)
+            .await
+            .is_err());
+        std::fs::remove_dir_all(&base)?;
+        Ok(())
+    }
+}
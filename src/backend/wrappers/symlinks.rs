@@ -0,0 +1,351 @@
+use futures_util::{FutureExt, StreamExt};
+use std::time::SystemTime;
+use tracing::debug;
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, DavProp, FsError, FsFuture, FsResult,
+        FsStream, OpenOptions, ReadDirMeta,
+    },
+};
+
+/// Filesystem wrapper which does not let requests go through symbolic links:
+/// symlinks are not listed and paths containing them are not found.
+#[derive(Clone)]
+pub struct NoSymlinksFs {
+    inner: Box<dyn DavFileSystem>,
+}
+
+impl NoSymlinksFs {
+    pub fn new(inner: Box<dyn DavFileSystem>) -> Box<dyn DavFileSystem> {
+        Box::new(NoSymlinksFs { inner }) as Box<dyn DavFileSystem>
+    }
+
+    /// Checks path and all its parents, path itself may not exist yet.
+    async fn check(&self, path: &DavPath) -> FsResult<()> {
+        let url = path.as_url_string();
+        let url = url.trim_end_matches('/');
+        let ends = url
+            .char_indices()
+            .filter(|(_, c)| *c == '/')
+            .map(|(i, _)| i)
+            .skip(1)
+            .chain(std::iter::once(url.len()))
+            .filter(|&i| i > 0);
+        for end in ends {
+            let p = DavPath::new(&url[..end]).map_err(|_| FsError::NotFound)?;
+            if let Ok(m) = self.inner.symlink_metadata(&p).await {
+                if m.is_symlink() {
+                    debug!(msg = "symlink in path", path = %url);
+                    return Err(FsError::NotFound);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DavFileSystem for NoSymlinksFs {
+    fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
+        async move {
+            self.check(path).await?;
+            self.inner.open(path, options).await
+        }
+        .boxed()
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        meta: ReadDirMeta,
+    ) -> FsFuture<FsStream<Box<dyn DavDirEntry>>> {
+        async move {
+            self.check(path).await?;
+            let entries = self.inner.read_dir(path, meta).await?;
+            let entries = entries.filter_map(|e| async move {
+                match e.is_symlink().await {
+                    Ok(true) => None,
+                    _ => Some(e),
+                }
+            });
+            Ok(Box::pin(entries) as FsStream<Box<dyn DavDirEntry>>)
+        }
+        .boxed()
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        async move {
+            self.check(path).await?;
+            self.inner.metadata(path).await
+        }
+        .boxed()
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        async move {
+            self.check(path).await?;
+            self.inner.symlink_metadata(path).await
+        }
+        .boxed()
+    }
+
+    fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        async move {
+            self.check(path).await?;
+            self.inner.create_dir(path).await
+        }
+        .boxed()
+    }
+
+    fn remove_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        async move {
+            self.check(path).await?;
+            self.inner.remove_dir(path).await
+        }
+        .boxed()
+    }
+
+    fn remove_file<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        async move {
+            self.check(path).await?;
+            self.inner.remove_file(path).await
+        }
+        .boxed()
+    }
+
+    fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        async move {
+            self.check(from).await?;
+            self.check(to).await?;
+            self.inner.rename(from, to).await
+        }
+        .boxed()
+    }
+
+    fn copy<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        async move {
+            self.check(from).await?;
+            self.check(to).await?;
+            self.inner.copy(from, to).await
+        }
+        .boxed()
+    }
+
+    fn set_accessed<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        async move {
+            self.check(path).await?;
+            self.inner.set_accessed(path, tm).await
+        }
+        .boxed()
+    }
+
+    fn set_modified<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        async move {
+            self.check(path).await?;
+            self.inner.set_modified(path, tm).await
+        }
+        .boxed()
+    }
+
+    fn have_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
+        self.inner.have_props(path)
+    }
+
+    fn patch_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+        patch: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(hyper::StatusCode, DavProp)>> {
+        self.inner.patch_props(path, patch)
+    }
+
+    fn get_props<'a>(&'a self, path: &'a DavPath, do_content: bool) -> FsFuture<Vec<DavProp>> {
+        self.inner.get_props(path, do_content)
+    }
+
+    fn get_prop<'a>(&'a self, path: &'a DavPath, prop: DavProp) -> FsFuture<Vec<u8>> {
+        self.inner.get_prop(path, prop)
+    }
+
+    fn get_quota<'a>(&'a self) -> FsFuture<(u64, Option<u64>)> {
+        self.inner.get_quota()
+    }
+}
+
+/// Filesystem wrapper listing symbolic links as entries they point to.
+/// Dav handler reads listings without following symlinks and skips them,
+/// this makes mount show them while other mounts keep them hidden.
+#[derive(Clone)]
+pub struct ListSymlinksFs {
+    inner: Box<dyn DavFileSystem>,
+}
+
+impl ListSymlinksFs {
+    pub fn new(inner: Box<dyn DavFileSystem>) -> Box<dyn DavFileSystem> {
+        Box::new(ListSymlinksFs { inner }) as Box<dyn DavFileSystem>
+    }
+}
+
+impl DavFileSystem for ListSymlinksFs {
+    fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
+        self.inner.open(path, options)
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        meta: ReadDirMeta,
+    ) -> FsFuture<FsStream<Box<dyn DavDirEntry>>> {
+        let meta = match meta {
+            ReadDirMeta::DataSymlink => ReadDirMeta::Data,
+            m => m,
+        };
+        self.inner.read_dir(path, meta)
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.metadata(path)
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.symlink_metadata(path)
+    }
+
+    fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.create_dir(path)
+    }
+
+    fn remove_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.remove_dir(path)
+    }
+
+    fn remove_file<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.remove_file(path)
+    }
+
+    fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        self.inner.rename(from, to)
+    }
+
+    fn copy<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        self.inner.copy(from, to)
+    }
+
+    fn set_accessed<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_accessed(path, tm)
+    }
+
+    fn set_modified<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_modified(path, tm)
+    }
+
+    fn have_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
+        self.inner.have_props(path)
+    }
+
+    fn patch_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+        patch: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(hyper::StatusCode, DavProp)>> {
+        self.inner.patch_props(path, patch)
+    }
+
+    fn get_props<'a>(&'a self, path: &'a DavPath, do_content: bool) -> FsFuture<Vec<DavProp>> {
+        self.inner.get_props(path, do_content)
+    }
+
+    fn get_prop<'a>(&'a self, path: &'a DavPath, prop: DavProp) -> FsFuture<Vec<u8>> {
+        self.inner.get_prop(path, prop)
+    }
+
+    fn get_quota<'a>(&'a self) -> FsFuture<(u64, Option<u64>)> {
+        self.inner.get_quota()
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::backend::open_opts;
+    use std::path::PathBuf;
+    use webdav_handler::localfs::LocalFs;
+
+    fn path(s: &str) -> DavPath {
+        DavPath::new(s).unwrap()
+    }
+
+    fn setup(name: &str) -> std::io::Result<PathBuf> {
+        let base = std::env::temp_dir().join(format!("webdav_ss_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(base.join("root/real"))?;
+        std::fs::create_dir_all(base.join("outside"))?;
+        std::fs::write(base.join("root/real/file.txt"), b"data")?;
+        std::fs::write(base.join("outside/secret.txt"), b"secret")?;
+        std::os::unix::fs::symlink(base.join("root/real"), base.join("root/link"))?;
+        std::os::unix::fs::symlink(base.join("outside"), base.join("root/escape"))?;
+        Ok(base)
+    }
+
+    #[tokio::test]
+    async fn follow_symlinked_dirs() -> anyhow::Result<()> {
+        let base = setup("follow")?;
+        let fs = LocalFs::new(base.join("root"), false, false, false);
+        assert!(fs.metadata(&path("/link/")).await?.is_dir());
+        assert!(fs.metadata(&path("/link/file.txt")).await.is_ok());
+        assert!(fs.metadata(&path("/escape/secret.txt")).await.is_ok());
+        std::fs::remove_dir_all(&base)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn no_symlinks() -> anyhow::Result<()> {
+        let base = setup("nofollow")?;
+        let fs = NoSymlinksFs::new(LocalFs::new(base.join("root"), false, false, false));
+        assert!(fs.metadata(&path("/real/file.txt")).await.is_ok());
+        assert!(fs.metadata(&path("/link/file.txt")).await.is_err());
+        assert!(fs.metadata(&path("/escape/secret.txt")).await.is_err());
+        assert!(fs
+            .open(&path("/escape/new.txt"), open_opts::write())
+            .await
+            .is_err());
+
+        let names = fs
+            .read_dir(&path("/"), ReadDirMeta::DataSymlink)
+            .await?
+            .map(|e| e.name())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(names, vec![b"real".to_vec()]);
+        std::fs::remove_dir_all(&base)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn list_symlinks() -> anyhow::Result<()> {
+        let base = setup("list")?;
+        let listed = |fs: Box<dyn DavFileSystem>| async move {
+            let mut links = vec![];
+            let mut entries = fs.read_dir(&path("/"), ReadDirMeta::DataSymlink).await?;
+            while let Some(e) = entries.next().await {
+                if e.name() == b"link" {
+                    links.push(e.metadata().await?.is_symlink());
+                }
+            }
+            Ok::<_, FsError>(links)
+        };
+        let root = base.join("root");
+        let fs = LocalFs::new(&root, false, false, false);
+        assert_eq!(listed(fs).await?, vec![true]);
+        let fs = ListSymlinksFs::new(LocalFs::new(&root, false, false, false));
+        assert_eq!(listed(fs).await?, vec![false]);
+        std::fs::remove_dir_all(&base)?;
+        Ok(())
+    }
+}
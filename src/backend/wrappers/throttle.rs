@@ -0,0 +1,227 @@
+use bytes::{Buf, Bytes};
+use futures_util::FutureExt;
+use std::{
+    io::SeekFrom,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, DavProp, FsFuture, FsStream, OpenOptions,
+        ReadDirMeta,
+    },
+};
+
+/// Token bucket shared by all files of a mount, up to one second of
+/// transfer can be used at once.
+#[derive(Debug)]
+pub struct Limiter {
+    rate: u64,
+    // available bytes, negative when transfer is ahead of rate
+    state: Mutex<(f64, Instant)>,
+}
+
+impl Limiter {
+    pub fn new(bytes_per_sec: u64) -> Self {
+        let rate = bytes_per_sec.max(1);
+        Limiter {
+            rate,
+            state: Mutex::new((rate as f64, Instant::now())),
+        }
+    }
+
+    /// Takes `n` bytes, waiting until rate allows them.
+    async fn take(&self, n: usize) {
+        let wait = {
+            let mut state = self.state.lock().unwrap();
+            let now = Instant::now();
+            let refill = now.duration_since(state.1).as_secs_f64() * self.rate as f64;
+            state.0 = (state.0 + refill).min(self.rate as f64) - n as f64;
+            state.1 = now;
+            if state.0 < 0.0 {
+                Duration::from_secs_f64(-state.0 / self.rate as f64)
+            } else {
+                Duration::ZERO
+            }
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Limits read and write bandwidth of a mount.
+#[derive(Clone)]
+pub struct ThrottleFs {
+    inner: Box<dyn DavFileSystem>,
+    read: Option<Arc<Limiter>>,
+    write: Option<Arc<Limiter>>,
+}
+
+impl ThrottleFs {
+    pub fn new(
+        inner: Box<dyn DavFileSystem>,
+        read: Option<u64>,
+        write: Option<u64>,
+    ) -> Box<dyn DavFileSystem> {
+        Box::new(ThrottleFs {
+            inner,
+            read: read.map(|r| Arc::new(Limiter::new(r))),
+            write: write.map(|w| Arc::new(Limiter::new(w))),
+        }) as Box<dyn DavFileSystem>
+    }
+}
+
+#[derive(Debug)]
+struct ThrottleFile {
+    inner: Box<dyn DavFile>,
+    read: Option<Arc<Limiter>>,
+    write: Option<Arc<Limiter>>,
+}
+
+impl DavFile for ThrottleFile {
+    fn metadata<'a>(&'a mut self) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.metadata()
+    }
+
+    fn write_buf<'a>(&'a mut self, buf: Box<dyn Buf + Send>) -> FsFuture<()> {
+        async move {
+            if let Some(ref l) = self.write {
+                l.take(buf.remaining()).await;
+            }
+            self.inner.write_buf(buf).await
+        }
+        .boxed()
+    }
+
+    fn write_bytes<'a>(&'a mut self, buf: Bytes) -> FsFuture<()> {
+        async move {
+            if let Some(ref l) = self.write {
+                l.take(buf.len()).await;
+            }
+            self.inner.write_bytes(buf).await
+        }
+        .boxed()
+    }
+
+    fn read_bytes<'a>(&'a mut self, count: usize) -> FsFuture<Bytes> {
+        async move {
+            let data = self.inner.read_bytes(count).await?;
+            if let Some(ref l) = self.read {
+                l.take(data.len()).await;
+            }
+            Ok(data)
+        }
+        .boxed()
+    }
+
+    fn seek<'a>(&'a mut self, pos: SeekFrom) -> FsFuture<u64> {
+        self.inner.seek(pos)
+    }
+
+    fn flush<'a>(&'a mut self) -> FsFuture<()> {
+        self.inner.flush()
+    }
+}
+
+impl DavFileSystem for ThrottleFs {
+    fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
+        async move {
+            let inner = self.inner.open(path, options).await?;
+            Ok(Box::new(ThrottleFile {
+                inner,
+                read: self.read.clone(),
+                write: self.write.clone(),
+            }) as Box<dyn DavFile>)
+        }
+        .boxed()
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        meta: ReadDirMeta,
+    ) -> FsFuture<FsStream<Box<dyn DavDirEntry>>> {
+        self.inner.read_dir(path, meta)
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.metadata(path)
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.symlink_metadata(path)
+    }
+
+    fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.create_dir(path)
+    }
+
+    fn remove_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.remove_dir(path)
+    }
+
+    fn remove_file<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.remove_file(path)
+    }
+
+    fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        self.inner.rename(from, to)
+    }
+
+    fn copy<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        self.inner.copy(from, to)
+    }
+
+    fn set_accessed<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_accessed(path, tm)
+    }
+
+    fn set_modified<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_modified(path, tm)
+    }
+
+    fn have_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
+        self.inner.have_props(path)
+    }
+
+    fn patch_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+        patch: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(hyper::StatusCode, DavProp)>> {
+        self.inner.patch_props(path, patch)
+    }
+
+    fn get_props<'a>(&'a self, path: &'a DavPath, do_content: bool) -> FsFuture<Vec<DavProp>> {
+        self.inner.get_props(path, do_content)
+    }
+
+    fn get_prop<'a>(&'a self, path: &'a DavPath, prop: DavProp) -> FsFuture<Vec<u8>> {
+        self.inner.get_prop(path, prop)
+    }
+
+    fn get_quota<'a>(&'a self) -> FsFuture<(u64, Option<u64>)> {
+        self.inner.get_quota()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn waits_for_rate() {
+        let limiter = Limiter::new(10_000);
+        let start = Instant::now();
+        // first second of transfer is available at once
+        limiter.take(10_000).await;
+        assert!(start.elapsed() < Duration::from_millis(100));
+        limiter.take(3_000).await;
+        assert!(start.elapsed() >= Duration::from_millis(250));
+    }
+}
@@ -0,0 +1,54 @@
+use super::path_map::PathMapper;
+use serde::Deserialize;
+use unicode_normalization::UnicodeNormalization;
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum UnicodeForm {
+    Nfc,
+    Nfd,
+}
+
+/// Brings paths to one Unicode normalization form so names created by
+/// clients using different forms (e.g. macOS and others) point to the same file.
+#[derive(Debug)]
+pub struct UnicodeNormalizer {
+    form: UnicodeForm,
+}
+
+impl UnicodeNormalizer {
+    pub fn new(form: UnicodeForm) -> Self {
+        UnicodeNormalizer { form }
+    }
+}
+
+impl PathMapper for UnicodeNormalizer {
+    fn map(&self, path: &str) -> String {
+        match self.form {
+            UnicodeForm::Nfc => path.nfc().collect(),
+            UnicodeForm::Nfd => path.nfd().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::wrappers::path_map::PathMapFs;
+    use std::sync::Arc;
+    use webdav_handler::{davpath::DavPath, memfs::MemFs};
+
+    #[tokio::test]
+    async fn same_file_for_both_forms() -> anyhow::Result<()> {
+        let fs = PathMapFs::new(
+            MemFs::new(),
+            Arc::new(UnicodeNormalizer::new(UnicodeForm::Nfc)),
+        );
+        // "é" precomposed and decomposed
+        let nfc = DavPath::new("/caf%c3%a9/").unwrap();
+        let nfd = DavPath::new("/cafe%cc%81/").unwrap();
+        fs.create_dir(&nfd).await?;
+        assert!(fs.metadata(&nfc).await?.is_dir());
+        Ok(())
+    }
+}
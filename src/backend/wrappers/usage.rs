@@ -0,0 +1,320 @@
+use crate::backend::walk::walk;
+use bytes::{Buf, Bytes};
+use futures_util::FutureExt;
+use std::{
+    io::SeekFrom,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
+use tracing::{debug, error};
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, DavProp, FsFuture, FsResult, FsStream,
+        OpenOptions, ReadDirMeta,
+    },
+};
+
+/// Bytes and number of files stored on a mount.
+#[derive(Debug, Default)]
+pub struct Usage {
+    bytes: AtomicU64,
+    objects: AtomicU64,
+}
+
+impl Usage {
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn objects(&self) -> u64 {
+        self.objects.load(Ordering::Relaxed)
+    }
+
+    fn set(&self, bytes: u64, objects: u64) {
+        self.bytes.store(bytes, Ordering::Relaxed);
+        self.objects.store(objects, Ordering::Relaxed);
+    }
+
+    fn add(&self, bytes: u64, objects: u64) {
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+        self.objects.fetch_add(objects, Ordering::Relaxed);
+    }
+
+    fn sub(&self, bytes: u64, objects: u64) {
+        // counters may be behind changes made outside until next recount
+        let sub = |c: &AtomicU64, v: u64| {
+            let _ = c.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |c| {
+                Some(c.saturating_sub(v))
+            });
+        };
+        sub(&self.bytes, bytes);
+        sub(&self.objects, objects);
+    }
+}
+
+/// Counts files of the whole filesystem.
+pub async fn recount(fs: &dyn DavFileSystem, usage: &Usage) -> FsResult<()> {
+    let root = DavPath::new("/").unwrap();
+    let (bytes, objects) = walk(fs, &root)
+        .await?
+        .iter()
+        .filter(|(_, meta)| !meta.is_dir())
+        .fold((0, 0), |(b, o), (_, meta)| (b + meta.len(), o + 1));
+    usage.set(bytes, objects);
+    Ok(())
+}
+
+/// Recounts usage right away and then every `interval` to correct drift of
+/// incremental updates, e.g. after changes made directly in backend.
+/// Zero interval counts only once.
+pub fn spawn_refresh(fs: Box<dyn DavFileSystem>, usage: Arc<Usage>, interval: Duration) {
+    tokio::spawn(async move {
+        loop {
+            match recount(fs.as_ref(), &usage).await {
+                Ok(()) => debug!(bytes = usage.bytes(), objects = usage.objects()),
+                Err(e) => error!("can't count mount usage: {:?}", e),
+            }
+            if interval.is_zero() {
+                break;
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+fn file_len(meta: FsResult<Box<dyn DavMetaData>>) -> Option<u64> {
+    meta.ok().filter(|m| !m.is_dir()).map(|m| m.len())
+}
+
+/// Keeps usage of a mount up to date on writes and reports it as quota.
+#[derive(Clone)]
+pub struct UsageFs {
+    inner: Box<dyn DavFileSystem>,
+    usage: Arc<Usage>,
+    quota: Option<u64>,
+}
+
+impl UsageFs {
+    pub fn new(
+        inner: Box<dyn DavFileSystem>,
+        usage: Arc<Usage>,
+        quota: Option<u64>,
+    ) -> Box<dyn DavFileSystem> {
+        Box::new(UsageFs {
+            inner,
+            usage,
+            quota,
+        }) as Box<dyn DavFileSystem>
+    }
+}
+
+/// File opened for writing, its size change is added to usage on flush.
+#[derive(Debug)]
+struct UsageFile {
+    inner: Box<dyn DavFile>,
+    usage: Arc<Usage>,
+    // None for files created by open
+    len: Option<u64>,
+}
+
+impl DavFile for UsageFile {
+    fn metadata<'a>(&'a mut self) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.metadata()
+    }
+
+    fn write_buf<'a>(&'a mut self, buf: Box<dyn Buf + Send>) -> FsFuture<()> {
+        self.inner.write_buf(buf)
+    }
+
+    fn write_bytes<'a>(&'a mut self, buf: Bytes) -> FsFuture<()> {
+        self.inner.write_bytes(buf)
+    }
+
+    fn read_bytes<'a>(&'a mut self, count: usize) -> FsFuture<Bytes> {
+        self.inner.read_bytes(count)
+    }
+
+    fn seek<'a>(&'a mut self, pos: SeekFrom) -> FsFuture<u64> {
+        self.inner.seek(pos)
+    }
+
+    fn flush<'a>(&'a mut self) -> FsFuture<()> {
+        async move {
+            self.inner.flush().await?;
+            let len = self.inner.metadata().await?.len();
+            match self.len {
+                Some(old) if len >= old => self.usage.add(len - old, 0),
+                Some(old) => self.usage.sub(old - len, 0),
+                None => self.usage.add(len, 1),
+            }
+            self.len = Some(len);
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+impl DavFileSystem for UsageFs {
+    fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
+        async move {
+            if !options.write {
+                return self.inner.open(path, options).await;
+            }
+            let len = file_len(self.inner.metadata(path).await);
+            let inner = self.inner.open(path, options).await?;
+            Ok(Box::new(UsageFile {
+                inner,
+                usage: self.usage.clone(),
+                len,
+            }) as Box<dyn DavFile>)
+        }
+        .boxed()
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        meta: ReadDirMeta,
+    ) -> FsFuture<FsStream<Box<dyn DavDirEntry>>> {
+        self.inner.read_dir(path, meta)
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.metadata(path)
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.symlink_metadata(path)
+    }
+
+    fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.create_dir(path)
+    }
+
+    fn remove_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        self.inner.remove_dir(path)
+    }
+
+    fn remove_file<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        async move {
+            let len = file_len(self.inner.metadata(path).await);
+            self.inner.remove_file(path).await?;
+            if let Some(len) = len {
+                self.usage.sub(len, 1);
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        async move {
+            let replaced = file_len(self.inner.metadata(to).await);
+            self.inner.rename(from, to).await?;
+            if let Some(len) = replaced {
+                self.usage.sub(len, 1);
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn copy<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        async move {
+            let len = file_len(self.inner.metadata(from).await);
+            let replaced = file_len(self.inner.metadata(to).await);
+            self.inner.copy(from, to).await?;
+            if let Some(len) = len {
+                self.usage.add(len, 1);
+            }
+            if let Some(len) = replaced {
+                self.usage.sub(len, 1);
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn set_accessed<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_accessed(path, tm)
+    }
+
+    fn set_modified<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        self.inner.set_modified(path, tm)
+    }
+
+    fn have_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
+        self.inner.have_props(path)
+    }
+
+    fn patch_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+        patch: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(hyper::StatusCode, DavProp)>> {
+        self.inner.patch_props(path, patch)
+    }
+
+    fn get_props<'a>(&'a self, path: &'a DavPath, do_content: bool) -> FsFuture<Vec<DavProp>> {
+        self.inner.get_props(path, do_content)
+    }
+
+    fn get_prop<'a>(&'a self, path: &'a DavPath, prop: DavProp) -> FsFuture<Vec<u8>> {
+        self.inner.get_prop(path, prop)
+    }
+
+    fn get_quota<'a>(&'a self) -> FsFuture<(u64, Option<u64>)> {
+        async move {
+            let used = self.usage.bytes();
+            if self.quota.is_some() {
+                return Ok((used, self.quota));
+            }
+            // without configured quota capacity is what is stored plus free
+            // space of backend, when it knows it
+            let free = match self.inner.get_quota().await {
+                Ok((u, Some(total))) => Some(total.saturating_sub(u)),
+                _ => None,
+            };
+            Ok((used, free.map(|f| used + f)))
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::put;
+    use webdav_handler::memfs::MemFs;
+
+    #[tokio::test]
+    async fn tracks_writes() -> anyhow::Result<()> {
+        let usage = Arc::new(Usage::default());
+        let fs = UsageFs::new(MemFs::new(), usage.clone(), Some(100));
+
+        put(fs.as_ref(), "/a.txt", b"hello").await?;
+        assert_eq!((usage.bytes(), usage.objects()), (5, 1));
+        put(fs.as_ref(), "/a.txt", b"abc").await?;
+        assert_eq!((usage.bytes(), usage.objects()), (3, 1));
+
+        fs.create_dir(&DavPath::new("/dir/")?).await?;
+        fs.copy(&DavPath::new("/a.txt")?, &DavPath::new("/dir/b.txt")?)
+            .await?;
+        assert_eq!((usage.bytes(), usage.objects()), (6, 2));
+        fs.remove_file(&DavPath::new("/a.txt")?).await?;
+        assert_eq!((usage.bytes(), usage.objects()), (3, 1));
+        assert_eq!(fs.get_quota().await?, (3, Some(100)));
+
+        usage.set(0, 0);
+        recount(fs.as_ref(), &usage).await?;
+        assert_eq!((usage.bytes(), usage.objects()), (3, 1));
+        Ok(())
+    }
+}
@@ -0,0 +1,302 @@
+use crate::backend::walk::child_path;
+use futures_util::{FutureExt, StreamExt};
+use std::time::SystemTime;
+use tracing::debug;
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{
+        DavDirEntry, DavFile, DavFileSystem, DavMetaData, DavProp, FsError, FsFuture, FsResult,
+        FsStream, OpenOptions, ReadDirMeta,
+    },
+};
+
+/// Hidden directory in mount root with previous versions of files.
+pub const VERSIONS_DIR: &str = ".versions";
+
+/// Keeps previous content of files which are overwritten or deleted under
+/// `/.versions/<file path>/<timestamp>`. Versions directory is not listed in
+/// mount root but can be browsed, it is read-only and a version is restored
+/// by copying it back.
+#[derive(Clone)]
+pub struct VersionsFs {
+    inner: Box<dyn DavFileSystem>,
+    keep: usize,
+}
+
+impl VersionsFs {
+    pub fn new(inner: Box<dyn DavFileSystem>, keep: usize) -> Box<dyn DavFileSystem> {
+        Box::new(VersionsFs { inner, keep }) as Box<dyn DavFileSystem>
+    }
+
+    async fn ensure_dir(&self, dir: &DavPath) -> FsResult<()> {
+        let s = dir.as_url_string();
+        for (i, _) in s.match_indices('/').skip(1) {
+            let p = DavPath::new(&s[..=i]).map_err(|_| FsError::GeneralFailure)?;
+            if self.inner.metadata(&p).await.is_err() {
+                self.inner.create_dir(&p).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn is_file(&self, path: &DavPath) -> bool {
+        matches!(self.inner.metadata(path).await, Ok(m) if !m.is_dir())
+    }
+
+    /// Copies or moves current content of file to a new version.
+    async fn save(&self, path: &DavPath, copy: bool) -> FsResult<()> {
+        let dir = versions_dir(path)?;
+        self.ensure_dir(&dir).await?;
+        let mut stamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_micros())
+            .unwrap_or(0);
+        let version = loop {
+            let v = child_path(&dir, format!("{:020}", stamp).as_bytes(), false)?;
+            if self.inner.metadata(&v).await.is_err() {
+                break v;
+            }
+            stamp += 1;
+        };
+        debug!(msg = "saving version", version = %version.as_url_string());
+        if copy {
+            self.inner.copy(path, &version).await?;
+        } else {
+            self.inner.rename(path, &version).await?;
+        }
+        self.prune(&dir).await
+    }
+
+    async fn prune(&self, dir: &DavPath) -> FsResult<()> {
+        let mut names = vec![];
+        let mut entries = self.inner.read_dir(dir, ReadDirMeta::None).await?;
+        while let Some(e) = entries.next().await {
+            names.push(e.name());
+        }
+        // names are zero padded timestamps so oldest go first
+        names.sort();
+        let old = names.len().saturating_sub(self.keep);
+        for name in &names[..old] {
+            self.inner
+                .remove_file(&child_path(dir, name, false)?)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+fn is_versions(path: &DavPath) -> bool {
+    let p = path.as_url_string();
+    let rest = p.trim_start_matches('/');
+    rest == VERSIONS_DIR || rest.starts_with(&format!("{}/", VERSIONS_DIR))
+}
+
+fn versions_dir(path: &DavPath) -> FsResult<DavPath> {
+    let p = path.as_url_string();
+    DavPath::new(&format!("/{}{}/", VERSIONS_DIR, p.trim_end_matches('/')))
+        .map_err(|_| FsError::GeneralFailure)
+}
+
+fn read_only(path: &DavPath) -> FsResult<()> {
+    if is_versions(path) {
+        Err(FsError::Forbidden)
+    } else {
+        Ok(())
+    }
+}
+
+impl DavFileSystem for VersionsFs {
+    fn open<'a>(&'a self, path: &'a DavPath, options: OpenOptions) -> FsFuture<Box<dyn DavFile>> {
+        async move {
+            if options.write {
+                read_only(path)?;
+                if !options.create_new && self.is_file(path).await {
+                    self.save(path, true).await?;
+                }
+            }
+            self.inner.open(path, options).await
+        }
+        .boxed()
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        meta: ReadDirMeta,
+    ) -> FsFuture<FsStream<Box<dyn DavDirEntry>>> {
+        async move {
+            let entries = self.inner.read_dir(path, meta).await?;
+            if !path.as_url_string().trim_matches('/').is_empty() {
+                return Ok(entries);
+            }
+            let entries = entries.filter(|e| {
+                let name = e.name();
+                let hide = name.strip_suffix(b"/").unwrap_or(&name) == VERSIONS_DIR.as_bytes();
+                futures_util::future::ready(!hide)
+            });
+            Ok(Box::pin(entries) as FsStream<Box<dyn DavDirEntry>>)
+        }
+        .boxed()
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.metadata(path)
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<Box<dyn DavMetaData>> {
+        self.inner.symlink_metadata(path)
+    }
+
+    fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        async move {
+            read_only(path)?;
+            self.inner.create_dir(path).await
+        }
+        .boxed()
+    }
+
+    fn remove_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        async move {
+            read_only(path)?;
+            self.inner.remove_dir(path).await
+        }
+        .boxed()
+    }
+
+    fn remove_file<'a>(&'a self, path: &'a DavPath) -> FsFuture<()> {
+        async move {
+            read_only(path)?;
+            if self.is_file(path).await {
+                // moving to versions removes it
+                self.save(path, false).await
+            } else {
+                self.inner.remove_file(path).await
+            }
+        }
+        .boxed()
+    }
+
+    fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        async move {
+            read_only(from)?;
+            read_only(to)?;
+            if self.is_file(to).await {
+                self.save(to, false).await?;
+            }
+            self.inner.rename(from, to).await
+        }
+        .boxed()
+    }
+
+    fn copy<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<()> {
+        async move {
+            read_only(to)?;
+            if self.is_file(to).await {
+                self.save(to, false).await?;
+            }
+            self.inner.copy(from, to).await
+        }
+        .boxed()
+    }
+
+    fn set_accessed<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        async move {
+            read_only(path)?;
+            self.inner.set_accessed(path, tm).await
+        }
+        .boxed()
+    }
+
+    fn set_modified<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<()> {
+        async move {
+            read_only(path)?;
+            self.inner.set_modified(path, tm).await
+        }
+        .boxed()
+    }
+
+    fn have_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+    ) -> std::pin::Pin<Box<dyn futures_util::Future<Output = bool> + Send + 'a>> {
+        self.inner.have_props(path)
+    }
+
+    fn patch_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+        patch: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(hyper::StatusCode, DavProp)>> {
+        async move {
+            read_only(path)?;
+            self.inner.patch_props(path, patch).await
+        }
+        .boxed()
+    }
+
+    fn get_props<'a>(&'a self, path: &'a DavPath, do_content: bool) -> FsFuture<Vec<DavProp>> {
+        self.inner.get_props(path, do_content)
+    }
+
+    fn get_prop<'a>(&'a self, path: &'a DavPath, prop: DavProp) -> FsFuture<Vec<u8>> {
+        self.inner.get_prop(path, prop)
+    }
+
+    fn get_quota<'a>(&'a self) -> FsFuture<(u64, Option<u64>)> {
+        self.inner.get_quota()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::open_opts;
+    use crate::test_utils::put;
+    use bytes::Bytes;
+    use webdav_handler::memfs::MemFs;
+
+    async fn read(fs: &dyn DavFileSystem, path: &DavPath) -> anyhow::Result<Bytes> {
+        let mut f = fs.open(path, open_opts::read()).await?;
+        Ok(f.read_bytes(1024).await?)
+    }
+
+    async fn names(fs: &dyn DavFileSystem, path: &str) -> anyhow::Result<Vec<Vec<u8>>> {
+        let mut names = vec![];
+        let mut entries = fs.read_dir(&DavPath::new(path)?, ReadDirMeta::None).await?;
+        while let Some(e) = entries.next().await {
+            names.push(e.name());
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    #[tokio::test]
+    async fn keeps_last_versions() -> anyhow::Result<()> {
+        let fs = VersionsFs::new(MemFs::new(), 2);
+        fs.create_dir(&DavPath::new("/dir/")?).await?;
+        for data in [&b"one"[..], b"two", b"three", b"four"].iter() {
+            put(fs.as_ref(), "/dir/a.txt", data).await?;
+        }
+        fs.remove_file(&DavPath::new("/dir/a.txt")?).await?;
+        assert!(fs.metadata(&DavPath::new("/dir/a.txt")?).await.is_err());
+
+        assert_eq!(names(fs.as_ref(), "/").await?, vec![b"dir".to_vec()]);
+        let versions = names(fs.as_ref(), "/.versions/dir/a.txt/").await?;
+        assert_eq!(versions.len(), 2);
+        let dir = DavPath::new("/.versions/dir/a.txt/")?;
+        let newest = child_path(&dir, versions.last().unwrap(), false)?;
+        assert_eq!(read(fs.as_ref(), &newest).await?, "four");
+
+        // read-only, restored by copying back
+        assert!(put(fs.as_ref(), "/.versions/dir/a.txt/x", b"x")
+            .await
+            .is_err());
+        assert!(fs.remove_file(&newest).await.is_err());
+        fs.copy(&newest, &DavPath::new("/dir/a.txt")?).await?;
+        assert_eq!(
+            read(fs.as_ref(), &DavPath::new("/dir/a.txt")?).await?,
+            "four"
+        );
+        Ok(())
+    }
+}
@@ -1,8 +1,10 @@
-use std::ops::Deref;
+use std::{collections::HashMap, ops::Deref};
 
 use config::{Config, ConfigError, Environment, File};
 use serde::Deserialize;
 
+use crate::backend::wrappers::{ignore::IgnoreAction, rewrite::RewriteRule, unicode::UnicodeForm};
+
 pub fn setup_tracing() {
     use tracing_subscriber::{fmt, prelude::*, registry::Registry, EnvFilter};
 
@@ -20,7 +22,7 @@ pub fn setup_tracing() {
     tracing::subscriber::set_global_default(collector).expect("can't set global default");
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
 pub struct Application {
     pub host: String,
     pub port: u16,
@@ -31,6 +33,30 @@ pub struct Application {
 pub enum Filesystem {
     FS {
         path: String,
+        /// create files readable by everyone
+        #[serde(default)]
+        public: bool,
+        /// apply macOS Finder workarounds
+        #[serde(default)]
+        macos: bool,
+        /// serve files behind symbolic links, including ones pointing outside
+        /// of path, and list them; when unset they are served but not listed
+        #[serde(default)]
+        follow_symlinks: Option<bool>,
+        /// find files ignoring case of path, requires `staging: false`
+        #[serde(default)]
+        case_insensitive: bool,
+        /// track changes made outside of webdav to keep props in sync
+        #[serde(default)]
+        watch: bool,
+        /// write uploads to temporary file first and rename it over target
+        /// when done
+        #[serde(default = "default_true")]
+        staging: bool,
+        /// directory of uploads in progress, `<path>.staging` by default; it
+        /// has to be on the same filesystem as path
+        #[serde(default)]
+        staging_dir: Option<String>,
     },
     Mem,
     S3 {
@@ -41,13 +67,95 @@ pub enum Filesystem {
         ensure_bucket: bool,
         #[serde(default)]
         auth: S3Authentication,
+        /// store dead properties as sidecar objects next to data, names ending
+        /// in `.davprops` are reserved for them
+        #[serde(default)]
+        sidecar_props: bool,
+        /// find objects ignoring case of path
+        #[serde(default)]
+        case_insensitive: bool,
+        /// milliseconds to remember missing paths for, 0 disables
+        #[serde(default = "default_negative_cache_ttl")]
+        negative_cache_ttl: u64,
+        /// bytes of open file kept in memory, bigger files are moved to temporary files
+        #[serde(default = "default_spool_threshold")]
+        spool_threshold: usize,
+        /// ask S3 for bucket region and endpoint on start and use them instead
+        /// of configured ones if they differ
+        #[serde(default)]
+        discover_region: bool,
+        /// GET of files at least this many bytes is answered with redirect to
+        /// presigned bucket url. Clients have to follow redirects
+        #[serde(default)]
+        redirect_downloads: Option<u64>,
+        /// seconds presigned download urls are valid for
+        #[serde(default = "default_redirect_expiry")]
+        redirect_expiry: u64,
+        /// bytes fetched by one ranged request when reading objects
+        #[serde(default = "default_download_range")]
+        download_range: u64,
+        /// ranged requests of one download sent at once
+        #[serde(default = "default_download_parallelism")]
+        download_parallelism: usize,
+        /// milliseconds own writes are added to listings for, for stores
+        /// with eventually consistent listings; 0 disables
+        #[serde(default)]
+        listing_cache: u64,
+        /// list whole subtree without delimiter and find children from keys,
+        /// for providers with broken delimiter handling; transfers more data
+        #[serde(default)]
+        deep_listing: bool,
+        /// provider prices to estimate monthly cost of requests and egress
+        /// from, reported by metrics
+        #[serde(default)]
+        pricing: Option<Pricing>,
     },
+    /// backend registered by program embedding the server
+    Plugin {
+        /// name backend is registered under
+        backend: String,
+        /// other fields of mount, passed to backend factory
+        #[serde(flatten)]
+        options: serde_yaml::Value,
+    },
+}
+
+fn default_negative_cache_ttl() -> u64 {
+    2000
+}
+
+fn default_spool_threshold() -> usize {
+    64 * 1024 * 1024
+}
+
+fn default_redirect_expiry() -> u64 {
+    300
+}
+
+fn default_download_range() -> u64 {
+    8 * 1024 * 1024
+}
+
+fn default_download_parallelism() -> usize {
+    4
 }
 
 #[derive(Debug, Deserialize, Clone, derivative::Derivative)]
 #[derivative(Default)]
 pub struct ConfAccessKey(#[derivative(Default(value = "\"AWS_ACCESS_KEY_ID\".into()"))] String);
 
+impl Filesystem {
+    /// Name of backend as written in `type` field, or registered name of plugin.
+    pub fn type_name(&self) -> &str {
+        match self {
+            Filesystem::FS { .. } => "fs",
+            Filesystem::Mem => "mem",
+            Filesystem::S3 { .. } => "s3",
+            Filesystem::Plugin { ref backend, .. } => backend,
+        }
+    }
+}
+
 impl Deref for ConfAccessKey {
     type Target = str;
 
@@ -101,20 +209,628 @@ pub struct FilesystemType {
     #[serde(flatten)]
     pub fs: Filesystem,
     pub mount_path: String,
+    /// advertise and allow class 2 (locking) on this mount
+    #[serde(default = "default_true")]
+    pub locking: bool,
+    /// lock system for this mount, global one is used if not set
+    #[serde(default)]
+    pub lock_storage: Option<LockStorage>,
+    /// convert paths to given unicode normalization form (nfc or nfd)
+    #[serde(default)]
+    pub unicode_normalization: Option<UnicodeForm>,
+    /// hide and do not store files matching patterns
+    #[serde(default)]
+    pub ignore: Option<IgnoreRules>,
+    /// rules applied to paths inside mount before they reach backend
+    #[serde(default)]
+    pub rewrite: Vec<RewriteRule>,
+    /// Cache-Control header value for GET/HEAD responses of this mount
+    #[serde(default)]
+    pub cache_control: Option<String>,
+    /// headers added to every response of this mount, replacing ones set by server
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// advertise CalDAV/CardDAV and answer their basic REPORTs
+    #[serde(default)]
+    pub groupware: bool,
+    /// take snapshots of mount content periodically (fs and s3 only)
+    #[serde(default)]
+    pub snapshots: Option<SnapshotPolicy>,
+    /// keep previous versions of overwritten and deleted files (fs and mem only)
+    #[serde(default)]
+    pub versions: Option<Versions>,
+    /// bytes reported as mount capacity in quota properties, not enforced;
+    /// fs mounts report free space of their disk without it
+    #[serde(default)]
+    pub quota: Option<u64>,
+    /// stop calling backend while it keeps failing
+    #[serde(default)]
+    pub breaker: Option<BreakerPolicy>,
+    /// wrappers applied to backend in listed order, after `versions` and `ignore`
+    #[serde(default)]
+    pub middleware: Vec<Middleware>,
+    /// methods allowed on this mount, all if not set
+    #[serde(default)]
+    pub allow_methods: Option<Vec<String>>,
+    /// methods rejected on this mount with 405
+    #[serde(default)]
+    pub deny_methods: Vec<String>,
+    /// headers of OPTIONS responses replacing computed ones
+    #[serde(default)]
+    pub advertise: Option<Advertise>,
+    /// uploads up to this many bytes are buffered in memory and not written
+    /// if their checksum matches stored one of replaced file
+    #[serde(default)]
+    pub dedupe: Option<usize>,
+    /// gzip/zstd compression of GET responses, none if not set
+    #[serde(default)]
+    pub compression: Option<Compression>,
+}
+
+/// Transport compression of mount, negotiated with Accept-Encoding.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Compression {
+    /// codings in order of preference, `zstd` and `gzip`
+    #[serde(default = "default_compression_encodings")]
+    pub encodings: Vec<String>,
+    /// content type prefixes compressed, all but compressed formats if empty
+    #[serde(default)]
+    pub types: Vec<String>,
+    /// responses smaller than this many bytes are sent as is
+    #[serde(default = "default_compression_min_size")]
+    pub min_size: u64,
+    #[serde(default = "default_compression_level")]
+    pub level: i32,
+}
+
+fn default_compression_encodings() -> Vec<String> {
+    vec!["zstd".to_string(), "gzip".to_string()]
+}
+
+fn default_compression_min_size() -> u64 {
+    1024
+}
+
+fn default_compression_level() -> i32 {
+    3
+}
+
+/// Compliance classes and methods mount advertises, for clients which change
+/// behavior depending on them.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Advertise {
+    /// DAV header, e.g. `1, 2` or `1, 3, calendar-access`
+    #[serde(default)]
+    pub dav: Option<String>,
+    /// Allow header, comma separated methods
+    #[serde(default)]
+    pub allow: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Middleware {
+    /// reject every change of mount content
+    ReadOnly,
+    /// accept uploads of new files only, nothing can be listed or read back
+    DropBox,
+    Ignore(IgnoreRules),
+    Versions(Versions),
+    /// limit bandwidth of mount in bytes per second
+    Throttle {
+        #[serde(default)]
+        read: Option<u64>,
+        #[serde(default)]
+        write: Option<u64>,
+    },
+    /// scan uploaded files with clamd
+    Antivirus {
+        /// unix socket path or host:port of clamd
+        address: String,
+        /// directory in mount infected files are moved to, they are removed if not set
+        #[serde(default)]
+        quarantine: Option<String>,
+    },
+}
+
+/// Prices in currency of provider, e.g. USD.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+pub struct Pricing {
+    /// price of 1000 PUT, COPY, POST and LIST requests
+    #[serde(default)]
+    pub per_1000_writes: f64,
+    /// price of 1000 GET and HEAD requests
+    #[serde(default)]
+    pub per_1000_reads: f64,
+    /// price of GB downloaded from bucket
+    #[serde(default)]
+    pub per_gb_egress: f64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SnapshotPolicy {
+    /// cron expression `minute hour day month weekday` in UTC, or @hourly, @daily, @weekly
+    pub schedule: String,
+    /// number of newest snapshots kept
+    #[serde(default = "default_snapshot_keep")]
+    pub keep: usize,
+    /// directory with fs mount snapshots, `<path>.snapshots` by default.
+    /// Dead properties from prop storage are not included.
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+fn default_snapshot_keep() -> usize {
+    7
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct BreakerPolicy {
+    /// consecutive backend failures after which mount is degraded
+    #[serde(default = "default_breaker_failures")]
+    pub failures: u32,
+    /// seconds between attempts to call degraded backend
+    #[serde(default = "default_breaker_cooldown")]
+    pub cooldown: u64,
+    /// backend serving reads of mount while it is degraded, e.g. replica
+    /// bucket, changes are refused meanwhile
+    #[serde(default)]
+    pub failover: Option<Filesystem>,
+}
+
+fn default_breaker_failures() -> u32 {
+    5
+}
+
+fn default_breaker_cooldown() -> u64 {
+    30
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Versions {
+    /// number of newest versions kept for every file. They are browsed under
+    /// `/.versions/` of mount and restored by copying back.
+    #[serde(default = "default_versions_keep")]
+    pub keep: usize,
+}
+
+fn default_versions_keep() -> usize {
+    10
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct IgnoreRules {
+    /// glob patterns matched against file name
+    #[serde(default = "default_ignore_patterns")]
+    pub patterns: Vec<String>,
+    #[serde(default = "default_ignore_action")]
+    pub action: IgnoreAction,
+}
+
+fn default_ignore_patterns() -> Vec<String> {
+    vec![
+        ".DS_Store".into(),
+        "._*".into(),
+        "Thumbs.db".into(),
+        "desktop.ini".into(),
+    ]
+}
+
+fn default_ignore_action() -> IgnoreAction {
+    IgnoreAction::Swallow
+}
+
+fn default_true() -> bool {
+    true
 }
 
 #[derive(Debug, Deserialize, Clone)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum PropsStorage {
-    Yaml { path: String },
+    Yaml {
+        path: String,
+        /// seconds between dumps of changed props to file
+        #[serde(default = "default_dump_interval")]
+        dump_interval: u64,
+    },
+    Sqlite {
+        path: String,
+    },
     Mem,
 }
 
-#[derive(Debug, Deserialize)]
+pub(crate) fn default_dump_interval() -> u64 {
+    5
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum LockStorage {
+    Mem,
+    /// accepts locks without keeping them, for clients which require locking
+    Fake,
+    /// locking is disabled
+    #[serde(rename = "none")]
+    Disabled,
+    Redis {
+        url: String,
+        #[serde(default = "default_lock_prefix")]
+        prefix: String,
+        /// seconds, locks without timeout or with bigger one are capped to it
+        #[serde(default = "default_lock_max_timeout")]
+        max_timeout: u64,
+    },
+}
+
+fn default_lock_prefix() -> String {
+    "webdav_ss".into()
+}
+
+fn default_lock_max_timeout() -> u64 {
+    3600
+}
+
+#[derive(Debug, Deserialize, Clone, derivative::Derivative)]
+#[derivative(Default)]
+pub struct LockPolicy {
+    /// seconds, used for locks requested without timeout when infinite locks are not allowed
+    #[serde(default)]
+    pub default_timeout: Option<u64>,
+    /// seconds, bigger timeouts are capped to it
+    #[serde(default)]
+    pub max_timeout: Option<u64>,
+    #[serde(default = "default_true")]
+    #[derivative(Default(value = "true"))]
+    pub allow_infinite: bool,
+}
+
+#[derive(Debug, Deserialize, Clone, derivative::Derivative)]
+#[derivative(Default)]
+pub struct Propfind {
+    /// allow PROPFIND with Depth: infinity
+    #[serde(default)]
+    pub allow_infinity: bool,
+    /// max number of entries returned for Depth: infinity
+    #[serde(default = "default_max_entries")]
+    #[derivative(Default(value = "10000"))]
+    pub max_entries: usize,
+    /// seconds totals in `tree-bytes` and `tree-items` props of collections
+    /// are cached for, props are disabled if not set
+    #[serde(default)]
+    pub tree_stats_ttl: Option<u64>,
+}
+
+fn default_max_entries() -> usize {
+    10000
+}
+
+#[derive(Debug, Deserialize, Clone, derivative::Derivative)]
+#[derivative(Default)]
+pub struct Events {
+    /// http urls receiving POST with JSON event
+    #[serde(default)]
+    pub webhooks: Vec<String>,
+    #[serde(default)]
+    pub mqtt: Vec<MqttSink>,
+    #[serde(default)]
+    pub nats: Vec<NatsSink>,
+    /// file where events are appended as JSON lines, see `audit` command
+    #[serde(default)]
+    pub audit: Option<String>,
+    /// request header with user name set by authenticating proxy, e.g. X-Remote-User
+    #[serde(default)]
+    pub user_header: Option<String>,
+    /// delivery attempts after the first failed one
+    #[serde(default = "default_event_retries")]
+    #[derivative(Default(value = "5"))]
+    pub retries: u32,
+    /// delay before first retry in seconds, doubled for every next one
+    #[serde(default = "default_event_retry_delay")]
+    #[derivative(Default(value = "1"))]
+    pub retry_delay: u64,
+    /// events waiting for delivery per sink, newer ones are dropped when full
+    #[serde(default = "default_event_queue_size")]
+    #[derivative(Default(value = "1000"))]
+    pub queue_size: usize,
+    /// seconds to wait for webhook response
+    #[serde(default = "default_webhook_timeout")]
+    #[derivative(Default(value = "10"))]
+    pub webhook_timeout: u64,
+}
+
+#[derive(Debug, Deserialize, Clone, derivative::Derivative)]
+#[derivative(Default)]
+pub struct Timeouts {
+    /// seconds to receive request headers, also limits idle keep-alive connections
+    #[serde(default = "default_header_timeout")]
+    #[derivative(Default(value = "30"))]
+    pub header: u64,
+    /// seconds to wait for next chunk of request body
+    #[serde(default = "default_body_timeout")]
+    #[derivative(Default(value = "60"))]
+    pub body: u64,
+    /// seconds for whole request including sending response, unlimited by default
+    #[serde(default)]
+    pub request: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct Connections {
+    /// connections one client address may keep open, further ones are
+    /// closed when accepted; unlimited if not set
+    #[serde(default)]
+    pub per_ip: Option<usize>,
+}
+
+fn default_header_timeout() -> u64 {
+    30
+}
+
+fn default_body_timeout() -> u64 {
+    60
+}
+
+#[derive(Debug, Deserialize, Clone, derivative::Derivative)]
+#[derivative(Default)]
+pub struct Metrics {
+    /// path of prometheus metrics endpoint, disabled if not set
+    #[serde(default)]
+    pub path: Option<String>,
+    /// path of readiness endpoint, answers 503 while any mount is degraded
+    #[serde(default)]
+    pub ready_path: Option<String>,
+    /// path listing uploads and downloads in progress with bytes
+    /// transferred, rate and client, disabled if not set
+    #[serde(default)]
+    pub transfers_path: Option<String>,
+    /// path listing open connections with counts per address, requests and
+    /// idle time, disabled if not set
+    #[serde(default)]
+    pub connections_path: Option<String>,
+    /// path answering totals of collection given in `path` query parameter
+    /// as JSON, needs `propfind.tree_stats_ttl`
+    #[serde(default)]
+    pub tree_path: Option<String>,
+    /// seconds between full recounts of mount usage, 0 counts only at start.
+    /// Writes update it in between
+    #[serde(default = "default_usage_refresh")]
+    #[derivative(Default(value = "3600"))]
+    pub usage_refresh: u64,
+}
+
+fn default_usage_refresh() -> u64 {
+    3600
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct Errors {
+    /// send error responses with XML body naming failed condition, e.g.
+    /// `DAV:quota-not-exceeded` for 507
+    #[serde(default)]
+    pub xml_bodies: bool,
+}
+
+/// Reports of panics and backend failures, sent to plain http endpoints.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ErrorReports {
+    /// url receiving POST with JSON report
+    #[serde(default)]
+    pub webhook: Option<String>,
+    /// `http://key@host/project` DSN of Sentry, e.g. of local Relay
+    #[serde(default)]
+    pub sentry_dsn: Option<String>,
+    /// most reports sent per minute, the rest are dropped
+    #[serde(default = "default_reports_per_minute")]
+    pub per_minute: u32,
+}
+
+fn default_reports_per_minute() -> u32 {
+    60
+}
+
+/// Dump of requests and responses for debugging client quirks.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Dump {
+    /// file exchanges are appended to
+    pub path: String,
+    /// dump every n-th request
+    #[serde(default = "default_dump_every")]
+    pub every: u64,
+    /// include XML bodies, they are read whole before being passed on
+    #[serde(default)]
+    pub bodies: bool,
+    /// bytes of each body written, the rest is only counted
+    #[serde(default = "default_dump_max_body")]
+    pub max_body: usize,
+}
+
+fn default_dump_every() -> u64 {
+    1
+}
+
+fn default_dump_max_body() -> usize {
+    64 * 1024
+}
+
+#[derive(Debug, Deserialize, Clone, derivative::Derivative)]
+#[derivative(Default)]
+pub struct Compat {
+    /// adjust responses for Windows WebClient (mapped network drives): OPTIONS
+    /// on root succeeds, MS-Author-Via is sent and responses other than
+    /// downloads have Content-Length instead of chunked body
+    #[serde(default)]
+    pub windows: bool,
+    /// adjust for macOS Finder: AppleDouble (`._*`) and `.DS_Store` writes
+    /// succeed without storing, lookups of Finder probe files are answered
+    /// without backends and mounts with locking disabled accept fake locks,
+    /// as Finder mounts servers without locking read-only
+    #[serde(default)]
+    pub macos: bool,
+}
+
+/// Resumable uploads with tus protocol.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Uploads {
+    /// endpoint path, uploads are created by POST to it
+    pub path: String,
+    /// local directory keeping unfinished uploads, temp dir by default
+    #[serde(default)]
+    pub dir: Option<String>,
+    /// largest upload in bytes
+    #[serde(default)]
+    pub max_size: Option<u64>,
+    /// hours after which unfinished upload is removed
+    #[serde(default = "default_uploads_expire")]
+    pub expire: u64,
+}
+
+fn default_uploads_expire() -> u64 {
+    24
+}
+
+/// External program deciding whether requests are allowed, denied or
+/// rewritten to another path.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RequestFilter {
+    /// program and its arguments
+    pub command: Vec<String>,
+    /// milliseconds to wait for decision, request is denied after
+    #[serde(default = "default_filter_timeout")]
+    pub timeout: u64,
+}
+
+fn default_filter_timeout() -> u64 {
+    1000
+}
+
+/// Index of file names and text content across mounts, searched with
+/// `GET <path>?q=words`.
+#[derive(Debug, Deserialize, Clone)]
+pub struct SearchIndex {
+    /// endpoint path
+    pub path: String,
+    /// text files up to this many bytes are searchable by content, 0 indexes names only
+    #[serde(default = "default_index_content_limit")]
+    pub content_limit: u64,
+    /// most results returned for one query
+    #[serde(default = "default_index_max_results")]
+    pub max_results: usize,
+}
+
+fn default_index_content_limit() -> u64 {
+    1024 * 1024
+}
+
+fn default_index_max_results() -> usize {
+    100
+}
+
+/// Built-in web file manager page, works over listings and plain WebDAV
+/// methods so the same access rules apply.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Ui {
+    /// path the page is served at
+    pub path: String,
+}
+
+/// Second listener serving the same mounts read-only, without request
+/// filter, so internal tools can fetch files without write credentials.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Mirror {
+    /// address to listen on, the one of `app` if not set
+    #[serde(default)]
+    pub host: Option<String>,
+    pub port: u16,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct MqttSink {
+    /// mqtt://host[:port]
+    pub url: String,
+    pub topic: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct NatsSink {
+    /// nats://host[:port]
+    pub url: String,
+    pub subject: String,
+}
+
+fn default_event_retries() -> u32 {
+    5
+}
+
+fn default_event_retry_delay() -> u64 {
+    1
+}
+
+fn default_event_queue_size() -> usize {
+    1000
+}
+
+fn default_webhook_timeout() -> u64 {
+    10
+}
+
+/// Mounts served instead of top-level ones for requests with matching Host
+/// header, other settings are shared with top level.
+#[derive(Debug, Deserialize, Clone)]
+pub struct VirtualHost {
+    /// host names without port
+    pub names: Vec<String>,
+    pub filesystems: Vec<FilesystemType>,
+    /// kept in memory if not set, top-level storage is not shared
+    pub prop_storage: Option<PropsStorage>,
+    /// kept in memory if not set, top-level storage is not shared
+    pub lock_storage: Option<LockStorage>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
 pub struct Configuration {
     pub app: Application,
     pub filesystems: Vec<FilesystemType>,
     pub prop_storage: Option<PropsStorage>,
+    pub lock_storage: Option<LockStorage>,
+    /// file journaling operations which change both data and props, props
+    /// of unfinished ones are fixed on start
+    #[serde(default)]
+    pub journal: Option<String>,
+    /// seconds between runs dropping props and locks of paths removed from
+    /// backends directly, never run if not set
+    #[serde(default)]
+    pub reconcile_interval: Option<u64>,
+    #[serde(default)]
+    pub lock_policy: LockPolicy,
+    #[serde(default)]
+    pub propfind: Propfind,
+    /// file extension to MIME type, overrides built-in detection
+    #[serde(default)]
+    pub content_types: HashMap<String, String>,
+    /// notifications about changes
+    #[serde(default)]
+    pub events: Events,
+    #[serde(default)]
+    pub timeouts: Timeouts,
+    #[serde(default)]
+    pub connections: Connections,
+    #[serde(default)]
+    pub metrics: Metrics,
+    pub uploads: Option<Uploads>,
+    pub request_filter: Option<RequestFilter>,
+    pub search_index: Option<SearchIndex>,
+    pub ui: Option<Ui>,
+    pub mirror: Option<Mirror>,
+    #[serde(default)]
+    pub compat: Compat,
+    pub dump: Option<Dump>,
+    #[serde(default)]
+    pub errors: Errors,
+    pub error_reports: Option<ErrorReports>,
+    /// virtual hosts with their own mounts, requests to other names are
+    /// served by top-level mounts
+    #[serde(default)]
+    pub hosts: Vec<VirtualHost>,
 }
 
 impl Configuration {
@@ -0,0 +1,121 @@
+use super::{Event, EventSink};
+use anyhow::{anyhow, Result};
+use futures_util::{future::BoxFuture, FutureExt};
+use std::{
+    io::BufRead,
+    path::{Path, PathBuf},
+};
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+use tracing::warn;
+
+/// Appends events as JSON lines to a file, keeping trail of who changed what.
+/// File is reopened for every event so it can be rotated externally.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(path: &str) -> Self {
+        AuditLog { path: path.into() }
+    }
+}
+
+impl EventSink for AuditLog {
+    fn send<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<()>> {
+        async move {
+            let mut line = serde_json::to_vec(event)?;
+            line.push(b'\n');
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .await?;
+            file.write_all(&line).await?;
+            file.flush().await?;
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AuditFilter {
+    pub user: Option<String>,
+    /// prefix of changed path or destination
+    pub path: Option<String>,
+    /// unix time in seconds
+    pub since: Option<u64>,
+}
+
+impl AuditFilter {
+    pub fn matches(&self, e: &Event) -> bool {
+        let user = self
+            .user
+            .as_ref()
+            .map_or(true, |u| e.user.as_ref() == Some(u));
+        let path = self.path.as_ref().map_or(true, |p| {
+            e.path.starts_with(p) || e.destination.as_ref().map_or(false, |d| d.starts_with(p))
+        });
+        let since = self.since.map_or(true, |s| e.timestamp >= s);
+        user && path && since
+    }
+}
+
+/// Reads events from audit log, oldest first.
+pub fn query(path: &Path, filter: &AuditFilter) -> Result<Vec<Event>> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| anyhow!("can't open audit log {}: {:?}", path.display(), e))?;
+    let mut events = vec![];
+    for (n, line) in std::io::BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Event>(&line) {
+            Ok(e) if filter.matches(&e) => events.push(e),
+            Ok(_) => {}
+            // last line may be cut if server was killed while writing it
+            Err(e) => warn!("skipping audit log line {}: {}", n + 1, e),
+        }
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn append_and_query() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("webdav_ss_audit_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let log = AuditLog::new(path.to_str().unwrap());
+
+        let mut delete = Event::new("DELETE", "/fs1/a.txt");
+        delete.user = Some("alice".into());
+        let mut moved = Event::new("MOVE", "/fs2/b.txt");
+        moved.user = Some("bob".into());
+        moved.destination = Some("/fs1/b.txt".into());
+        log.send(&delete).await?;
+        log.send(&moved).await?;
+
+        assert_eq!(
+            query(&path, &AuditFilter::default())?,
+            vec![delete.clone(), moved]
+        );
+        let by_user = AuditFilter {
+            user: Some("alice".into()),
+            ..Default::default()
+        };
+        assert_eq!(query(&path, &by_user)?, vec![delete.clone()]);
+        let by_path = AuditFilter {
+            path: Some("/fs1/".into()),
+            since: Some(delete.timestamp),
+            ..Default::default()
+        };
+        assert_eq!(query(&path, &by_path)?.len(), 2);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
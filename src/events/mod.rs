@@ -0,0 +1,212 @@
+pub mod audit;
+pub mod mqtt;
+pub mod nats;
+pub mod webhook;
+
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Debug,
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tracing::{debug, error, warn};
+
+static DROPPED: AtomicU64 = AtomicU64::new(0);
+
+/// Events dropped since start because queue of a sink was full.
+pub fn dropped() -> u64 {
+    DROPPED.load(Ordering::Relaxed)
+}
+
+/// Change made through WebDAV, sent to configured sinks after request succeeded.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Event {
+    pub method: String,
+    pub path: String,
+    /// target of MOVE and COPY
+    pub destination: Option<String>,
+    pub mount: Option<String>,
+    /// taken from `events.user_header` of request
+    pub user: Option<String>,
+    pub size: Option<u64>,
+    /// unix time in seconds
+    pub timestamp: u64,
+}
+
+impl Event {
+    pub fn new(method: &str, path: &str) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Event {
+            method: method.into(),
+            path: path.into(),
+            destination: None,
+            mount: None,
+            user: None,
+            size: None,
+            timestamp,
+        }
+    }
+}
+
+/// Time to wait for broker response before connection is considered broken.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Reads response of broker failing when it does not arrive in time.
+async fn response<T>(read: impl Future<Output = std::io::Result<T>>) -> anyhow::Result<T> {
+    match tokio::time::timeout(RESPONSE_TIMEOUT, read).await {
+        Ok(r) => Ok(r?),
+        Err(_) => Err(anyhow::anyhow!("broker did not respond in time")),
+    }
+}
+
+pub trait EventSink: Debug + Send + Sync {
+    fn send<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, anyhow::Result<()>>;
+}
+
+/// Queues events and delivers them to every sink in background. Each sink has
+/// its own queue so slow or failing sink does not delay others, failed
+/// deliveries are retried with exponential backoff. Queues hold up to
+/// `capacity` events, newer ones are dropped while a queue is full.
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    queues: Vec<mpsc::Sender<Event>>,
+}
+
+impl Notifier {
+    pub fn new(
+        sinks: Vec<Arc<dyn EventSink>>,
+        retries: u32,
+        retry_delay: Duration,
+        capacity: usize,
+    ) -> Self {
+        let queues = sinks
+            .into_iter()
+            .map(|sink| {
+                let (tx, rx) = mpsc::channel(capacity.max(1));
+                tokio::spawn(deliver(sink, rx, retries, retry_delay));
+                tx
+            })
+            .collect();
+        Notifier { queues }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queues.is_empty()
+    }
+
+    pub fn notify(&self, event: Event) {
+        debug!(?event);
+        for q in &self.queues {
+            match q.try_send(event.clone()) {
+                Ok(()) => {}
+                Err(TrySendError::Full(e)) => {
+                    DROPPED.fetch_add(1, Ordering::Relaxed);
+                    warn!(msg = "event queue full, event dropped", path = %e.path);
+                }
+                Err(TrySendError::Closed(_)) => error!("event queue closed"),
+            }
+        }
+    }
+}
+
+async fn deliver(
+    sink: Arc<dyn EventSink>,
+    mut rx: mpsc::Receiver<Event>,
+    retries: u32,
+    retry_delay: Duration,
+) {
+    while let Some(event) = rx.recv().await {
+        let mut delay = retry_delay;
+        let mut attempt = 0;
+        loop {
+            match sink.send(&event).await {
+                Ok(()) => break,
+                Err(e) if attempt < retries => {
+                    warn!(msg = "event delivery failed, retrying", sink = ?sink, err = %e);
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    error!(msg = "event dropped", sink = ?sink, path = %event.path, err = %e);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Default)]
+    struct Flaky {
+        fails: Mutex<u32>,
+        received: Mutex<Vec<Event>>,
+    }
+
+    impl EventSink for Flaky {
+        fn send<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, anyhow::Result<()>> {
+            async move {
+                let mut fails = self.fails.lock().unwrap();
+                if *fails > 0 {
+                    *fails -= 1;
+                    return Err(anyhow::anyhow!("unavailable"));
+                }
+                self.received.lock().unwrap().push(event.clone());
+                Ok(())
+            }
+            .boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn retries() -> anyhow::Result<()> {
+        let sink = Arc::new(Flaky {
+            fails: Mutex::new(2),
+            ..Default::default()
+        });
+        let notifier = Notifier::new(vec![sink.clone()], 3, Duration::from_millis(1), 16);
+        notifier.notify(Event::new("PUT", "/fs1/a.txt"));
+        notifier.notify(Event::new("DELETE", "/fs1/b.txt"));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let received = sink.received.lock().unwrap();
+        let paths = received.iter().map(|e| e.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(paths, vec!["/fs1/a.txt", "/fs1/b.txt"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn drops_when_full() -> anyhow::Result<()> {
+        let sink = Arc::new(Flaky {
+            fails: Mutex::new(1),
+            ..Default::default()
+        });
+        let before = dropped();
+        let notifier = Notifier::new(vec![sink.clone()], 1, Duration::from_millis(50), 1);
+        for p in ["/fs1/a.txt", "/fs1/b.txt", "/fs1/c.txt", "/fs1/d.txt"].iter() {
+            notifier.notify(Event::new("PUT", p));
+            tokio::task::yield_now().await;
+        }
+        assert!(dropped() - before >= 1);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let received = sink.received.lock().unwrap();
+        assert!(received.len() < 4);
+        assert_eq!(received[0].path, "/fs1/a.txt");
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use super::{response, Event, EventSink};
+use anyhow::{anyhow, Result};
+use futures_util::{future::BoxFuture, FutureExt};
+use hyper::Uri;
+use std::{
+    sync::atomic::{AtomicU16, Ordering},
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    sync::Mutex,
+};
+use tracing::debug;
+
+const KEEP_ALIVE_SECS: u16 = 60;
+
+fn remaining_length(mut len: usize, buf: &mut Vec<u8>) {
+    loop {
+        let mut b = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            b |= 0x80;
+        }
+        buf.push(b);
+        if len == 0 {
+            break;
+        }
+    }
+}
+
+fn string(s: &str, buf: &mut Vec<u8>) {
+    buf.extend_from_slice(&(s.len() as u16).to_be_bytes());
+    buf.extend_from_slice(s.as_bytes());
+}
+
+fn packet(header: u8, body: Vec<u8>) -> Vec<u8> {
+    let mut p = vec![header];
+    remaining_length(body.len(), &mut p);
+    p.extend(body);
+    p
+}
+
+/// MQTT 3.1.1 CONNECT with clean session.
+fn connect_packet(client_id: &str) -> Vec<u8> {
+    let mut body = vec![];
+    string("MQTT", &mut body);
+    body.push(4);
+    body.push(0x02);
+    body.extend_from_slice(&KEEP_ALIVE_SECS.to_be_bytes());
+    string(client_id, &mut body);
+    packet(0x10, body)
+}
+
+/// PUBLISH with QoS 1 so delivery is acknowledged by broker.
+fn publish_packet(topic: &str, id: u16, payload: &[u8]) -> Vec<u8> {
+    let mut body = vec![];
+    string(topic, &mut body);
+    body.extend_from_slice(&id.to_be_bytes());
+    body.extend_from_slice(payload);
+    packet(0x32, body)
+}
+
+/// Publishes events as JSON to MQTT topic. Connection is opened lazily and
+/// reopened after errors, or when it was idle for keep-alive interval since
+/// broker may close it then.
+#[derive(derivative::Derivative)]
+#[derivative(Debug)]
+pub struct MqttSink {
+    addr: String,
+    topic: String,
+    client_id: String,
+    #[derivative(Debug = "ignore")]
+    conn: Mutex<Option<(TcpStream, Instant)>>,
+    #[derivative(Debug = "ignore")]
+    packet_id: AtomicU16,
+}
+
+impl MqttSink {
+    /// url is `mqtt://host[:port]`
+    pub fn new(url: &str, topic: &str) -> Result<Self> {
+        let uri: Uri = url.parse()?;
+        if uri.scheme_str() != Some("mqtt") {
+            return Err(anyhow!("unsupported mqtt url: {}", url));
+        }
+        let host = uri.host().ok_or_else(|| anyhow!("missing host: {}", url))?;
+        let id = uuid::Uuid::new_v4().to_simple().to_string();
+        Ok(MqttSink {
+            addr: format!("{}:{}", host, uri.port_u16().unwrap_or(1883)),
+            topic: topic.into(),
+            // 3.1.1 brokers are only required to accept ids up to 23 chars
+            client_id: format!("webdav_ss-{}", &id[..12]),
+            conn: Mutex::new(None),
+            packet_id: AtomicU16::new(1),
+        })
+    }
+
+    async fn connect(&self) -> Result<TcpStream> {
+        let mut s = TcpStream::connect(&self.addr).await?;
+        s.write_all(&connect_packet(&self.client_id)).await?;
+        let mut ack = [0u8; 4];
+        response(s.read_exact(&mut ack)).await?;
+        if ack[0] != 0x20 || ack[3] != 0 {
+            return Err(anyhow!("mqtt connection refused: {:?}", ack));
+        }
+        debug!(msg = "connected to mqtt broker", addr = %self.addr);
+        Ok(s)
+    }
+
+    async fn publish(&self, s: &mut TcpStream, payload: &[u8]) -> Result<()> {
+        let id = match self.packet_id.fetch_add(1, Ordering::Relaxed) {
+            0 => self.packet_id.fetch_add(1, Ordering::Relaxed),
+            id => id,
+        };
+        s.write_all(&publish_packet(&self.topic, id, payload))
+            .await?;
+        let mut ack = [0u8; 4];
+        response(s.read_exact(&mut ack)).await?;
+        if ack[0] != 0x40 || ack[2..] != id.to_be_bytes() {
+            return Err(anyhow!("unexpected mqtt response: {:?}", ack));
+        }
+        Ok(())
+    }
+}
+
+impl EventSink for MqttSink {
+    fn send<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<()>> {
+        async move {
+            let payload = serde_json::to_vec(event)?;
+            let mut conn = self.conn.lock().await;
+            let keep_alive = Duration::from_secs(KEEP_ALIVE_SECS.into());
+            let mut s = match conn.take() {
+                Some((s, used)) if used.elapsed() < keep_alive => s,
+                _ => self.connect().await?,
+            };
+            // connection is dropped on error and reopened on next attempt
+            self.publish(&mut s, &payload).await?;
+            *conn = Some((s, Instant::now()));
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packets() {
+        let mut b = vec![];
+        remaining_length(321, &mut b);
+        assert_eq!(b, vec![0xC1, 0x02]);
+
+        assert_eq!(
+            connect_packet("id"),
+            vec![0x10, 14, 0, 4, b'M', b'Q', b'T', b'T', 4, 2, 0, 60, 0, 2, b'i', b'd']
+        );
+        assert_eq!(
+            publish_packet("a/b", 7, b"{}"),
+            vec![0x32, 9, 0, 3, b'a', b'/', b'b', 0, 7, b'{', b'}']
+        );
+    }
+}
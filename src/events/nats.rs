@@ -0,0 +1,109 @@
+use super::{response, Event, EventSink};
+use anyhow::{anyhow, Result};
+use futures_util::{future::BoxFuture, FutureExt};
+use hyper::Uri;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufStream},
+    net::TcpStream,
+    sync::Mutex,
+};
+use tracing::debug;
+
+fn pub_command(subject: &str, payload: &[u8]) -> Vec<u8> {
+    let mut c = format!("PUB {} {}\r\n", subject, payload.len()).into_bytes();
+    c.extend_from_slice(payload);
+    c.extend_from_slice(b"\r\n");
+    c
+}
+
+/// Publishes events as JSON to NATS subject. Every publish is followed by
+/// PING so errors reported by server are noticed.
+#[derive(derivative::Derivative)]
+#[derivative(Debug)]
+pub struct NatsSink {
+    addr: String,
+    subject: String,
+    #[derivative(Debug = "ignore")]
+    conn: Mutex<Option<BufStream<TcpStream>>>,
+}
+
+impl NatsSink {
+    /// url is `nats://host[:port]`
+    pub fn new(url: &str, subject: &str) -> Result<Self> {
+        let uri: Uri = url.parse()?;
+        if uri.scheme_str() != Some("nats") {
+            return Err(anyhow!("unsupported nats url: {}", url));
+        }
+        let host = uri.host().ok_or_else(|| anyhow!("missing host: {}", url))?;
+        Ok(NatsSink {
+            addr: format!("{}:{}", host, uri.port_u16().unwrap_or(4222)),
+            subject: subject.into(),
+            conn: Mutex::new(None),
+        })
+    }
+
+    async fn connect(&self) -> Result<BufStream<TcpStream>> {
+        let mut s = BufStream::new(TcpStream::connect(&self.addr).await?);
+        let mut info = String::new();
+        response(s.read_line(&mut info)).await?;
+        if !info.starts_with("INFO") {
+            return Err(anyhow!("unexpected nats greeting: {}", info.trim()));
+        }
+        s.write_all(b"CONNECT {\"verbose\":false,\"pedantic\":false}\r\n")
+            .await?;
+        debug!(msg = "connected to nats", addr = %self.addr);
+        Ok(s)
+    }
+
+    async fn publish(&self, s: &mut BufStream<TcpStream>, payload: &[u8]) -> Result<()> {
+        s.write_all(&pub_command(&self.subject, payload)).await?;
+        s.write_all(b"PING\r\n").await?;
+        s.flush().await?;
+        loop {
+            let mut line = String::new();
+            if response(s.read_line(&mut line)).await? == 0 {
+                return Err(anyhow!("nats connection closed"));
+            }
+            match line.trim_end() {
+                "PONG" => return Ok(()),
+                "PING" => {
+                    s.write_all(b"PONG\r\n").await?;
+                    s.flush().await?;
+                }
+                l if l.starts_with("-ERR") => return Err(anyhow!("nats error: {}", l)),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl EventSink for NatsSink {
+    fn send<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<()>> {
+        async move {
+            let payload = serde_json::to_vec(event)?;
+            let mut conn = self.conn.lock().await;
+            let mut s = match conn.take() {
+                Some(s) => s,
+                None => self.connect().await?,
+            };
+            self.publish(&mut s, &payload).await?;
+            *conn = Some(s);
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command() {
+        assert_eq!(
+            pub_command("files.changed", b"{}"),
+            b"PUB files.changed 2\r\n{}\r\n"
+        );
+        assert!(NatsSink::new("http://localhost", "s").is_err());
+    }
+}
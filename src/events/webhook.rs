@@ -0,0 +1,49 @@
+use super::{Event, EventSink};
+use anyhow::{anyhow, Result};
+use futures_util::{future::BoxFuture, FutureExt};
+use hyper::{client::HttpConnector, Body, Client, Method, Request, Uri};
+use std::time::Duration;
+
+/// POSTs events as JSON to url. Only plain http endpoints are supported,
+/// requests taking longer than `timeout` count as failed.
+#[derive(Debug, Clone)]
+pub struct WebhookSink {
+    url: Uri,
+    client: Client<HttpConnector>,
+    timeout: Duration,
+}
+
+impl WebhookSink {
+    pub fn new(url: &str, timeout: Duration) -> Result<Self> {
+        let url: Uri = url.parse()?;
+        if url.scheme_str() != Some("http") {
+            return Err(anyhow!("unsupported webhook url: {}", url));
+        }
+        Ok(WebhookSink {
+            url,
+            client: Client::new(),
+            timeout,
+        })
+    }
+}
+
+impl EventSink for WebhookSink {
+    fn send<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<()>> {
+        async move {
+            let body = serde_json::to_vec(event)?;
+            let req = Request::builder()
+                .method(Method::POST)
+                .uri(self.url.clone())
+                .header("Content-Type", "application/json")
+                .body(Body::from(body))?;
+            let resp = tokio::time::timeout(self.timeout, self.client.request(req))
+                .await
+                .map_err(|_| anyhow!("webhook timed out"))??;
+            if !resp.status().is_success() {
+                return Err(anyhow!("webhook responded with {}", resp.status()));
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+}
@@ -0,0 +1,374 @@
+use crate::backend::open_opts;
+use crate::{
+    application::build_mount,
+    backend::{
+        live_props::{is_live, WEBDAV_SS_NAMESPACE},
+        normalized_path::NormalizedPath,
+        walk::walk,
+        wrappers::path_map::ENC,
+    },
+    configuration::Configuration,
+};
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use percent_encoding::percent_encode;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{Read, Write},
+    path::Path,
+    time::SystemTime,
+};
+use tokio::runtime::Handle;
+use tracing::debug;
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{DavFile, DavFileSystem, DavProp, FsError},
+};
+
+const CHUNK: usize = 1024 * 1024;
+/// Archive entry with dead properties, written after all files.
+const PROPS_ENTRY: &str = ".webdav_ss-props.yml";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ArchiveStats {
+    pub dirs: usize,
+    pub files: usize,
+    pub props: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchivedProp {
+    name: String,
+    namespace: Option<String>,
+    prefix: Option<String>,
+    xml: Option<String>,
+}
+
+impl From<DavProp> for ArchivedProp {
+    fn from(p: DavProp) -> Self {
+        ArchivedProp {
+            name: p.name,
+            namespace: p.namespace,
+            prefix: p.prefix,
+            xml: p.xml.map(|x| String::from_utf8_lossy(&x).into_owned()),
+        }
+    }
+}
+
+impl From<ArchivedProp> for DavProp {
+    fn from(p: ArchivedProp) -> Self {
+        DavProp {
+            name: p.name,
+            namespace: p.namespace,
+            prefix: p.prefix,
+            xml: p.xml.map(|x| x.into_bytes()),
+        }
+    }
+}
+
+/// Sync reader over webdav file used by tar writer. Needs multi-threaded runtime.
+struct FileReader {
+    file: Box<dyn DavFile>,
+    buf: Bytes,
+    handle: Handle,
+}
+
+impl Read for FileReader {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        if self.buf.is_empty() {
+            let file = &mut self.file;
+            let handle = &self.handle;
+            self.buf = tokio::task::block_in_place(|| handle.block_on(file.read_bytes(CHUNK)))
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("{:?}", e)))?;
+        }
+        let n = std::cmp::min(out.len(), self.buf.len());
+        out[..n].copy_from_slice(&self.buf.split_to(n));
+        Ok(n)
+    }
+}
+
+fn relative(root: &DavPath, path: &DavPath) -> String {
+    let root: NormalizedPath = root.into();
+    let path: NormalizedPath = path.into();
+    if root.is_root() {
+        return path.to_string();
+    }
+    path.strip_prefix(&root).to_string()
+}
+
+fn unix_time(t: Result<SystemTime, FsError>) -> u64 {
+    t.ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Props of webdav_ss namespace are derived from content (checksums), they
+/// are neither exported nor restored.
+fn internal(namespace: Option<&str>) -> bool {
+    namespace == Some(WEBDAV_SS_NAMESPACE)
+}
+
+async fn dead_props(fs: &dyn DavFileSystem, path: &DavPath) -> Vec<ArchivedProp> {
+    fs.get_props(path, true)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|p| !is_live(p) && !internal(p.namespace.as_deref()))
+        .map(ArchivedProp::from)
+        .collect()
+}
+
+/// Writes tar archive with every file and directory below `root` and their
+/// dead properties.
+pub async fn write_archive<W: Write>(
+    fs: &dyn DavFileSystem,
+    root: &DavPath,
+    out: W,
+) -> Result<ArchiveStats> {
+    let mut stats = ArchiveStats::default();
+    let mut props = BTreeMap::new();
+    let mut tar = tar::Builder::new(out);
+
+    let root_props = dead_props(fs, root).await;
+    if !root_props.is_empty() {
+        props.insert(String::new(), root_props);
+    }
+
+    let entries = walk(fs, root)
+        .await
+        .map_err(|e| anyhow!("can't list mount: {:?}", e))?;
+    for (path, meta) in entries {
+        let rel = relative(root, &path);
+        debug!(path = %rel);
+        let mut header = tar::Header::new_gnu();
+        header.set_mtime(unix_time(meta.modified()));
+        if meta.is_dir() {
+            header.set_entry_type(tar::EntryType::Directory);
+            header.set_mode(0o755);
+            header.set_size(0);
+            tar.append_data(&mut header, &rel, std::io::empty())?;
+            stats.dirs += 1;
+        } else {
+            header.set_mode(0o644);
+            header.set_size(meta.len());
+            let file = fs
+                .open(&path, open_opts::read())
+                .await
+                .map_err(|e| anyhow!("can't open {}: {:?}", rel, e))?;
+            let reader = FileReader {
+                file,
+                buf: Bytes::new(),
+                handle: Handle::current(),
+            };
+            tar.append_data(&mut header, &rel, reader)?;
+            stats.files += 1;
+        }
+        let p = dead_props(fs, &path).await;
+        if !p.is_empty() {
+            props.insert(rel, p);
+        }
+    }
+
+    stats.props = props.values().map(|p| p.len()).sum();
+    let data = serde_yaml::to_vec(&props)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_mode(0o644);
+    header.set_size(data.len() as u64);
+    tar.append_data(&mut header, PROPS_ENTRY, data.as_slice())?;
+    tar.finish()?;
+    Ok(stats)
+}
+
+fn child(root: &DavPath, rel: &str, dir: bool) -> Result<DavPath> {
+    let mut url = root.as_url_string();
+    if !url.ends_with('/') {
+        url.push('/');
+    }
+    url.push_str(&percent_encode(rel.trim_matches('/').as_bytes(), ENC).to_string());
+    if dir && !url.ends_with('/') {
+        url.push('/');
+    }
+    DavPath::new(&url).map_err(|_| anyhow!("invalid path in archive: {}", rel))
+}
+
+/// Restores archive written by `write_archive` below `root`. Existing files
+/// are overwritten.
+pub async fn read_archive<R: Read>(
+    fs: &dyn DavFileSystem,
+    root: &DavPath,
+    input: R,
+) -> Result<ArchiveStats> {
+    let mut stats = ArchiveStats::default();
+    let mut archive = tar::Archive::new(input);
+    let mut props: BTreeMap<String, Vec<ArchivedProp>> = BTreeMap::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let rel = entry.path()?.to_string_lossy().into_owned();
+        if rel == PROPS_ENTRY {
+            let mut data = vec![];
+            entry.read_to_end(&mut data)?;
+            props = serde_yaml::from_slice(&data)?;
+            continue;
+        }
+        let is_dir = entry.header().entry_type().is_dir();
+        let path = child(root, &rel, is_dir)?;
+        debug!(path = %rel);
+        if is_dir {
+            match fs.create_dir(&path).await {
+                Ok(()) | Err(FsError::Exists) => {}
+                Err(e) => return Err(anyhow!("can't create {}: {:?}", rel, e)),
+            }
+            stats.dirs += 1;
+            continue;
+        }
+        let mut opts = open_opts::write();
+        opts.create = true;
+        opts.truncate = true;
+        let mut file = fs
+            .open(&path, opts)
+            .await
+            .map_err(|e| anyhow!("can't create {}: {:?}", rel, e))?;
+        let mut buf = vec![0u8; CHUNK];
+        loop {
+            let n = entry.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            file.write_bytes(Bytes::copy_from_slice(&buf[..n]))
+                .await
+                .map_err(|e| anyhow!("can't write {}: {:?}", rel, e))?;
+        }
+        file.flush()
+            .await
+            .map_err(|e| anyhow!("can't write {}: {:?}", rel, e))?;
+        stats.files += 1;
+    }
+
+    for (rel, p) in props {
+        let meta = fs.metadata(&child(root, &rel, false)?).await;
+        let is_dir = meta.map(|m| m.is_dir()).unwrap_or(false);
+        let path = child(root, &rel, is_dir)?;
+        let p: Vec<_> = p
+            .into_iter()
+            .filter(|p| !internal(p.namespace.as_deref()))
+            .collect();
+        stats.props += p.len();
+        let patch = p.into_iter().map(|p| (true, p.into())).collect();
+        fs.patch_props(&path, patch)
+            .await
+            .map_err(|e| anyhow!("can't restore props of {}: {:?}", rel, e))?;
+    }
+    Ok(stats)
+}
+
+fn is_zstd(path: &Path) -> bool {
+    path.extension().map(|e| e == "zst").unwrap_or(false)
+}
+
+fn mount_root(mount: &str) -> Result<DavPath> {
+    DavPath::new(&format!("{}/", mount.trim_end_matches('/')))
+        .map_err(|_| anyhow!("invalid mount path: {}", mount))
+}
+
+/// Exports mount to tar archive, compressed with zstd if file name ends with `.zst`.
+pub async fn export(config: Configuration, mount: &str, output: &Path) -> Result<ArchiveStats> {
+    let (fs, props) = build_mount(config, mount).await?;
+    let root = mount_root(mount)?;
+    let file = File::create(output)?;
+    let stats = if is_zstd(output) {
+        let enc = zstd::stream::write::Encoder::new(file, 3)?.auto_finish();
+        write_archive(fs.as_ref(), &root, enc).await?
+    } else {
+        write_archive(fs.as_ref(), &root, file).await?
+    };
+    props
+        .flush()
+        .await
+        .map_err(|e| anyhow!("can't flush prop storage: {:?}", e))?;
+    Ok(stats)
+}
+
+/// Imports archive created by `export` into mount.
+pub async fn import(config: Configuration, mount: &str, input: &Path) -> Result<ArchiveStats> {
+    let (fs, props) = build_mount(config, mount).await?;
+    let root = mount_root(mount)?;
+    let file = File::open(input)?;
+    let stats = if is_zstd(input) {
+        read_archive(fs.as_ref(), &root, zstd::stream::read::Decoder::new(file)?).await?
+    } else {
+        read_archive(fs.as_ref(), &root, file).await?
+    };
+    props
+        .flush()
+        .await
+        .map_err(|e| anyhow!("can't flush prop storage: {:?}", e))?;
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        aggregate::AggregateBuilder, backend::prop_storages::mem::Memory, test_utils::put,
+    };
+    use webdav_handler::memfs::MemFs;
+
+    fn path(s: &str) -> DavPath {
+        DavPath::new(s).unwrap()
+    }
+
+    fn prop() -> DavProp {
+        DavProp {
+            name: "color".into(),
+            namespace: Some("urn:test".into()),
+            prefix: None,
+            xml: Some(b"<color xmlns=\"urn:test\">red</color>".to_vec()),
+        }
+    }
+
+    async fn read(fs: &dyn DavFileSystem, p: &str) -> Vec<u8> {
+        let mut f = fs.open(&path(p), open_opts::read()).await.unwrap();
+        f.read_bytes(1024).await.unwrap().to_vec()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn round_trip() -> anyhow::Result<()> {
+        let src = AggregateBuilder::new()
+            .set_props_storage(Memory::new())
+            .add_route(("/m", MemFs::new()))
+            .build()?;
+        src.create_dir(&path("/m/docs/")).await?;
+        put(src.as_ref(), "/m/docs/a%20b.txt", b"hello").await?;
+        src.patch_props(&path("/m/docs/a%20b.txt"), vec![(true, prop())])
+            .await?;
+        src.patch_props(&path("/m/docs/"), vec![(true, prop())])
+            .await?;
+
+        let mut archive = vec![];
+        let stats = write_archive(src.as_ref(), &path("/m/"), &mut archive).await?;
+        assert_eq!(
+            stats,
+            ArchiveStats {
+                dirs: 1,
+                files: 1,
+                props: 2
+            }
+        );
+
+        let dst = AggregateBuilder::new()
+            .set_props_storage(Memory::new())
+            .add_route(("/n", MemFs::new()))
+            .build()?;
+        let stats = read_archive(dst.as_ref(), &path("/n/"), archive.as_slice()).await?;
+        assert_eq!(stats.files, 1);
+        assert_eq!(read(dst.as_ref(), "/n/docs/a%20b.txt").await, b"hello");
+        let restored = dst.get_prop(&path("/n/docs/a%20b.txt"), prop()).await?;
+        assert_eq!(Some(restored), prop().xml);
+        assert!(dst.get_prop(&path("/n/docs/"), prop()).await.is_ok());
+        Ok(())
+    }
+}
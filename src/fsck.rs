@@ -0,0 +1,23 @@
+use crate::{
+    backend::{
+        content_type::ContentTypes,
+        s3_backend::{Issue, S3Backend},
+    },
+    configuration::{Configuration, Filesystem},
+};
+use anyhow::{anyhow, Result};
+
+/// Checks bucket of s3 mount for orphaned or missing directory markers and
+/// props objects. With `repair` fixable issues are fixed.
+pub async fn fsck(config: Configuration, mount: &str, repair: bool) -> Result<Vec<Issue>> {
+    let fs = config
+        .filesystems
+        .into_iter()
+        .find(|f| f.mount_path == mount)
+        .ok_or_else(|| anyhow!("mount not found: {}", mount))?;
+    if !matches!(fs.fs, Filesystem::S3 { .. }) {
+        return Err(anyhow!("fsck supports only s3 mounts"));
+    }
+    let backend = S3Backend::new_unboxed(fs.fs, ContentTypes::new(config.content_types)).await?;
+    backend.fsck(repair).await
+}
@@ -1,5 +1,15 @@
 pub mod aggregate;
+pub mod application;
+pub mod backend;
 pub mod configuration;
+pub mod events;
+pub mod export;
+pub mod fsck;
+pub mod migrate;
+#[cfg(feature = "mock-s3")]
+pub mod mock_s3;
 pub mod repository;
-pub mod backend;
-pub mod application;
\ No newline at end of file
+pub mod snapshot;
+#[cfg(test)]
+mod test_utils;
+pub mod verify;
@@ -1,14 +1,20 @@
-use clap::{App, Arg};
+use clap::{App, Arg, SubCommand};
 use webdav_ss::{
     application::Application,
     configuration::{setup_tracing, Configuration},
+    events::audit::{query, AuditFilter},
+    export::{export, import},
+    fsck::fsck,
+    migrate::migrate,
+    snapshot::snapshots,
+    verify::verify,
 };
 
 #[tokio::main]
 async fn main() {
     setup_tracing();
 
-    let matches = App::new("webdav_ss")
+    let app = App::new("webdav_ss")
         .version("0.1")
         .author("Igor Gilmutdinov <bladoff@gmail.com>")
         .arg(
@@ -19,11 +25,353 @@ async fn main() {
                 .help("sets custom config file")
                 .takes_value(true),
         )
-        .get_matches();
+        .subcommand(
+            SubCommand::with_name("verify")
+                .about("verifies stored files against their checksums")
+                .arg(
+                    Arg::with_name("mount")
+                        .help("mount path to verify")
+                        .required(true)
+                        .index(1),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("fsck")
+                .about("checks s3 mount for inconsistent directory markers and props objects")
+                .arg(
+                    Arg::with_name("mount")
+                        .help("mount path to check")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("repair")
+                        .long("repair")
+                        .help("fixes issues which can be fixed automatically"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("writes snapshot of mount files and properties to tar archive")
+                .arg(
+                    Arg::with_name("mount")
+                        .help("mount path to export")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .short("o")
+                        .value_name("FILE")
+                        .help("archive path, compressed with zstd if it ends with .zst")
+                        .required(true)
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("import")
+                .about("restores mount files and properties from tar archive")
+                .arg(
+                    Arg::with_name("mount")
+                        .help("mount path to import into")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("input")
+                        .long("input")
+                        .short("i")
+                        .value_name("FILE")
+                        .help("archive created by export")
+                        .required(true)
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("snapshot")
+                .about("mount snapshots management")
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about("lists snapshots, oldest first")
+                        .arg(
+                            Arg::with_name("mount")
+                                .help("mount path")
+                                .required(true)
+                                .index(1),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("create")
+                        .about("takes snapshot now")
+                        .arg(
+                            Arg::with_name("mount")
+                                .help("mount path")
+                                .required(true)
+                                .index(1),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("restore")
+                        .about("replaces mount content with snapshot, server should be stopped")
+                        .arg(
+                            Arg::with_name("mount")
+                                .help("mount path")
+                                .required(true)
+                                .index(1),
+                        )
+                        .arg(
+                            Arg::with_name("name")
+                                .help("snapshot name")
+                                .required(true)
+                                .index(2),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("audit")
+                .about("prints changes recorded in audit log, oldest first")
+                .arg(
+                    Arg::with_name("user")
+                        .long("user")
+                        .value_name("NAME")
+                        .help("only changes made by user")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("path")
+                        .long("path")
+                        .value_name("PREFIX")
+                        .help("only changes of paths starting with prefix")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("since")
+                        .long("since")
+                        .value_name("TIMESTAMP")
+                        .help("only changes made since unix time in seconds")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("props")
+                .about("prop storage maintenance")
+                .subcommand(
+                    SubCommand::with_name("migrate")
+                        .about("copies all properties between prop storages")
+                        .arg(
+                            Arg::with_name("from")
+                                .long("from")
+                                .value_name("STORAGE")
+                                .help("source storage, e.g. yaml:/path/to/props.yml")
+                                .takes_value(true)
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("to")
+                                .long("to")
+                                .value_name("STORAGE")
+                                .help("destination storage, e.g. sqlite:/path/to/props.db")
+                                .takes_value(true)
+                                .required(true),
+                        ),
+                ),
+        );
+    #[cfg(feature = "mock-s3")]
+    let app = app.subcommand(
+        SubCommand::with_name("mock-s3")
+            .about("serves in-memory S3 for development, S3 mounts can use it instead of minio")
+            .arg(
+                Arg::with_name("host")
+                    .long("host")
+                    .value_name("HOST")
+                    .default_value("127.0.0.1")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("port")
+                    .long("port")
+                    .value_name("PORT")
+                    .default_value("9000")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("bucket")
+                    .long("bucket")
+                    .value_name("NAME")
+                    .help("creates bucket at start, may be repeated")
+                    .takes_value(true)
+                    .multiple(true),
+            ),
+    );
+    let matches = app.get_matches();
+
+    #[cfg(feature = "mock-s3")]
+    {
+        if let ("mock-s3", Some(m)) = matches.subcommand() {
+            let s3 = webdav_ss::mock_s3::MockS3::new();
+            for bucket in m.values_of("bucket").into_iter().flatten() {
+                s3.create_bucket(bucket);
+            }
+            let addr = format!(
+                "{}:{}",
+                m.value_of("host").unwrap(),
+                m.value_of("port").unwrap()
+            )
+            .parse()
+            .expect("invalid listen address");
+            s3.serve(addr).await.expect("can't serve mock S3");
+            return;
+        }
+    }
+
+    if let ("props", Some(m)) = matches.subcommand() {
+        if let ("migrate", Some(m)) = m.subcommand() {
+            let count = migrate(m.value_of("from").unwrap(), m.value_of("to").unwrap())
+                .await
+                .expect("can't migrate props");
+            println!("migrated {} properties", count);
+        }
+        return;
+    }
 
     let config = matches.value_of("config").unwrap_or("webdav_ss.yml");
 
     let config = Configuration::new(config).expect("can't get configuration");
-    let app = Application::build(config).await;
+
+    if let ("verify", Some(m)) = matches.subcommand() {
+        let mount = m.value_of("mount").unwrap();
+        let report = verify(config, mount).await.expect("can't verify mount");
+        for p in &report.corrupted {
+            println!("corrupted: {}", p);
+        }
+        for p in &report.missing {
+            println!("missing: {}", p);
+        }
+        for p in &report.unchecked {
+            println!("no checksum: {}", p);
+        }
+        println!(
+            "verified: {}, corrupted: {}, missing: {}, no checksum: {}",
+            report.verified.len(),
+            report.corrupted.len(),
+            report.missing.len(),
+            report.unchecked.len()
+        );
+        if !report.is_ok() {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let ("fsck", Some(m)) = matches.subcommand() {
+        let repair = m.is_present("repair");
+        let issues = fsck(config, m.value_of("mount").unwrap(), repair)
+            .await
+            .expect("can't check mount");
+        for i in &issues {
+            let state = if repair && i.repairable() {
+                "repaired"
+            } else {
+                "found"
+            };
+            println!("{}: {}", state, i);
+        }
+        println!("issues: {}", issues.len());
+        if !repair && !issues.is_empty() {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let ("export", Some(m)) = matches.subcommand() {
+        let output = std::path::Path::new(m.value_of("output").unwrap());
+        let stats = export(config, m.value_of("mount").unwrap(), output)
+            .await
+            .expect("can't export mount");
+        println!(
+            "exported dirs: {}, files: {}, properties: {}",
+            stats.dirs, stats.files, stats.props
+        );
+        return;
+    }
+
+    if let ("import", Some(m)) = matches.subcommand() {
+        let input = std::path::Path::new(m.value_of("input").unwrap());
+        let stats = import(config, m.value_of("mount").unwrap(), input)
+            .await
+            .expect("can't import mount");
+        println!(
+            "imported dirs: {}, files: {}, properties: {}",
+            stats.dirs, stats.files, stats.props
+        );
+        return;
+    }
+
+    if let ("snapshot", Some(m)) = matches.subcommand() {
+        let (cmd, args) = match m.subcommand() {
+            (cmd, Some(args)) => (cmd, args),
+            _ => {
+                eprintln!("{}", m.usage());
+                std::process::exit(2);
+            }
+        };
+        let s = snapshots(config, args.value_of("mount").unwrap())
+            .await
+            .expect("can't open snapshots");
+        match cmd {
+            "list" => {
+                for name in s.list().await.expect("can't list snapshots") {
+                    println!("{}", name);
+                }
+            }
+            "create" => {
+                let name = s.create().await.expect("can't create snapshot");
+                println!("created snapshot {}", name);
+            }
+            "restore" => {
+                let name = args.value_of("name").unwrap();
+                s.restore(name).await.expect("can't restore snapshot");
+                println!("restored snapshot {}", name);
+            }
+            _ => unreachable!(),
+        }
+        return;
+    }
+
+    if let ("audit", Some(m)) = matches.subcommand() {
+        let path = config
+            .events
+            .audit
+            .expect("audit log is not configured, set events.audit");
+        let filter = AuditFilter {
+            user: m.value_of("user").map(String::from),
+            path: m.value_of("path").map(String::from),
+            since: m
+                .value_of("since")
+                .map(|s| s.parse().expect("invalid --since timestamp")),
+        };
+        let events = query(std::path::Path::new(&path), &filter).expect("can't read audit log");
+        for e in &events {
+            let dest = e
+                .destination
+                .as_ref()
+                .map(|d| format!(" -> {}", d))
+                .unwrap_or_default();
+            println!(
+                "{} {} {} {}{}",
+                e.timestamp,
+                e.user.as_deref().unwrap_or("-"),
+                e.method,
+                e.path,
+                dest
+            );
+        }
+        return;
+    }
+
+    let app = Application::build(config)
+        .await
+        .expect("can't build application");
     app.run().await;
 }
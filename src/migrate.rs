@@ -0,0 +1,135 @@
+use crate::{
+    application::get_props_storage_by_conf,
+    backend::prop_storages::PropStorage,
+    configuration::{default_dump_interval, PropsStorage},
+};
+use anyhow::{anyhow, Result};
+use tracing::debug;
+
+/// Parses prop storage given as `type:path`, e.g. `yaml:/var/lib/props.yml`,
+/// `sqlite:/var/lib/props.db` or `mem`.
+pub fn parse_storage(s: &str) -> Result<PropsStorage> {
+    let (kind, path) = match s.find(':') {
+        Some(i) => (&s[..i], Some(&s[i + 1..])),
+        None => (s, None),
+    };
+    match (kind, path) {
+        ("yaml", Some(path)) if !path.is_empty() => Ok(PropsStorage::Yaml {
+            path: path.into(),
+            dump_interval: default_dump_interval(),
+        }),
+        ("sqlite", Some(path)) if !path.is_empty() => {
+            Ok(PropsStorage::Sqlite { path: path.into() })
+        }
+        ("mem", None) => Ok(PropsStorage::Mem),
+        _ => Err(anyhow!("unsupported prop storage: {}", s)),
+    }
+}
+
+/// Copies all properties from one storage to another. Returns number of copied properties.
+pub async fn migrate_props(from: &dyn PropStorage, to: &dyn PropStorage) -> Result<usize> {
+    let all = from
+        .get_all()
+        .await
+        .map_err(|e| anyhow!("can't read source storage: {:?}", e))?;
+    let mut count = 0;
+    for (path, props) in all {
+        for prop in props {
+            debug!(path = %path, prop = ?prop.name);
+            to.patch_prop(&path, (true, prop))
+                .await
+                .map_err(|e| anyhow!("can't write prop for {}: {:?}", path, e))?;
+            count += 1;
+        }
+    }
+    to.flush()
+        .await
+        .map_err(|e| anyhow!("can't flush destination storage: {:?}", e))?;
+    Ok(count)
+}
+
+pub async fn migrate(from: &str, to: &str) -> Result<usize> {
+    let from = parse_storage(from)?;
+    let to = parse_storage(to)?;
+    if let PropsStorage::Mem = to {
+        return Err(anyhow!("mem storage is lost on exit, can't migrate to it"));
+    }
+    let from = get_props_storage_by_conf(from);
+    let to = get_props_storage_by_conf(to);
+    migrate_props(from.as_ref(), to.as_ref()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::prop_storages::{mem::Memory, sqlite::Sqlite};
+    use webdav_handler::fs::DavProp;
+
+    #[test]
+    fn storage_spec() {
+        assert!(matches!(
+            parse_storage("yaml:/tmp/props.yml"),
+            Ok(PropsStorage::Yaml { path, .. }) if path == "/tmp/props.yml"
+        ));
+        assert!(matches!(parse_storage("mem"), Ok(PropsStorage::Mem)));
+        assert!(matches!(
+            parse_storage("sqlite:/tmp/props.db"),
+            Ok(PropsStorage::Sqlite { path }) if path == "/tmp/props.db"
+        ));
+        assert!(parse_storage("yaml").is_err());
+        assert!(parse_storage("redis:/tmp").is_err());
+    }
+
+    #[tokio::test]
+    async fn copy_all() -> anyhow::Result<()> {
+        let from = Memory::new();
+        let to = Memory::new();
+        let prop = DavProp {
+            name: "name1".into(),
+            namespace: Some("namespace1".into()),
+            prefix: None,
+            xml: Some([1, 2, 3].into()),
+        };
+        from.patch_prop(&"/fs/a".into(), (true, prop.clone()))
+            .await?;
+        from.patch_prop(&"/fs/dir/".into(), (true, prop.clone()))
+            .await?;
+
+        assert_eq!(migrate_props(from.as_ref(), to.as_ref()).await?, 2);
+        assert_eq!(
+            to.get_prop(&"/fs/a".into(), prop.clone()).await?,
+            vec![1, 2, 3]
+        );
+        assert!(to.have_props(&"/fs/dir/".into()).await);
+        Ok(())
+    }
+    #[tokio::test]
+    async fn yaml_to_sqlite() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir();
+        let yaml = dir.join(format!("webdav_ss_migrate_{}.yml", std::process::id()));
+        let db = dir.join(format!("webdav_ss_migrate_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&db);
+        std::fs::write(
+            &yaml,
+            "fs/a: [{name: color, namespace: \"urn:test\", value: cmVk}]",
+        )?;
+
+        let (from, to) = (
+            format!("yaml:{}", yaml.display()),
+            format!("sqlite:{}", db.display()),
+        );
+        assert!(migrate(&from, "mem").await.is_err());
+        assert_eq!(migrate(&from, &to).await?, 1);
+        let to = Sqlite::new(&db)?;
+        let prop = DavProp {
+            name: "color".into(),
+            namespace: Some("urn:test".into()),
+            prefix: None,
+            xml: None,
+        };
+        assert_eq!(to.get_prop(&"/fs/a".into(), prop).await?, b"red");
+        std::fs::remove_file(&yaml)?;
+        std::fs::remove_file(&db)?;
+        Ok(())
+    }
+}
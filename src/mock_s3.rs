@@ -0,0 +1,646 @@
+use crate::backend::xml::escape;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use hyper::{
+    header::{CONTENT_LENGTH, CONTENT_TYPE, ETAG, HOST, LAST_MODIFIED, RANGE},
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use percent_encoding::percent_decode_str;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    convert::Infallible,
+    future::Future,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::SystemTime,
+};
+use tracing::{debug, info};
+
+/// Keys listed by one ListObjects request if it does not ask for less.
+const MAX_KEYS: usize = 1000;
+
+#[derive(Clone)]
+struct Object {
+    data: Bytes,
+    etag: String,
+    modified: SystemTime,
+}
+
+impl Object {
+    fn new(data: Bytes) -> Object {
+        let hash = Sha256::digest(&data);
+        Object {
+            etag: format!("\"{}\"", hex(&hash[..16])),
+            data,
+            modified: SystemTime::now(),
+        }
+    }
+}
+
+struct Upload {
+    bucket: String,
+    key: String,
+    parts: BTreeMap<u32, Bytes>,
+}
+
+#[derive(Default)]
+struct State {
+    buckets: BTreeMap<String, BTreeMap<String, Object>>,
+    uploads: HashMap<String, Upload>,
+}
+
+/// In-memory S3 for development and tests, so S3 mounts run without docker.
+/// It answers requests S3 backend sends: objects with ranges and copies,
+/// ListObjects v1 and v2, multipart uploads and bucket creation. Both path
+/// and virtual host style addressing work, signatures are not checked and
+/// nothing is kept after it stops.
+#[derive(Default)]
+pub struct MockS3 {
+    state: Mutex<State>,
+    next_upload: AtomicU64,
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode(s: &str) -> String {
+    percent_decode_str(&s.replace('+', " "))
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+fn query(req: &Request<Body>) -> HashMap<String, String> {
+    req.uri()
+        .query()
+        .unwrap_or("")
+        .split('&')
+        .filter(|kv| !kv.is_empty())
+        .map(|kv| {
+            let mut it = kv.splitn(2, '=');
+            let k = decode(it.next().unwrap_or(""));
+            (k, decode(it.next().unwrap_or("")))
+        })
+        .collect()
+}
+
+fn http_date(t: SystemTime) -> String {
+    DateTime::<Utc>::from(t)
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string()
+}
+
+fn iso_date(t: SystemTime) -> String {
+    DateTime::<Utc>::from(t)
+        .format("%Y-%m-%dT%H:%M:%S%.3fZ")
+        .to_string()
+}
+
+fn xml(status: StatusCode, body: String) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/xml")
+        .body(Body::from(format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{}",
+            body
+        )))
+        .unwrap()
+}
+
+fn error(status: StatusCode, code: &str) -> Response<Body> {
+    xml(
+        status,
+        format!(
+            "<Error><Code>{}</Code><Message>{}</Message></Error>",
+            code, code
+        ),
+    )
+}
+
+fn empty(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// Byte range of `bytes=start-end` header, end is inclusive.
+fn range(header: &str, len: u64) -> Option<(u64, u64)> {
+    let spec = header.strip_prefix("bytes=")?;
+    let mut it = spec.splitn(2, '-');
+    let (start, end) = (it.next()?, it.next()?);
+    if start.is_empty() {
+        let suffix: u64 = end.parse().ok()?;
+        return Some((len.saturating_sub(suffix), len.checked_sub(1)?));
+    }
+    let start: u64 = start.parse().ok()?;
+    let end = match end {
+        "" => len.checked_sub(1)?,
+        e => e.parse::<u64>().ok()?.min(len.checked_sub(1)?),
+    };
+    if start > end {
+        return None;
+    }
+    Some((start, end))
+}
+
+/// Listing of `objects` as ListObjectsV2 gives it: keys after `marker`
+/// starting with `prefix`, grouped into common prefixes by `delimiter`.
+/// Returns keys, common prefixes and marker of the next page.
+fn list<'a>(
+    objects: &'a BTreeMap<String, Object>,
+    prefix: &str,
+    delimiter: Option<&str>,
+    marker: Option<&str>,
+    max_keys: usize,
+) -> (Vec<(&'a String, &'a Object)>, Vec<String>, Option<String>) {
+    let mut keys = vec![];
+    let mut prefixes = BTreeSet::new();
+    let mut last = None;
+    for (key, obj) in objects.range(prefix.to_string()..) {
+        if !key.starts_with(prefix) {
+            break;
+        }
+        if let Some(m) = marker {
+            // marker which is common prefix stands for all keys in it
+            let in_marker = delimiter.map_or(false, |d| m.ends_with(d) && key.starts_with(m));
+            if key.as_str() <= m || in_marker {
+                continue;
+            }
+        }
+        let group = delimiter.and_then(|d| {
+            let rest = &key[prefix.len()..];
+            rest.find(d)
+                .map(|i| format!("{}{}", prefix, &rest[..i + d.len()]))
+        });
+        if let Some(ref g) = group {
+            if prefixes.contains(g) {
+                continue;
+            }
+        }
+        if keys.len() + prefixes.len() == max_keys {
+            return (keys, prefixes.into_iter().collect(), last);
+        }
+        match group {
+            Some(g) => {
+                last = Some(g.clone());
+                prefixes.insert(g);
+            }
+            None => {
+                last = Some(key.clone());
+                keys.push((key, obj));
+            }
+        }
+    }
+    (keys, prefixes.into_iter().collect(), None)
+}
+
+impl MockS3 {
+    pub fn new() -> Arc<MockS3> {
+        Arc::new(MockS3::default())
+    }
+
+    pub fn create_bucket(&self, name: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.buckets.entry(name.to_string()).or_default();
+    }
+
+    /// Serves requests on `addr` until process stops.
+    pub async fn serve(self: Arc<Self>, addr: SocketAddr) -> hyper::Result<()> {
+        let (addr, server) = self.bind(addr)?;
+        info!("mock S3 listening on {}", addr);
+        server.await
+    }
+
+    /// Binds `addr`, which may have port 0, and returns bound address with
+    /// future serving requests.
+    pub fn bind(
+        self: Arc<Self>,
+        addr: SocketAddr,
+    ) -> hyper::Result<(SocketAddr, impl Future<Output = hyper::Result<()>>)> {
+        let make_svc = make_service_fn(move |_| {
+            let s3 = self.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let s3 = s3.clone();
+                    async move { Ok::<_, Infallible>(s3.handle(req).await) }
+                }))
+            }
+        });
+        let server = Server::try_bind(&addr)?.serve(make_svc);
+        Ok((server.local_addr(), server))
+    }
+
+    /// Bucket and key of request, key is empty for bucket requests.
+    fn target(req: &Request<Body>) -> (String, String) {
+        let path = percent_decode_str(req.uri().path()).decode_utf8_lossy();
+        let path = path.trim_start_matches('/');
+        let host = req
+            .headers()
+            .get(HOST)
+            .and_then(|h| h.to_str().ok())
+            .unwrap_or("");
+        let host = host.split(':').next().unwrap_or("");
+        // virtual host style, <bucket>.<endpoint>
+        let label = host.split('.').next().unwrap_or("");
+        if host.contains('.') && label.parse::<u8>().is_err() {
+            return (label.to_string(), path.to_string());
+        }
+        let mut it = path.splitn(2, '/');
+        let bucket = it.next().unwrap_or("").to_string();
+        (bucket, it.next().unwrap_or("").to_string())
+    }
+
+    pub async fn handle(&self, req: Request<Body>) -> Response<Body> {
+        let (bucket, key) = MockS3::target(&req);
+        let query = query(&req);
+        debug!(method = %req.method(), bucket = %bucket, key = %key, query = ?query);
+        if bucket.is_empty() {
+            return error(StatusCode::BAD_REQUEST, "InvalidBucketName");
+        }
+        if key.is_empty() {
+            return self.bucket(req.method(), &bucket, &query);
+        }
+        if !self.state.lock().unwrap().buckets.contains_key(&bucket) {
+            return error(StatusCode::NOT_FOUND, "NoSuchBucket");
+        }
+        let method = req.method().clone();
+        match method {
+            Method::GET | Method::HEAD => self.get(&req, &bucket, &key),
+            Method::PUT => self.put(req, &bucket, &key, &query).await,
+            Method::POST => self.post(req, &bucket, &key, &query).await,
+            Method::DELETE => {
+                let mut state = self.state.lock().unwrap();
+                match query.get("uploadId") {
+                    Some(id) => {
+                        state.uploads.remove(id);
+                    }
+                    None => {
+                        state.buckets.get_mut(&bucket).unwrap().remove(&key);
+                    }
+                }
+                empty(StatusCode::NO_CONTENT)
+            }
+            _ => error(StatusCode::METHOD_NOT_ALLOWED, "MethodNotAllowed"),
+        }
+    }
+
+    fn bucket(
+        &self,
+        method: &Method,
+        bucket: &str,
+        query: &HashMap<String, String>,
+    ) -> Response<Body> {
+        let mut state = self.state.lock().unwrap();
+        if *method == Method::PUT {
+            if state.buckets.contains_key(bucket) {
+                return error(StatusCode::CONFLICT, "BucketAlreadyOwnedByYou");
+            }
+            state.buckets.insert(bucket.to_string(), BTreeMap::new());
+            return empty(StatusCode::OK);
+        }
+        if *method == Method::DELETE {
+            return match state.buckets.get(bucket).map(|o| o.is_empty()) {
+                Some(true) => {
+                    state.buckets.remove(bucket);
+                    empty(StatusCode::NO_CONTENT)
+                }
+                Some(false) => error(StatusCode::CONFLICT, "BucketNotEmpty"),
+                None => error(StatusCode::NOT_FOUND, "NoSuchBucket"),
+            };
+        }
+        let objects = match state.buckets.get(bucket) {
+            Some(o) => o,
+            None => return error(StatusCode::NOT_FOUND, "NoSuchBucket"),
+        };
+        match *method {
+            Method::HEAD => empty(StatusCode::OK),
+            Method::GET if query.contains_key("location") => xml(
+                StatusCode::OK,
+                "<LocationConstraint xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\"/>".into(),
+            ),
+            Method::GET => {
+                let prefix = query.get("prefix").map_or("", |p| p.as_str());
+                let delimiter = query.get("delimiter").filter(|d| !d.is_empty());
+                let v2 = query.get("list-type").map_or(false, |t| t == "2");
+                let marker = ["continuation-token", "start-after", "marker"]
+                    .iter()
+                    .find_map(|k| query.get(*k))
+                    .filter(|m| !m.is_empty());
+                let max_keys = query
+                    .get("max-keys")
+                    .and_then(|m| m.parse().ok())
+                    .unwrap_or(MAX_KEYS)
+                    .min(MAX_KEYS);
+                let (keys, prefixes, next) = list(
+                    objects,
+                    prefix,
+                    delimiter.map(|d| d.as_str()),
+                    marker.map(|m| m.as_str()),
+                    max_keys,
+                );
+                let mut body = format!(
+                    "<ListBucketResult xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\"><Name>{}</Name><Prefix>{}</Prefix><MaxKeys>{}</MaxKeys><KeyCount>{}</KeyCount><IsTruncated>{}</IsTruncated>",
+                    escape(bucket),
+                    escape(prefix),
+                    max_keys,
+                    keys.len() + prefixes.len(),
+                    next.is_some()
+                );
+                if let Some(d) = delimiter {
+                    body.push_str(&format!("<Delimiter>{}</Delimiter>", escape(d)));
+                }
+                if let Some(n) = next {
+                    let tag = if v2 {
+                        "NextContinuationToken"
+                    } else {
+                        "NextMarker"
+                    };
+                    body.push_str(&format!("<{tag}>{}</{tag}>", escape(&n), tag = tag));
+                }
+                for (key, obj) in keys {
+                    body.push_str(&format!(
+                        "<Contents><Key>{}</Key><LastModified>{}</LastModified><ETag>{}</ETag><Size>{}</Size><StorageClass>STANDARD</StorageClass></Contents>",
+                        escape(key),
+                        iso_date(obj.modified),
+                        escape(&obj.etag),
+                        obj.data.len()
+                    ));
+                }
+                for p in prefixes {
+                    body.push_str(&format!(
+                        "<CommonPrefixes><Prefix>{}</Prefix></CommonPrefixes>",
+                        escape(&p)
+                    ));
+                }
+                body.push_str("</ListBucketResult>");
+                xml(StatusCode::OK, body)
+            }
+            _ => error(StatusCode::METHOD_NOT_ALLOWED, "MethodNotAllowed"),
+        }
+    }
+
+    fn get(&self, req: &Request<Body>, bucket: &str, key: &str) -> Response<Body> {
+        let obj = match self.state.lock().unwrap().buckets[bucket].get(key) {
+            Some(o) => o.clone(),
+            None if req.method() == Method::HEAD => return empty(StatusCode::NOT_FOUND),
+            None => return error(StatusCode::NOT_FOUND, "NoSuchKey"),
+        };
+        let len = obj.data.len() as u64;
+        let resp = Response::builder()
+            .header(ETAG, &obj.etag)
+            .header(LAST_MODIFIED, http_date(obj.modified))
+            .header(CONTENT_TYPE, "application/octet-stream")
+            .header("Accept-Ranges", "bytes");
+        let requested = req.headers().get(RANGE).and_then(|r| r.to_str().ok());
+        let (resp, data) = match requested {
+            Some(r) => match range(r, len) {
+                Some((start, end)) => (
+                    resp.status(StatusCode::PARTIAL_CONTENT)
+                        .header("Content-Range", format!("bytes {}-{}/{}", start, end, len)),
+                    obj.data.slice(start as usize..end as usize + 1),
+                ),
+                None => return error(StatusCode::RANGE_NOT_SATISFIABLE, "InvalidRange"),
+            },
+            None => (resp, obj.data),
+        };
+        let resp = resp.header(CONTENT_LENGTH, data.len());
+        if req.method() == Method::HEAD {
+            return resp.body(Body::empty()).unwrap();
+        }
+        resp.body(Body::from(data)).unwrap()
+    }
+
+    async fn put(
+        &self,
+        req: Request<Body>,
+        bucket: &str,
+        key: &str,
+        query: &HashMap<String, String>,
+    ) -> Response<Body> {
+        let source = req
+            .headers()
+            .get("x-amz-copy-source")
+            .and_then(|s| s.to_str().ok())
+            .map(decode);
+        if let Some(source) = source {
+            let mut state = self.state.lock().unwrap();
+            let source = source.trim_start_matches('/');
+            let mut it = source.splitn(2, '/');
+            let (from_bucket, from_key) = (it.next().unwrap_or(""), it.next().unwrap_or(""));
+            let obj = match state.buckets.get(from_bucket).and_then(|b| b.get(from_key)) {
+                Some(o) => Object::new(o.data.clone()),
+                None => return error(StatusCode::NOT_FOUND, "NoSuchKey"),
+            };
+            let body = format!(
+                "<CopyObjectResult><LastModified>{}</LastModified><ETag>{}</ETag></CopyObjectResult>",
+                iso_date(obj.modified),
+                escape(&obj.etag)
+            );
+            state
+                .buckets
+                .get_mut(bucket)
+                .unwrap()
+                .insert(key.to_string(), obj);
+            return xml(StatusCode::OK, body);
+        }
+        let data = match hyper::body::to_bytes(req.into_body()).await {
+            Ok(d) => d,
+            Err(_) => return error(StatusCode::BAD_REQUEST, "IncompleteBody"),
+        };
+        let mut state = self.state.lock().unwrap();
+        if let Some(id) = query.get("uploadId") {
+            let number = match query.get("partNumber").and_then(|n| n.parse().ok()) {
+                Some(n) => n,
+                None => return error(StatusCode::BAD_REQUEST, "InvalidArgument"),
+            };
+            let etag = Object::new(data.clone()).etag;
+            return match state.uploads.get_mut(id) {
+                Some(u) => {
+                    u.parts.insert(number, data);
+                    Response::builder()
+                        .header(ETAG, etag)
+                        .body(Body::empty())
+                        .unwrap()
+                }
+                None => error(StatusCode::NOT_FOUND, "NoSuchUpload"),
+            };
+        }
+        let obj = Object::new(data);
+        let etag = obj.etag.clone();
+        state
+            .buckets
+            .get_mut(bucket)
+            .unwrap()
+            .insert(key.to_string(), obj);
+        Response::builder()
+            .header(ETAG, etag)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    async fn post(
+        &self,
+        req: Request<Body>,
+        bucket: &str,
+        key: &str,
+        query: &HashMap<String, String>,
+    ) -> Response<Body> {
+        if query.contains_key("uploads") {
+            let id = format!("upload-{}", self.next_upload.fetch_add(1, Ordering::SeqCst));
+            let upload = Upload {
+                bucket: bucket.to_string(),
+                key: key.to_string(),
+                parts: BTreeMap::new(),
+            };
+            self.state
+                .lock()
+                .unwrap()
+                .uploads
+                .insert(id.clone(), upload);
+            return xml(
+                StatusCode::OK,
+                format!(
+                    "<InitiateMultipartUploadResult><Bucket>{}</Bucket><Key>{}</Key><UploadId>{}</UploadId></InitiateMultipartUploadResult>",
+                    escape(bucket),
+                    escape(key),
+                    id
+                ),
+            );
+        }
+        let id = match query.get("uploadId") {
+            Some(id) => id,
+            None => return error(StatusCode::BAD_REQUEST, "InvalidRequest"),
+        };
+        // part list of request is not checked, all uploaded parts are joined
+        let _ = hyper::body::to_bytes(req.into_body()).await;
+        let mut state = self.state.lock().unwrap();
+        let upload = match state.uploads.remove(id) {
+            Some(u) if u.bucket == bucket && u.key == key => u,
+            _ => return error(StatusCode::NOT_FOUND, "NoSuchUpload"),
+        };
+        let data = upload
+            .parts
+            .values()
+            .flat_map(|p| p.iter().copied())
+            .collect::<Vec<_>>();
+        let obj = Object::new(data.into());
+        let body = format!(
+            "<CompleteMultipartUploadResult><Bucket>{}</Bucket><Key>{}</Key><ETag>{}</ETag></CompleteMultipartUploadResult>",
+            escape(bucket),
+            escape(key),
+            escape(&obj.etag)
+        );
+        state
+            .buckets
+            .get_mut(bucket)
+            .unwrap()
+            .insert(key.to_string(), obj);
+        xml(StatusCode::OK, body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn req(method: &str, uri: &str, body: &'static str) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(HOST, "localhost:9000")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    async fn text(resp: Response<Body>) -> String {
+        let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn objects_and_listings() {
+        let s3 = MockS3::new();
+        s3.create_bucket("test");
+        for key in ["a.txt", "dir/b.txt", "dir/sub/c.txt"].iter() {
+            let resp = s3
+                .handle(req("PUT", &format!("/test/{}", key), "data"))
+                .await;
+            assert_eq!(resp.status(), StatusCode::OK);
+        }
+
+        let resp = s3
+            .handle(req(
+                "GET",
+                "/test?list-type=2&delimiter=%2F&prefix=dir%2F",
+                "",
+            ))
+            .await;
+        let body = text(resp).await;
+        assert!(body.contains("<Key>dir/b.txt</Key>"));
+        assert!(body.contains("<CommonPrefixes><Prefix>dir/sub/</Prefix></CommonPrefixes>"));
+        assert!(!body.contains("dir/sub/c.txt"));
+
+        let resp = s3
+            .handle(req("GET", "/test?list-type=2&max-keys=1", ""))
+            .await;
+        let body = text(resp).await;
+        assert!(body.contains("<IsTruncated>true</IsTruncated>"));
+        assert!(body.contains("<NextContinuationToken>a.txt</NextContinuationToken>"));
+
+        let mut get = req("GET", "/test/dir/b.txt", "");
+        get.headers_mut()
+            .insert(RANGE, "bytes=1-2".parse().unwrap());
+        let resp = s3.handle(get).await;
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(text(resp).await, "at");
+
+        let mut copy = req("PUT", "/test/copy.txt", "");
+        copy.headers_mut()
+            .insert("x-amz-copy-source", "/test/a.txt".parse().unwrap());
+        assert_eq!(s3.handle(copy).await.status(), StatusCode::OK);
+        let resp = s3.handle(req("DELETE", "/test/a.txt", "")).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        let resp = s3.handle(req("HEAD", "/test/a.txt", "")).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            text(s3.handle(req("GET", "/test/copy.txt", "")).await).await,
+            "data"
+        );
+    }
+
+    #[tokio::test]
+    async fn multipart_upload() {
+        let s3 = MockS3::new();
+        let resp = s3.handle(req("PUT", "/test", "")).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = s3.handle(req("POST", "/test/big?uploads", "")).await;
+        let body = text(resp).await;
+        assert!(body.contains("<UploadId>upload-0</UploadId>"));
+        for (n, part) in [(2, "world"), (1, "hello ")].iter() {
+            let uri = format!("/test/big?partNumber={}&uploadId=upload-0", n);
+            let resp = s3.handle(req("PUT", &uri, part)).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+        }
+        let resp = s3
+            .handle(req("POST", "/test/big?uploadId=upload-0", ""))
+            .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = text(s3.handle(req("GET", "/test/big", "")).await).await;
+        assert_eq!(body, "hello world");
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(range("bytes=0-3", 10), Some((0, 3)));
+        assert_eq!(range("bytes=5-", 10), Some((5, 9)));
+        assert_eq!(range("bytes=-4", 10), Some((6, 9)));
+        assert_eq!(range("bytes=8-20", 10), Some((8, 9)));
+        assert_eq!(range("bytes=11-12", 10), None);
+    }
+}
@@ -0,0 +1,16 @@
+use crate::{
+    application::get_snapshots_by_conf,
+    backend::{content_type::ContentTypes, snapshot::Snapshots},
+    configuration::Configuration,
+};
+use anyhow::{anyhow, Result};
+
+/// Snapshots of configured mount for manual management.
+pub async fn snapshots(config: Configuration, mount: &str) -> Result<Snapshots> {
+    let fs = config
+        .filesystems
+        .iter()
+        .find(|f| f.mount_path == mount)
+        .ok_or_else(|| anyhow!("mount not found: {}", mount))?;
+    get_snapshots_by_conf(fs, &ContentTypes::new(config.content_types.clone())).await
+}
@@ -0,0 +1,24 @@
+//! Fixtures shared by unit tests.
+
+use crate::backend::open_opts;
+use bytes::Bytes;
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{DavFileSystem, OpenOptions},
+};
+
+/// Options creating file or truncating existing one.
+pub(crate) fn write_opts() -> OpenOptions {
+    let mut opts = open_opts::write();
+    opts.create = true;
+    opts.truncate = true;
+    opts
+}
+
+/// Writes `data` to `path` of `fs`, replacing previous content.
+pub(crate) async fn put(fs: &dyn DavFileSystem, path: &str, data: &[u8]) -> anyhow::Result<()> {
+    let mut f = fs.open(&DavPath::new(path)?, write_opts()).await?;
+    f.write_bytes(Bytes::copy_from_slice(data)).await?;
+    f.flush().await?;
+    Ok(())
+}
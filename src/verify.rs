@@ -0,0 +1,108 @@
+use crate::backend::open_opts;
+use crate::{
+    application::build_mount,
+    backend::{
+        checksum::{checksum_prop, parse_checksum},
+        normalized_path::NormalizedPath,
+        walk::walk,
+    },
+    configuration::Configuration,
+};
+use anyhow::{anyhow, Result};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use tracing::debug;
+use webdav_handler::{
+    davpath::DavPath,
+    fs::{DavFileSystem, FsResult},
+};
+
+const READ_CHUNK: u64 = 1024 * 1024;
+
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    pub verified: Vec<String>,
+    pub corrupted: Vec<String>,
+    pub missing: Vec<String>,
+    pub unchecked: Vec<String>,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.corrupted.is_empty() && self.missing.is_empty()
+    }
+}
+
+async fn read_checksum(fs: &dyn DavFileSystem, path: &DavPath, len: u64) -> FsResult<String> {
+    let mut file = fs.open(path, open_opts::read()).await?;
+    let mut hasher = Sha256::new();
+    let mut left = len;
+    while left > 0 {
+        let chunk = file
+            .read_bytes(std::cmp::min(left, READ_CHUNK) as usize)
+            .await?;
+        if chunk.is_empty() {
+            break;
+        }
+        hasher.update(&chunk);
+        left = left.saturating_sub(chunk.len() as u64);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Re-reads every file of the mount and compares its content with checksum
+/// stored in prop storage at write time.
+pub async fn verify(config: Configuration, mount: &str) -> Result<VerifyReport> {
+    let (agg, props) = build_mount(config, mount).await?;
+
+    let root = DavPath::new(&format!("{}/", mount.trim_end_matches('/')))
+        .map_err(|_| anyhow!("invalid mount path: {}", mount))?;
+    let prop = checksum_prop(None);
+    let mut report = VerifyReport::default();
+    let mut seen = HashSet::new();
+
+    let entries = walk(agg.as_ref(), &root)
+        .await
+        .map_err(|e| anyhow!("can't list mount: {:?}", e))?;
+    for (path, meta) in entries {
+        if meta.is_dir() {
+            continue;
+        }
+        let np: NormalizedPath = (&path).into();
+        seen.insert(np.to_string());
+        let expected = match agg.get_prop(&path, prop.clone()).await {
+            Ok(xml) => parse_checksum(&xml),
+            Err(_) => None,
+        };
+        let expected = match expected {
+            Some(k) => k,
+            None => {
+                report.unchecked.push(np.to_string());
+                continue;
+            }
+        };
+        match read_checksum(agg.as_ref(), &path, meta.len()).await {
+            Ok(actual) if actual == expected => report.verified.push(np.to_string()),
+            r => {
+                debug!(path = %np, expected = %expected, actual = ?r);
+                report.corrupted.push(np.to_string());
+            }
+        }
+    }
+
+    let mount_prefix: NormalizedPath = mount.into();
+    let mount_prefix = mount_prefix.as_dir();
+    let stored = props
+        .paths_with_prop(&prop)
+        .await
+        .map_err(|e| anyhow!("can't read prop storage: {:?}", e))?;
+    for p in stored {
+        if (p.starts_with(mount_prefix.as_str()) || mount_prefix.is_root())
+            && !seen.contains(p.as_str())
+        {
+            report.missing.push(p.to_string());
+        }
+    }
+
+    Ok(report)
+}
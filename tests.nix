@@ -25,6 +25,10 @@ import "${toString pkgs.path}/nixos/tests/make-test-python.nix" ({ lib, ... }:
 					mount_path = "/fs1";
 					type = "mem";
 				}
+				{
+					mount_path = "/fs1x";
+					type = "mem";
+				}
 				{
 					mount_path = "/fs2";
 					path = "/tmp/webdav_ss/fs2";
@@ -71,6 +75,7 @@ machine.wait_for_open_port(9000)
 machine.wait_for_unit("webdav_ss.service")
 machine.wait_for_open_port(5000)
 machine.succeed("litmus http://localhost:5000/fs1")
+machine.succeed("TESTS=props litmus http://localhost:5000/fs1x")
 machine.succeed("litmus http://localhost:5000/fs2")
 machine.succeed("litmus http://localhost:5000/fs3")
 '';
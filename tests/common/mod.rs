@@ -0,0 +1,345 @@
+//! Harness running litmus suites against server built from configuration.
+#![allow(dead_code)]
+
+use futures_util::{select, FutureExt};
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Server,
+};
+use std::{
+    convert::Infallible,
+    sync::atomic::{AtomicU16, Ordering},
+};
+use testcontainers::{Container, Docker, Image};
+use tokio::process::Command;
+use webdav_ss::{
+    application::Application,
+    configuration::{
+        Application as ConfigApplication, Configuration, Filesystem, FilesystemType, LockStorage,
+        PropsStorage, S3Authentication,
+    },
+};
+
+pub const SUITES: &[&str] = &["basic", "copymove", "props", "locks", "http"];
+
+/// Mount nested in `/fs`, `/fs/nested%20mount` in urls.
+const NESTED_MOUNT: &str = "/fs/nested mount";
+
+static NEXT_PORT: AtomicU16 = AtomicU16::new(18080);
+
+pub struct ContainerDrop<'d, D: Docker, I: Image> {
+    pub container: Container<'d, D, I>,
+}
+
+impl<'d, D: Docker, I: Image> Drop for ContainerDrop<'d, D, I> {
+    fn drop(&mut self) {
+        self.container.stop();
+
+        let mut container_stdout = String::new();
+        self.container
+            .logs()
+            .stdout
+            .read_to_string(&mut container_stdout)
+            .unwrap();
+        let mut container_stderr = String::new();
+        self.container
+            .logs()
+            .stderr
+            .read_to_string(&mut container_stderr)
+            .unwrap();
+
+        println!("container stdout: {}", container_stdout);
+        println!("container stderr: {}", container_stderr);
+
+        self.container.rm();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backend {
+    Mem,
+    Fs,
+    /// minio listening on localhost:9000
+    S3 {
+        path_style: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Props {
+    Mem,
+    Yaml,
+    /// s3 only
+    Sidecar,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Locks {
+    Mem,
+    Redis(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Combination {
+    pub backend: Backend,
+    pub props: Props,
+    pub locks: Locks,
+}
+
+impl std::fmt::Display for Combination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let locks = match self.locks {
+            Locks::Mem => "mem",
+            Locks::Redis(_) => "redis",
+        };
+        write!(f, "{:?}/{:?}/{}", self.backend, self.props, locks)
+    }
+}
+
+/// Every valid combination of given backends, prop and lock storages.
+pub fn matrix(backends: &[Backend], locks: &[Locks]) -> Vec<Combination> {
+    let mut r = vec![];
+    for &backend in backends {
+        let mut props = vec![Props::Mem, Props::Yaml];
+        if matches!(backend, Backend::S3 { .. }) {
+            props.push(Props::Sidecar);
+        }
+        for &props in &props {
+            for locks in locks {
+                r.push(Combination {
+                    backend,
+                    props,
+                    locks: locks.clone(),
+                });
+            }
+        }
+    }
+    r
+}
+
+pub fn mount(fs: Filesystem) -> FilesystemType {
+    FilesystemType {
+        mount_path: "/fs".into(),
+        fs,
+        locking: true,
+        lock_storage: None,
+        unicode_normalization: None,
+        ignore: None,
+        rewrite: vec![],
+        cache_control: None,
+        headers: Default::default(),
+        groupware: false,
+        snapshots: None,
+        versions: None,
+        quota: None,
+        breaker: None,
+        middleware: vec![],
+        allow_methods: None,
+        deny_methods: vec![],
+        advertise: None,
+        dedupe: None,
+        compression: None,
+    }
+}
+
+pub fn s3(path_style: bool, sidecar_props: bool) -> Filesystem {
+    s3_at("http://localhost:9000", path_style, sidecar_props)
+}
+
+pub fn s3_at(url: &str, path_style: bool, sidecar_props: bool) -> Filesystem {
+    Filesystem::S3 {
+        region: "us-east-1".into(),
+        bucket: "test".into(),
+        url: url.into(),
+        path_style,
+        ensure_bucket: true,
+        auth: S3Authentication::Values {
+            access_key_value: "minioadmin".into(),
+            secret_key_value: "minioadmin".into(),
+        },
+        sidecar_props,
+        case_insensitive: false,
+        negative_cache_ttl: 2000,
+        spool_threshold: 64 * 1024 * 1024,
+        discover_region: false,
+        redirect_downloads: None,
+        redirect_expiry: 300,
+        download_range: 8 * 1024 * 1024,
+        download_parallelism: 4,
+        listing_cache: 0,
+        deep_listing: false,
+        pricing: None,
+    }
+}
+
+pub fn config(
+    port: u16,
+    fs: FilesystemType,
+    props: PropsStorage,
+    locks: LockStorage,
+) -> Configuration {
+    Configuration {
+        app: ConfigApplication {
+            host: "127.0.0.1".into(),
+            port,
+        },
+        filesystems: vec![fs],
+        prop_storage: Some(props),
+        lock_storage: Some(locks),
+        journal: None,
+        reconcile_interval: None,
+        lock_policy: Default::default(),
+        propfind: Default::default(),
+        content_types: Default::default(),
+        events: Default::default(),
+        timeouts: Default::default(),
+        connections: Default::default(),
+        metrics: Default::default(),
+        uploads: None,
+        request_filter: None,
+        search_index: None,
+        ui: None,
+        mirror: None,
+        compat: Default::default(),
+        dump: None,
+        errors: Default::default(),
+        error_reports: None,
+        hosts: vec![],
+    }
+}
+
+fn combination_config(c: &Combination, port: u16) -> Configuration {
+    let data = std::env::temp_dir().join(format!("webdav_ss_litmus_{}", port));
+    let _ = std::fs::remove_dir_all(&data);
+    std::fs::create_dir_all(&data).unwrap();
+
+    let fs = match c.backend {
+        Backend::Mem => Filesystem::Mem,
+        Backend::Fs => Filesystem::FS {
+            path: data.join("fs").to_string_lossy().into_owned(),
+            public: false,
+            macos: false,
+            follow_symlinks: None,
+            case_insensitive: false,
+            watch: false,
+            staging: true,
+            staging_dir: None,
+        },
+        Backend::S3 { path_style } => s3(path_style, c.props == Props::Sidecar),
+    };
+    let props = match c.props {
+        Props::Yaml => PropsStorage::Yaml {
+            path: data.join("props.yml").to_string_lossy().into_owned(),
+            dump_interval: 5,
+        },
+        Props::Mem | Props::Sidecar => PropsStorage::Mem,
+    };
+    let locks = match c.locks {
+        Locks::Mem => LockStorage::Mem,
+        Locks::Redis(ref url) => LockStorage::Redis {
+            url: url.clone(),
+            prefix: format!("litmus_{}", port),
+            max_timeout: 3600,
+        },
+    };
+    config(port, mount(fs), props, locks)
+}
+
+/// Combination served under a mount nested in another one, whose name
+/// has to be percent-encoded and which keeps locks while outer mount has
+/// locking disabled. Lock checks must follow the same routing as backends.
+fn nested_config(c: &Combination, port: u16) -> Configuration {
+    let mut config = combination_config(c, port);
+    let mut nested = config.filesystems[0].clone();
+    nested.mount_path = NESTED_MOUNT.into();
+    nested.lock_storage = config.lock_storage.take();
+    config.lock_storage = Some(LockStorage::Disabled);
+    config.filesystems.push(nested);
+    config
+}
+
+/// Serves `config` while litmus runs each suite against mount at `path`. Returns
+/// suites which failed.
+pub async fn run_suites(config: Configuration, path: &str, suites: &[&str]) -> Vec<String> {
+    let url = format!("http://{}:{}{}", config.app.host, config.app.port, path);
+    let app = Application::build(config)
+        .await
+        .expect("can't build application");
+    let mut app = Box::pin(app.run().fuse());
+    let mut failed = vec![];
+    for suite in suites {
+        let mut cmd = Box::pin(litmus(&url, suite).fuse());
+        select! {
+            passed = cmd => {
+                if !passed {
+                    failed.push(suite.to_string());
+                }
+            },
+            _ = app => panic!("server stopped"),
+        };
+    }
+    failed
+}
+
+/// Same as `run_suites`, application is served on free port instead of
+/// configured one.
+pub async fn run_suites_on_free_port(
+    config: Configuration,
+    path: &str,
+    suites: &[&str],
+) -> Vec<String> {
+    let handler = Application::build(config)
+        .await
+        .expect("can't build application")
+        .handler();
+    let make_svc = make_service_fn(move |_| {
+        let handler = handler.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let handler = handler.clone();
+                async move { Ok::<_, Infallible>(handler.handle(req).await) }
+            }))
+        }
+    });
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_svc);
+    let url = format!("http://{}{}", server.local_addr(), path);
+    tokio::spawn(server);
+    let mut failed = vec![];
+    for suite in suites {
+        if !litmus(&url, suite).await {
+            failed.push(suite.to_string());
+        }
+    }
+    failed
+}
+
+async fn litmus(url: &str, suite: &str) -> bool {
+    let result = Command::new("litmus")
+        .arg(url)
+        .env("TESTS", suite)
+        .output()
+        .await
+        .expect("can't run litmus");
+    let (out, err) = (&result.stdout, &result.stderr);
+    println!("suite {} stdout: {}", suite, String::from_utf8_lossy(out));
+    println!("suite {} stderr: {}", suite, String::from_utf8_lossy(err));
+    result.status.success()
+}
+
+/// Runs every suite for each combination and fails listing all broken ones.
+pub async fn run_matrix(combinations: &[Combination]) {
+    let mut failed = vec![];
+    for c in combinations {
+        let port = NEXT_PORT.fetch_add(1, Ordering::SeqCst);
+        println!("running litmus for {}", c);
+        for suite in run_suites(combination_config(c, port), "/fs", SUITES).await {
+            failed.push(format!("{}: {}", c, suite));
+        }
+        let port = NEXT_PORT.fetch_add(1, Ordering::SeqCst);
+        let url_path = NESTED_MOUNT.replace(' ', "%20");
+        for suite in run_suites(nested_config(c, port), &url_path, &["locks"]).await {
+            failed.push(format!("{} nested: {}", c, suite));
+        }
+    }
+    assert!(failed.is_empty(), "failed suites:\n{}", failed.join("\n"));
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "integration")]
+
+mod common;
+
+use common::{s3, ContainerDrop};
+use testcontainers::{
+    clients::Cli,
+    images::generic::{GenericImage, Stream, WaitFor},
+    Docker, Image, RunArgs,
+};
+use webdav_handler::davpath::DavPath;
+use webdav_ss::backend::{conformance::check, content_type::ContentTypes, s3_backend::S3Backend};
+
+#[tokio::test]
+async fn s3_conformance() {
+    let docker = Cli::default();
+    let minio = GenericImage::new("minio/minio")
+        .with_wait_for(WaitFor::LogMessage {
+            message: "Detected default credentials".into(),
+            stream: Stream::StdOut,
+        })
+        .with_args(vec!["server".into(), "/data".into()]);
+    let _minio = ContainerDrop {
+        container: docker.run_with_args(minio, RunArgs::default().with_mapped_port((9000, 9000))),
+    };
+
+    for sidecar_props in [false, true].iter() {
+        let fs = S3Backend::new(s3(true, *sidecar_props), ContentTypes::default())
+            .await
+            .unwrap();
+        let base = DavPath::new(&format!("/conformance_{}/", sidecar_props)).unwrap();
+        fs.create_dir(&base).await.unwrap();
+        if let Err(e) = check(fs.as_ref(), &base).await {
+            panic!("sidecar_props {}: {}", sidecar_props, e);
+        }
+    }
+}
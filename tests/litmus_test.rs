@@ -0,0 +1,46 @@
+#![cfg(feature = "integration")]
+
+mod common;
+
+use common::{matrix, run_matrix, Backend, ContainerDrop, Locks};
+use testcontainers::{
+    clients::Cli,
+    images::generic::{GenericImage, Stream, WaitFor},
+    Docker, Image, RunArgs,
+};
+
+/// Every litmus suite against every backend with each prop and lock storage.
+/// Containers bind fixed ports, so the whole matrix is one test. Redis lock
+/// storage blocks in place, hence multi-threaded runtime.
+#[tokio::test(flavor = "multi_thread")]
+async fn litmus_matrix() {
+    webdav_ss::configuration::setup_tracing();
+    let docker = Cli::default();
+
+    let redis = GenericImage::new("redis:6").with_wait_for(WaitFor::LogMessage {
+        message: "Ready to accept connections".into(),
+        stream: Stream::StdOut,
+    });
+    let _redis = ContainerDrop {
+        container: docker.run_with_args(redis, RunArgs::default().with_mapped_port((6379, 6379))),
+    };
+    let minio = GenericImage::new("minio/minio")
+        .with_wait_for(WaitFor::LogMessage {
+            message: "Detected default credentials".into(),
+            stream: Stream::StdOut,
+        })
+        .with_args(vec!["server".into(), "/data".into()])
+        .with_env_var("MINIO_DOMAIN", "localhost");
+    let _minio = ContainerDrop {
+        container: docker.run_with_args(minio, RunArgs::default().with_mapped_port((9000, 9000))),
+    };
+
+    let backends = [
+        Backend::Mem,
+        Backend::Fs,
+        Backend::S3 { path_style: false },
+        Backend::S3 { path_style: true },
+    ];
+    let locks = [Locks::Mem, Locks::Redis("redis://127.0.0.1:6379".into())];
+    run_matrix(&matrix(&backends, &locks)).await;
+}
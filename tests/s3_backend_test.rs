@@ -1,46 +1,15 @@
-use futures_util::{select, FutureExt};
+#![cfg(feature = "integration")]
+
+mod common;
+
+use common::{config, mount, run_suites, s3, ContainerDrop, SUITES};
 use std::env;
 use testcontainers::{
     clients::Cli,
     images::generic::{GenericImage, Stream, WaitFor},
-    Container, Docker, Image, RunArgs,
-};
-use tokio::process::*;
-use webdav_ss::{
-    application::Application,
-    configuration::{
-        Application as ConfigApplication, Configuration, Filesystem, FilesystemType, PropsStorage,
-        S3Authentication,
-    },
+    Docker, Image, RunArgs,
 };
-
-struct ContainerDrop<'d, D: Docker, I: Image> {
-    container: Container<'d, D, I>,
-}
-
-impl<'d, D: Docker, I: Image> Drop for ContainerDrop<'d, D, I> {
-    fn drop(&mut self) {
-        self.container.stop();
-
-        let mut container_stdout = String::new();
-        self.container
-            .logs()
-            .stdout
-            .read_to_string(&mut container_stdout)
-            .unwrap();
-        let mut container_stderr = String::new();
-        self.container
-            .logs()
-            .stderr
-            .read_to_string(&mut container_stderr)
-            .unwrap();
-
-        println!("container stdout: {}", container_stdout);
-        println!("container stderr: {}", container_stderr);
-
-        self.container.rm();
-    }
-}
+use webdav_ss::configuration::{Filesystem, FilesystemType, LockStorage, PropsStorage};
 
 async fn run_in_container(image: GenericImage, args: RunArgs, fs: FilesystemType) {
     let docker = Cli::default();
@@ -48,22 +17,18 @@ async fn run_in_container(image: GenericImage, args: RunArgs, fs: FilesystemType
 
     let _cont = ContainerDrop { container: cont };
 
-    let config = Configuration {
-        app: ConfigApplication {
-            host: "127.0.0.1".into(),
-            port: 8080,
-        },
-        filesystems: vec![
-            fs,
-            FilesystemType {
-                mount_path: "/fs2".into(),
-                fs: Filesystem::Mem,
-            },
-        ],
-        prop_storage: Some(PropsStorage::Yaml {
+    let mut config = config(
+        8080,
+        fs,
+        PropsStorage::Yaml {
             path: "/tmp/webdav_props.yml".into(),
-        }),
-    };
+            dump_interval: 5,
+        },
+        LockStorage::Mem,
+    );
+    let mut second = mount(Filesystem::Mem);
+    second.mount_path = "/fs2".into();
+    config.filesystems.push(second);
 
     if std::fs::metadata("/tmp/webdav_props.yml")
         .map(|_| true)
@@ -72,30 +37,11 @@ async fn run_in_container(image: GenericImage, args: RunArgs, fs: FilesystemType
         let _ = std::fs::remove_file("/tmp/webdav_props.yml");
     }
 
-    let mut app = Box::pin(Application::build(config).await.run().fuse());
-    let cmd = Command::new("litmus")
-        .arg(format!("http://localhost:8080/fs3"))
-        .current_dir(env::current_dir().unwrap())
-        .output()
-        .fuse();
-    let mut cmd = Box::pin(cmd);
-
-    select! {
-        res = cmd => {
-            let result = res.unwrap();
-            let stdout = String::from_utf8(result.stdout).unwrap();
-            let stderr = String::from_utf8(result.stderr).unwrap();
-
-            println!("stdout: {}", stdout);
-            println!("stderr: {}", stderr);
-            assert!(result.status.success());
-        },
-        _ = app => {} ,
-    };
+    let failed = run_suites(config, "/fs3", SUITES).await;
+    assert!(failed.is_empty(), "failed suites: {:?}", failed);
 }
 
 #[tokio::test]
-#[cfg(feature = "integration")]
 async fn s3_backend_minio() {
     // env::set_var("RUST_LOG", "webdav_ss=debug,webdav_handler=debug");
     webdav_ss::configuration::setup_tracing();
@@ -112,26 +58,13 @@ async fn s3_backend_minio() {
     env::set_var("AWS_ACCESS_KEY_ID", "minioadmin");
     env::set_var("AWS_SECRET_ACCESS_KEY", "minioadmin");
 
-    let fs = FilesystemType {
-        mount_path: "/fs3".into(),
-        fs: Filesystem::S3 {
-            region: "us-east-1".into(),
-            bucket: "test".into(),
-            url: format!("http://localhost:{}", 9000),
-            path_style: false,
-            ensure_bucket: true,
-            auth: S3Authentication::Values {
-                access_key_value: "minioadmin".into(),
-                secret_key_value: "minioadmin".into(),
-            },
-        },
-    };
+    let mut fs = mount(s3(false, false));
+    fs.mount_path = "/fs3".into();
 
     run_in_container(image, args, fs).await;
 }
 
 #[tokio::test]
-#[cfg(feature = "integration")]
 async fn s3_backend_minio_pathstyle() {
     // env::set_var("RUST_LOG", "webdav_ss=debug,webdav_handler=debug");
     webdav_ss::configuration::setup_tracing();
@@ -144,20 +77,8 @@ async fn s3_backend_minio_pathstyle() {
         })
         .with_args(vec!["server".into(), "/data".into()]);
 
-    let fs = FilesystemType {
-        mount_path: "/fs3".into(),
-        fs: Filesystem::S3 {
-            region: "us-east-1".into(),
-            bucket: "test".into(),
-            url: format!("http://localhost:{}", 9000),
-            path_style: true,
-            ensure_bucket: true,
-            auth: S3Authentication::Values {
-                access_key_value: "minioadmin".into(),
-                secret_key_value: "minioadmin".into(),
-            },
-        },
-    };
+    let mut fs = mount(s3(true, false));
+    fs.mount_path = "/fs3".into();
 
     run_in_container(image, args, fs).await;
 }
@@ -0,0 +1,23 @@
+#![cfg(feature = "mock-s3")]
+
+mod common;
+
+use common::{config, mount, run_suites_on_free_port, s3_at, SUITES};
+use webdav_ss::configuration::{LockStorage, PropsStorage};
+
+/// Same suites as against minio, using built-in mock so docker is not needed.
+#[tokio::test]
+async fn s3_backend_mock() {
+    webdav_ss::configuration::setup_tracing();
+
+    let mock = webdav_ss::mock_s3::MockS3::new();
+    let (addr, server) = mock.bind(([127, 0, 0, 1], 0).into()).unwrap();
+    tokio::spawn(server);
+
+    let mut fs = mount(s3_at(&format!("http://{}", addr), true, false));
+    fs.mount_path = "/fs3".into();
+    let config = config(0, fs, PropsStorage::Mem, LockStorage::Mem);
+
+    let failed = run_suites_on_free_port(config, "/fs3", SUITES).await;
+    assert!(failed.is_empty(), "failed suites: {:?}", failed);
+}